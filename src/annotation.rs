@@ -0,0 +1,163 @@
+//!
+//! Annotations (comments) attached to a cell.
+//!
+//! ```
+//! use spreadsheet_ods::annotation::Annotation;
+//! use spreadsheet_ods::{Length, Sheet};
+//!
+//! let mut sheet = Sheet::new("one");
+//! sheet.set_value(0, 0, 4711);
+//!
+//! let mut note = Annotation::new("Check this value.");
+//! note.set_creator(Some("Reviewer".to_string()));
+//! note.set_display(true);
+//! note.set_display_rect(Length::Cm(2.0), Length::Cm(0.5), Length::Cm(4.0), Length::Cm(1.5));
+//! sheet.set_annotation(0, 0, note);
+//!
+//! assert!(sheet.annotation(0, 0).is_some());
+//! ```
+//!
+
+use chrono::NaiveDateTime;
+
+use crate::attrmap2::AttrMap2;
+use crate::style::ParseStyleAttr;
+use crate::text::{TextP, TextTag};
+use crate::{Length, OdsError};
+
+/// An annotation for a cell.
+///
+/// The text consists of paragraphs, the same as the content of a cell.
+#[derive(Clone, Debug, Default)]
+pub struct Annotation {
+    name: Option<String>,
+    display: bool,
+    creator: Option<String>,
+    date: Option<NaiveDateTime>,
+    text: Vec<TextTag>,
+    attr: AttrMap2,
+}
+
+impl Annotation {
+    /// Annotation with one paragraph of text.
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        let mut s = Self::new_empty();
+        s.push_text(TextP::new().text(text).into_xmltag());
+        s
+    }
+
+    /// Annotation without any text.
+    pub fn new_empty() -> Self {
+        Self {
+            name: None,
+            display: false,
+            creator: None,
+            date: None,
+            text: Vec::new(),
+            attr: Default::default(),
+        }
+    }
+
+    /// Name of the annotation. Only needed if the annotation spans a
+    /// range of text.
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    /// Name of the annotation.
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    /// The annotation is always shown, not only when hovering over the cell.
+    pub fn display(&self) -> bool {
+        self.display
+    }
+
+    /// The annotation is always shown, not only when hovering over the cell.
+    pub fn set_display(&mut self, display: bool) {
+        self.display = display;
+    }
+
+    /// Author of the annotation.
+    pub fn creator(&self) -> Option<&String> {
+        self.creator.as_ref()
+    }
+
+    /// Author of the annotation.
+    pub fn set_creator(&mut self, creator: Option<String>) {
+        self.creator = creator;
+    }
+
+    /// Date and time of creation.
+    pub fn date(&self) -> Option<&NaiveDateTime> {
+        self.date.as_ref()
+    }
+
+    /// Date and time of creation.
+    pub fn set_date(&mut self, date: Option<NaiveDateTime>) {
+        self.date = date;
+    }
+
+    /// Text paragraphs.
+    pub fn text(&self) -> &Vec<TextTag> {
+        &self.text
+    }
+
+    /// Text paragraphs.
+    pub fn text_mut(&mut self) -> &mut Vec<TextTag> {
+        &mut self.text
+    }
+
+    /// Replaces the text.
+    pub fn set_text(&mut self, text: Vec<TextTag>) {
+        self.text = text;
+    }
+
+    /// Appends a paragraph.
+    pub fn push_text(&mut self, text: TextTag) {
+        self.text.push(text);
+    }
+
+    /// Position and size of the annotation when it is displayed.
+    /// The position is relative to the cell.
+    pub fn set_display_rect(&mut self, x: Length, y: Length, width: Length, height: Length) {
+        self.attr.set_attr("svg:x", x.to_string());
+        self.attr.set_attr("svg:y", y.to_string());
+        self.attr.set_attr("svg:width", width.to_string());
+        self.attr.set_attr("svg:height", height.to_string());
+    }
+
+    /// Position and size of the annotation when it is displayed as
+    /// (x, y, width, height).
+    pub fn display_rect(&self) -> Result<Option<(Length, Length, Length, Length)>, OdsError> {
+        let x = Length::parse_attr(self.attr.attr("svg:x"))?;
+        let y = Length::parse_attr(self.attr.attr("svg:y"))?;
+        let width = Length::parse_attr(self.attr.attr("svg:width"))?;
+        let height = Length::parse_attr(self.attr.attr("svg:height"))?;
+
+        match (x, y, width, height) {
+            (Some(x), Some(y), Some(width), Some(height)) => Ok(Some((x, y, width, height))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Removes the position and size.
+    pub fn clear_display_rect(&mut self) {
+        self.attr.clear_attr("svg:x");
+        self.attr.clear_attr("svg:y");
+        self.attr.clear_attr("svg:width");
+        self.attr.clear_attr("svg:height");
+    }
+
+    /// Allows access to all attributes of the annotation. This includes
+    /// the display rectangle and the draw styles.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Allows access to all attributes of the annotation.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+}
@@ -328,6 +328,48 @@ macro_rules! style_cell_protect {
             self.$acc
                 .set_attr("style:cell-protect", protect.to_string());
         }
+
+        /// Cell protection.
+        pub fn cell_protect(&self) -> Result<Option<CellProtect>, OdsError> {
+            CellProtect::parse_attr(self.$acc.attr("style:cell-protect"))
+        }
+
+        /// Cell content cannot be edited if the sheet is protected.
+        /// Keeps the formula-hidden flag, but resets hidden-and-protected.
+        ///
+        /// Without an explicit style:cell-protect the cell counts as
+        /// protected, as in LibreOffice.
+        pub fn set_protected(&mut self, protected: bool) {
+            let formula_hidden = self
+                .cell_protect()
+                .ok()
+                .flatten()
+                .map(|v| v.is_formula_hidden())
+                .unwrap_or(false);
+            self.set_cell_protect(CellProtect::from_flags(protected, formula_hidden));
+        }
+
+        /// A formula is not displayed if the sheet is protected.
+        /// Keeps the protected flag, but resets hidden-and-protected.
+        pub fn set_formula_hidden(&mut self, formula_hidden: bool) {
+            let protected = self
+                .cell_protect()
+                .ok()
+                .flatten()
+                .map(|v| v.is_protected())
+                .unwrap_or(true);
+            self.set_cell_protect(CellProtect::from_flags(protected, formula_hidden));
+        }
+
+        /// Cell content is neither displayed nor editable if the sheet is
+        /// protected. Resetting this leaves the cell protected.
+        pub fn set_hidden_and_protected(&mut self, hidden: bool) {
+            if hidden {
+                self.set_cell_protect(CellProtect::HiddenAndProtected);
+            } else {
+                self.set_cell_protect(CellProtect::Protected);
+            }
+        }
     };
 }
 
@@ -428,6 +470,14 @@ macro_rules! style_print_content {
         pub fn set_print_content(&mut self, print: bool) {
             self.$acc.set_attr("style:print-content", print.to_string());
         }
+
+        /// Cell content is printed.
+        pub fn print_content(&self) -> Option<bool> {
+            match self.$acc.attr("style:print-content") {
+                None => None,
+                Some(s) => FromStr::from_str(s.as_str()).ok(),
+            }
+        }
     };
 }
 
@@ -0,0 +1,178 @@
+//!
+//! Settings for the calculation of formulas.
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use spreadsheet_ods::WorkBook;
+//!
+//! let mut book = WorkBook::new_empty();
+//! let calc = book.calculation_settings_mut();
+//! calc.set_null_date(NaiveDate::from_ymd_opt(1904, 1, 1).unwrap());
+//! calc.set_iteration(true);
+//! calc.set_iteration_steps(50);
+//! calc.set_use_regular_expressions(false);
+//! calc.set_use_wildcards(true);
+//! ```
+//!
+
+use chrono::NaiveDate;
+
+/// Calculation settings of the workbook.
+///
+/// The defaults are the ones given by the ODF specification.
+/// LibreOffice defaults to wildcards instead of regular expressions
+/// and doesn't search for labels automatically.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalculationSettings {
+    case_sensitive: bool,
+    precision_as_shown: bool,
+    search_criteria_must_apply_to_whole_cell: bool,
+    automatic_find_labels: bool,
+    use_regular_expressions: bool,
+    use_wildcards: bool,
+    null_year: u32,
+    null_date: NaiveDate,
+    iteration: bool,
+    iteration_steps: u32,
+    iteration_minimum_difference: f64,
+}
+
+impl Default for CalculationSettings {
+    fn default() -> Self {
+        Self {
+            case_sensitive: true,
+            precision_as_shown: false,
+            search_criteria_must_apply_to_whole_cell: true,
+            automatic_find_labels: true,
+            use_regular_expressions: true,
+            use_wildcards: false,
+            null_year: 1930,
+            null_date: NaiveDate::from_ymd_opt(1899, 12, 30).expect("valid date"),
+            iteration: false,
+            iteration_steps: 100,
+            iteration_minimum_difference: 0.001,
+        }
+    }
+}
+
+impl CalculationSettings {
+    /// Default settings.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Text comparisons are case sensitive.
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// Text comparisons are case sensitive.
+    pub fn set_case_sensitive(&mut self, case_sensitive: bool) {
+        self.case_sensitive = case_sensitive;
+    }
+
+    /// Calculations use the rounded value as displayed.
+    pub fn precision_as_shown(&self) -> bool {
+        self.precision_as_shown
+    }
+
+    /// Calculations use the rounded value as displayed.
+    pub fn set_precision_as_shown(&mut self, precision_as_shown: bool) {
+        self.precision_as_shown = precision_as_shown;
+    }
+
+    /// Search criteria in functions like MATCH must match the whole cell.
+    pub fn search_criteria_must_apply_to_whole_cell(&self) -> bool {
+        self.search_criteria_must_apply_to_whole_cell
+    }
+
+    /// Search criteria in functions like MATCH must match the whole cell.
+    pub fn set_search_criteria_must_apply_to_whole_cell(&mut self, whole_cell: bool) {
+        self.search_criteria_must_apply_to_whole_cell = whole_cell;
+    }
+
+    /// Column and row labels can be used as references in formulas.
+    pub fn automatic_find_labels(&self) -> bool {
+        self.automatic_find_labels
+    }
+
+    /// Column and row labels can be used as references in formulas.
+    pub fn set_automatic_find_labels(&mut self, automatic_find_labels: bool) {
+        self.automatic_find_labels = automatic_find_labels;
+    }
+
+    /// Search criteria in formulas are regular expressions.
+    pub fn use_regular_expressions(&self) -> bool {
+        self.use_regular_expressions
+    }
+
+    /// Search criteria in formulas are regular expressions. Should not
+    /// be set together with wildcards.
+    pub fn set_use_regular_expressions(&mut self, use_regular_expressions: bool) {
+        self.use_regular_expressions = use_regular_expressions;
+    }
+
+    /// Search criteria in formulas can contain the wildcards ? and *.
+    pub fn use_wildcards(&self) -> bool {
+        self.use_wildcards
+    }
+
+    /// Search criteria in formulas can contain the wildcards ? and *.
+    /// Should not be set together with regular expressions.
+    pub fn set_use_wildcards(&mut self, use_wildcards: bool) {
+        self.use_wildcards = use_wildcards;
+    }
+
+    /// Two digit years are interpreted as the 100 years starting with
+    /// this year.
+    pub fn null_year(&self) -> u32 {
+        self.null_year
+    }
+
+    /// Two digit years are interpreted as the 100 years starting with
+    /// this year.
+    pub fn set_null_year(&mut self, null_year: u32) {
+        self.null_year = null_year;
+    }
+
+    /// The date that corresponds to the value 0. Default is 1899-12-30,
+    /// some spreadsheets use 1904-01-01.
+    pub fn null_date(&self) -> NaiveDate {
+        self.null_date
+    }
+
+    /// The date that corresponds to the value 0.
+    pub fn set_null_date(&mut self, null_date: NaiveDate) {
+        self.null_date = null_date;
+    }
+
+    /// Iterative calculation of circular references.
+    pub fn iteration(&self) -> bool {
+        self.iteration
+    }
+
+    /// Iterative calculation of circular references.
+    pub fn set_iteration(&mut self, iteration: bool) {
+        self.iteration = iteration;
+    }
+
+    /// Maximum number of iterations.
+    pub fn iteration_steps(&self) -> u32 {
+        self.iteration_steps
+    }
+
+    /// Maximum number of iterations.
+    pub fn set_iteration_steps(&mut self, steps: u32) {
+        self.iteration_steps = steps;
+    }
+
+    /// The iteration stops when the change is smaller than this.
+    pub fn iteration_minimum_difference(&self) -> f64 {
+        self.iteration_minimum_difference
+    }
+
+    /// The iteration stops when the change is smaller than this.
+    pub fn set_iteration_minimum_difference(&mut self, difference: f64) {
+        self.iteration_minimum_difference = difference;
+    }
+}
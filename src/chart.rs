@@ -0,0 +1,351 @@
+//!
+//! Charts anchored to a cell.
+//!
+//! Each chart is written as a separate chart document in the package,
+//! named "Object 1", "Object 2", ... and referenced by a draw:frame
+//! within the table-cell it is anchored to. The data is not copied into
+//! the chart, LibreOffice reads it from the referenced cell-ranges.
+//!
+//! ```
+//! use spreadsheet_ods::chart::{Chart, ChartType, LegendPosition, Series};
+//! use spreadsheet_ods::{CellRange, CellRef, Length, Sheet};
+//!
+//! let mut sheet = Sheet::new("kpi");
+//! sheet.set_value(0, 1, "Sales");
+//! for i in 0..12 {
+//!     sheet.set_value(i + 1, 0, format!("M{}", i + 1));
+//!     sheet.set_value(i + 1, 1, 100 + i);
+//! }
+//!
+//! let mut chart = Chart::new(ChartType::Bar);
+//! chart.set_title(Some("Sales per month".to_string()));
+//! chart.set_legend(Some(LegendPosition::End));
+//! chart.set_categories(Some(CellRange::remote("kpi", 1, 0, 12, 0)));
+//! chart.set_y_axis_title(Some("EUR".to_string()));
+//! let mut series = Series::new(CellRange::remote("kpi", 1, 1, 12, 1));
+//! series.set_label(Some(CellRef::remote("kpi", 0, 1)));
+//! chart.push_series(series);
+//!
+//! sheet.add_chart(0, 3, Length::Cm(12.0), Length::Cm(8.0), chart);
+//! ```
+//!
+
+use crate::attrmap2::AttrMap2;
+use crate::{CellRange, CellRef, Length};
+
+/// Kind of chart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartType {
+    /// Vertical bars.
+    Bar,
+    /// Lines.
+    Line,
+    /// Pie chart.
+    Pie,
+    /// XY chart. Each series needs a domain for the x-values.
+    Scatter,
+}
+
+impl ChartType {
+    /// Value for chart:class.
+    pub(crate) fn as_class(&self) -> &'static str {
+        match self {
+            ChartType::Bar => "chart:bar",
+            ChartType::Line => "chart:line",
+            ChartType::Pie => "chart:circle",
+            ChartType::Scatter => "chart:scatter",
+        }
+    }
+
+    /// Parses chart:class.
+    pub(crate) fn from_class(class: &str) -> Option<Self> {
+        match class {
+            "chart:bar" => Some(ChartType::Bar),
+            "chart:line" => Some(ChartType::Line),
+            "chart:circle" => Some(ChartType::Pie),
+            "chart:scatter" => Some(ChartType::Scatter),
+            _ => None,
+        }
+    }
+}
+
+/// Position of the legend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum LegendPosition {
+    Start,
+    End,
+    Top,
+    Bottom,
+}
+
+impl LegendPosition {
+    /// Value for chart:legend-position.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            LegendPosition::Start => "start",
+            LegendPosition::End => "end",
+            LegendPosition::Top => "top",
+            LegendPosition::Bottom => "bottom",
+        }
+    }
+
+    /// Parses chart:legend-position. The corner positions are mapped
+    /// to the nearest side.
+    pub(crate) fn parse(value: &str) -> Self {
+        match value {
+            "start" => LegendPosition::Start,
+            "top" | "top-start" | "top-end" => LegendPosition::Top,
+            "bottom" | "bottom-start" | "bottom-end" => LegendPosition::Bottom,
+            _ => LegendPosition::End,
+        }
+    }
+}
+
+/// One data series of a chart.
+#[derive(Clone, Debug, Default)]
+pub struct Series {
+    values: CellRange,
+    label: Option<CellRef>,
+    domain: Option<CellRange>,
+}
+
+impl Series {
+    /// Series with the given values. The range must contain the sheet name.
+    pub fn new(values: CellRange) -> Self {
+        Self {
+            values,
+            label: None,
+            domain: None,
+        }
+    }
+
+    /// Cell-range of the values.
+    pub fn values(&self) -> &CellRange {
+        &self.values
+    }
+
+    /// Cell-range of the values.
+    pub fn set_values(&mut self, values: CellRange) {
+        self.values = values;
+    }
+
+    /// Cell that contains the name of the series.
+    pub fn label(&self) -> Option<&CellRef> {
+        self.label.as_ref()
+    }
+
+    /// Cell that contains the name of the series.
+    pub fn set_label(&mut self, label: Option<CellRef>) {
+        self.label = label;
+    }
+
+    /// Cell-range of the x-values for a scatter chart.
+    pub fn domain(&self) -> Option<&CellRange> {
+        self.domain.as_ref()
+    }
+
+    /// Cell-range of the x-values for a scatter chart.
+    pub fn set_domain(&mut self, domain: Option<CellRange>) {
+        self.domain = domain;
+    }
+}
+
+/// A chart anchored to a cell.
+#[derive(Clone, Debug)]
+pub struct Chart {
+    name: Option<String>,
+    href: Option<String>,
+    chart_type: ChartType,
+    title: Option<String>,
+    legend: Option<LegendPosition>,
+    x_axis_title: Option<String>,
+    y_axis_title: Option<String>,
+    categories: Option<CellRange>,
+    series: Vec<Series>,
+    x: Length,
+    y: Length,
+    width: Length,
+    height: Length,
+    attr: AttrMap2,
+}
+
+impl Chart {
+    /// Empty chart of the given type.
+    pub fn new(chart_type: ChartType) -> Self {
+        Self {
+            name: None,
+            href: None,
+            chart_type,
+            title: None,
+            legend: None,
+            x_axis_title: None,
+            y_axis_title: None,
+            categories: None,
+            series: Vec::new(),
+            x: Length::Cm(0.0),
+            y: Length::Cm(0.0),
+            width: Default::default(),
+            height: Default::default(),
+            attr: Default::default(),
+        }
+    }
+
+    /// Name of the drawing object.
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    /// Name of the drawing object.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = Some(name.into());
+    }
+
+    /// Path of the chart document within the package. This is assigned
+    /// when writing if it is not set.
+    pub fn href(&self) -> Option<&String> {
+        self.href.as_ref()
+    }
+
+    /// Path of the chart document within the package. Must be unique.
+    pub fn set_href<S: Into<String>>(&mut self, href: S) {
+        self.href = Some(href.into());
+    }
+
+    /// Kind of chart.
+    pub fn chart_type(&self) -> ChartType {
+        self.chart_type
+    }
+
+    /// Kind of chart.
+    pub fn set_chart_type(&mut self, chart_type: ChartType) {
+        self.chart_type = chart_type;
+    }
+
+    /// Title of the chart.
+    pub fn title(&self) -> Option<&String> {
+        self.title.as_ref()
+    }
+
+    /// Title of the chart.
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
+
+    /// Position of the legend. No legend if None.
+    pub fn legend(&self) -> Option<LegendPosition> {
+        self.legend
+    }
+
+    /// Position of the legend. No legend if None.
+    pub fn set_legend(&mut self, legend: Option<LegendPosition>) {
+        self.legend = legend;
+    }
+
+    /// Label of the x-axis.
+    pub fn x_axis_title(&self) -> Option<&String> {
+        self.x_axis_title.as_ref()
+    }
+
+    /// Label of the x-axis.
+    pub fn set_x_axis_title(&mut self, title: Option<String>) {
+        self.x_axis_title = title;
+    }
+
+    /// Label of the y-axis.
+    pub fn y_axis_title(&self) -> Option<&String> {
+        self.y_axis_title.as_ref()
+    }
+
+    /// Label of the y-axis.
+    pub fn set_y_axis_title(&mut self, title: Option<String>) {
+        self.y_axis_title = title;
+    }
+
+    /// Cell-range with the category names for the x-axis.
+    pub fn categories(&self) -> Option<&CellRange> {
+        self.categories.as_ref()
+    }
+
+    /// Cell-range with the category names for the x-axis.
+    pub fn set_categories(&mut self, categories: Option<CellRange>) {
+        self.categories = categories;
+    }
+
+    /// Data series.
+    pub fn series(&self) -> &Vec<Series> {
+        &self.series
+    }
+
+    /// Data series.
+    pub fn series_mut(&mut self) -> &mut Vec<Series> {
+        &mut self.series
+    }
+
+    /// Adds a data series.
+    pub fn push_series(&mut self, series: Series) {
+        self.series.push(series);
+    }
+
+    /// Position relative to the anchor cell.
+    pub fn position(&self) -> (Length, Length) {
+        (self.x, self.y)
+    }
+
+    /// Position relative to the anchor cell.
+    pub fn set_position(&mut self, x: Length, y: Length) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// Size as (width, height).
+    pub fn size(&self) -> (Length, Length) {
+        (self.width, self.height)
+    }
+
+    /// Size of the chart.
+    pub fn set_size(&mut self, width: Length, height: Length) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Allows access to all other attributes of the draw:frame.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Allows access to all other attributes of the draw:frame.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+
+    /// All cell-ranges used by the chart.
+    pub(crate) fn ranges(&self) -> Vec<CellRange> {
+        let mut ranges = Vec::new();
+        if let Some(categories) = &self.categories {
+            ranges.push(categories.clone());
+        }
+        for series in &self.series {
+            if let Some(label) = &series.label {
+                ranges.push(CellRange::new_all(
+                    label.iri().cloned(),
+                    label.table().cloned(),
+                    label.row_abs(),
+                    label.row(),
+                    label.col_abs(),
+                    label.col(),
+                    None,
+                    label.row_abs(),
+                    label.row(),
+                    label.col_abs(),
+                    label.col(),
+                ));
+            }
+            if let Some(domain) = &series.domain {
+                ranges.push(domain.clone());
+            }
+            ranges.push(series.values.clone());
+        }
+        ranges
+    }
+}
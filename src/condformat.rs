@@ -0,0 +1,737 @@
+//!
+//! Conditional formats as used by LibreOffice (calcext:conditional-formats).
+//!
+//! A conditional format applies to a list of cell-ranges and contains
+//! any number of entries. The entries are checked in order, the first
+//! one that matches is used.
+//!
+//! ```
+//! use color::Rgb;
+//! use spreadsheet_ods::condformat::{
+//!     ColorScale, ConditionalFormat, ConditionRule, DataBar, FormatCondition, FormatEntry,
+//!     FormatEntryType,
+//! };
+//! use spreadsheet_ods::style::{StyleOrigin, StyleUse};
+//! use spreadsheet_ods::{CellRange, CellRef, CellStyle, Sheet, WorkBook};
+//!
+//! let mut book = WorkBook::new_empty();
+//! let mut red = CellStyle::new_empty();
+//! red.set_name("red");
+//! red.set_origin(StyleOrigin::Styles);
+//! red.set_styleuse(StyleUse::Named);
+//! red.set_color(Rgb::new(255, 0, 0));
+//! let red = book.add_cellstyle(red);
+//!
+//! let mut sheet = Sheet::new("one");
+//!
+//! let mut cf = ConditionalFormat::new(vec![CellRange::remote("one", 0, 0, 9, 0)]);
+//! cf.push_condition(ConditionRule::new(
+//!     FormatCondition::lt(0),
+//!     &red,
+//!     Some(CellRef::remote("one", 0, 0)),
+//! ));
+//! sheet.add_conditional_format(cf);
+//!
+//! let mut cf = ConditionalFormat::new(vec![CellRange::remote("one", 0, 1, 9, 1)]);
+//! let mut scale = ColorScale::new();
+//! scale.push_entry(FormatEntry::new(FormatEntryType::Minimum, ""), Rgb::new(255, 0, 0));
+//! scale.push_entry(FormatEntry::new(FormatEntryType::Maximum, ""), Rgb::new(0, 255, 0));
+//! cf.push_color_scale(scale);
+//! sheet.add_conditional_format(cf);
+//!
+//! let mut cf = ConditionalFormat::new(vec![CellRange::remote("one", 0, 2, 9, 2)]);
+//! cf.push_data_bar(DataBar::new(Rgb::new(0x2a, 0x60, 0x99)));
+//! sheet.add_conditional_format(cf);
+//!
+//! book.push_sheet(sheet);
+//! ```
+//!
+
+use std::fmt::{Display, Formatter};
+
+use color::Rgb;
+
+use crate::attrmap2::AttrMap2;
+use crate::condition::Value;
+use crate::style::CellStyleRef;
+use crate::{CellRange, CellRef, OdsError};
+
+/// A conditional format for a list of cell-ranges.
+#[derive(Clone, Debug, Default)]
+pub struct ConditionalFormat {
+    ranges: Vec<CellRange>,
+    entries: Vec<ConditionalEntry>,
+}
+
+impl ConditionalFormat {
+    /// Conditional format for the cell-ranges. The ranges must contain
+    /// the sheet name.
+    pub fn new(ranges: Vec<CellRange>) -> Self {
+        Self {
+            ranges,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Target cell-ranges.
+    pub fn ranges(&self) -> &Vec<CellRange> {
+        &self.ranges
+    }
+
+    /// Target cell-ranges.
+    pub fn set_ranges(&mut self, ranges: Vec<CellRange>) {
+        self.ranges = ranges;
+    }
+
+    /// The entries in order.
+    pub fn entries(&self) -> &Vec<ConditionalEntry> {
+        &self.entries
+    }
+
+    /// The entries in order.
+    pub fn entries_mut(&mut self) -> &mut Vec<ConditionalEntry> {
+        &mut self.entries
+    }
+
+    /// Adds an entry.
+    pub fn push_entry(&mut self, entry: ConditionalEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Adds a condition with a cell-style.
+    pub fn push_condition(&mut self, rule: ConditionRule) {
+        self.entries.push(ConditionalEntry::Condition(rule));
+    }
+
+    /// Adds a color scale.
+    pub fn push_color_scale(&mut self, scale: ColorScale) {
+        self.entries.push(ConditionalEntry::ColorScale(scale));
+    }
+
+    /// Adds a data bar.
+    pub fn push_data_bar(&mut self, bar: DataBar) {
+        self.entries.push(ConditionalEntry::DataBar(bar));
+    }
+
+    /// Adds an icon set.
+    pub fn push_icon_set(&mut self, icons: IconSet) {
+        self.entries.push(ConditionalEntry::IconSet(icons));
+    }
+
+    /// Adds a date condition.
+    pub fn push_date_is(&mut self, date_is: DateIs) {
+        self.entries.push(ConditionalEntry::DateIs(date_is));
+    }
+}
+
+/// One entry of a conditional format.
+#[derive(Clone, Debug)]
+pub enum ConditionalEntry {
+    /// Applies a cell-style if the condition is true.
+    Condition(ConditionRule),
+    /// Background color depending on the value.
+    ColorScale(ColorScale),
+    /// Bar with a length depending on the value.
+    DataBar(DataBar),
+    /// Icon depending on the value.
+    IconSet(IconSet),
+    /// Applies a cell-style for a date.
+    DateIs(DateIs),
+}
+
+/// Condition for a conditional format.
+#[derive(Clone, Debug, Default)]
+pub struct FormatCondition {
+    cond: String,
+}
+
+impl Display for FormatCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.cond)
+    }
+}
+
+impl FormatCondition {
+    /// Creates a condition from a string that was read.
+    pub(crate) fn new<S: Into<String>>(str: S) -> Self {
+        Self { cond: str.into() }
+    }
+
+    fn cmp<V: Into<Value>>(op: &str, value: V) -> Self {
+        let mut buf = String::new();
+        buf.push_str(op);
+        buf.push_str(value.into().to_string().as_str());
+        FormatCondition { cond: buf }
+    }
+
+    fn func<S: AsRef<str>>(name: &str, args: &[S]) -> Self {
+        let mut buf = String::new();
+        buf.push_str(name);
+        buf.push('(');
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            buf.push_str(arg.as_ref());
+        }
+        buf.push(')');
+        FormatCondition { cond: buf }
+    }
+
+    /// Compares the cell-content with a value.
+    pub fn eq<V: Into<Value>>(value: V) -> Self {
+        Self::cmp("=", value)
+    }
+
+    /// Compares the cell-content with a value.
+    pub fn ne<V: Into<Value>>(value: V) -> Self {
+        Self::cmp("!=", value)
+    }
+
+    /// Compares the cell-content with a value.
+    pub fn lt<V: Into<Value>>(value: V) -> Self {
+        Self::cmp("<", value)
+    }
+
+    /// Compares the cell-content with a value.
+    pub fn gt<V: Into<Value>>(value: V) -> Self {
+        Self::cmp(">", value)
+    }
+
+    /// Compares the cell-content with a value.
+    pub fn lte<V: Into<Value>>(value: V) -> Self {
+        Self::cmp("<=", value)
+    }
+
+    /// Compares the cell-content with a value.
+    pub fn gte<V: Into<Value>>(value: V) -> Self {
+        Self::cmp(">=", value)
+    }
+
+    /// Range check of the cell-content.
+    pub fn between<V: Into<Value>>(from: V, to: V) -> Self {
+        Self::func("between", &[from.into().to_string(), to.into().to_string()])
+    }
+
+    /// Range check of the cell-content.
+    pub fn not_between<V: Into<Value>>(from: V, to: V) -> Self {
+        Self::func(
+            "not-between",
+            &[from.into().to_string(), to.into().to_string()],
+        )
+    }
+
+    /// The value occurs more than once in the ranges.
+    pub fn duplicate() -> Self {
+        Self::new("duplicate")
+    }
+
+    /// The value occurs only once in the ranges.
+    pub fn unique() -> Self {
+        Self::new("unique")
+    }
+
+    /// The value is one of the n largest values.
+    pub fn top_elements(n: u32) -> Self {
+        Self::func("top-elements", &[n.to_string()])
+    }
+
+    /// The value is one of the n smallest values.
+    pub fn bottom_elements(n: u32) -> Self {
+        Self::func("bottom-elements", &[n.to_string()])
+    }
+
+    /// The value is in the top n percent.
+    pub fn top_percent(n: u32) -> Self {
+        Self::func("top-percent", &[n.to_string()])
+    }
+
+    /// The value is in the bottom n percent.
+    pub fn bottom_percent(n: u32) -> Self {
+        Self::func("bottom-percent", &[n.to_string()])
+    }
+
+    /// The value is above the average of the ranges.
+    pub fn above_average() -> Self {
+        Self::new("above-average")
+    }
+
+    /// The value is below the average of the ranges.
+    pub fn below_average() -> Self {
+        Self::new("below-average")
+    }
+
+    /// The cell contains an error.
+    pub fn error() -> Self {
+        Self::new("is-error")
+    }
+
+    /// The cell contains no error.
+    pub fn no_error() -> Self {
+        Self::new("is-no-error")
+    }
+
+    /// The text starts with the value.
+    pub fn begins_with<V: Into<Value>>(value: V) -> Self {
+        Self::func("begins-with", &[value.into().to_string()])
+    }
+
+    /// The text ends with the value.
+    pub fn ends_with<V: Into<Value>>(value: V) -> Self {
+        Self::func("ends-with", &[value.into().to_string()])
+    }
+
+    /// The text contains the value.
+    pub fn contains_text<V: Into<Value>>(value: V) -> Self {
+        Self::func("contains-text", &[value.into().to_string()])
+    }
+
+    /// The text doesn't contain the value.
+    pub fn not_contains_text<V: Into<Value>>(value: V) -> Self {
+        Self::func("not-contains-text", &[value.into().to_string()])
+    }
+
+    /// Evaluates a formula. Relative references are resolved with the
+    /// base cell.
+    pub fn formula_is<S: AsRef<str>>(formula: S) -> Self {
+        Self::func("formula-is", &[formula])
+    }
+}
+
+/// Applies a cell-style if the condition is true.
+#[derive(Clone, Debug, Default)]
+pub struct ConditionRule {
+    condition: FormatCondition,
+    style: String,
+    base_cell: Option<CellRef>,
+}
+
+impl ConditionRule {
+    /// Condition with the applied style. The style must be a named style
+    /// with StyleOrigin::Styles and StyleUse::Named. The base cell is used
+    /// to resolve relative references in the condition.
+    pub fn new(
+        condition: FormatCondition,
+        style: &CellStyleRef,
+        base_cell: Option<CellRef>,
+    ) -> Self {
+        Self {
+            condition,
+            style: style.to_string(),
+            base_cell,
+        }
+    }
+
+    /// Condition.
+    pub fn condition(&self) -> &FormatCondition {
+        &self.condition
+    }
+
+    /// Condition.
+    pub fn set_condition(&mut self, condition: FormatCondition) {
+        self.condition = condition;
+    }
+
+    /// The applied cell-style.
+    pub fn style(&self) -> &String {
+        &self.style
+    }
+
+    /// The applied cell-style.
+    pub fn set_style(&mut self, style: &CellStyleRef) {
+        self.style = style.to_string();
+    }
+
+    /// Base cell.
+    pub fn base_cell(&self) -> Option<&CellRef> {
+        self.base_cell.as_ref()
+    }
+
+    /// Base cell.
+    pub fn set_base_cell(&mut self, base_cell: Option<CellRef>) {
+        self.base_cell = base_cell;
+    }
+}
+
+/// How the value of a format entry is interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormatEntryType {
+    /// Smallest value in the range.
+    Minimum,
+    /// Largest value in the range.
+    Maximum,
+    /// Smallest value in the range or 0.
+    AutoMinimum,
+    /// Largest value in the range or 0.
+    AutoMaximum,
+    /// The value as number.
+    Number,
+    /// Percent of the value range.
+    Percent,
+    /// Percentile of the values.
+    Percentile,
+    /// Result of a formula.
+    Formula,
+}
+
+impl FormatEntryType {
+    /// Value for calcext:type.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            FormatEntryType::Minimum => "minimum",
+            FormatEntryType::Maximum => "maximum",
+            FormatEntryType::AutoMinimum => "auto-minimum",
+            FormatEntryType::AutoMaximum => "auto-maximum",
+            FormatEntryType::Number => "number",
+            FormatEntryType::Percent => "percent",
+            FormatEntryType::Percentile => "percentile",
+            FormatEntryType::Formula => "formula",
+        }
+    }
+
+    /// Parses calcext:type.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "minimum" => Ok(FormatEntryType::Minimum),
+            "maximum" => Ok(FormatEntryType::Maximum),
+            "auto-minimum" => Ok(FormatEntryType::AutoMinimum),
+            "auto-maximum" => Ok(FormatEntryType::AutoMaximum),
+            "number" => Ok(FormatEntryType::Number),
+            "percent" => Ok(FormatEntryType::Percent),
+            "percentile" => Ok(FormatEntryType::Percentile),
+            "formula" => Ok(FormatEntryType::Formula),
+            _ => Err(OdsError::Parse(format!("unknown entry type {}", value))),
+        }
+    }
+}
+
+/// Threshold for color scales, data bars and icon sets.
+#[derive(Clone, Debug)]
+pub struct FormatEntry {
+    entry_type: FormatEntryType,
+    value: String,
+}
+
+impl FormatEntry {
+    /// Threshold. The value is not used for minimum and maximum.
+    pub fn new<S: Into<String>>(entry_type: FormatEntryType, value: S) -> Self {
+        Self {
+            entry_type,
+            value: value.into(),
+        }
+    }
+
+    /// How the value is interpreted.
+    pub fn entry_type(&self) -> FormatEntryType {
+        self.entry_type
+    }
+
+    /// How the value is interpreted.
+    pub fn set_entry_type(&mut self, entry_type: FormatEntryType) {
+        self.entry_type = entry_type;
+    }
+
+    /// Value.
+    pub fn value(&self) -> &String {
+        &self.value
+    }
+
+    /// Value.
+    pub fn set_value<S: Into<String>>(&mut self, value: S) {
+        self.value = value.into();
+    }
+}
+
+/// Background color depending on the value. The colors are interpolated
+/// between the entries.
+#[derive(Clone, Debug, Default)]
+pub struct ColorScale {
+    entries: Vec<(FormatEntry, Rgb<u8>)>,
+}
+
+impl ColorScale {
+    /// Empty color scale. Needs two or three entries.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Entries with their color.
+    pub fn entries(&self) -> &Vec<(FormatEntry, Rgb<u8>)> {
+        &self.entries
+    }
+
+    /// Entries with their color.
+    pub fn entries_mut(&mut self) -> &mut Vec<(FormatEntry, Rgb<u8>)> {
+        &mut self.entries
+    }
+
+    /// Adds an entry.
+    pub fn push_entry(&mut self, entry: FormatEntry, color: Rgb<u8>) {
+        self.entries.push((entry, color));
+    }
+}
+
+/// Bar with a length depending on the value.
+#[derive(Clone, Debug)]
+pub struct DataBar {
+    min: FormatEntry,
+    max: FormatEntry,
+    positive_color: Rgb<u8>,
+    negative_color: Option<Rgb<u8>>,
+    axis_color: Option<Rgb<u8>>,
+    gradient: bool,
+    show_value: bool,
+    attr: AttrMap2,
+}
+
+impl DataBar {
+    /// Data bar from the automatic minimum to the automatic maximum.
+    pub fn new(positive_color: Rgb<u8>) -> Self {
+        Self {
+            min: FormatEntry::new(FormatEntryType::AutoMinimum, "0"),
+            max: FormatEntry::new(FormatEntryType::AutoMaximum, "0"),
+            positive_color,
+            negative_color: None,
+            axis_color: None,
+            gradient: true,
+            show_value: true,
+            attr: Default::default(),
+        }
+    }
+
+    /// Value for the shortest bar.
+    pub fn min(&self) -> &FormatEntry {
+        &self.min
+    }
+
+    /// Value for the shortest bar.
+    pub fn set_min(&mut self, min: FormatEntry) {
+        self.min = min;
+    }
+
+    /// Value for the longest bar.
+    pub fn max(&self) -> &FormatEntry {
+        &self.max
+    }
+
+    /// Value for the longest bar.
+    pub fn set_max(&mut self, max: FormatEntry) {
+        self.max = max;
+    }
+
+    /// Color of the bar.
+    pub fn positive_color(&self) -> Rgb<u8> {
+        self.positive_color
+    }
+
+    /// Color of the bar.
+    pub fn set_positive_color(&mut self, color: Rgb<u8>) {
+        self.positive_color = color;
+    }
+
+    /// Color for negative values.
+    pub fn negative_color(&self) -> Option<Rgb<u8>> {
+        self.negative_color
+    }
+
+    /// Color for negative values.
+    pub fn set_negative_color(&mut self, color: Option<Rgb<u8>>) {
+        self.negative_color = color;
+    }
+
+    /// Color of the axis between negative and positive values.
+    pub fn axis_color(&self) -> Option<Rgb<u8>> {
+        self.axis_color
+    }
+
+    /// Color of the axis between negative and positive values.
+    pub fn set_axis_color(&mut self, color: Option<Rgb<u8>>) {
+        self.axis_color = color;
+    }
+
+    /// Gradient fill of the bar.
+    pub fn gradient(&self) -> bool {
+        self.gradient
+    }
+
+    /// Gradient fill of the bar.
+    pub fn set_gradient(&mut self, gradient: bool) {
+        self.gradient = gradient;
+    }
+
+    /// Show the value too.
+    pub fn show_value(&self) -> bool {
+        self.show_value
+    }
+
+    /// Show the value too.
+    pub fn set_show_value(&mut self, show_value: bool) {
+        self.show_value = show_value;
+    }
+
+    /// Allows access to all other attributes of the calcext:data-bar.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Allows access to all other attributes of the calcext:data-bar.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+}
+
+/// Icon depending on the value.
+#[derive(Clone, Debug)]
+pub struct IconSet {
+    icon_set_type: String,
+    show_value: bool,
+    entries: Vec<FormatEntry>,
+}
+
+impl IconSet {
+    /// Icon set like "3Arrows", "3TrafficLights1", "4Rating" or "5Quarters".
+    /// Needs one entry per icon, the first is the lower bound for the
+    /// first icon.
+    pub fn new<S: Into<String>>(icon_set_type: S) -> Self {
+        Self {
+            icon_set_type: icon_set_type.into(),
+            show_value: true,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Icon set.
+    pub fn icon_set_type(&self) -> &String {
+        &self.icon_set_type
+    }
+
+    /// Icon set.
+    pub fn set_icon_set_type<S: Into<String>>(&mut self, icon_set_type: S) {
+        self.icon_set_type = icon_set_type.into();
+    }
+
+    /// Show the value too.
+    pub fn show_value(&self) -> bool {
+        self.show_value
+    }
+
+    /// Show the value too.
+    pub fn set_show_value(&mut self, show_value: bool) {
+        self.show_value = show_value;
+    }
+
+    /// Thresholds.
+    pub fn entries(&self) -> &Vec<FormatEntry> {
+        &self.entries
+    }
+
+    /// Thresholds.
+    pub fn entries_mut(&mut self) -> &mut Vec<FormatEntry> {
+        &mut self.entries
+    }
+
+    /// Adds a threshold.
+    pub fn push_entry(&mut self, entry: FormatEntry) {
+        self.entries.push(entry);
+    }
+}
+
+/// Date relative to today.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum DateType {
+    Today,
+    Yesterday,
+    Tomorrow,
+    Last7Days,
+    ThisWeek,
+    LastWeek,
+    NextWeek,
+    ThisMonth,
+    LastMonth,
+    NextMonth,
+    ThisYear,
+    LastYear,
+    NextYear,
+}
+
+impl DateType {
+    /// Value for calcext:date.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            DateType::Today => "today",
+            DateType::Yesterday => "yesterday",
+            DateType::Tomorrow => "tomorrow",
+            DateType::Last7Days => "last-7-days",
+            DateType::ThisWeek => "this-week",
+            DateType::LastWeek => "last-week",
+            DateType::NextWeek => "next-week",
+            DateType::ThisMonth => "this-month",
+            DateType::LastMonth => "last-month",
+            DateType::NextMonth => "next-month",
+            DateType::ThisYear => "this-year",
+            DateType::LastYear => "last-year",
+            DateType::NextYear => "next-year",
+        }
+    }
+
+    /// Parses calcext:date.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "today" => Ok(DateType::Today),
+            "yesterday" => Ok(DateType::Yesterday),
+            "tomorrow" => Ok(DateType::Tomorrow),
+            "last-7-days" => Ok(DateType::Last7Days),
+            "this-week" => Ok(DateType::ThisWeek),
+            "last-week" => Ok(DateType::LastWeek),
+            "next-week" => Ok(DateType::NextWeek),
+            "this-month" => Ok(DateType::ThisMonth),
+            "last-month" => Ok(DateType::LastMonth),
+            "next-month" => Ok(DateType::NextMonth),
+            "this-year" => Ok(DateType::ThisYear),
+            "last-year" => Ok(DateType::LastYear),
+            "next-year" => Ok(DateType::NextYear),
+            _ => Err(OdsError::Parse(format!("unknown date {}", value))),
+        }
+    }
+}
+
+/// Applies a cell-style if the date matches.
+#[derive(Clone, Debug)]
+pub struct DateIs {
+    date: DateType,
+    style: String,
+}
+
+impl DateIs {
+    /// Date condition with the applied style. The style must be a named
+    /// style.
+    pub fn new(date: DateType, style: &CellStyleRef) -> Self {
+        Self {
+            date,
+            style: style.to_string(),
+        }
+    }
+
+    /// Date.
+    pub fn date(&self) -> DateType {
+        self.date
+    }
+
+    /// Date.
+    pub fn set_date(&mut self, date: DateType) {
+        self.date = date;
+    }
+
+    /// The applied cell-style.
+    pub fn style(&self) -> &String {
+        &self.style
+    }
+
+    /// The applied cell-style.
+    pub fn set_style(&mut self, style: &CellStyleRef) {
+        self.style = style.to_string();
+    }
+}
@@ -0,0 +1,494 @@
+//!
+//! Database ranges with autofilter and sort.
+//!
+//! A database range names a cell-range of a sheet and can show filter
+//! buttons in the header row. The range belongs to the sheet given in
+//! its target cell-range. LibreOffice uses the name
+//! "__Anonymous_Sheet_DB__" + sheet index for the autofilter of a sheet.
+//!
+//! ```
+//! use spreadsheet_ods::database::{
+//!     DatabaseRange, Filter, FilterCondition, FilterItem, FilterOperator, SortKey, SortOrder,
+//! };
+//! use spreadsheet_ods::{CellRange, Sheet, WorkBook};
+//!
+//! let mut book = WorkBook::new_empty();
+//! let mut sheet = Sheet::new("Orders");
+//! sheet.set_value(0, 0, "Customer");
+//! sheet.set_value(0, 1, "Amount");
+//! book.push_sheet(sheet);
+//!
+//! let mut db = DatabaseRange::new("Orders", CellRange::remote("Orders", 0, 0, 100, 1));
+//! db.set_display_filter_buttons(true);
+//! db.set_filter(Some(Filter::new(FilterItem::And(vec![
+//!     FilterItem::Condition(FilterCondition::new(0, FilterOperator::BeginsWith, "A")),
+//!     FilterItem::Condition(FilterCondition::number(1, FilterOperator::Greater, 100.0)),
+//! ]))));
+//! db.push_sort_key(SortKey::new(1, SortOrder::Descending));
+//! book.add_database_range(db);
+//! ```
+//!
+
+use crate::attrmap2::AttrMap2;
+use crate::xmltree::XmlTag;
+use crate::{CellRange, OdsError};
+
+/// A named cell-range used as a database table.
+#[derive(Clone, Debug)]
+pub struct DatabaseRange {
+    name: String,
+    range: CellRange,
+    contains_header: bool,
+    display_filter_buttons: bool,
+    filter: Option<Filter>,
+    sort: Vec<SortKey>,
+    attr: AttrMap2,
+    extra: Vec<XmlTag>,
+}
+
+impl DatabaseRange {
+    /// Database range. The range must contain the sheet name.
+    pub fn new<S: Into<String>>(name: S, range: CellRange) -> Self {
+        Self {
+            name: name.into(),
+            range,
+            contains_header: true,
+            display_filter_buttons: false,
+            filter: None,
+            sort: Vec::new(),
+            attr: Default::default(),
+            extra: Vec::new(),
+        }
+    }
+
+    /// Name.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Name.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// The cell-range.
+    pub fn range(&self) -> &CellRange {
+        &self.range
+    }
+
+    /// The cell-range.
+    pub fn set_range(&mut self, range: CellRange) {
+        self.range = range;
+    }
+
+    /// The first row contains the column headers. Default is true.
+    pub fn contains_header(&self) -> bool {
+        self.contains_header
+    }
+
+    /// The first row contains the column headers.
+    pub fn set_contains_header(&mut self, contains_header: bool) {
+        self.contains_header = contains_header;
+    }
+
+    /// Shows the autofilter buttons in the header row.
+    pub fn display_filter_buttons(&self) -> bool {
+        self.display_filter_buttons
+    }
+
+    /// Shows the autofilter buttons in the header row.
+    pub fn set_display_filter_buttons(&mut self, display: bool) {
+        self.display_filter_buttons = display;
+    }
+
+    /// Filter.
+    pub fn filter(&self) -> Option<&Filter> {
+        self.filter.as_ref()
+    }
+
+    /// Filter.
+    pub fn filter_mut(&mut self) -> Option<&mut Filter> {
+        self.filter.as_mut()
+    }
+
+    /// Filter. Only sets the definition, the rows are not hidden.
+    /// Rows that don't match should be set to Visibility::Filtered.
+    pub fn set_filter(&mut self, filter: Option<Filter>) {
+        self.filter = filter;
+    }
+
+    /// Sort keys.
+    pub fn sort_keys(&self) -> &Vec<SortKey> {
+        &self.sort
+    }
+
+    /// Sort keys.
+    pub fn sort_keys_mut(&mut self) -> &mut Vec<SortKey> {
+        &mut self.sort
+    }
+
+    /// Adds a sort key. Only sets the definition, the data is not sorted.
+    pub fn push_sort_key(&mut self, key: SortKey) {
+        self.sort.push(key);
+    }
+
+    /// Allows access to all other attributes of the table:database-range.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Allows access to all other attributes of the table:database-range.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+
+    /// Other content like the data source or subtotal rules.
+    /// Pass through only.
+    pub fn extra(&self) -> &Vec<XmlTag> {
+        &self.extra
+    }
+
+    /// Other content like the data source or subtotal rules.
+    pub fn extra_mut(&mut self) -> &mut Vec<XmlTag> {
+        &mut self.extra
+    }
+}
+
+/// Filter of a database range.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    item: FilterItem,
+    display_duplicates: bool,
+}
+
+impl Filter {
+    /// Filter with the given conditions.
+    pub fn new(item: FilterItem) -> Self {
+        Self {
+            item,
+            display_duplicates: true,
+        }
+    }
+
+    /// The filter conditions.
+    pub fn item(&self) -> &FilterItem {
+        &self.item
+    }
+
+    /// The filter conditions.
+    pub fn item_mut(&mut self) -> &mut FilterItem {
+        &mut self.item
+    }
+
+    /// The filter conditions.
+    pub fn set_item(&mut self, item: FilterItem) {
+        self.item = item;
+    }
+
+    /// Show rows with duplicate values. Default is true.
+    pub fn display_duplicates(&self) -> bool {
+        self.display_duplicates
+    }
+
+    /// Show rows with duplicate values.
+    pub fn set_display_duplicates(&mut self, display: bool) {
+        self.display_duplicates = display;
+    }
+}
+
+/// Combination of filter conditions.
+#[derive(Clone, Debug)]
+pub enum FilterItem {
+    /// All must match.
+    And(Vec<FilterItem>),
+    /// Any must match.
+    Or(Vec<FilterItem>),
+    /// Single condition.
+    Condition(FilterCondition),
+}
+
+/// Compares the values of one column of the database range.
+#[derive(Clone, Debug)]
+pub struct FilterCondition {
+    field: u32,
+    operator: FilterOperator,
+    value: String,
+    data_type: FilterDataType,
+    case_sensitive: bool,
+    set_items: Vec<String>,
+}
+
+impl FilterCondition {
+    /// Compares the column with a text. The field is the column index
+    /// relative to the start of the range.
+    pub fn new<S: Into<String>>(field: u32, operator: FilterOperator, value: S) -> Self {
+        Self {
+            field,
+            operator,
+            value: value.into(),
+            data_type: FilterDataType::Text,
+            case_sensitive: false,
+            set_items: Vec::new(),
+        }
+    }
+
+    /// Compares the column with a number. The field is the column index
+    /// relative to the start of the range.
+    pub fn number(field: u32, operator: FilterOperator, value: f64) -> Self {
+        Self {
+            field,
+            operator,
+            value: value.to_string(),
+            data_type: FilterDataType::Number,
+            case_sensitive: false,
+            set_items: Vec::new(),
+        }
+    }
+
+    /// Matches any of the given values. This is what an autofilter with
+    /// a selection of values uses.
+    pub fn one_of<S: Into<String>>(field: u32, values: Vec<S>) -> Self {
+        Self {
+            field,
+            operator: FilterOperator::Equal,
+            value: String::new(),
+            data_type: FilterDataType::Text,
+            case_sensitive: false,
+            set_items: values.into_iter().map(|v| v.into()).collect(),
+        }
+    }
+
+    /// Column index relative to the start of the range.
+    pub fn field(&self) -> u32 {
+        self.field
+    }
+
+    /// Column index relative to the start of the range.
+    pub fn set_field(&mut self, field: u32) {
+        self.field = field;
+    }
+
+    /// Operator.
+    pub fn operator(&self) -> FilterOperator {
+        self.operator
+    }
+
+    /// Operator.
+    pub fn set_operator(&mut self, operator: FilterOperator) {
+        self.operator = operator;
+    }
+
+    /// Value to compare with.
+    pub fn value(&self) -> &String {
+        &self.value
+    }
+
+    /// Value to compare with.
+    pub fn set_value<S: Into<String>>(&mut self, value: S) {
+        self.value = value.into();
+    }
+
+    /// Compare as text or as number.
+    pub fn data_type(&self) -> FilterDataType {
+        self.data_type
+    }
+
+    /// Compare as text or as number.
+    pub fn set_data_type(&mut self, data_type: FilterDataType) {
+        self.data_type = data_type;
+    }
+
+    /// Case sensitive comparison.
+    pub fn case_sensitive(&self) -> bool {
+        self.case_sensitive
+    }
+
+    /// Case sensitive comparison.
+    pub fn set_case_sensitive(&mut self, case_sensitive: bool) {
+        self.case_sensitive = case_sensitive;
+    }
+
+    /// Values for a multiple selection. If this is not empty the value
+    /// is ignored.
+    pub fn set_items(&self) -> &Vec<String> {
+        &self.set_items
+    }
+
+    /// Values for a multiple selection.
+    pub fn set_items_mut(&mut self) -> &mut Vec<String> {
+        &mut self.set_items
+    }
+}
+
+/// Value type for a filter condition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterDataType {
+    /// Compare as text.
+    Text,
+    /// Compare as number.
+    Number,
+}
+
+impl FilterDataType {
+    /// Value for table:data-type.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            FilterDataType::Text => "text",
+            FilterDataType::Number => "number",
+        }
+    }
+
+    /// Parses table:data-type.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "text" => Ok(FilterDataType::Text),
+            "number" => Ok(FilterDataType::Number),
+            _ => Err(OdsError::Parse(format!("unknown data-type {}", value))),
+        }
+    }
+}
+
+/// Comparison of a filter condition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum FilterOperator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    BeginsWith,
+    DoesNotBeginWith,
+    EndsWith,
+    DoesNotEndWith,
+    Contains,
+    DoesNotContain,
+    /// Regular expression.
+    Match,
+    /// Regular expression.
+    NoMatch,
+    Empty,
+    NotEmpty,
+    TopValues,
+    BottomValues,
+    TopPercent,
+    BottomPercent,
+}
+
+impl FilterOperator {
+    /// Value for table:operator.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            FilterOperator::Equal => "=",
+            FilterOperator::NotEqual => "!=",
+            FilterOperator::Less => "<",
+            FilterOperator::LessEqual => "<=",
+            FilterOperator::Greater => ">",
+            FilterOperator::GreaterEqual => ">=",
+            FilterOperator::BeginsWith => "begins-with",
+            FilterOperator::DoesNotBeginWith => "does-not-begin-with",
+            FilterOperator::EndsWith => "ends-with",
+            FilterOperator::DoesNotEndWith => "does-not-end-with",
+            FilterOperator::Contains => "contains",
+            FilterOperator::DoesNotContain => "does-not-contain",
+            FilterOperator::Match => "match",
+            FilterOperator::NoMatch => "!match",
+            FilterOperator::Empty => "empty",
+            FilterOperator::NotEmpty => "!empty",
+            FilterOperator::TopValues => "top values",
+            FilterOperator::BottomValues => "bottom values",
+            FilterOperator::TopPercent => "top percent",
+            FilterOperator::BottomPercent => "bottom percent",
+        }
+    }
+
+    /// Parses table:operator.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "=" => Ok(FilterOperator::Equal),
+            "!=" => Ok(FilterOperator::NotEqual),
+            "<" => Ok(FilterOperator::Less),
+            "<=" => Ok(FilterOperator::LessEqual),
+            ">" => Ok(FilterOperator::Greater),
+            ">=" => Ok(FilterOperator::GreaterEqual),
+            "begins-with" => Ok(FilterOperator::BeginsWith),
+            "does-not-begin-with" => Ok(FilterOperator::DoesNotBeginWith),
+            "ends-with" => Ok(FilterOperator::EndsWith),
+            "does-not-end-with" => Ok(FilterOperator::DoesNotEndWith),
+            "contains" => Ok(FilterOperator::Contains),
+            "does-not-contain" => Ok(FilterOperator::DoesNotContain),
+            "match" => Ok(FilterOperator::Match),
+            "!match" => Ok(FilterOperator::NoMatch),
+            "empty" => Ok(FilterOperator::Empty),
+            "!empty" => Ok(FilterOperator::NotEmpty),
+            "top values" => Ok(FilterOperator::TopValues),
+            "bottom values" => Ok(FilterOperator::BottomValues),
+            "top percent" => Ok(FilterOperator::TopPercent),
+            "bottom percent" => Ok(FilterOperator::BottomPercent),
+            _ => Err(OdsError::Parse(format!(
+                "unknown filter operator {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Sort order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// Sort key of a database range.
+#[derive(Clone, Debug)]
+pub struct SortKey {
+    field: u32,
+    order: SortOrder,
+    data_type: String,
+}
+
+impl SortKey {
+    /// Sorts by the column. The field is the column index relative to
+    /// the start of the range.
+    pub fn new(field: u32, order: SortOrder) -> Self {
+        Self {
+            field,
+            order,
+            data_type: "automatic".to_string(),
+        }
+    }
+
+    /// Column index relative to the start of the range.
+    pub fn field(&self) -> u32 {
+        self.field
+    }
+
+    /// Column index relative to the start of the range.
+    pub fn set_field(&mut self, field: u32) {
+        self.field = field;
+    }
+
+    /// Sort order.
+    pub fn order(&self) -> SortOrder {
+        self.order
+    }
+
+    /// Sort order.
+    pub fn set_order(&mut self, order: SortOrder) {
+        self.order = order;
+    }
+
+    /// Data type: "automatic", "text", "number" or the name of a
+    /// user defined sort list.
+    pub fn data_type(&self) -> &String {
+        &self.data_type
+    }
+
+    /// Data type.
+    pub fn set_data_type<S: Into<String>>(&mut self, data_type: S) {
+        self.data_type = data_type.into();
+    }
+}
@@ -0,0 +1,867 @@
+//!
+//! Pivot tables, called data pilot tables in ODF.
+//!
+//! A data pilot table summarizes a source cell-range and writes the
+//! result to a target cell-range. Only the definition is stored here,
+//! the result cells are not calculated. LibreOffice recalculates them
+//! with Data/Pivot Table/Refresh.
+//!
+//! ```
+//! use spreadsheet_ods::datapilot::{
+//!     DataPilotField, DataPilotFunction, DataPilotOrientation, DataPilotTable, GrandTotal,
+//! };
+//! use spreadsheet_ods::{CellRange, Sheet, WorkBook};
+//!
+//! let mut book = WorkBook::new_empty();
+//! let mut sheet = Sheet::new("Sales");
+//! sheet.set_value(0, 0, "Region");
+//! sheet.set_value(0, 1, "Product");
+//! sheet.set_value(0, 2, "Amount");
+//! book.push_sheet(sheet);
+//! book.push_sheet(Sheet::new("Pivot"));
+//!
+//! let mut pivot = DataPilotTable::new(
+//!     "Sales by region",
+//!     CellRange::remote("Sales", 0, 0, 100, 2),
+//!     CellRange::remote("Pivot", 0, 0, 10, 4),
+//! );
+//! pivot.set_grand_total(GrandTotal::Column);
+//! pivot.push_field(DataPilotField::new("Region", DataPilotOrientation::Row));
+//! pivot.push_field(DataPilotField::new("Product", DataPilotOrientation::Column));
+//! pivot.push_field(DataPilotField::data("Amount", DataPilotFunction::Sum));
+//! book.add_data_pilot_table(pivot);
+//! ```
+//!
+
+use crate::attrmap2::AttrMap2;
+use crate::database::{Filter, SortOrder};
+use crate::xmltree::XmlTag;
+use crate::{CellRange, OdsError};
+
+/// A pivot table.
+#[derive(Clone, Debug)]
+pub struct DataPilotTable {
+    name: String,
+    source: CellRange,
+    source_filter: Option<Filter>,
+    target: CellRange,
+    grand_total: GrandTotal,
+    ignore_empty_rows: bool,
+    identify_categories: bool,
+    show_filter_button: bool,
+    drill_down_on_double_click: bool,
+    fields: Vec<DataPilotField>,
+    attr: AttrMap2,
+    extra: Vec<XmlTag>,
+}
+
+impl DataPilotTable {
+    /// Pivot table for the source range. The result is written to the
+    /// target range. Both ranges must contain the sheet name.
+    pub fn new<S: Into<String>>(name: S, source: CellRange, target: CellRange) -> Self {
+        Self {
+            name: name.into(),
+            source,
+            source_filter: None,
+            target,
+            grand_total: GrandTotal::Both,
+            ignore_empty_rows: false,
+            identify_categories: false,
+            show_filter_button: true,
+            drill_down_on_double_click: true,
+            fields: Vec::new(),
+            attr: Default::default(),
+            extra: Vec::new(),
+        }
+    }
+
+    /// Name.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Name.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// Source cell-range. The first row contains the field names.
+    pub fn source(&self) -> &CellRange {
+        &self.source
+    }
+
+    /// Source cell-range. The first row contains the field names.
+    pub fn set_source(&mut self, source: CellRange) {
+        self.source = source;
+    }
+
+    /// Filter for the source data.
+    pub fn source_filter(&self) -> Option<&Filter> {
+        self.source_filter.as_ref()
+    }
+
+    /// Filter for the source data.
+    pub fn set_source_filter(&mut self, filter: Option<Filter>) {
+        self.source_filter = filter;
+    }
+
+    /// Target cell-range for the result.
+    pub fn target(&self) -> &CellRange {
+        &self.target
+    }
+
+    /// Target cell-range for the result.
+    pub fn set_target(&mut self, target: CellRange) {
+        self.target = target;
+    }
+
+    /// Grand totals for rows and/or columns. Default is both.
+    pub fn grand_total(&self) -> GrandTotal {
+        self.grand_total
+    }
+
+    /// Grand totals for rows and/or columns.
+    pub fn set_grand_total(&mut self, grand_total: GrandTotal) {
+        self.grand_total = grand_total;
+    }
+
+    /// Empty rows of the source are ignored.
+    pub fn ignore_empty_rows(&self) -> bool {
+        self.ignore_empty_rows
+    }
+
+    /// Empty rows of the source are ignored.
+    pub fn set_ignore_empty_rows(&mut self, ignore: bool) {
+        self.ignore_empty_rows = ignore;
+    }
+
+    /// Rows without a value for a category get the value of the row
+    /// above.
+    pub fn identify_categories(&self) -> bool {
+        self.identify_categories
+    }
+
+    /// Rows without a value for a category get the value of the row
+    /// above.
+    pub fn set_identify_categories(&mut self, identify: bool) {
+        self.identify_categories = identify;
+    }
+
+    /// Shows the filter button. Default is true.
+    pub fn show_filter_button(&self) -> bool {
+        self.show_filter_button
+    }
+
+    /// Shows the filter button.
+    pub fn set_show_filter_button(&mut self, show: bool) {
+        self.show_filter_button = show;
+    }
+
+    /// Double click shows the details. Default is true.
+    pub fn drill_down_on_double_click(&self) -> bool {
+        self.drill_down_on_double_click
+    }
+
+    /// Double click shows the details.
+    pub fn set_drill_down_on_double_click(&mut self, drill_down: bool) {
+        self.drill_down_on_double_click = drill_down;
+    }
+
+    /// Fields.
+    pub fn fields(&self) -> &Vec<DataPilotField> {
+        &self.fields
+    }
+
+    /// Fields.
+    pub fn fields_mut(&mut self) -> &mut Vec<DataPilotField> {
+        &mut self.fields
+    }
+
+    /// Adds a field.
+    pub fn push_field(&mut self, field: DataPilotField) {
+        self.fields.push(field);
+    }
+
+    /// Returns the first field with this source field name.
+    pub fn field(&self, name: &str) -> Option<&DataPilotField> {
+        self.fields.iter().find(|v| v.source_field_name == name)
+    }
+
+    /// Returns the first field with this source field name.
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut DataPilotField> {
+        self.fields.iter_mut().find(|v| v.source_field_name == name)
+    }
+
+    /// Allows access to all other attributes of the table:data-pilot-table.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Allows access to all other attributes of the table:data-pilot-table.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+
+    /// Other content, like database sources or the grand total labels.
+    pub fn extra(&self) -> &Vec<XmlTag> {
+        &self.extra
+    }
+
+    /// Other content, like database sources or the grand total labels.
+    pub fn extra_mut(&mut self) -> &mut Vec<XmlTag> {
+        &mut self.extra
+    }
+}
+
+/// Grand totals of a pivot table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum GrandTotal {
+    None,
+    Row,
+    Column,
+    Both,
+}
+
+impl GrandTotal {
+    /// Value for table:grand-total.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            GrandTotal::None => "none",
+            GrandTotal::Row => "row",
+            GrandTotal::Column => "column",
+            GrandTotal::Both => "both",
+        }
+    }
+
+    /// Parses table:grand-total.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "none" => Ok(GrandTotal::None),
+            "row" => Ok(GrandTotal::Row),
+            "column" => Ok(GrandTotal::Column),
+            "both" => Ok(GrandTotal::Both),
+            _ => Err(OdsError::Parse(format!("unknown grand-total {}", value))),
+        }
+    }
+}
+
+/// Where a field is used in the pivot table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum DataPilotOrientation {
+    Row,
+    Column,
+    Data,
+    /// Filter for the whole table.
+    Page,
+    Hidden,
+}
+
+impl DataPilotOrientation {
+    /// Value for table:orientation.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            DataPilotOrientation::Row => "row",
+            DataPilotOrientation::Column => "column",
+            DataPilotOrientation::Data => "data",
+            DataPilotOrientation::Page => "page",
+            DataPilotOrientation::Hidden => "hidden",
+        }
+    }
+
+    /// Parses table:orientation.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "row" => Ok(DataPilotOrientation::Row),
+            "column" => Ok(DataPilotOrientation::Column),
+            "data" => Ok(DataPilotOrientation::Data),
+            "page" => Ok(DataPilotOrientation::Page),
+            "hidden" => Ok(DataPilotOrientation::Hidden),
+            _ => Err(OdsError::Parse(format!("unknown orientation {}", value))),
+        }
+    }
+}
+
+/// Aggregate function for data fields and subtotals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum DataPilotFunction {
+    /// Sum for numbers, count otherwise.
+    Auto,
+    Average,
+    /// Counts all values.
+    Count,
+    /// Counts numeric values.
+    CountNums,
+    Max,
+    Min,
+    Product,
+    StDev,
+    StDevP,
+    Sum,
+    Var,
+    VarP,
+}
+
+impl DataPilotFunction {
+    /// Value for table:function.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            DataPilotFunction::Auto => "auto",
+            DataPilotFunction::Average => "average",
+            DataPilotFunction::Count => "count",
+            DataPilotFunction::CountNums => "countnums",
+            DataPilotFunction::Max => "max",
+            DataPilotFunction::Min => "min",
+            DataPilotFunction::Product => "product",
+            DataPilotFunction::StDev => "stdev",
+            DataPilotFunction::StDevP => "stdevp",
+            DataPilotFunction::Sum => "sum",
+            DataPilotFunction::Var => "var",
+            DataPilotFunction::VarP => "varp",
+        }
+    }
+
+    /// Parses table:function.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "auto" => Ok(DataPilotFunction::Auto),
+            "average" => Ok(DataPilotFunction::Average),
+            "count" => Ok(DataPilotFunction::Count),
+            "countnums" => Ok(DataPilotFunction::CountNums),
+            "max" => Ok(DataPilotFunction::Max),
+            "min" => Ok(DataPilotFunction::Min),
+            "product" => Ok(DataPilotFunction::Product),
+            "stdev" => Ok(DataPilotFunction::StDev),
+            "stdevp" => Ok(DataPilotFunction::StDevP),
+            "sum" => Ok(DataPilotFunction::Sum),
+            "var" => Ok(DataPilotFunction::Var),
+            "varp" => Ok(DataPilotFunction::VarP),
+            _ => Err(OdsError::Parse(format!("unknown function {}", value))),
+        }
+    }
+}
+
+/// A field of the pivot table. Refers to a column of the source range
+/// by its header.
+#[derive(Clone, Debug)]
+pub struct DataPilotField {
+    source_field_name: String,
+    orientation: DataPilotOrientation,
+    function: Option<DataPilotFunction>,
+    is_data_layout_field: bool,
+    selected_page: Option<String>,
+    show_empty: bool,
+    subtotals: Vec<DataPilotFunction>,
+    members: Vec<DataPilotMember>,
+    sort: Option<DataPilotSort>,
+    groups: Option<DataPilotGroups>,
+    attr: AttrMap2,
+    level_attr: AttrMap2,
+    level_extra: Vec<XmlTag>,
+    extra: Vec<XmlTag>,
+}
+
+impl DataPilotField {
+    /// Field for the column with this header.
+    pub fn new<S: Into<String>>(source_field_name: S, orientation: DataPilotOrientation) -> Self {
+        Self {
+            source_field_name: source_field_name.into(),
+            orientation,
+            function: None,
+            is_data_layout_field: false,
+            selected_page: None,
+            show_empty: false,
+            subtotals: Vec::new(),
+            members: Vec::new(),
+            sort: None,
+            groups: None,
+            attr: Default::default(),
+            level_attr: Default::default(),
+            level_extra: Vec::new(),
+            extra: Vec::new(),
+        }
+    }
+
+    /// Data field with an aggregate function.
+    pub fn data<S: Into<String>>(source_field_name: S, function: DataPilotFunction) -> Self {
+        let mut field = Self::new(source_field_name, DataPilotOrientation::Data);
+        field.function = Some(function);
+        field
+    }
+
+    /// Page field, that filters the whole table by one value.
+    pub fn page<S: Into<String>>(source_field_name: S, selected_page: Option<String>) -> Self {
+        let mut field = Self::new(source_field_name, DataPilotOrientation::Page);
+        field.selected_page = selected_page;
+        field
+    }
+
+    /// The pseudo field that places multiple data fields in rows or
+    /// columns. LibreOffice adds it to every pivot table.
+    pub fn data_layout(orientation: DataPilotOrientation) -> Self {
+        let mut field = Self::new("", orientation);
+        field.is_data_layout_field = true;
+        field
+    }
+
+    /// Header of the column in the source range.
+    pub fn source_field_name(&self) -> &String {
+        &self.source_field_name
+    }
+
+    /// Header of the column in the source range.
+    pub fn set_source_field_name<S: Into<String>>(&mut self, name: S) {
+        self.source_field_name = name.into();
+    }
+
+    /// Orientation.
+    pub fn orientation(&self) -> DataPilotOrientation {
+        self.orientation
+    }
+
+    /// Orientation.
+    pub fn set_orientation(&mut self, orientation: DataPilotOrientation) {
+        self.orientation = orientation;
+    }
+
+    /// Aggregate function of a data field.
+    pub fn function(&self) -> Option<DataPilotFunction> {
+        self.function
+    }
+
+    /// Aggregate function of a data field.
+    pub fn set_function(&mut self, function: Option<DataPilotFunction>) {
+        self.function = function;
+    }
+
+    /// Pseudo field for the layout of the data fields.
+    pub fn is_data_layout_field(&self) -> bool {
+        self.is_data_layout_field
+    }
+
+    /// Pseudo field for the layout of the data fields.
+    pub fn set_data_layout_field(&mut self, data_layout: bool) {
+        self.is_data_layout_field = data_layout;
+    }
+
+    /// Selected value of a page field.
+    pub fn selected_page(&self) -> Option<&String> {
+        self.selected_page.as_ref()
+    }
+
+    /// Selected value of a page field.
+    pub fn set_selected_page(&mut self, selected_page: Option<String>) {
+        self.selected_page = selected_page;
+    }
+
+    /// Shows members without data.
+    pub fn show_empty(&self) -> bool {
+        self.show_empty
+    }
+
+    /// Shows members without data.
+    pub fn set_show_empty(&mut self, show_empty: bool) {
+        self.show_empty = show_empty;
+    }
+
+    /// Subtotal functions. Empty means no subtotals.
+    pub fn subtotals(&self) -> &Vec<DataPilotFunction> {
+        &self.subtotals
+    }
+
+    /// Subtotal functions. Empty means no subtotals.
+    pub fn subtotals_mut(&mut self) -> &mut Vec<DataPilotFunction> {
+        &mut self.subtotals
+    }
+
+    /// Settings for single values of the field.
+    pub fn members(&self) -> &Vec<DataPilotMember> {
+        &self.members
+    }
+
+    /// Settings for single values of the field.
+    pub fn members_mut(&mut self) -> &mut Vec<DataPilotMember> {
+        &mut self.members
+    }
+
+    /// Adds settings for a value of the field.
+    pub fn push_member(&mut self, member: DataPilotMember) {
+        self.members.push(member);
+    }
+
+    /// Sorting of the values.
+    pub fn sort(&self) -> Option<&DataPilotSort> {
+        self.sort.as_ref()
+    }
+
+    /// Sorting of the values.
+    pub fn set_sort(&mut self, sort: Option<DataPilotSort>) {
+        self.sort = sort;
+    }
+
+    /// Grouping of the values.
+    pub fn groups(&self) -> Option<&DataPilotGroups> {
+        self.groups.as_ref()
+    }
+
+    /// Grouping of the values.
+    pub fn groups_mut(&mut self) -> Option<&mut DataPilotGroups> {
+        self.groups.as_mut()
+    }
+
+    /// Grouping of the values.
+    pub fn set_groups(&mut self, groups: Option<DataPilotGroups>) {
+        self.groups = groups;
+    }
+
+    /// Allows access to all other attributes of the table:data-pilot-field.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Allows access to all other attributes of the table:data-pilot-field.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+
+    /// Other attributes of the table:data-pilot-level.
+    pub fn level_attrmap(&self) -> &AttrMap2 {
+        &self.level_attr
+    }
+
+    /// Other attributes of the table:data-pilot-level.
+    pub fn level_attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.level_attr
+    }
+
+    /// Other content of the table:data-pilot-level, like display-info
+    /// and layout-info.
+    pub fn level_extra(&self) -> &Vec<XmlTag> {
+        &self.level_extra
+    }
+
+    /// Other content of the table:data-pilot-level.
+    pub fn level_extra_mut(&mut self) -> &mut Vec<XmlTag> {
+        &mut self.level_extra
+    }
+
+    /// Other content, like the field reference.
+    pub fn extra(&self) -> &Vec<XmlTag> {
+        &self.extra
+    }
+
+    /// Other content, like the field reference.
+    pub fn extra_mut(&mut self) -> &mut Vec<XmlTag> {
+        &mut self.extra
+    }
+}
+
+/// Settings for one value of a field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataPilotMember {
+    name: String,
+    display: bool,
+    show_details: bool,
+}
+
+impl DataPilotMember {
+    /// Visible value with details.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            display: true,
+            show_details: true,
+        }
+    }
+
+    /// Value.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Value.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// The value is shown. Hidden values are filtered.
+    pub fn display(&self) -> bool {
+        self.display
+    }
+
+    /// The value is shown. Hidden values are filtered.
+    pub fn set_display(&mut self, display: bool) {
+        self.display = display;
+    }
+
+    /// The details are expanded.
+    pub fn show_details(&self) -> bool {
+        self.show_details
+    }
+
+    /// The details are expanded.
+    pub fn set_show_details(&mut self, show_details: bool) {
+        self.show_details = show_details;
+    }
+}
+
+/// Sorting of the values of a field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataPilotSort {
+    mode: DataPilotSortMode,
+    order: SortOrder,
+}
+
+impl DataPilotSort {
+    /// Sorting.
+    pub fn new(mode: DataPilotSortMode, order: SortOrder) -> Self {
+        Self { mode, order }
+    }
+
+    /// Sort by.
+    pub fn mode(&self) -> &DataPilotSortMode {
+        &self.mode
+    }
+
+    /// Sort by.
+    pub fn set_mode(&mut self, mode: DataPilotSortMode) {
+        self.mode = mode;
+    }
+
+    /// Sort order.
+    pub fn order(&self) -> SortOrder {
+        self.order
+    }
+
+    /// Sort order.
+    pub fn set_order(&mut self, order: SortOrder) {
+        self.order = order;
+    }
+}
+
+/// What the values of a field are sorted by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataPilotSortMode {
+    /// Unsorted.
+    None,
+    /// Order of the members.
+    Manual,
+    /// By value.
+    Name,
+    /// By the result of this data field.
+    Data(String),
+}
+
+/// Grouping of the values of a field.
+///
+/// Numbers and dates are grouped by range and step, other values by
+/// explicit groups of members.
+#[derive(Clone, Debug)]
+pub struct DataPilotGroups {
+    source_field_name: String,
+    grouped_by: Option<DataPilotGroupedBy>,
+    start: Option<f64>,
+    end: Option<f64>,
+    step: Option<f64>,
+    groups: Vec<DataPilotGroup>,
+    attr: AttrMap2,
+}
+
+impl Default for DataPilotGroups {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DataPilotGroups {
+    /// Empty grouping.
+    pub fn new() -> Self {
+        Self {
+            source_field_name: Default::default(),
+            grouped_by: None,
+            start: None,
+            end: None,
+            step: None,
+            groups: Vec::new(),
+            attr: Default::default(),
+        }
+    }
+
+    /// Numeric grouping. None for start or end means automatic.
+    pub fn numeric(start: Option<f64>, end: Option<f64>, step: f64) -> Self {
+        let mut groups = Self::new();
+        groups.start = start;
+        groups.end = end;
+        groups.step = Some(step);
+        groups
+    }
+
+    /// Date grouping with an automatic range.
+    pub fn date(grouped_by: DataPilotGroupedBy) -> Self {
+        let mut groups = Self::new();
+        groups.grouped_by = Some(grouped_by);
+        groups.attr.set_attr("table:date-start", "auto".to_string());
+        groups.attr.set_attr("table:date-end", "auto".to_string());
+        groups
+    }
+
+    /// Field whose values are grouped. If this is empty, it's the field
+    /// that contains the grouping.
+    pub fn source_field_name(&self) -> &String {
+        &self.source_field_name
+    }
+
+    /// Field whose values are grouped. If this is empty, it's the field
+    /// that contains the grouping.
+    pub fn set_source_field_name<S: Into<String>>(&mut self, name: S) {
+        self.source_field_name = name.into();
+    }
+
+    /// Date grouping.
+    pub fn grouped_by(&self) -> Option<DataPilotGroupedBy> {
+        self.grouped_by
+    }
+
+    /// Date grouping.
+    pub fn set_grouped_by(&mut self, grouped_by: Option<DataPilotGroupedBy>) {
+        self.grouped_by = grouped_by;
+    }
+
+    /// Start of a numeric grouping. None is automatic.
+    pub fn start(&self) -> Option<f64> {
+        self.start
+    }
+
+    /// Start of a numeric grouping. None is automatic.
+    pub fn set_start(&mut self, start: Option<f64>) {
+        self.start = start;
+    }
+
+    /// End of a numeric grouping. None is automatic.
+    pub fn end(&self) -> Option<f64> {
+        self.end
+    }
+
+    /// End of a numeric grouping. None is automatic.
+    pub fn set_end(&mut self, end: Option<f64>) {
+        self.end = end;
+    }
+
+    /// Step of the grouping.
+    pub fn step(&self) -> Option<f64> {
+        self.step
+    }
+
+    /// Step of the grouping.
+    pub fn set_step(&mut self, step: Option<f64>) {
+        self.step = step;
+    }
+
+    /// Explicit groups.
+    pub fn groups(&self) -> &Vec<DataPilotGroup> {
+        &self.groups
+    }
+
+    /// Explicit groups.
+    pub fn groups_mut(&mut self) -> &mut Vec<DataPilotGroup> {
+        &mut self.groups
+    }
+
+    /// Adds an explicit group.
+    pub fn push_group(&mut self, group: DataPilotGroup) {
+        self.groups.push(group);
+    }
+
+    /// Allows access to all other attributes of the
+    /// table:data-pilot-groups, like date-start and date-end.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Allows access to all other attributes of the
+    /// table:data-pilot-groups, like date-start and date-end.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+}
+
+/// Unit of a date grouping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum DataPilotGroupedBy {
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Months,
+    Quarters,
+    Years,
+}
+
+impl DataPilotGroupedBy {
+    /// Value for table:grouped-by.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            DataPilotGroupedBy::Seconds => "seconds",
+            DataPilotGroupedBy::Minutes => "minutes",
+            DataPilotGroupedBy::Hours => "hours",
+            DataPilotGroupedBy::Days => "days",
+            DataPilotGroupedBy::Months => "months",
+            DataPilotGroupedBy::Quarters => "quarters",
+            DataPilotGroupedBy::Years => "years",
+        }
+    }
+
+    /// Parses table:grouped-by.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "seconds" => Ok(DataPilotGroupedBy::Seconds),
+            "minutes" => Ok(DataPilotGroupedBy::Minutes),
+            "hours" => Ok(DataPilotGroupedBy::Hours),
+            "days" => Ok(DataPilotGroupedBy::Days),
+            "months" => Ok(DataPilotGroupedBy::Months),
+            "quarters" => Ok(DataPilotGroupedBy::Quarters),
+            "years" => Ok(DataPilotGroupedBy::Years),
+            _ => Err(OdsError::Parse(format!("unknown grouped-by {}", value))),
+        }
+    }
+}
+
+/// A named group of values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataPilotGroup {
+    name: String,
+    members: Vec<String>,
+}
+
+impl DataPilotGroup {
+    /// Group of these values.
+    pub fn new<S: Into<String>>(name: S, members: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            members,
+        }
+    }
+
+    /// Name of the group.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Name of the group.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// Values in the group.
+    pub fn members(&self) -> &Vec<String> {
+        &self.members
+    }
+
+    /// Values in the group.
+    pub fn members_mut(&mut self) -> &mut Vec<String> {
+        &mut self.members
+    }
+}
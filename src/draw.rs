@@ -0,0 +1,237 @@
+//!
+//! Images anchored to a cell or to the page.
+//!
+//! The image data is stored in the Pictures/ directory of the package.
+//! Each image is written as a draw:frame within the table-cell it is
+//! anchored to. Images anchored to the page are written to the
+//! table:shapes of the sheet.
+//!
+//! ```
+//! use spreadsheet_ods::style::GraphicStyle;
+//! use spreadsheet_ods::{Length, Sheet, WorkBook};
+//!
+//! let mut book = WorkBook::new_empty();
+//! let gr = book.add_graphicstyle(GraphicStyle::new_empty());
+//!
+//! let png = std::fs::read("tests/pixel.png").unwrap();
+//!
+//! let mut sheet = Sheet::new("one");
+//! let image = sheet.add_image(2, 1, Length::Cm(3.0), Length::Cm(2.0), png, "image/png");
+//! image.set_style(&gr);
+//! book.push_sheet(sheet);
+//! ```
+//!
+
+use sha1::{Digest, Sha1};
+
+use crate::attrmap2::AttrMap2;
+use crate::style::GraphicStyleRef;
+use crate::Length;
+
+/// An image anchored to a cell.
+#[derive(Clone, Debug, Default)]
+pub struct Image {
+    name: Option<String>,
+    style: Option<String>,
+    x: Length,
+    y: Length,
+    width: Length,
+    height: Length,
+    href: String,
+    mime_type: String,
+    data: Vec<u8>,
+    title: Option<String>,
+    desc: Option<String>,
+    attr: AttrMap2,
+}
+
+impl Image {
+    /// Creates an image from the raw data. The mime-type must match the
+    /// data, e.g. "image/png".
+    pub fn new<S: Into<String>>(mime_type: S, data: Vec<u8>) -> Self {
+        let mime_type = mime_type.into();
+        let href = picture_href(&mime_type, &data);
+        Self {
+            name: None,
+            style: None,
+            x: Length::Cm(0.0),
+            y: Length::Cm(0.0),
+            width: Default::default(),
+            height: Default::default(),
+            href,
+            mime_type,
+            data,
+            title: None,
+            desc: None,
+            attr: Default::default(),
+        }
+    }
+
+    /// Name of the drawing object.
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    /// Name of the drawing object.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = Some(name.into());
+    }
+
+    /// Graphic style.
+    pub fn style(&self) -> Option<&String> {
+        self.style.as_ref()
+    }
+
+    /// Graphic style.
+    pub fn set_style(&mut self, style: &GraphicStyleRef) {
+        self.style = Some(style.to_string());
+    }
+
+    /// Removes the style.
+    pub fn clear_style(&mut self) {
+        self.style = None;
+    }
+
+    /// Position relative to the anchor cell.
+    pub fn position(&self) -> (Length, Length) {
+        (self.x, self.y)
+    }
+
+    /// Position relative to the anchor cell.
+    pub fn set_position(&mut self, x: Length, y: Length) {
+        self.x = x;
+        self.y = y;
+    }
+
+    /// Size as (width, height).
+    pub fn size(&self) -> (Length, Length) {
+        (self.width, self.height)
+    }
+
+    /// Size of the image.
+    pub fn set_size(&mut self, width: Length, height: Length) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Path of the image data within the package.
+    pub fn href(&self) -> &str {
+        self.href.as_str()
+    }
+
+    /// Path of the image data within the package. Must be unique for
+    /// different data.
+    pub fn set_href<S: Into<String>>(&mut self, href: S) {
+        self.href = href.into();
+    }
+
+    /// Mime-type of the data.
+    pub fn mime_type(&self) -> &str {
+        self.mime_type.as_str()
+    }
+
+    /// Mime-type of the data.
+    pub fn set_mime_type<S: Into<String>>(&mut self, mime_type: S) {
+        self.mime_type = mime_type.into();
+    }
+
+    /// Image data.
+    pub fn data(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    /// Replaces the image data. This assigns a new path within the package.
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        self.href = picture_href(&self.mime_type, &data);
+        self.data = data;
+    }
+
+    /// Short text for accessibility.
+    pub fn title(&self) -> Option<&String> {
+        self.title.as_ref()
+    }
+
+    /// Short text for accessibility.
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
+
+    /// Description for accessibility.
+    pub fn desc(&self) -> Option<&String> {
+        self.desc.as_ref()
+    }
+
+    /// Description for accessibility.
+    pub fn set_desc(&mut self, desc: Option<String>) {
+        self.desc = desc;
+    }
+
+    /// Allows access to all other attributes of the draw:frame.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Allows access to all other attributes of the draw:frame.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+
+    // Sets the data as read from the package, keeping the path.
+    pub(crate) fn set_data_raw(&mut self, data: Vec<u8>) {
+        self.data = data;
+    }
+}
+
+// Path for the image data. The same data gets the same path.
+pub(crate) fn picture_href(mime_type: &str, data: &[u8]) -> String {
+    let ext = match mime_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "image/bmp" => "bmp",
+        "image/tiff" => "tif",
+        "image/webp" => "webp",
+        _ => "bin",
+    };
+
+    let mut href = String::from("Pictures/");
+    for b in Sha1::digest(data) {
+        href.push_str(&format!("{:02x}", b));
+    }
+    href.push('.');
+    href.push_str(ext);
+    href
+}
+
+// Mime-type for image data without one. Uses the extension of the path
+// or the signature of the data.
+pub(crate) fn guess_mime_type(href: &str, data: &[u8]) -> &'static str {
+    let ext = href.rsplit_once('.').map(|v| v.1.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("png") => return "image/png",
+        Some("jpg") | Some("jpeg") => return "image/jpeg",
+        Some("gif") => return "image/gif",
+        Some("svg") => return "image/svg+xml",
+        Some("bmp") => return "image/bmp",
+        Some("tif") | Some("tiff") => return "image/tiff",
+        Some("webp") => return "image/webp",
+        _ => {}
+    }
+
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if data.starts_with(b"\xff\xd8\xff") {
+        "image/jpeg"
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if data.starts_with(b"BM") {
+        "image/bmp"
+    } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        "image/tiff"
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(&b"WEBP"[..]) {
+        "image/webp"
+    } else {
+        "application/octet-stream"
+    }
+}
@@ -23,6 +23,7 @@ pub enum OdsError {
     SystemTime(std::time::SystemTimeError),
     Nom(nom::error::Error<String>),
     CellRef(String),
+    Password(String),
 }
 
 impl Display for OdsError {
@@ -43,6 +44,7 @@ impl Display for OdsError {
             OdsError::Nom(e) => write!(f, "Nom {}", e)?,
             OdsError::Escape(s) => write!(f, "Escape {}", s)?,
             OdsError::CellRef(e) => write!(f, "CellRef {:?}", e)?,
+            OdsError::Password(e) => write!(f, "Password {}", e)?,
         }
 
         Ok(())
@@ -67,6 +69,7 @@ impl std::error::Error for OdsError {
             OdsError::Nom(e) => Some(e),
             OdsError::Escape(_) => None,
             OdsError::CellRef(_) => None,
+            OdsError::Password(_) => None,
         }
     }
 }
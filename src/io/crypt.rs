@@ -0,0 +1,290 @@
+//!
+//! Encryption of package files as defined in ODF 1.2, part 3, chapter 3.4.
+//!
+//! Supported for decryption are AES-CBC and the legacy Blowfish CFB, both
+//! with PBKDF2 key derivation and a SHA-1 or SHA-256 start key.
+//!
+//! Encryption always uses AES-256-CBC with a SHA-256 start key, the same
+//! as LibreOffice does.
+//!
+
+use std::io::{Read, Write};
+
+use aes::{Aes128, Aes192, Aes256};
+use blowfish::Blowfish;
+use cbc::cipher::block_padding::{NoPadding, Pkcs7};
+use cbc::cipher::{
+    AsyncStreamCipher, BlockDecryptMut, BlockEncryptMut, InnerIvInit, KeyInit, KeyIvInit,
+};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use pbkdf2::pbkdf2_hmac;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::OdsError;
+
+const AES128_CBC: &str = "http://www.w3.org/2001/04/xmlenc#aes128-cbc";
+const AES192_CBC: &str = "http://www.w3.org/2001/04/xmlenc#aes192-cbc";
+const AES256_CBC: &str = "http://www.w3.org/2001/04/xmlenc#aes256-cbc";
+const BLOWFISH_CFB: &str = "Blowfish CFB";
+
+const SHA1: &str = "SHA1";
+const SHA1_URN: &str = "http://www.w3.org/2000/09/xmldsig#sha1";
+const SHA256: &str = "SHA256";
+const SHA256_URN: &str = "http://www.w3.org/2000/09/xmldsig#sha256";
+const SHA256_URN2: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+
+const SHA1_1K: &str = "SHA1/1K";
+const SHA1_1K_URN: &str = "urn:oasis:names:tc:opendocument:xmlns:manifest:1.0#sha1-1k";
+const SHA256_1K: &str = "SHA256/1K";
+const SHA256_1K_URN: &str = "urn:oasis:names:tc:opendocument:xmlns:manifest:1.0#sha256-1k";
+
+const PBKDF2: &str = "PBKDF2";
+
+// Same as LibreOffice.
+const ITERATION_COUNT: u32 = 100_000;
+
+/// Encryption data for one file in the package.
+/// The defaults are the ones from the specification.
+#[derive(Clone, Debug)]
+pub(crate) struct EncryptionData {
+    pub(crate) checksum_type: String,
+    pub(crate) checksum: Vec<u8>,
+    pub(crate) algorithm: String,
+    pub(crate) iv: Vec<u8>,
+    pub(crate) start_key_generation: String,
+    pub(crate) start_key_size: usize,
+    pub(crate) key_derivation: String,
+    pub(crate) key_size: usize,
+    pub(crate) iteration_count: u32,
+    pub(crate) salt: Vec<u8>,
+}
+
+impl Default for EncryptionData {
+    fn default() -> Self {
+        Self {
+            checksum_type: Default::default(),
+            checksum: Default::default(),
+            algorithm: Default::default(),
+            iv: Default::default(),
+            start_key_generation: SHA1.to_string(),
+            start_key_size: 20,
+            key_derivation: PBKDF2.to_string(),
+            key_size: 16,
+            iteration_count: 0,
+            salt: Default::default(),
+        }
+    }
+}
+
+/// Derives the keys for decryption.
+///
+/// Files with the same salt and parameters share a key, so it is derived
+/// only once.
+pub(crate) struct DecryptionKeys<'a> {
+    password: &'a str,
+    keys: Vec<(EncryptionData, Vec<u8>)>,
+}
+
+impl<'a> DecryptionKeys<'a> {
+    pub(crate) fn new(password: &'a str) -> Self {
+        Self {
+            password,
+            keys: Vec::new(),
+        }
+    }
+
+    fn key(&mut self, enc: &EncryptionData) -> Result<&[u8], OdsError> {
+        let found = self.keys.iter().position(|(v, _)| {
+            v.start_key_generation == enc.start_key_generation
+                && v.key_derivation == enc.key_derivation
+                && v.salt == enc.salt
+                && v.iteration_count == enc.iteration_count
+                && v.key_size == enc.key_size
+        });
+        let idx = match found {
+            Some(idx) => idx,
+            None => {
+                let key = derive_key(enc, self.password)?;
+                self.keys.push((enc.clone(), key));
+                self.keys.len() - 1
+            }
+        };
+        Ok(&self.keys[idx].1)
+    }
+
+    /// Decrypts and inflates the data of one file.
+    pub(crate) fn decrypt(
+        &mut self,
+        enc: &EncryptionData,
+        data: &[u8],
+    ) -> Result<Vec<u8>, OdsError> {
+        let key = self.key(enc)?;
+        decrypt(enc, key, data)
+    }
+}
+
+fn derive_key(enc: &EncryptionData, password: &str) -> Result<Vec<u8>, OdsError> {
+    let start_key = match enc.start_key_generation.as_str() {
+        SHA1 | SHA1_URN => Sha1::digest(password.as_bytes()).to_vec(),
+        SHA256 | SHA256_URN | SHA256_URN2 => Sha256::digest(password.as_bytes()).to_vec(),
+        v => {
+            return Err(OdsError::Ods(format!(
+                "Unsupported start key generation {}",
+                v
+            )))
+        }
+    };
+
+    if enc.key_derivation != PBKDF2 {
+        return Err(OdsError::Ods(format!(
+            "Unsupported key derivation {}",
+            enc.key_derivation
+        )));
+    }
+    let mut key = vec![0u8; enc.key_size];
+    pbkdf2_hmac::<Sha1>(&start_key, &enc.salt, enc.iteration_count, &mut key);
+
+    Ok(key)
+}
+
+fn decrypt(enc: &EncryptionData, key: &[u8], data: &[u8]) -> Result<Vec<u8>, OdsError> {
+    let plain = match enc.algorithm.as_str() {
+        AES128_CBC => decrypt_cbc::<Aes128>(key, &enc.iv, data)?,
+        AES192_CBC => decrypt_cbc::<Aes192>(key, &enc.iv, data)?,
+        AES256_CBC => decrypt_cbc::<Aes256>(key, &enc.iv, data)?,
+        BLOWFISH_CFB => decrypt_cfb(key, &enc.iv, data)?,
+        v => return Err(OdsError::Ods(format!("Unsupported encryption {}", v))),
+    };
+
+    // The checksum is calculated before inflating.
+    let checksum = match enc.checksum_type.as_str() {
+        "" => None,
+        SHA1 => Some(Sha1::digest(&plain).to_vec()),
+        SHA1_1K | SHA1_1K_URN => Some(Sha1::digest(first_1k(&plain)).to_vec()),
+        SHA256_1K | SHA256_1K_URN => Some(Sha256::digest(first_1k(&plain)).to_vec()),
+        v => return Err(OdsError::Ods(format!("Unsupported checksum {}", v))),
+    };
+    if let Some(checksum) = checksum {
+        if checksum != enc.checksum {
+            return Err(OdsError::Password("Wrong password.".to_string()));
+        }
+    }
+
+    let mut buf = Vec::new();
+    DeflateDecoder::new(plain.as_slice())
+        .read_to_end(&mut buf)
+        .map_err(|_| OdsError::Password("Wrong password.".to_string()))?;
+
+    Ok(buf)
+}
+
+fn first_1k(data: &[u8]) -> &[u8] {
+    &data[..data.len().min(1024)]
+}
+
+// The padding is defined by xmlenc. The last byte gives the length of the
+// padding, the rest of the padding is arbitrary.
+fn decrypt_cbc<C>(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, OdsError>
+where
+    C: BlockDecryptMut + cbc::cipher::BlockCipher + KeyInit,
+{
+    let dec = cbc::Decryptor::<C>::new_from_slices(key, iv)
+        .map_err(|_| OdsError::Ods("Invalid key or iv length.".to_string()))?;
+
+    let mut buf = data.to_vec();
+    let len = dec
+        .decrypt_padded_mut::<NoPadding>(&mut buf)
+        .map_err(|_| OdsError::Ods("Invalid length of encrypted data.".to_string()))?
+        .len();
+
+    let pad = match buf.last() {
+        Some(v) => *v as usize,
+        None => 0,
+    };
+    if pad == 0 || pad > 16 || pad > len {
+        return Err(OdsError::Password("Wrong password.".to_string()));
+    }
+    buf.truncate(len - pad);
+
+    Ok(buf)
+}
+
+fn decrypt_cfb(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>, OdsError> {
+    let cipher = Blowfish::new_from_slice(key)
+        .map_err(|_| OdsError::Ods("Invalid key length.".to_string()))?;
+    let dec = cfb_mode::Decryptor::<Blowfish>::inner_iv_slice_init(cipher, iv)
+        .map_err(|_| OdsError::Ods("Invalid iv length.".to_string()))?;
+
+    let mut buf = data.to_vec();
+    dec.decrypt(&mut buf);
+
+    Ok(buf)
+}
+
+/// Key for encrypting the files of one package.
+///
+/// The key is derived only once, each file gets its own iv.
+pub(crate) struct EncryptionKey {
+    key: Vec<u8>,
+    start_key_size: usize,
+    salt: Vec<u8>,
+}
+
+impl EncryptionKey {
+    /// Derives a new key with a random salt.
+    pub(crate) fn new(password: &str) -> Result<Self, OdsError> {
+        let salt = random_bytes(16)?;
+        let start_key = Sha256::digest(password.as_bytes());
+        let mut key = vec![0u8; 32];
+        pbkdf2_hmac::<Sha1>(&start_key, &salt, ITERATION_COUNT, &mut key);
+
+        Ok(Self {
+            key,
+            start_key_size: start_key.len(),
+            salt,
+        })
+    }
+
+    /// Deflates and encrypts the data of one file.
+    pub(crate) fn encrypt(&self, data: &[u8]) -> Result<(EncryptionData, Vec<u8>), OdsError> {
+        let mut deflate = DeflateEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(data)?;
+        let plain = deflate.finish()?;
+
+        let iv = random_bytes(16)?;
+        let enc = cbc::Encryptor::<Aes256>::new_from_slices(&self.key, &iv)
+            .map_err(|_| OdsError::Ods("Invalid key or iv length.".to_string()))?;
+        let mut buf = plain.clone();
+        buf.resize(plain.len() + 16, 0);
+        let len = enc
+            .encrypt_padded_mut::<Pkcs7>(&mut buf, plain.len())
+            .map_err(|_| OdsError::Ods("Invalid length of data.".to_string()))?
+            .len();
+        buf.truncate(len);
+
+        let data = EncryptionData {
+            checksum_type: SHA256_1K_URN.to_string(),
+            checksum: Sha256::digest(first_1k(&plain)).to_vec(),
+            algorithm: AES256_CBC.to_string(),
+            iv,
+            start_key_generation: SHA256_URN.to_string(),
+            start_key_size: self.start_key_size,
+            key_derivation: PBKDF2.to_string(),
+            key_size: self.key.len(),
+            iteration_count: ITERATION_COUNT,
+            salt: self.salt.clone(),
+        };
+
+        Ok((data, buf))
+    }
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, OdsError> {
+    let mut buf = vec![0u8; len];
+    getrandom::getrandom(&mut buf)
+        .map_err(|e| OdsError::Ods(format!("No random data available: {}", e)))?;
+    Ok(buf)
+}
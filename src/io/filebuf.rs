@@ -57,6 +57,33 @@ impl FileBuf {
         false
     }
 
+    pub(crate) fn get_file<S: AsRef<str>>(&self, name: S) -> Option<&[u8]> {
+        for it in &self.buf {
+            if let FileBufEntry::File(n, data) = it {
+                if n == name.as_ref() {
+                    return Some(data.as_slice());
+                }
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn remove<S: AsRef<str>>(&mut self, name: S) {
+        self.buf.retain(|v| match v {
+            FileBufEntry::Dir(_) => true,
+            FileBufEntry::File(n, _) => n != name.as_ref(),
+        });
+    }
+
+    // Removes a directory and everything within. The name must end with a '/'.
+    pub(crate) fn remove_dir<S: AsRef<str>>(&mut self, name: S) {
+        self.buf.retain(|v| match v {
+            FileBufEntry::Dir(n) => !n.starts_with(name.as_ref()),
+            FileBufEntry::File(n, _) => !n.starts_with(name.as_ref()),
+        });
+    }
+
     pub(crate) fn push_dir<S: Into<String>>(&mut self, dir: S) {
         self.buf.push(FileBufEntry::Dir(dir.into()));
     }
@@ -1,9 +1,9 @@
+pub(crate) mod crypt;
 pub(crate) mod filebuf;
 pub(crate) mod parse;
 pub(crate) mod read;
 pub(crate) mod write;
 
-mod tmp2zip;
 mod xmlwriter;
 mod zip_out;
 
@@ -4,8 +4,11 @@
 //! For many cases this omits the transformation to a &str
 
 use crate::{OdsError, Visibility};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
 use chrono::Duration;
 use chrono::NaiveDateTime;
+use color::Rgb;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::digit1;
@@ -34,6 +37,13 @@ pub(crate) fn parse_currency(input: &[u8]) -> Result<String, OdsError> {
     Ok(String::from_utf8_lossy(input).to_string())
 }
 
+/// Parse base64 encoded binary data.
+pub(crate) fn parse_base64(input: &[u8]) -> Result<Vec<u8>, OdsError> {
+    STANDARD
+        .decode(input)
+        .map_err(|e| OdsError::Parse(e.to_string()))
+}
+
 /// Parse a bool.
 pub(crate) fn parse_bool(input: &[u8]) -> Result<bool, OdsError> {
     Ok(token_bool(input)?.1)
@@ -64,6 +74,19 @@ pub(crate) fn parse_f64(input: &[u8]) -> Result<f64, OdsError> {
     Ok(token_float(input)?.1)
 }
 
+/// Parse a color "#rrggbb".
+pub(crate) fn parse_color(input: &[u8]) -> Result<Rgb<u8>, OdsError> {
+    let s = from_utf8(input)?;
+    match s
+        .strip_prefix('#')
+        .filter(|v| v.len() == 6)
+        .and_then(|v| u32::from_str_radix(v, 16).ok())
+    {
+        Some(v) => Ok(Rgb::new((v >> 16) as u8, (v >> 8) as u8, v as u8)),
+        None => Err(OdsError::Parse(format!("invalid color {}", s))),
+    }
+}
+
 /// Parse a XML Schema datetime.
 pub(crate) fn parse_datetime(input: &[u8]) -> Result<NaiveDateTime, OdsError> {
     Ok(token_datetime(input)?.1)
@@ -263,11 +286,13 @@ fn token_duration(input: &[u8]) -> IResult<&[u8], Duration> {
         eof,
     )(input)?;
 
+    // Years and months have no fixed length and are ignored.
+    let days = result.3.unwrap_or(0);
+    let seconds = days * 86400 + result.5 * 3600 + result.6 * 60 + result.7 .0;
     let result = if let Some(nanos) = result.7 .1 {
-        Duration::seconds(result.5 * 3600 + result.6 * 60 + result.7 .0)
-            + Duration::nanoseconds(nanos)
+        Duration::seconds(seconds) + Duration::nanoseconds(nanos)
     } else {
-        Duration::seconds(result.5 * 3600 + result.6 * 60 + result.7 .0)
+        Duration::seconds(seconds)
     };
 
     Ok((input, result))
@@ -352,6 +377,7 @@ mod tests {
             parse_duration(b"PT12H12M12.223S")?.num_milliseconds(),
             43932223
         );
+        assert_eq!(parse_duration(b"P3DT2H10M34S")?.num_seconds(), 267034);
         Ok(())
     }
 
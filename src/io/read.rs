@@ -1,39 +1,71 @@
+use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
+use std::fmt;
 use std::fs::File;
-use std::io::{BufReader, Cursor, Read, Seek, Write};
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, Write};
+use std::mem;
 use std::path::Path;
 
-use chrono::{Duration, NaiveDateTime};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use color::Rgb;
 use quick_xml::events::{BytesStart, Event};
 use zip::read::ZipFile;
 use zip::ZipArchive;
 
+use crate::annotation::Annotation;
 use crate::attrmap2::AttrMap2;
+use crate::chart::{Chart, ChartType, LegendPosition, Series};
+use crate::condformat::{
+    ColorScale, ConditionRule, ConditionalFormat, DataBar, DateIs, DateType, FormatCondition,
+    FormatEntry, FormatEntryType, IconSet,
+};
 use crate::condition::{Condition, ValueCondition};
 use crate::config::{Config, ConfigItem, ConfigItemType, ConfigValue};
+use crate::database::{
+    DatabaseRange, Filter, FilterCondition, FilterDataType, FilterItem, FilterOperator, SortKey,
+    SortOrder,
+};
+use crate::datapilot::{
+    DataPilotField, DataPilotFunction, DataPilotGroup, DataPilotGroupedBy, DataPilotGroups,
+    DataPilotMember, DataPilotOrientation, DataPilotSort, DataPilotSortMode, DataPilotTable,
+    GrandTotal,
+};
+use crate::draw::{guess_mime_type, Image};
 use crate::ds::bufstack::BufStack;
 use crate::ds::detach::Detach;
 use crate::error::OdsError;
 use crate::format::{FormatPart, FormatPartType, ValueFormatTrait};
+use crate::io::crypt::{DecryptionKeys, EncryptionData};
+use crate::io::filebuf::FileBuf;
 use crate::io::parse::{
-    parse_bool, parse_currency, parse_datetime, parse_duration, parse_f64, parse_i16, parse_i32,
-    parse_i64, parse_u32, parse_visibility,
+    parse_base64, parse_bool, parse_color, parse_currency, parse_datetime, parse_duration,
+    parse_f64, parse_i16, parse_i32, parse_i64, parse_u32, parse_visibility,
 };
 use crate::io::{DUMP_UNUSED, DUMP_XML};
-use crate::refs::{parse_cellranges, parse_cellref};
+use crate::metadata::{MetaValue, Metadata};
+use crate::named::{NamedExpression, NamedRange};
+use crate::protection::{ProtectionOptions, SheetProtection};
+use crate::refs::{parse_cellrange, parse_cellranges, parse_cellref};
+use crate::scenario::Scenario;
 use crate::style::stylemap::StyleMap;
 use crate::style::tabstop::TabStop;
 use crate::style::{
-    ColStyle, FontFaceDecl, GraphicStyle, HeaderFooter, MasterPage, PageStyle, ParagraphStyle,
-    RowStyle, StyleOrigin, StyleUse, TableStyle, TextStyle,
+    CellStyleRef, ColStyle, FontFaceDecl, GraphicStyle, GraphicStyleRef, HeaderFooter, MasterPage,
+    PageStyle, ParagraphStyle, ParseStyleAttr, RowStyle, StyleOrigin, StyleUse, TableStyle,
+    TextStyle,
 };
 use crate::text::{TextP, TextTag};
+use crate::trackedchanges::{
+    AcceptanceState, ChangeAddress, ChangeCell, ChangeDeletion, ChangeInfo, ChangeKind,
+    ChangeRange, ChangeType, TrackedChange, TrackedChanges,
+};
 use crate::validation::{MessageType, Validation, ValidationError, ValidationHelp};
-use crate::xmltree::XmlTag;
+use crate::xmltree::{XmlContent, XmlTag};
 use crate::{
-    CellData, CellStyle, ColRange, Length, RowRange, Sheet, SplitMode, Value, ValueFormatBoolean,
-    ValueFormatCurrency, ValueFormatDateTime, ValueFormatNumber, ValueFormatPercentage,
-    ValueFormatText, ValueFormatTimeDuration, ValueType, Visibility, WorkBook,
+    add_group, CellContentRef, CellData, CellRange, CellStyle, ColRange, Grouped, Length,
+    RowRange, Sheet, SplitMode, Value, ValueFormatBoolean, ValueFormatCurrency,
+    ValueFormatDateTime, ValueFormatNumber, ValueFormatPercentage, ValueFormatText,
+    ValueFormatTimeDuration, ValueType, Visibility, WorkBook,
 };
 use quick_xml::events::attributes::Attribute;
 use std::borrow::Cow;
@@ -42,201 +74,295 @@ use std::str::from_utf8;
 /// Reads an ODS-file from a buffer
 pub fn read_ods_buf(buf: &[u8]) -> Result<WorkBook, OdsError> {
     let zip = ZipArchive::new(Cursor::new(buf))?;
-    read_ods_impl(zip)
+    read_ods_impl(zip, None, &ReadOptions::default())
 }
 
 /// Reads an ODS-file.
 pub fn read_ods<P: AsRef<Path>>(path: P) -> Result<WorkBook, OdsError> {
     let file = File::open(path.as_ref())?;
     let zip = ZipArchive::new(file)?;
-    read_ods_impl(zip)
+    read_ods_impl(zip, None, &ReadOptions::default())
 }
 
-/// Reads an ODS-file.
-fn read_ods_impl<R: Read + Seek>(mut zip: ZipArchive<R>) -> Result<WorkBook, OdsError> {
-    let mut book = WorkBook::new_empty();
-    let mut bufstack = BufStack::new();
+/// Reads an ODS-file from any seekable source.
+pub fn read_ods_from<R: Read + Seek>(read: R) -> Result<WorkBook, OdsError> {
+    let zip = ZipArchive::new(read)?;
+    read_ods_impl(zip, None, &ReadOptions::default())
+}
 
-    read_content(&mut bufstack, &mut book, &mut zip.by_name("content.xml")?)?;
-    read_styles(&mut bufstack, &mut book, &mut zip.by_name("styles.xml")?)?;
-    // may not exist.
-    if let Ok(mut z) = zip.by_name("settings.xml") {
-        read_settings(&mut bufstack, &mut book, &mut z)?;
-    } else {
-        book.config = default_settings();
-    }
+/// Reads an encrypted ODS-file.
+///
+/// Supported is the encryption of ODF 1.2 with AES-CBC and the older
+/// Blowfish CFB, which is what LibreOffice uses when saving with a password.
+///
+/// The manifest is not kept, it is rewritten without encryption when
+/// the workbook is written.
+pub fn read_ods_with_password<P: AsRef<Path>>(
+    path: P,
+    password: &str,
+) -> Result<WorkBook, OdsError> {
+    let file = File::open(path.as_ref())?;
+    let zip = ZipArchive::new(file)?;
+    read_ods_impl(zip, Some(password), &ReadOptions::default())
+}
 
-    // read all extras.
-    read_filebuf(&mut book, &mut zip)?;
+/// Reads an encrypted ODS-file from a buffer.
+pub fn read_ods_buf_with_password(buf: &[u8], password: &str) -> Result<WorkBook, OdsError> {
+    let zip = ZipArchive::new(Cursor::new(buf))?;
+    read_ods_impl(zip, Some(password), &ReadOptions::default())
+}
 
-    // We do some data duplication here, to make everything easier to use.
-    calc_derived(&mut book)?;
+/// Options for reading only parts of an ODS-file.
+///
+/// By default everything is read, the same as [read_ods]. For bulk data
+/// ingestion most of the styles and settings are not needed, and skipping
+/// them saves time and memory.
+///
+/// References to anything skipped are removed from the sheets, so the
+/// resulting workbook can still be written.
+///
+/// ```
+/// use spreadsheet_ods::{OdsError, ReadOptions};
+///
+/// let book = ReadOptions::new()
+///     .values_only()
+///     .sheets(["Lieferanten"])
+///     .read_ods("tests/orders.ods")?;
+/// assert_eq!(book.num_sheets(), 1);
+/// # Ok::<(), OdsError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    skip_styles: bool,
+    skip_formats: bool,
+    skip_validations: bool,
+    skip_settings: bool,
+    keep_extra_xml: bool,
+    sheets: Option<Vec<String>>,
+}
 
-    Ok(book)
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            skip_styles: false,
+            skip_formats: false,
+            skip_validations: false,
+            skip_settings: false,
+            keep_extra_xml: true,
+            sheets: None,
+        }
+    }
 }
 
-// Loads all unprocessed files as byte blobs into a buffer.
-fn read_filebuf<R: Read + Seek>(
-    book: &mut WorkBook,
-    zip: &mut ZipArchive<R>,
-) -> Result<(), OdsError> {
-    for idx in 0..zip.len() {
-        let mut ze = zip.by_index(idx)?;
+impl ReadOptions {
+    /// Reads everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        // These three are always interpreted and rewritten from scratch.
-        // They have their own mechanism to cope with unknown data.
-        if !matches!(ze.name(), "settings.xml" | "styles.xml" | "content.xml") {
-            if ze.is_dir() {
-                book.filebuf.push_dir(ze.name());
-            } else if ze.is_file() {
-                let mut buf = Vec::new();
-                ze.read_to_end(&mut buf)?;
-                book.filebuf.push_file(ze.name(), buf);
-            }
-        }
+    /// Reads only the cell values and formulas. Everything else is skipped.
+    pub fn values_only(mut self) -> Self {
+        self.skip_styles = true;
+        self.skip_formats = true;
+        self.skip_validations = true;
+        self.skip_settings = true;
+        self.keep_extra_xml = false;
+        self
     }
 
-    Ok(())
-}
+    /// Skips fonts, styles, page layouts and master pages.
+    pub fn skip_styles(mut self, skip: bool) -> Self {
+        self.skip_styles = skip;
+        self
+    }
 
-// Sets some values from the styles on the corresponding data fields.
-fn calc_derived(book: &mut WorkBook) -> Result<(), OdsError> {
-    let v = book
-        .config
-        .get_value(&["ooo:view-settings", "Views", "0", "ActiveTable"]);
-    if let Some(ConfigValue::String(n)) = v {
-        book.config_mut().active_table = n.clone();
+    /// Skips the value formats.
+    pub fn skip_formats(mut self, skip: bool) -> Self {
+        self.skip_formats = skip;
+        self
     }
-    let v = book
-        .config
-        .get_value(&["ooo:view-settings", "Views", "0", "HasSheetTabs"]);
-    if let Some(ConfigValue::Boolean(n)) = v {
-        book.config_mut().has_sheet_tabs = *n;
+
+    /// Skips the content validations.
+    pub fn skip_validations(mut self, skip: bool) -> Self {
+        self.skip_validations = skip;
+        self
     }
-    let v = book
-        .config
-        .get_value(&["ooo:view-settings", "Views", "0", "ShowGrid"]);
-    if let Some(ConfigValue::Boolean(n)) = v {
-        book.config_mut().show_grid = *n;
+
+    /// Skips settings.xml. The default settings are used instead.
+    pub fn skip_settings(mut self, skip: bool) -> Self {
+        self.skip_settings = skip;
+        self
     }
-    let v = book
-        .config
-        .get_value(&["ooo:view-settings", "Views", "0", "ShowPageBreaks"]);
-    if let Some(ConfigValue::Boolean(n)) = v {
-        book.config_mut().show_page_breaks = *n;
+
+    /// Keeps all the xml that is not interpreted, and all files of the
+    /// package other than content.xml, styles.xml, settings.xml and meta.xml.
+    /// Defaults to true.
+    pub fn keep_extra_xml(mut self, keep: bool) -> Self {
+        self.keep_extra_xml = keep;
+        self
     }
 
-    for i in 0..book.num_sheets() {
-        let mut sheet = book.detach_sheet(i);
+    /// Reads only the sheets with these names. All other sheets are skipped.
+    pub fn sheets<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.sheets = Some(names.into_iter().map(|v| v.into()).collect());
+        self
+    }
 
-        // Set the column widths.
-        for ch in sheet.col_header.values_mut() {
-            if let Some(style_name) = &ch.style {
-                if let Some(style) = book.colstyle(style_name) {
-                    if style.use_optimal_col_width()? {
-                        ch.set_width(Length::Default);
-                    } else {
-                        ch.set_width(style.col_width()?);
-                    }
-                }
-            }
-        }
+    /// Reads an ODS-file.
+    pub fn read_ods<P: AsRef<Path>>(&self, path: P) -> Result<WorkBook, OdsError> {
+        let file = File::open(path.as_ref())?;
+        let zip = ZipArchive::new(file)?;
+        read_ods_impl(zip, None, self)
+    }
 
-        // Set the row heights
-        for rh in sheet.row_header.values_mut() {
-            if let Some(style_name) = &rh.style {
-                if let Some(style) = book.rowstyle(style_name) {
-                    if style.use_optimal_row_height()? {
-                        rh.set_height(Length::Default);
-                    } else {
-                        rh.set_height(style.row_height()?);
-                    }
-                }
-            }
+    /// Reads an ODS-file from a buffer.
+    pub fn read_ods_buf(&self, buf: &[u8]) -> Result<WorkBook, OdsError> {
+        let zip = ZipArchive::new(Cursor::new(buf))?;
+        read_ods_impl(zip, None, self)
+    }
+
+    /// Reads an ODS-file from any seekable source.
+    pub fn read_ods_from<R: Read + Seek>(&self, read: R) -> Result<WorkBook, OdsError> {
+        let zip = ZipArchive::new(read)?;
+        read_ods_impl(zip, None, self)
+    }
+
+    /// Reads a flat ODS-file (.fods).
+    pub fn read_fods<P: AsRef<Path>>(&self, path: P) -> Result<WorkBook, OdsError> {
+        let file = File::open(path.as_ref())?;
+        read_fods_impl(BufReader::new(file), self)
+    }
+
+    /// Reads a flat ODS-file (.fods) from a buffer.
+    pub fn read_fods_buf(&self, buf: &[u8]) -> Result<WorkBook, OdsError> {
+        read_fods_impl(buf, self)
+    }
+
+    // Is the sheet part of the selection.
+    fn use_sheet(&self, name: &str) -> bool {
+        match &self.sheets {
+            Some(sheets) => sheets.iter().any(|v| v == name),
+            None => true,
         }
+    }
+}
 
-        let v = book.config.get(&[
-            "ooo:view-settings",
-            "Views",
-            "0",
-            "Tables",
-            sheet.name().as_str(),
-        ]);
+/// Reads an ODS-file.
+fn read_ods_impl<R: Read + Seek>(
+    mut zip: ZipArchive<R>,
+    password: Option<&str>,
+    opts: &ReadOptions,
+) -> Result<WorkBook, OdsError> {
+    let mut book = WorkBook::new_empty();
+    let mut bufstack = BufStack::new();
+    let mut keys = password.map(DecryptionKeys::new);
 
-        if let Some(cc) = v {
-            if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["CursorPositionX"]) {
-                sheet.config_mut().cursor_x = *n as u32;
-            }
-            if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["CursorPositionY"]) {
-                sheet.config_mut().cursor_y = *n as u32;
-            }
-            if let Some(ConfigValue::Short(n)) = cc.get_value_rec(&["HorizontalSplitMode"]) {
-                sheet.config_mut().hor_split_mode = SplitMode::try_from(*n)?;
-            }
-            if let Some(ConfigValue::Short(n)) = cc.get_value_rec(&["VerticalSplitMode"]) {
-                sheet.config_mut().vert_split_mode = SplitMode::try_from(*n)?;
-            }
-            if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["HorizontalSplitPosition"]) {
-                sheet.config_mut().hor_split_pos = *n as u32;
-            }
-            if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["VerticalSplitPosition"]) {
-                sheet.config_mut().vert_split_pos = *n as u32;
-            }
-            if let Some(ConfigValue::Short(n)) = cc.get_value_rec(&["ActiveSplitRange"]) {
-                sheet.config_mut().active_split_range = *n;
-            }
-            if let Some(ConfigValue::Short(n)) = cc.get_value_rec(&["ZoomType"]) {
-                sheet.config_mut().zoom_type = *n;
-            }
-            if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["ZoomValue"]) {
-                sheet.config_mut().zoom_value = *n;
-            }
-            if let Some(ConfigValue::Boolean(n)) = cc.get_value_rec(&["ShowGrid"]) {
-                sheet.config_mut().show_grid = *n;
+    let encryption = if let Ok(z) = zip.by_name("META-INF/manifest.xml") {
+        read_manifest(&mut bufstack, z)?
+    } else {
+        HashMap::new()
+    };
+
+    let z = read_zip_file(&mut zip, "content.xml", &encryption, &mut keys)?;
+    read_content(&mut bufstack, &mut book, opts, z)?;
+    let z = read_zip_file(&mut zip, "styles.xml", &encryption, &mut keys)?;
+    read_styles(&mut bufstack, &mut book, opts, z)?;
+    // may not exist.
+    if !opts.skip_settings && zip.by_name("settings.xml").is_ok() {
+        let z = read_zip_file(&mut zip, "settings.xml", &encryption, &mut keys)?;
+        read_settings(&mut bufstack, &mut book, z)?;
+    } else {
+        book.config = default_settings();
+    }
+    // may not exist.
+    if zip.by_name("meta.xml").is_ok() {
+        let mut z = read_zip_file(&mut zip, "meta.xml", &encryption, &mut keys)?;
+        read_meta(&mut bufstack, &mut book, &mut z)?;
+    }
+
+    // read all extras.
+    if opts.keep_extra_xml {
+        read_filebuf(&mut book, &mut zip, &encryption, &mut keys)?;
+
+        // The image data is kept with the images, the charts are
+        // parsed and written from scratch.
+        let mut used_images = Vec::new();
+        for sheet in book.sheets.iter_mut() {
+            used_images.extend(load_images(&book.filebuf, sheet));
+            for href in load_charts(&mut bufstack, &book.filebuf, sheet)? {
+                book.filebuf.remove_dir(format!("{}/", href));
             }
         }
-
-        book.attach_sheet(sheet);
+        for href in used_images {
+            book.filebuf.remove(href);
+        }
     }
 
-    Ok(())
+    clear_skipped(&mut book, opts);
+
+    // We do some data duplication here, to make everything easier to use.
+    calc_derived(&mut book)?;
+
+    Ok(book)
 }
 
-// Reads the content.xml
-fn read_content(
+/// Reads a flat ODS-file (.fods) from a buffer.
+pub fn read_fods_buf(buf: &[u8]) -> Result<WorkBook, OdsError> {
+    read_fods_impl(buf, &ReadOptions::default())
+}
+
+/// Reads a flat ODS-file (.fods).
+pub fn read_fods<P: AsRef<Path>>(path: P) -> Result<WorkBook, OdsError> {
+    let file = File::open(path.as_ref())?;
+    read_fods_impl(BufReader::new(file), &ReadOptions::default())
+}
+
+/// Reads a flat ODS-file.
+fn read_fods_impl<R: BufRead>(read: R, opts: &ReadOptions) -> Result<WorkBook, OdsError> {
+    let mut book = WorkBook::new_empty();
+    let mut bufstack = BufStack::new();
+
+    read_fods_document(&mut bufstack, &mut book, opts, read)?;
+
+    clear_skipped(&mut book, opts);
+
+    // We do some data duplication here, to make everything easier to use.
+    calc_derived(&mut book)?;
+
+    Ok(book)
+}
+
+// Reads the office:document of a flat ODS-file. This contains the same
+// parts as content.xml, styles.xml and settings.xml combined.
+fn read_fods_document<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
-    zip_file: &mut ZipFile<'_>,
+    opts: &ReadOptions,
+    read: R,
 ) -> Result<(), OdsError> {
-    // xml parser
-    let mut xml = quick_xml::Reader::from_reader(BufReader::new(zip_file));
+    let mut xml = quick_xml::Reader::from_reader(read);
     // Do not trim text data. All text read contains significant whitespace.
     // The rest is ignored anyway.
     //
     // xml.trim_text(true);
 
+    let mut has_settings = false;
+
     let mut buf = bs.get_buf();
     loop {
         let evt = xml.read_event_into(&mut buf)?;
         let empty_tag = matches!(evt, Event::Empty(_));
         if DUMP_XML {
-            println!(" read_content {:?}", evt);
+            println!(" read_fods_document {:?}", evt);
         }
         match evt {
             Event::Decl(_) => {}
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"office:body"
-                || xml_tag.name().as_ref() == b"office:spreadsheet" => {
-                // noop
-            }
-            Event::End(xml_tag)
-            if xml_tag.name().as_ref() == b"office:body"
-                || xml_tag.name().as_ref() == b"office:spreadsheet" => {
-                // noop
-            }
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"office:document-content" => {
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:document" => {
                 for attr in xml_tag.attributes().with_checks(false) {
                     match attr? {
                         attr if attr.key.as_ref() == b"office:version" => {
@@ -248,80 +374,71 @@ fn read_content(
                     }
                 }
             }
-            Event::End(xml_tag)
-            if xml_tag.name().as_ref() == b"office:document-content" => {
+            Event::End(xml_tag) if xml_tag.name().as_ref() == b"office:document" => {
                 // noop
             }
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"office:font-face-decls" =>
-                read_fonts(bs, book, StyleOrigin::Content, &mut xml)?,
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"office:meta" =>
+            {
+                let v = read_xml(bs, xml_tag.name().as_ref(), &mut xml, &xml_tag, empty_tag)?;
+                book.metadata = read_office_meta(&v)?;
+            }
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"office:styles" =>
-                read_styles_tag(bs, book, StyleOrigin::Content, &mut xml)?,
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:settings" => {
+                if opts.skip_settings {
+                    skip_element(bs, &mut xml, &xml_tag, false)?;
+                } else {
+                    book.config = Detach::new(read_office_settings(bs, &mut xml)?);
+                    has_settings = true;
+                }
+            }
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"office:automatic-styles" =>
-                read_auto_styles(bs, book, StyleOrigin::Content, &mut xml)?,
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:font-face-decls" => {
+                if opts.skip_styles {
+                    skip_element(bs, &mut xml, &xml_tag, false)?;
+                } else {
+                    read_fonts(bs, book, StyleOrigin::Styles, &mut xml)?;
+                }
+            }
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"office:master-styles" =>
-                read_master_styles(bs, book, StyleOrigin::Content, &mut xml)?,
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:styles" => {
+                read_styles_tag(bs, book, opts, StyleOrigin::Styles, &mut xml)?
+            }
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"table:content-validations" =>
-                read_validations(bs, book, &mut xml)?,
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:automatic-styles" => {
+                read_auto_styles(bs, book, opts, StyleOrigin::Content, &mut xml)?
+            }
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table" =>
-                book.push_sheet(read_table(bs, &mut xml, xml_tag)?),
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:master-styles" => {
+                if opts.skip_styles {
+                    skip_element(bs, &mut xml, &xml_tag, false)?;
+                } else {
+                    read_master_styles(bs, book, StyleOrigin::Styles, &mut xml)?;
+                }
+            }
 
-            Event::Empty(xml_tag) |
-            Event::Start(xml_tag)
-            if /* prelude */ xml_tag.name().as_ref() == b"office:scripts" ||
-                xml_tag.name().as_ref() == b"table:tracked-changes" ||
-                xml_tag.name().as_ref() == b"text:variable-decls" ||
-                xml_tag.name().as_ref() == b"text:sequence-decls" ||
-                xml_tag.name().as_ref() == b"text:user-field-decls" ||
-                xml_tag.name().as_ref() == b"text:dde-connection-decls" ||
-                // xml_tag.name().as_ref() == b"text:alphabetical-index-auto-mark-file" ||
-                xml_tag.name().as_ref() == b"table:calculation-settings" ||
-                xml_tag.name().as_ref() == b"table:label-ranges" ||
-                /* epilogue */
-                xml_tag.name().as_ref() == b"table:named-expressions" ||
-                xml_tag.name().as_ref() == b"table:database-ranges" ||
-                xml_tag.name().as_ref() == b"table:data-pilot-tables" ||
-                xml_tag.name().as_ref() == b"table:consolidation" ||
-                xml_tag.name().as_ref() == b"table:dde-links" => {
-                let v = read_xml(bs, xml_tag.name().as_ref(), &mut xml, &xml_tag, empty_tag)?;
-                book.extra.push(v);
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"office:scripts" =>
+            {
+                if opts.keep_extra_xml {
+                    let v = read_xml(bs, xml_tag.name().as_ref(), &mut xml, &xml_tag, empty_tag)?;
+                    book.extra.push(v);
+                } else {
+                    skip_element(bs, &mut xml, &xml_tag, empty_tag)?;
+                }
             }
 
-            Event::End(xml_tag)
-            if /* prelude */ xml_tag.name().as_ref() == b"office:scripts" ||
-                xml_tag.name().as_ref() == b"table:tracked-changes" ||
-                xml_tag.name().as_ref() == b"text:variable-decls" ||
-                xml_tag.name().as_ref() == b"text:sequence-decls" ||
-                xml_tag.name().as_ref() == b"text:user-field-decls" ||
-                xml_tag.name().as_ref() == b"text:dde-connection-decls" ||
-                // xml_tag.name().as_ref() == b"text:alphabetical-index-auto-mark-file" ||
-                xml_tag.name().as_ref() == b"table:calculation-settings" ||
-                xml_tag.name().as_ref() == b"table:label-ranges" ||
-                /* epilogue */
-                xml_tag.name().as_ref() == b"table:named-expressions" ||
-                xml_tag.name().as_ref() == b"table:database-ranges" ||
-                xml_tag.name().as_ref() == b"table:data-pilot-tables" ||
-                xml_tag.name().as_ref() == b"table:consolidation" ||
-                xml_tag.name().as_ref() == b"table:dde-links" => {
-                // noop
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:body" => {
+                // The rest is the same as in content.xml.
+                read_sheets(bs, book, opts, &mut xml)?;
             }
 
             Event::Eof => {
                 break;
             }
             _ => {
-                dump_unused2("read_content", &evt)?;
+                dump_unused2("read_fods_document", &evt)?;
             }
         }
 
@@ -329,770 +446,3379 @@ fn read_content(
     }
     bs.push(buf);
 
+    if !has_settings {
+        book.config = default_settings();
+    }
+
     Ok(())
 }
 
-// Reads the table.
-fn read_table(
-    bs: &mut BufStack,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
-    xml_tag: BytesStart<'_>,
-) -> Result<Sheet, OdsError> {
-    let mut sheet = Sheet::new("");
+/// Reads an ODS-file row by row.
+///
+/// Styles, value formats and settings are read when the reader is created.
+/// The cell data is only read on demand via [OdsReader::content], which
+/// returns the rows of each sheet in turn. Nothing but the current row
+/// is kept in memory, and reading can be stopped at any time.
+///
+/// Encrypted files are not supported, use [read_ods_with_password]
+/// for them.
+///
+/// ```
+/// use spreadsheet_ods::{OdsError, OdsReader};
+///
+/// let mut ods = OdsReader::open("tests/orders.ods")?;
+/// let mut content = ods.content()?;
+/// while let Some(sheet) = content.next_sheet()? {
+///     println!("{}", sheet.name());
+///     while let Some((row, cells)) = content.next_row()? {
+///         for (col, cell) in cells {
+///             println!("{} {} {:?}", row, col, cell.value());
+///         }
+///     }
+/// }
+/// # Ok::<(), OdsError>(())
+/// ```
+pub struct OdsReader<R: Read + Seek> {
+    zip: ZipArchive<R>,
+    book: WorkBook,
+    bufstack: BufStack,
+}
 
-    read_table_attr(&mut sheet, xml_tag)?;
+impl<R: Read + Seek> fmt::Debug for OdsReader<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OdsReader")
+            .field("book", &self.book)
+            .finish()
+    }
+}
 
-    // Position within table-columns
-    let mut table_col: u32 = 0;
+impl OdsReader<File> {
+    /// Opens an ODS-file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OdsError> {
+        Self::new(File::open(path.as_ref())?)
+    }
+}
 
-    // Cell position
-    let mut row: u32 = 0;
-    let mut col: u32 = 0;
+impl<'a> OdsReader<Cursor<&'a [u8]>> {
+    /// Reads an ODS-file from a buffer.
+    pub fn from_buf(buf: &'a [u8]) -> Result<Self, OdsError> {
+        Self::new(Cursor::new(buf))
+    }
+}
 
-    // Rows can be repeated. In reality only empty ones ever are.
-    let mut row_repeat: u32 = 1;
-    let mut rowstyle: Option<String> = None;
-    let mut row_cellstyle: Option<String> = None;
-    let mut row_visible: Visibility = Default::default();
+impl<R: Read + Seek> OdsReader<R> {
+    /// Reads the styles and settings of the ODS-file.
+    pub fn new(read: R) -> Result<Self, OdsError> {
+        let mut zip = ZipArchive::new(read)?;
+        let mut book = WorkBook::new_empty();
+        let mut bufstack = BufStack::new();
 
-    let mut col_range_from = 0;
-    let mut row_range_from = 0;
+        if let Ok(z) = zip.by_name("META-INF/manifest.xml") {
+            if !read_manifest(&mut bufstack, z)?.is_empty() {
+                return Err(OdsError::Password(
+                    "OdsReader can't read encrypted files, use read_ods_with_password."
+                        .to_string(),
+                ));
+            }
+        }
 
-    let mut buf = bs.get_buf();
-    loop {
-        let evt = xml.read_event_into(&mut buf)?;
-        let empty_tag = matches!(evt, Event::Empty(_));
-        if DUMP_XML {
-            println!(" read_table {:?}", evt);
+        read_styles(
+            &mut bufstack,
+            &mut book,
+            &ReadOptions::default(),
+            &mut zip.by_name("styles.xml")?,
+        )?;
+        // may not exist.
+        if let Ok(mut z) = zip.by_name("settings.xml") {
+            read_settings(&mut bufstack, &mut book, &mut z)?;
+        } else {
+            book.config = default_settings();
+        }
+        // may not exist.
+        if let Ok(mut z) = zip.by_name("meta.xml") {
+            read_meta(&mut bufstack, &mut book, &mut z)?;
         }
-        match evt {
-            Event::End(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table" => {
-                // TODO: Maybe find a better fix for the repeat error.
-                // Reset the repeat count for the last two rows to one if it exceeds
-                // some arbitrary limit. 
-                let mut it = sheet.row_header.iter_mut().rev();
-                if let Some((_row, last)) = it.next() {
-                    if last.repeat > 1000 {
-                        last.repeat = 1;
-                    }
-                }
-                if let Some((_row, last)) = it.next() {
-                    if last.repeat > 1000 {
-                        last.repeat = 1;
-                    }
-                }
-                break;
-            }
 
-            Event::Start(xml_tag) |
-            Event::Empty(xml_tag)
-            if /* prelude */ xml_tag.name().as_ref() == b"table:title" ||
-                xml_tag.name().as_ref() == b"table:desc" ||
-                xml_tag.name().as_ref() == b"table:table-source" ||
-                xml_tag.name().as_ref() == b"office:dde-source" ||
-                xml_tag.name().as_ref() == b"table:scenario" ||
-                xml_tag.name().as_ref() == b"office:forms" ||
-                xml_tag.name().as_ref() == b"table:shapes" ||
-                /* epilogue */
-                xml_tag.name().as_ref() == b"table:named-expressions" ||
-                xml_tag.name().as_ref() == b"calcext:conditional-formats" => {
-                sheet.extra.push(read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?);
-            }
+        calc_derived(&mut book)?;
 
-            Event::End(xml_tag)
-            if /* prelude */ xml_tag.name().as_ref() == b"table:title" ||
-                xml_tag.name().as_ref() == b"table:desc" ||
-                xml_tag.name().as_ref() == b"table:table-source" ||
-                xml_tag.name().as_ref() == b"office:dde-source" ||
-                xml_tag.name().as_ref() == b"table:scenario" ||
-                xml_tag.name().as_ref() == b"office:forms" ||
-                xml_tag.name().as_ref() == b"table:shapes" ||
-                /* epilogue */
-                xml_tag.name().as_ref() == b"table:named-expressions" ||
-                xml_tag.name().as_ref() == b"calcext:conditional-formats" => {}
+        Ok(Self {
+            zip,
+            book,
+            bufstack,
+        })
+    }
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-header-columns" => {
-                col_range_from = table_col;
-            }
+    /// Workbook with all the styles, formats and settings, but without
+    /// any sheets.
+    ///
+    /// The styles contained in content.xml are added once the content
+    /// is read.
+    pub fn book(&self) -> &WorkBook {
+        &self.book
+    }
 
-            Event::End(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-header-columns" => {
-                sheet.header_cols = Some(ColRange::new(col_range_from, table_col - 1));
-            }
+    /// Starts reading the content.xml. Everything up to the first
+    /// sheet is read immediately.
+    pub fn content(&mut self) -> Result<OdsContentReader<'_>, OdsError> {
+        let zip_file = self.zip.by_name("content.xml")?;
+        let mut xml = quick_xml::Reader::from_reader(BufReader::new(zip_file));
+
+        let next_table = read_content_until_table(
+            &mut self.bufstack,
+            &mut self.book,
+            &ReadOptions::default(),
+            &mut xml,
+        )?;
+
+        Ok(OdsContentReader {
+            book: &mut self.book,
+            bufstack: &mut self.bufstack,
+            xml,
+            next_table,
+            in_table: false,
+            first_row: false,
+            sheet: Sheet::new(""),
+            table: ReadTable::new(),
+            cells: Vec::new(),
+        })
+    }
+}
 
-            Event::Empty(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-column" => {
-                table_col = read_table_col_attr(&mut sheet, table_col,  &xml_tag)?;
-            }
+/// Reads the sheets of content.xml one row at a time.
+///
+/// Created by [OdsReader::content].
+pub struct OdsContentReader<'a> {
+    book: &'a mut WorkBook,
+    bufstack: &'a mut BufStack,
+    xml: quick_xml::Reader<BufReader<ZipFile<'a>>>,
+    // Start of the next table, if any.
+    next_table: Option<BytesStart<'static>>,
+    // Currently reading the rows of a table.
+    in_table: bool,
+    // Nothing read for the current table yet.
+    first_row: bool,
+    // Current sheet without the cell data.
+    sheet: Sheet,
+    table: ReadTable,
+    cells: Vec<(u32, CellData)>,
+}
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-header-rows" => {
-                row_range_from = row;
-            }
+impl<'a> fmt::Debug for OdsContentReader<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OdsContentReader")
+            .field("next_table", &self.next_table)
+            .field("in_table", &self.in_table)
+            .field("sheet", &self.sheet)
+            .field("table", &self.table)
+            .finish()
+    }
+}
 
-            Event::End(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-header-rows" => {
-                sheet.header_rows = Some(RowRange::new(row_range_from, row - 1));
-            }
+impl<'a> OdsContentReader<'a> {
+    /// Workbook with all the styles, formats and settings, but without
+    /// any sheets.
+    pub fn book(&self) -> &WorkBook {
+        self.book
+    }
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-row" => {
-                let (repeat, style, cellstyle, visible) = read_table_row_attr( xml_tag)?;
-                row_repeat = repeat;
-                rowstyle = style;
-                row_cellstyle = cellstyle;
-                row_visible = visible;
-            }
+    /// The current sheet. This contains the sheet attributes, column
+    /// headers and the row header of the last row returned, but no cell data.
+    pub fn sheet(&self) -> &Sheet {
+        &self.sheet
+    }
 
-            Event::End(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-row" => {
-                if row_repeat > 1 {
-                    sheet.set_row_repeat(row, row_repeat);
-                }
-                if let Some(rowstyle) = rowstyle {
-                    sheet.set_rowstyle(row, &rowstyle.into());
-                }
-                rowstyle = None;
-                if let Some(row_cellstyle) = row_cellstyle {
-                    sheet.set_row_cellstyle(row, &row_cellstyle.into());
-                }
-                row_cellstyle = None;
-                if row_visible != Visibility::Visible {
-                    sheet.set_row_visible(row, row_visible);
-                }
-                row_visible = Default::default();
+    /// Advances to the next sheet. Any remaining rows of the current sheet
+    /// are skipped. Returns None if there are no more sheets.
+    pub fn next_sheet(&mut self) -> Result<Option<&Sheet>, OdsError> {
+        if self.in_table {
+            while self.read_row()?.is_some() {}
+        }
 
-                row += row_repeat;
-                col = 0;
-                row_repeat = 1;
-            }
+        if let Some(xml_tag) = self.next_table.take() {
+            let mut sheet = Sheet::new("");
+            read_table_attr(&mut sheet, xml_tag)?;
+            calc_sheet_config(self.book, &mut sheet)?;
 
-            Event::Empty(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-cell" || xml_tag.name().as_ref() == b"table:covered-table-cell" => {
-                col = read_empty_table_cell(&mut sheet, row, col, xml_tag)?;
-            }
+            self.sheet = sheet;
+            self.table = ReadTable::new();
+            self.in_table = true;
+            self.first_row = true;
 
-            Event::Start(xml_tag)
-            if xml_tag.name().as_ref() == b"table:table-cell" || xml_tag.name().as_ref() == b"table:covered-table-cell" => {
-                col = read_table_cell2(bs, &mut sheet, row, col, xml, xml_tag)?;
-            }
+            Ok(Some(&self.sheet))
+        } else {
+            Ok(None)
+        }
+    }
 
-            _ => {
-                dump_unused2("read_table", &evt)?;
+    /// Reads the next row of the current sheet that contains any cells.
+    /// Returns the row index and the cells as (column, content), or None at
+    /// the end of the sheet.
+    #[allow(clippy::type_complexity)]
+    pub fn next_row(&mut self) -> Result<Option<(u32, Vec<(u32, CellContentRef<'_>)>)>, OdsError> {
+        while let Some(row) = self.read_row()? {
+            if !self.cells.is_empty() {
+                let cells = self
+                    .cells
+                    .iter()
+                    .map(|(col, cell)| (*col, cell.into()))
+                    .collect();
+                return Ok(Some((row, cells)));
             }
         }
-        buf.clear();
+        Ok(None)
     }
-    bs.push(buf);
 
-    Ok(sheet)
-}
+    // Reads one table-row into cells. Only the row header for this row
+    // is kept.
+    fn read_row(&mut self) -> Result<Option<u32>, OdsError> {
+        if !self.in_table {
+            return Ok(None);
+        }
 
-// Reads the table attributes.
-fn read_table_attr(sheet: &mut Sheet, xml_tag: BytesStart<'_>) -> Result<(), OdsError> {
-    for attr in xml_tag.attributes().with_checks(false) {
-        match attr? {
-            attr if attr.key.as_ref() == b"table:name" => {
-                sheet.set_name(attr.unescape_value()?.to_string());
-            }
-            attr if attr.key.as_ref() == b"table:style-name" => {
-                sheet.set_style(&attr.unescape_value()?.as_ref().into());
-            }
-            attr if attr.key.as_ref() == b"table:print" => {
-                sheet.set_print(parse_bool(&attr.value)?);
-            }
-            attr if attr.key.as_ref() == b"table:display" => {
-                sheet.set_display(parse_bool(&attr.value)?);
-            }
-            attr if attr.key.as_ref() == b"table:print-ranges" => {
-                let v = attr.unescape_value()?;
-                let mut pos = 0usize;
-                sheet.print_ranges = parse_cellranges(v.as_ref(), &mut pos)?;
-            }
-            attr => {
-                dump_unused("read_table_attr", xml_tag.name().as_ref(), &attr)?;
-            }
+        self.cells.clear();
+        self.sheet.row_header.clear();
+
+        let row = read_table_row(
+            self.bufstack,
+            &mut self.xml,
+            &mut self.sheet,
+            &mut self.table,
+            &mut self.cells,
+        )?;
+
+        // The columns are complete with the first row.
+        if self.first_row {
+            calc_col_widths(self.book, &mut self.sheet)?;
+            self.first_row = false;
+        }
+
+        if row.is_some() {
+            calc_row_heights(self.book, &mut self.sheet)?;
+        } else {
+            self.in_table = false;
+            self.next_table = read_content_until_table(
+                self.bufstack,
+                self.book,
+                &ReadOptions::default(),
+                &mut self.xml,
+            )?;
         }
+
+        Ok(row)
     }
+}
 
-    Ok(())
+/// Workbook that decodes its sheets only when they are first accessed.
+///
+/// Styles, formats and settings are read immediately. The content.xml is
+/// scanned once for the boundaries of the sheets, but the cell data of a
+/// sheet is only parsed when the sheet is accessed via [LazyWorkBook::sheet]
+/// or [LazyWorkBook::sheet_mut].
+///
+/// Sheets that are not loaded yet are detached in the underlying
+/// [WorkBook]. Use [LazyWorkBook::into_workbook] to get a complete
+/// workbook.
+///
+/// Encrypted files are not supported, use [read_ods_with_password]
+/// for them.
+///
+/// ```
+/// use spreadsheet_ods::{LazyWorkBook, OdsError};
+///
+/// let mut book = LazyWorkBook::open("tests/orders.ods")?;
+/// if let Some(idx) = book.sheet_idx("Lieferanten") {
+///     let sheet = book.sheet(idx)?;
+///     println!("{:?}", sheet.value(0, 0));
+/// }
+/// # Ok::<(), OdsError>(())
+/// ```
+pub struct LazyWorkBook {
+    book: WorkBook,
+    bufstack: BufStack,
+    // Uncompressed content.xml
+    content: Vec<u8>,
+    tables: Vec<LazyTable>,
 }
 
-// Reads table-row attributes. Returns the repeat-count.
-fn read_table_row_attr(
-    xml_tag: BytesStart<'_>,
-) -> Result<(u32, Option<String>, Option<String>, Visibility), OdsError> {
-    let mut row_repeat: u32 = 1;
-    let mut row_visible: Visibility = Default::default();
-    let mut rowstyle: Option<String> = None;
-    let mut row_cellstyle: Option<String> = None;
+// Position of one table in the content.xml.
+struct LazyTable {
+    name: String,
+    // The table:table tag.
+    xml_tag: BytesStart<'static>,
+    // Everything after the start tag up to and including the end tag.
+    start: usize,
+    end: usize,
+}
 
-    for attr in xml_tag.attributes().with_checks(false) {
-        match attr? {
-            // table:default-cell-style-name 19.615, table:visibility 19.749 and xml:id 19.914.
-            attr if attr.key.as_ref() == b"table:number-rows-repeated" => {
-                row_repeat = parse_u32(&attr.value)?;
-            }
-            attr if attr.key.as_ref() == b"table:style-name" => {
-                rowstyle = Some(attr.unescape_value()?.to_string());
-            }
-            attr if attr.key.as_ref() == b"table:default-cell-style-name" => {
-                row_cellstyle = Some(attr.unescape_value()?.to_string());
-            }
-            attr if attr.key.as_ref() == b"table:visibility" => {
-                row_visible = parse_visibility(&attr.value)?;
-            }
-            attr => {
-                dump_unused("read_table_row_attr", xml_tag.name().as_ref(), &attr)?;
+impl fmt::Debug for LazyWorkBook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyWorkBook")
+            .field("book", &self.book)
+            .field(
+                "sheets",
+                &self.tables.iter().map(|v| &v.name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl LazyWorkBook {
+    /// Opens an ODS-file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, OdsError> {
+        Self::new(File::open(path.as_ref())?)
+    }
+
+    /// Reads an ODS-file from a buffer.
+    pub fn from_buf(buf: &[u8]) -> Result<Self, OdsError> {
+        Self::new(Cursor::new(buf))
+    }
+
+    /// Reads everything but the sheets of the ODS-file.
+    pub fn new<R: Read + Seek>(read: R) -> Result<Self, OdsError> {
+        let mut zip = ZipArchive::new(read)?;
+        let mut book = WorkBook::new_empty();
+        let mut bufstack = BufStack::new();
+
+        if let Ok(z) = zip.by_name("META-INF/manifest.xml") {
+            if !read_manifest(&mut bufstack, z)?.is_empty() {
+                return Err(OdsError::Ods(
+                    "LazyWorkBook can't read encrypted files, use read_ods_with_password."
+                        .to_string(),
+                ));
             }
         }
+
+        let mut content = Vec::new();
+        zip.by_name("content.xml")?.read_to_end(&mut content)?;
+
+        let mut tables = Vec::new();
+        let mut xml = quick_xml::Reader::from_reader(content.as_slice());
+        let mut buf = bufstack.get_buf();
+        while let Some(xml_tag) =
+            read_content_until_table(&mut bufstack, &mut book, &ReadOptions::default(), &mut xml)?
+        {
+            let name = table_name(&xml_tag)?;
+
+            let start = xml.buffer_position();
+            xml.read_to_end_into(xml_tag.name(), &mut buf)?;
+            let end = xml.buffer_position();
+            buf.clear();
+
+            tables.push(LazyTable {
+                name,
+                xml_tag,
+                start,
+                end,
+            });
+        }
+        bufstack.push(buf);
+
+        read_styles(
+            &mut bufstack,
+            &mut book,
+            &ReadOptions::default(),
+            &mut zip.by_name("styles.xml")?,
+        )?;
+        // may not exist.
+        if let Ok(mut z) = zip.by_name("settings.xml") {
+            read_settings(&mut bufstack, &mut book, &mut z)?;
+        } else {
+            book.config = default_settings();
+        }
+        // may not exist.
+        if let Ok(mut z) = zip.by_name("meta.xml") {
+            read_meta(&mut bufstack, &mut book, &mut z)?;
+        }
+
+        // read all extras.
+        read_filebuf(&mut book, &mut zip, &HashMap::new(), &mut None)?;
+
+        calc_derived(&mut book)?;
+
+        // not loaded yet.
+        for _ in 0..tables.len() {
+            book.sheets.push(Detach::default());
+        }
+
+        Ok(Self {
+            book,
+            bufstack,
+            content,
+            tables,
+        })
     }
 
-    Ok((row_repeat, rowstyle, row_cellstyle, row_visible))
-}
+    /// Workbook with all the styles, formats and settings.
+    ///
+    /// Sheets that have not been loaded yet are detached, and accessing
+    /// them via the workbook panics.
+    pub fn book(&self) -> &WorkBook {
+        &self.book
+    }
 
-// Reads the table-column attributes. Creates as many copies as indicated.
-fn read_table_col_attr(
-    sheet: &mut Sheet,
-    mut table_col: u32,
-    xml_tag: &BytesStart<'_>,
-) -> Result<u32, OdsError> {
-    let mut style = None;
-    let mut cellstyle = None;
-    let mut repeat: u32 = 1;
-    let mut visible: Visibility = Default::default();
+    /// Number of sheets.
+    pub fn num_sheets(&self) -> usize {
+        self.tables.len()
+    }
 
-    for attr in xml_tag.attributes().with_checks(false) {
-        match attr? {
-            attr if attr.key.as_ref() == b"table:number-columns-repeated" => {
-                repeat = parse_u32(&attr.value)?;
-            }
-            attr if attr.key.as_ref() == b"table:style-name" => {
-                style = Some(attr.unescape_value()?);
-            }
-            attr if attr.key.as_ref() == b"table:default-cell-style-name" => {
-                cellstyle = Some(attr.unescape_value()?);
-            }
-            attr if attr.key.as_ref() == b"table:visibility" => {
-                visible = parse_visibility(&attr.value)?;
+    /// Name of the n-th sheet. Available without loading the sheet.
+    ///
+    /// Panics
+    ///
+    /// Panics if n is out of bounds.
+    pub fn sheet_name(&self, n: usize) -> &str {
+        self.tables[n].name.as_str()
+    }
+
+    /// Finds the sheet index by the sheet-name.
+    pub fn sheet_idx<S: AsRef<str>>(&self, name: S) -> Option<usize> {
+        self.tables.iter().position(|v| v.name == name.as_ref())
+    }
+
+    /// Has the sheet already been loaded.
+    ///
+    /// Panics
+    ///
+    /// Panics if n is out of bounds.
+    pub fn is_loaded(&self, n: usize) -> bool {
+        !self.book.sheets[n].is_detached()
+    }
+
+    /// Returns a certain sheet. The sheet is loaded if necessary.
+    ///
+    /// Panics
+    ///
+    /// Panics if n is out of bounds.
+    pub fn sheet(&mut self, n: usize) -> Result<&Sheet, OdsError> {
+        self.load_sheet(n)?;
+        Ok(self.book.sheet(n))
+    }
+
+    /// Returns a certain sheet. The sheet is loaded if necessary.
+    ///
+    /// Panics
+    ///
+    /// Panics if n is out of bounds.
+    pub fn sheet_mut(&mut self, n: usize) -> Result<&mut Sheet, OdsError> {
+        self.load_sheet(n)?;
+        Ok(self.book.sheet_mut(n))
+    }
+
+    /// Loads all remaining sheets and returns the complete workbook.
+    pub fn into_workbook(mut self) -> Result<WorkBook, OdsError> {
+        for n in 0..self.tables.len() {
+            self.load_sheet(n)?;
+        }
+        Ok(self.book)
+    }
+
+    fn load_sheet(&mut self, n: usize) -> Result<(), OdsError> {
+        if self.book.sheets[n].is_detached() {
+            let table = &self.tables[n];
+
+            let mut xml = quick_xml::Reader::from_reader(&self.content[table.start..table.end]);
+            // The start tag is not part of the slice.
+            xml.check_end_names(false);
+
+            let mut sheet = read_table(&mut self.bufstack, &mut xml, table.xml_tag.clone())?;
+
+            // The media is kept with the sheet and removed from the package
+            // buffer, the same as when reading the complete workbook.
+            let used_images = load_images(&self.book.filebuf, &mut sheet);
+            load_shared_images(&self.book, &mut sheet);
+            for href in used_images {
+                self.book.filebuf.remove(href);
             }
-            attr => {
-                dump_unused("read_table_col_attr", xml_tag.name().as_ref(), &attr)?;
+            for href in load_charts(&mut self.bufstack, &self.book.filebuf, &mut sheet)? {
+                self.book.filebuf.remove_dir(format!("{}/", href));
             }
+            calc_col_widths(&self.book, &mut sheet)?;
+            calc_row_heights(&self.book, &mut sheet)?;
+            calc_sheet_config(&self.book, &mut sheet)?;
+
+            self.book.sheets[n] = Detach::new(sheet);
         }
+        Ok(())
     }
+}
 
-    while repeat > 0 {
-        if let Some(style) = &style {
-            sheet.set_colstyle(table_col, &style.as_ref().into());
+// Loads all unprocessed files as byte blobs into a buffer.
+fn read_filebuf<R: Read + Seek>(
+    book: &mut WorkBook,
+    zip: &mut ZipArchive<R>,
+    encryption: &HashMap<String, EncryptionData>,
+    keys: &mut Option<DecryptionKeys<'_>>,
+) -> Result<(), OdsError> {
+    for idx in 0..zip.len() {
+        let mut ze = zip.by_index(idx)?;
+
+        // These are always interpreted and rewritten from scratch.
+        // They have their own mechanism to cope with unknown data.
+        if matches!(
+            ze.name(),
+            "settings.xml" | "styles.xml" | "content.xml" | "meta.xml"
+        ) {
+            continue;
         }
-        if let Some(cellstyle) = &cellstyle {
-            sheet.set_col_cellstyle(table_col, &cellstyle.as_ref().into());
+        // The manifest of an encrypted file is no longer valid after decryption.
+        if !encryption.is_empty() && ze.name() == "META-INF/manifest.xml" {
+            continue;
+        }
+
+        if ze.is_dir() {
+            book.filebuf.push_dir(ze.name());
+        } else if ze.is_file() {
+            let mut buf = Vec::new();
+            ze.read_to_end(&mut buf)?;
+            if let Some(enc) = encryption.get(ze.name()) {
+                buf = keys_for(keys)?.decrypt(enc, &buf)?;
+            }
+            book.filebuf.push_file(ze.name(), buf);
         }
-        sheet.set_col_visible(table_col, visible);
-        table_col += 1;
-        repeat -= 1;
     }
 
-    Ok(table_col)
+    Ok(())
 }
 
-#[derive(Debug)]
-#[allow(variant_size_differences)]
-enum TextContent2 {
-    Empty,
-    Text(String),
-    Xml(TextTag),
-    XmlVec(Vec<TextTag>),
+// Opens a file from the zip. Encrypted files are decrypted into a buffer.
+fn read_zip_file<'a, R: Read + Seek>(
+    zip: &'a mut ZipArchive<R>,
+    name: &str,
+    encryption: &HashMap<String, EncryptionData>,
+    keys: &mut Option<DecryptionKeys<'_>>,
+) -> Result<Box<dyn Read + 'a>, OdsError> {
+    let mut ze = zip.by_name(name)?;
+    if let Some(enc) = encryption.get(name) {
+        let mut buf = Vec::new();
+        ze.read_to_end(&mut buf)?;
+        let buf = keys_for(keys)?.decrypt(enc, &buf)?;
+        Ok(Box::new(Cursor::new(buf)))
+    } else {
+        Ok(Box::new(ze))
+    }
 }
 
-#[derive(Debug)]
-struct ReadTableCell2 {
-    val_type: ValueType,
-    val_datetime: Option<NaiveDateTime>,
-    val_duration: Option<Duration>,
-    val_float: Option<f64>,
-    val_bool: Option<bool>,
-    val_string: Option<String>,
-    val_currency: Option<String>,
-
-    content: TextContent2,
+fn keys_for<'a, 'b>(
+    keys: &'a mut Option<DecryptionKeys<'b>>,
+) -> Result<&'a mut DecryptionKeys<'b>, OdsError> {
+    match keys {
+        Some(v) => Ok(v),
+        None => Err(OdsError::Password(
+            "The file is encrypted, a password is required.".to_string(),
+        )),
+    }
 }
 
-fn read_table_cell2(
+// Reads the encryption data for all encrypted files from the manifest.
+fn read_manifest<R: Read>(
     bs: &mut BufStack,
-    sheet: &mut Sheet,
-    row: u32,
-    mut col: u32,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
-    xml_tag: BytesStart<'_>,
-) -> Result<u32, OdsError> {
-    // Current cell tag
-    let tag_name = xml_tag.name();
+    read: R,
+) -> Result<HashMap<String, EncryptionData>, OdsError> {
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(read));
 
-    let mut cell_repeat: u32 = 1;
+    let mut encryption = HashMap::new();
+    let mut full_path = String::new();
+    let mut enc = EncryptionData::default();
 
-    let mut cell = CellData {
-        value: Default::default(),
-        formula: None,
-        style: None,
-        validation_name: None,
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        if DUMP_XML {
+            println!(" read_manifest {:?}", evt);
+        }
+        match evt {
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"manifest:file-entry" =>
+            {
+                for attr in xml_tag.attributes().with_checks(false) {
+                    let attr = attr?;
+                    if attr.key.as_ref() == b"manifest:full-path" {
+                        full_path = attr.unescape_value()?.to_string();
+                    }
+                }
+            }
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"manifest:encryption-data" => {
+                enc = EncryptionData::default();
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"manifest:checksum-type" => {
+                            enc.checksum_type = attr.unescape_value()?.to_string();
+                        }
+                        attr if attr.key.as_ref() == b"manifest:checksum" => {
+                            enc.checksum = parse_base64(&attr.value)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::End(xml_tag) if xml_tag.name().as_ref() == b"manifest:encryption-data" => {
+                encryption.insert(full_path.clone(), mem::take(&mut enc));
+            }
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"manifest:algorithm" =>
+            {
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"manifest:algorithm-name" => {
+                            enc.algorithm = attr.unescape_value()?.to_string();
+                        }
+                        attr if attr.key.as_ref() == b"manifest:initialisation-vector" => {
+                            enc.iv = parse_base64(&attr.value)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"manifest:start-key-generation" =>
+            {
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"manifest:start-key-generation-name" => {
+                            enc.start_key_generation = attr.unescape_value()?.to_string();
+                        }
+                        attr if attr.key.as_ref() == b"manifest:key-size" => {
+                            enc.start_key_size = parse_u32(&attr.value)? as usize;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"manifest:key-derivation" =>
+            {
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"manifest:key-derivation-name" => {
+                            enc.key_derivation = attr.unescape_value()?.to_string();
+                        }
+                        attr if attr.key.as_ref() == b"manifest:key-size" => {
+                            enc.key_size = parse_u32(&attr.value)? as usize;
+                        }
+                        attr if attr.key.as_ref() == b"manifest:iteration-count" => {
+                            enc.iteration_count = parse_u32(&attr.value)?;
+                        }
+                        attr if attr.key.as_ref() == b"manifest:salt" => {
+                            enc.salt = parse_base64(&attr.value)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::Eof => {
+                break;
+            }
+            _ => {}
+        }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(encryption)
+}
+
+// Sets some values from the styles on the corresponding data fields.
+fn calc_derived(book: &mut WorkBook) -> Result<(), OdsError> {
+    let v = book
+        .config
+        .get_value(&["ooo:view-settings", "Views", "0", "ActiveTable"]);
+    if let Some(ConfigValue::String(n)) = v {
+        book.config_mut().active_table = n.clone();
+    }
+    let v = book
+        .config
+        .get_value(&["ooo:view-settings", "Views", "0", "HasSheetTabs"]);
+    if let Some(ConfigValue::Boolean(n)) = v {
+        book.config_mut().has_sheet_tabs = *n;
+    }
+    let v = book
+        .config
+        .get_value(&["ooo:view-settings", "Views", "0", "ShowGrid"]);
+    if let Some(ConfigValue::Boolean(n)) = v {
+        book.config_mut().show_grid = *n;
+    }
+    let v = book
+        .config
+        .get_value(&["ooo:view-settings", "Views", "0", "ShowPageBreaks"]);
+    if let Some(ConfigValue::Boolean(n)) = v {
+        book.config_mut().show_page_breaks = *n;
+    }
+
+    for i in 0..book.num_sheets() {
+        let mut sheet = book.detach_sheet(i);
+
+        calc_col_widths(book, &mut sheet)?;
+        calc_row_heights(book, &mut sheet)?;
+        calc_sheet_config(book, &mut sheet)?;
+
+        book.attach_sheet(sheet);
+    }
+
+    Ok(())
+}
+
+// Set the column widths.
+fn calc_col_widths(book: &WorkBook, sheet: &mut Sheet) -> Result<(), OdsError> {
+    for ch in sheet.col_header.values_mut() {
+        if let Some(style_name) = &ch.style {
+            if let Some(style) = book.colstyle(style_name) {
+                if style.use_optimal_col_width()? {
+                    ch.set_width(Length::Default);
+                } else {
+                    ch.set_width(style.col_width()?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Set the row heights
+fn calc_row_heights(book: &WorkBook, sheet: &mut Sheet) -> Result<(), OdsError> {
+    for rh in sheet.row_header.values_mut() {
+        if let Some(style_name) = &rh.style {
+            if let Some(style) = book.rowstyle(style_name) {
+                if style.use_optimal_row_height()? {
+                    rh.set_height(Length::Default);
+                } else {
+                    rh.set_height(style.row_height()?);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Copy the sheet specific view settings.
+fn calc_sheet_config(book: &WorkBook, sheet: &mut Sheet) -> Result<(), OdsError> {
+    let v = book.config.get(&[
+        "ooo:view-settings",
+        "Views",
+        "0",
+        "Tables",
+        sheet.name().as_str(),
+    ]);
+
+    if let Some(cc) = v {
+        if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["CursorPositionX"]) {
+            sheet.config_mut().cursor_x = *n as u32;
+        }
+        if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["CursorPositionY"]) {
+            sheet.config_mut().cursor_y = *n as u32;
+        }
+        if let Some(ConfigValue::Short(n)) = cc.get_value_rec(&["HorizontalSplitMode"]) {
+            sheet.config_mut().hor_split_mode = SplitMode::try_from(*n)?;
+        }
+        if let Some(ConfigValue::Short(n)) = cc.get_value_rec(&["VerticalSplitMode"]) {
+            sheet.config_mut().vert_split_mode = SplitMode::try_from(*n)?;
+        }
+        if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["HorizontalSplitPosition"]) {
+            sheet.config_mut().hor_split_pos = *n as u32;
+        }
+        if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["VerticalSplitPosition"]) {
+            sheet.config_mut().vert_split_pos = *n as u32;
+        }
+        if let Some(ConfigValue::Short(n)) = cc.get_value_rec(&["ActiveSplitRange"]) {
+            sheet.config_mut().active_split_range = *n;
+        }
+        if let Some(ConfigValue::Short(n)) = cc.get_value_rec(&["ZoomType"]) {
+            sheet.config_mut().zoom_type = *n;
+        }
+        if let Some(ConfigValue::Int(n)) = cc.get_value_rec(&["ZoomValue"]) {
+            sheet.config_mut().zoom_value = *n;
+        }
+        if let Some(ConfigValue::Boolean(n)) = cc.get_value_rec(&["ShowGrid"]) {
+            sheet.config_mut().show_grid = *n;
+        }
+    }
+
+    Ok(())
+}
+
+// Reads the content.xml
+fn read_content<R: Read>(
+    bs: &mut BufStack,
+    book: &mut WorkBook,
+    opts: &ReadOptions,
+    read: R,
+) -> Result<(), OdsError> {
+    // xml parser
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(read));
+    // Do not trim text data. All text read contains significant whitespace.
+    // The rest is ignored anyway.
+    //
+    // xml.trim_text(true);
+
+    read_sheets(bs, book, opts, &mut xml)
+}
+
+// Reads all the tables of the office:spreadsheet. Tables not selected
+// by the options are skipped.
+fn read_sheets<R: BufRead>(
+    bs: &mut BufStack,
+    book: &mut WorkBook,
+    opts: &ReadOptions,
+    xml: &mut quick_xml::Reader<R>,
+) -> Result<(), OdsError> {
+    while let Some(xml_tag) = read_content_until_table(bs, book, opts, xml)? {
+        if opts.use_sheet(&table_name(&xml_tag)?) {
+            book.push_sheet(read_table(bs, xml, xml_tag)?);
+        } else {
+            skip_element(bs, xml, &xml_tag, false)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Name of the table from the table:table tag.
+fn table_name(xml_tag: &BytesStart<'_>) -> Result<String, OdsError> {
+    for attr in xml_tag.attributes().with_checks(false) {
+        let attr = attr?;
+        if attr.key.as_ref() == b"table:name" {
+            return Ok(attr.unescape_value()?.to_string());
+        }
+    }
+    Ok(String::new())
+}
+
+// Skips an element and all its content.
+fn skip_element<R: BufRead>(
+    bs: &mut BufStack,
+    xml: &mut quick_xml::Reader<R>,
+    xml_tag: &BytesStart<'_>,
+    empty_tag: bool,
+) -> Result<(), OdsError> {
+    if !empty_tag {
+        let mut buf = bs.get_buf();
+        xml.read_to_end_into(xml_tag.name(), &mut buf)?;
+        bs.push(buf);
+    }
+    Ok(())
+}
+
+// Removes the references to anything that was skipped while reading.
+fn clear_skipped(book: &mut WorkBook, opts: &ReadOptions) {
+    if opts.skip_formats {
+        for style in book.cellstyles.values_mut() {
+            style.attrmap_mut().clear_attr("style:data-style-name");
+        }
+    }
+
+    for sheet in book.sheets.iter_mut() {
+        if opts.skip_styles {
+            sheet.style = None;
+            for ch in sheet.col_header.values_mut() {
+                ch.style = None;
+                ch.cellstyle = None;
+            }
+            for rh in sheet.row_header.values_mut() {
+                rh.style = None;
+                rh.cellstyle = None;
+            }
+            for cell in sheet.data.values_mut() {
+                cell.style = None;
+            }
+        }
+        if opts.skip_validations {
+            for cell in sheet.data.values_mut() {
+                cell.validation_name = None;
+            }
+        }
+        if !opts.keep_extra_xml {
+            sheet.extra.clear();
+            sheet.page_images.clear();
+            // The image data and the charts are not loaded either.
+            for cell in sheet.data.values_mut() {
+                if let Some(extra) = &mut cell.extra {
+                    extra.images.clear();
+                    extra.charts.clear();
+                }
+            }
+        }
+    }
+}
+
+// Reads the content.xml up to the start of the next table.
+// Returns the table:table tag or None at the end of the document.
+fn read_content_until_table<R: BufRead>(
+    bs: &mut BufStack,
+    book: &mut WorkBook,
+    opts: &ReadOptions,
+    xml: &mut quick_xml::Reader<R>,
+) -> Result<Option<BytesStart<'static>>, OdsError> {
+    let mut result = None;
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        let empty_tag = matches!(evt, Event::Empty(_));
+        if DUMP_XML {
+            println!(" read_content {:?}", evt);
+        }
+        match evt {
+            Event::Decl(_) => {}
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:body"
+                || xml_tag.name().as_ref() == b"office:spreadsheet" => {
+                // noop
+            }
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"office:body"
+                || xml_tag.name().as_ref() == b"office:spreadsheet" => {
+                // noop
+            }
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:document-content" => {
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"office:version" => {
+                            book.set_version(attr.unescape_value()?.to_string());
+                        }
+                        _ => {
+                            // noop
+                        }
+                    }
+                }
+            }
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"office:document-content" => {
+                // noop
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:font-face-decls" && opts.skip_styles =>
+                skip_element(bs, xml, &xml_tag, false)?,
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:font-face-decls" =>
+                read_fonts(bs, book, StyleOrigin::Content, xml)?,
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:styles" =>
+                read_styles_tag(bs, book, opts, StyleOrigin::Content, xml)?,
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:automatic-styles" =>
+                read_auto_styles(bs, book, opts, StyleOrigin::Content, xml)?,
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:master-styles" && opts.skip_styles =>
+                skip_element(bs, xml, &xml_tag, false)?,
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"office:master-styles" =>
+                read_master_styles(bs, book, StyleOrigin::Content, xml)?,
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:content-validations" && opts.skip_validations =>
+                skip_element(bs, xml, &xml_tag, false)?,
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:content-validations" =>
+                read_validations(bs, book, xml)?,
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table" => {
+                result = Some(xml_tag.into_owned());
+                break;
+            }
+
+            Event::Empty(xml_tag) |
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:named-expressions" => {
+                let (ranges, exprs) = read_named_expressions(bs, xml, empty_tag)?;
+                for range in ranges {
+                    book.add_named_range(range);
+                }
+                for expr in exprs {
+                    book.add_named_expression(expr);
+                }
+            }
+
+            Event::Empty(xml_tag) |
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:calculation-settings" => {
+                let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                read_calculation_settings(book, &v)?;
+            }
+
+            Event::Empty(xml_tag) |
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:database-ranges" => {
+                let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                for db in read_database_ranges(&v)? {
+                    book.add_database_range(db);
+                }
+            }
+
+            Event::Empty(xml_tag) |
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:data-pilot-tables" => {
+                let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                for dp in read_data_pilot_tables(&v)? {
+                    book.add_data_pilot_table(dp);
+                }
+            }
+
+            Event::Empty(xml_tag) |
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:tracked-changes" => {
+                let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                book.tracked_changes = Some(read_tracked_changes(&v)?);
+            }
+
+            Event::Empty(xml_tag) |
+            Event::Start(xml_tag)
+            if /* prelude */ xml_tag.name().as_ref() == b"office:scripts" ||
+                xml_tag.name().as_ref() == b"text:variable-decls" ||
+                xml_tag.name().as_ref() == b"text:sequence-decls" ||
+                xml_tag.name().as_ref() == b"text:user-field-decls" ||
+                xml_tag.name().as_ref() == b"text:dde-connection-decls" ||
+                // xml_tag.name().as_ref() == b"text:alphabetical-index-auto-mark-file" ||
+                xml_tag.name().as_ref() == b"table:label-ranges" ||
+                /* epilogue */
+                xml_tag.name().as_ref() == b"table:consolidation" ||
+                xml_tag.name().as_ref() == b"table:dde-links" => {
+                if opts.keep_extra_xml {
+                    let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                    book.extra.push(v);
+                } else {
+                    skip_element(bs, xml, &xml_tag, empty_tag)?;
+                }
+            }
+
+            Event::End(xml_tag)
+            if /* prelude */ xml_tag.name().as_ref() == b"office:scripts" ||
+                xml_tag.name().as_ref() == b"text:variable-decls" ||
+                xml_tag.name().as_ref() == b"text:sequence-decls" ||
+                xml_tag.name().as_ref() == b"text:user-field-decls" ||
+                xml_tag.name().as_ref() == b"text:dde-connection-decls" ||
+                // xml_tag.name().as_ref() == b"text:alphabetical-index-auto-mark-file" ||
+                xml_tag.name().as_ref() == b"table:label-ranges" ||
+                /* epilogue */
+                xml_tag.name().as_ref() == b"table:consolidation" ||
+                xml_tag.name().as_ref() == b"table:dde-links" => {
+                // noop
+            }
+
+            Event::Eof => {
+                break;
+            }
+            _ => {
+                dump_unused2("read_content", &evt)?;
+            }
+        }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(result)
+}
+
+// Reads the table.
+fn read_table<R: BufRead>(
+    bs: &mut BufStack,
+    xml: &mut quick_xml::Reader<R>,
+    xml_tag: BytesStart<'_>,
+) -> Result<Sheet, OdsError> {
+    let mut sheet = Sheet::new("");
+
+    read_table_attr(&mut sheet, xml_tag)?;
+
+    let mut table = ReadTable::new();
+    let mut cells = Vec::new();
+    while let Some(row) = read_table_row(bs, xml, &mut sheet, &mut table, &mut cells)? {
+        for (col, cell) in cells.drain(..) {
+            sheet.add_cell_data(row, col, cell);
+        }
+    }
+
+    Ok(sheet)
+}
+
+// Position and row state while reading a table.
+#[derive(Debug)]
+struct ReadTable {
+    // Position within table-columns
+    table_col: u32,
+
+    // Cell position
+    row: u32,
+    col: u32,
+
+    // Rows can be repeated. In reality only empty ones ever are.
+    row_repeat: u32,
+    rowstyle: Option<String>,
+    row_cellstyle: Option<String>,
+    row_visible: Visibility,
+
+    col_range_from: u32,
+    row_range_from: u32,
+
+    // Open row/column groups with start and display flag.
+    row_groups: Vec<(u32, bool)>,
+    col_groups: Vec<(u32, bool)>,
+}
+
+impl ReadTable {
+    fn new() -> Self {
+        Self {
+            table_col: 0,
+            row: 0,
+            col: 0,
+            row_repeat: 1,
+            rowstyle: None,
+            row_cellstyle: None,
+            row_visible: Default::default(),
+            col_range_from: 0,
+            row_range_from: 0,
+            row_groups: Vec::new(),
+            col_groups: Vec::new(),
+        }
+    }
+}
+
+// Reads the table until the end of the next table-row. The cells of this
+// row are appended to cells, everything else is stored in the sheet.
+// Returns the row index, or None at the end of the table.
+fn read_table_row<R: BufRead>(
+    bs: &mut BufStack,
+    xml: &mut quick_xml::Reader<R>,
+    sheet: &mut Sheet,
+    table: &mut ReadTable,
+    cells: &mut Vec<(u32, CellData)>,
+) -> Result<Option<u32>, OdsError> {
+    let mut result = None;
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        let empty_tag = matches!(evt, Event::Empty(_));
+        if DUMP_XML {
+            println!(" read_table {:?}", evt);
+        }
+        match evt {
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table" => {
+                // TODO: Maybe find a better fix for the repeat error.
+                // Reset the repeat count for the last two rows to one if it exceeds
+                // some arbitrary limit. 
+                let mut it = sheet.row_header.iter_mut().rev();
+                if let Some((_row, last)) = it.next() {
+                    if last.repeat > 1000 {
+                        last.repeat = 1;
+                    }
+                }
+                if let Some((_row, last)) = it.next() {
+                    if last.repeat > 1000 {
+                        last.repeat = 1;
+                    }
+                }
+                break;
+            }
+
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if /* prelude */ xml_tag.name().as_ref() == b"table:title" ||
+                xml_tag.name().as_ref() == b"table:desc" ||
+                xml_tag.name().as_ref() == b"table:table-source" ||
+                xml_tag.name().as_ref() == b"office:dde-source" ||
+                xml_tag.name().as_ref() == b"office:forms" => {
+                sheet.extra.push(read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?);
+            }
+
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"table:shapes" => {
+                let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                read_shapes(sheet, v)?;
+            }
+
+            Event::End(xml_tag)
+            if /* prelude */ xml_tag.name().as_ref() == b"table:title" ||
+                xml_tag.name().as_ref() == b"table:desc" ||
+                xml_tag.name().as_ref() == b"table:table-source" ||
+                xml_tag.name().as_ref() == b"office:dde-source" ||
+                xml_tag.name().as_ref() == b"office:forms" ||
+                xml_tag.name().as_ref() == b"table:shapes" => {}
+
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"table:scenario" => {
+                let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                sheet.scenario = Some(read_scenario(&v)?);
+            }
+
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"loext:table-protection" => {
+                let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                read_table_protection(sheet, &v)?;
+            }
+
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"calcext:conditional-formats" => {
+                let v = read_xml(bs, xml_tag.name().as_ref(), xml, &xml_tag, empty_tag)?;
+                sheet.conditional_formats.append(&mut read_conditional_formats(&v)?);
+            }
+
+            Event::Start(xml_tag) |
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"table:named-expressions" => {
+                let (ranges, exprs) = read_named_expressions(bs, xml, empty_tag)?;
+                for range in ranges {
+                    sheet.add_named_range(range);
+                }
+                for expr in exprs {
+                    sheet.add_named_expression(expr);
+                }
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-header-columns" => {
+                table.col_range_from = table.table_col;
+            }
+
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-header-columns" => {
+                sheet.header_cols = Some(ColRange::new(table.col_range_from, table.table_col - 1));
+            }
+
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-column" => {
+                table.table_col = read_table_col_attr(sheet, table.table_col, &xml_tag)?;
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-column-group" => {
+                let display = read_group_attr(&xml_tag)?;
+                table.col_groups.push((table.table_col, display));
+            }
+
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-column-group" => {
+                if let Some((from, display)) = table.col_groups.pop() {
+                    // empty groups are dropped.
+                    if table.table_col > from {
+                        add_group(&mut sheet.group_cols, Grouped::new(from, table.table_col - 1, display))?;
+                    }
+                }
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-row-group" => {
+                let display = read_group_attr(&xml_tag)?;
+                table.row_groups.push((table.row, display));
+            }
+
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-row-group" => {
+                if let Some((from, display)) = table.row_groups.pop() {
+                    // empty groups are dropped.
+                    if table.row > from {
+                        add_group(&mut sheet.group_rows, Grouped::new(from, table.row - 1, display))?;
+                    }
+                }
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-header-rows" => {
+                table.row_range_from = table.row;
+            }
+
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-header-rows" => {
+                sheet.header_rows = Some(RowRange::new(table.row_range_from, table.row - 1));
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-row" => {
+                let (repeat, style, cellstyle, visible) = read_table_row_attr( xml_tag)?;
+                table.row_repeat = repeat;
+                table.rowstyle = style;
+                table.row_cellstyle = cellstyle;
+                table.row_visible = visible;
+            }
+
+            Event::End(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-row" => {
+                let row = table.row;
+                if table.row_repeat > 1 {
+                    sheet.set_row_repeat(row, table.row_repeat);
+                }
+                if let Some(rowstyle) = table.rowstyle.take() {
+                    sheet.set_rowstyle(row, &rowstyle.into());
+                }
+                if let Some(row_cellstyle) = table.row_cellstyle.take() {
+                    sheet.set_row_cellstyle(row, &row_cellstyle.into());
+                }
+                if table.row_visible != Visibility::Visible {
+                    sheet.set_row_visible(row, table.row_visible);
+                }
+                table.row_visible = Default::default();
+
+                table.row += table.row_repeat;
+                table.col = 0;
+                table.row_repeat = 1;
+
+                result = Some(row);
+                break;
+            }
+
+            Event::Empty(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-cell" || xml_tag.name().as_ref() == b"table:covered-table-cell" => {
+                table.col = read_empty_table_cell(cells, table.col, xml_tag)?;
+            }
+
+            Event::Start(xml_tag)
+            if xml_tag.name().as_ref() == b"table:table-cell" || xml_tag.name().as_ref() == b"table:covered-table-cell" => {
+                table.col = read_table_cell2(bs, cells, table.col, xml, xml_tag)?;
+            }
+
+            Event::Eof => {
+                break;
+            }
+
+            _ => {
+                dump_unused2("read_table", &evt)?;
+            }
+        }
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(result)
+}
+
+// Reads the table attributes.
+fn read_table_attr(sheet: &mut Sheet, xml_tag: BytesStart<'_>) -> Result<(), OdsError> {
+    let mut protected = false;
+    let mut protection = SheetProtection::default();
+    for attr in xml_tag.attributes().with_checks(false) {
+        match attr? {
+            attr if attr.key.as_ref() == b"table:name" => {
+                sheet.set_name(attr.unescape_value()?.to_string());
+            }
+            attr if attr.key.as_ref() == b"table:style-name" => {
+                sheet.set_style(&attr.unescape_value()?.as_ref().into());
+            }
+            attr if attr.key.as_ref() == b"table:print" => {
+                sheet.set_print(parse_bool(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"table:display" => {
+                sheet.set_display(parse_bool(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"table:print-ranges" => {
+                let v = attr.unescape_value()?;
+                let mut pos = 0usize;
+                sheet.print_ranges = parse_cellranges(v.as_ref(), &mut pos)?;
+            }
+            attr if attr.key.as_ref() == b"table:protected" => {
+                protected = parse_bool(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:protection-key" => {
+                protection.key = attr.unescape_value()?.to_string();
+            }
+            attr if attr.key.as_ref() == b"table:protection-key-digest-algorithm" => {
+                protection.digest_algorithm = Some(attr.unescape_value()?.to_string());
+            }
+            attr => {
+                dump_unused("read_table_attr", xml_tag.name().as_ref(), &attr)?;
+            }
+        }
+    }
+    if protected {
+        sheet.protection = Some(protection);
+    }
+
+    Ok(())
+}
+
+// Reads loext:table-protection. A missing attribute means not allowed.
+fn read_table_protection(sheet: &mut Sheet, xml_tag: &XmlTag) -> Result<(), OdsError> {
+    let mut options = ProtectionOptions::new();
+    options.set_select_protected_cells(false);
+    options.set_select_unprotected_cells(false);
+    for (k, v) in xml_tag.attrmap().iter() {
+        match k.as_ref() {
+            "loext:select-protected-cells" => {
+                options.set_select_protected_cells(parse_bool(v.as_bytes())?);
+            }
+            "loext:select-unprotected-cells" => {
+                options.set_select_unprotected_cells(parse_bool(v.as_bytes())?);
+            }
+            "loext:insert-columns" => options.set_insert_columns(parse_bool(v.as_bytes())?),
+            "loext:insert-rows" => options.set_insert_rows(parse_bool(v.as_bytes())?),
+            "loext:delete-columns" => options.set_delete_columns(parse_bool(v.as_bytes())?),
+            "loext:delete-rows" => options.set_delete_rows(parse_bool(v.as_bytes())?),
+            _ => {}
+        }
+    }
+    if let Some(protection) = &mut sheet.protection {
+        protection.options = options;
+    }
+    Ok(())
+}
+
+// Reads table:scenario.
+fn read_scenario(xml_tag: &XmlTag) -> Result<Scenario, OdsError> {
+    let mut scenario = Scenario::default();
+    for (k, v) in xml_tag.attrmap().iter() {
+        match k.as_ref() {
+            // all ranges might have been deleted.
+            "table:scenario-ranges" if !v.is_empty() => {
+                scenario.set_ranges(parse_cellranges(v, &mut 0)?.unwrap_or_default());
+            }
+            "table:is-active" => scenario.set_active(parse_bool(v.as_bytes())?),
+            "table:display-border" => scenario.set_display_border(parse_bool(v.as_bytes())?),
+            "table:border-color" => scenario.set_border_color(Some(parse_color(v.as_bytes())?)),
+            "table:copy-back" => scenario.set_copy_back(parse_bool(v.as_bytes())?),
+            "table:copy-styles" => scenario.set_copy_styles(parse_bool(v.as_bytes())?),
+            "table:copy-formulas" => scenario.set_copy_formulas(parse_bool(v.as_bytes())?),
+            "table:protected" => scenario.set_protected(parse_bool(v.as_bytes())?),
+            "table:comment" => scenario.set_comment(v.as_str()),
+            _ => {}
+        }
+    }
+    Ok(scenario)
+}
+
+// Reads the attributes of a row or column group. Returns the display flag.
+fn read_group_attr(xml_tag: &BytesStart<'_>) -> Result<bool, OdsError> {
+    let mut display = true;
+    for attr in xml_tag.attributes().with_checks(false) {
+        match attr? {
+            attr if attr.key.as_ref() == b"table:display" => {
+                display = parse_bool(&attr.value)?;
+            }
+            attr => {
+                dump_unused("read_group_attr", xml_tag.name().as_ref(), &attr)?;
+            }
+        }
+    }
+    Ok(display)
+}
+
+// Reads table-row attributes. Returns the repeat-count.
+fn read_table_row_attr(
+    xml_tag: BytesStart<'_>,
+) -> Result<(u32, Option<String>, Option<String>, Visibility), OdsError> {
+    let mut row_repeat: u32 = 1;
+    let mut row_visible: Visibility = Default::default();
+    let mut rowstyle: Option<String> = None;
+    let mut row_cellstyle: Option<String> = None;
+
+    for attr in xml_tag.attributes().with_checks(false) {
+        match attr? {
+            // table:default-cell-style-name 19.615, table:visibility 19.749 and xml:id 19.914.
+            attr if attr.key.as_ref() == b"table:number-rows-repeated" => {
+                row_repeat = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:style-name" => {
+                rowstyle = Some(attr.unescape_value()?.to_string());
+            }
+            attr if attr.key.as_ref() == b"table:default-cell-style-name" => {
+                row_cellstyle = Some(attr.unescape_value()?.to_string());
+            }
+            attr if attr.key.as_ref() == b"table:visibility" => {
+                row_visible = parse_visibility(&attr.value)?;
+            }
+            attr => {
+                dump_unused("read_table_row_attr", xml_tag.name().as_ref(), &attr)?;
+            }
+        }
+    }
+
+    Ok((row_repeat, rowstyle, row_cellstyle, row_visible))
+}
+
+// Reads the table-column attributes. Creates as many copies as indicated.
+fn read_table_col_attr(
+    sheet: &mut Sheet,
+    mut table_col: u32,
+    xml_tag: &BytesStart<'_>,
+) -> Result<u32, OdsError> {
+    let mut style = None;
+    let mut cellstyle = None;
+    let mut repeat: u32 = 1;
+    let mut visible: Visibility = Default::default();
+
+    for attr in xml_tag.attributes().with_checks(false) {
+        match attr? {
+            attr if attr.key.as_ref() == b"table:number-columns-repeated" => {
+                repeat = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:style-name" => {
+                style = Some(attr.unescape_value()?);
+            }
+            attr if attr.key.as_ref() == b"table:default-cell-style-name" => {
+                cellstyle = Some(attr.unescape_value()?);
+            }
+            attr if attr.key.as_ref() == b"table:visibility" => {
+                visible = parse_visibility(&attr.value)?;
+            }
+            attr => {
+                dump_unused("read_table_col_attr", xml_tag.name().as_ref(), &attr)?;
+            }
+        }
+    }
+
+    while repeat > 0 {
+        if let Some(style) = &style {
+            sheet.set_colstyle(table_col, &style.as_ref().into());
+        }
+        if let Some(cellstyle) = &cellstyle {
+            sheet.set_col_cellstyle(table_col, &cellstyle.as_ref().into());
+        }
+        sheet.set_col_visible(table_col, visible);
+        table_col += 1;
+        repeat -= 1;
+    }
+
+    Ok(table_col)
+}
+
+#[derive(Debug)]
+#[allow(variant_size_differences)]
+enum TextContent2 {
+    Empty,
+    Text(String),
+    Xml(TextTag),
+    XmlVec(Vec<TextTag>),
+}
+
+#[derive(Debug)]
+struct ReadTableCell2 {
+    val_type: ValueType,
+    val_datetime: Option<NaiveDateTime>,
+    val_duration: Option<Duration>,
+    val_float: Option<f64>,
+    val_bool: Option<bool>,
+    val_string: Option<String>,
+    val_currency: Option<String>,
+
+    content: TextContent2,
+}
+
+fn read_table_cell2<R: BufRead>(
+    bs: &mut BufStack,
+    cells: &mut Vec<(u32, CellData)>,
+    mut col: u32,
+    xml: &mut quick_xml::Reader<R>,
+    xml_tag: BytesStart<'_>,
+) -> Result<u32, OdsError> {
+    // Current cell tag
+    let tag_name = xml_tag.name();
+
+    let mut cell_repeat: u32 = 1;
+
+    let mut cell = CellData {
+        value: Default::default(),
+        formula: None,
+        style: None,
+        validation_name: None,
+        extra: None,
         span: Default::default(),
     };
 
-    let mut tc = ReadTableCell2 {
-        val_type: ValueType::Empty,
-        val_datetime: None,
-        val_duration: None,
-        val_float: None,
-        val_bool: None,
-        val_string: None,
-        val_currency: None,
-        content: TextContent2::Empty,
-    };
+    let mut tc = ReadTableCell2 {
+        val_type: ValueType::Empty,
+        val_datetime: None,
+        val_duration: None,
+        val_float: None,
+        val_bool: None,
+        val_string: None,
+        val_currency: None,
+        content: TextContent2::Empty,
+    };
+
+    for attr in xml_tag.attributes().with_checks(false) {
+        match attr? {
+            attr if attr.key.as_ref() == b"table:number-columns-repeated" => {
+                cell_repeat = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:number-rows-spanned" => {
+                cell.span.row_span = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:number-columns-spanned" => {
+                cell.span.col_span = parse_u32(&attr.value)?;
+            }
+            attr if attr.key.as_ref() == b"table:content-validation-name" => {
+                cell.validation_name = Some(attr.unescape_value()?.to_string());
+            }
+            attr if attr.key.as_ref() == b"calcext:value-type" => {
+                // not used. office:value-type seems to be good enough.
+            }
+            attr if attr.key.as_ref() == b"office:value-type" => {
+                tc.val_type = match attr.value.as_ref() {
+                    b"string" => ValueType::Text,
+                    b"float" => ValueType::Number,
+                    b"percentage" => ValueType::Percentage,
+                    b"date" => ValueType::DateTime,
+                    b"time" => ValueType::TimeDuration,
+                    b"boolean" => ValueType::Boolean,
+                    b"currency" => ValueType::Currency,
+                    other => return Err(OdsError::Parse(format!("Unknown cell-type {:?}", other))),
+                }
+            }
+            attr if attr.key.as_ref() == b"office:date-value" => {
+                tc.val_datetime = Some(parse_datetime(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"office:time-value" => {
+                tc.val_duration = Some(parse_duration(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"office:value" => {
+                tc.val_float = Some(parse_f64(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"office:boolean-value" => {
+                tc.val_bool = Some(parse_bool(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"office:string-value" => {
+                tc.val_string = Some(attr.unescape_value()?.to_string());
+            }
+            attr if attr.key.as_ref() == b"office:currency" => {
+                tc.val_currency = Some(parse_currency(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"table:formula" => {
+                cell.formula = Some(attr.unescape_value()?.to_string());
+            }
+            attr if attr.key.as_ref() == b"table:style-name" => {
+                cell.style = Some(attr.unescape_value()?.to_string());
+            }
+            attr => {
+                dump_unused("read_table_cell2", xml_tag.name().as_ref(), &attr)?;
+            }
+        }
+    }
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        if DUMP_XML {
+            println!(" read_table_cell {:?}", evt);
+        }
+        match evt {
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"text:p" => {
+                let new_txt = read_text_or_tag(bs, b"text:p", xml, &xml_tag, false)?;
+                tc.content = append_text(new_txt, tc.content);
+            }
+            Event::Empty(xml_tag) if xml_tag.name().as_ref() == b"text:p" => {
+                // noop
+            }
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:annotation" => {
+                cell.extra_mut().annotation = Some(read_annotation(bs, xml, &xml_tag)?);
+            }
+            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"draw:frame" => {
+                let frame = read_xml(bs, b"draw:frame", xml, &xml_tag, false)?;
+                // Only images and charts, anything else is dropped.
+                if let Some(chart) = read_chart(&frame)? {
+                    cell.extra_mut().charts.push(chart);
+                } else if let Some(image) = read_image(&frame)? {
+                    cell.extra_mut().images.push(image);
+                }
+            }
+
+            Event::End(xml_tag) if xml_tag.name() == tag_name => {
+                parse_value2(tc, &mut cell)?;
+
+                while cell_repeat > 1 {
+                    cells.push((col, cell.clone()));
+                    col += 1;
+                    cell_repeat -= 1;
+                }
+                cells.push((col, cell));
+                col += 1;
+
+                break;
+            }
+
+            Event::Eof => {
+                break;
+            }
+
+            _ => {
+                dump_unused2("read_table_cell", &evt)?;
+            }
+        }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(col)
+}
+
+// Reads the office:annotation of a cell.
+fn read_annotation<R: BufRead>(
+    bs: &mut BufStack,
+    xml: &mut quick_xml::Reader<R>,
+    xml_tag: &BytesStart<'_>,
+) -> Result<Annotation, OdsError> {
+    let mut annotation = Annotation::new_empty();
+
+    for attr in xml_tag.attributes().with_checks(false) {
+        match attr? {
+            attr if attr.key.as_ref() == b"office:display" => {
+                annotation.set_display(parse_bool(&attr.value)?);
+            }
+            attr if attr.key.as_ref() == b"office:name" => {
+                annotation.set_name(Some(attr.unescape_value()?.to_string()));
+            }
+            attr => {
+                let k = from_utf8(attr.key.as_ref())?;
+                let v = attr.unescape_value()?.to_string();
+                annotation.attrmap_mut().set_attr(k, v);
+            }
+        }
+    }
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        let empty_tag = matches!(evt, Event::Empty(_));
+        if DUMP_XML {
+            println!(" read_annotation {:?}", evt);
+        }
+        match evt {
+            Event::Start(ref xml_tag) | Event::Empty(ref xml_tag) => {
+                match xml_tag.name().as_ref() {
+                    b"dc:creator" => {
+                        let v = read_xml(bs, b"dc:creator", xml, xml_tag, empty_tag)?;
+                        let mut creator = String::new();
+                        v.extract_text(&mut creator);
+                        annotation.set_creator(Some(creator));
+                    }
+                    b"dc:date" => {
+                        let v = read_xml(bs, b"dc:date", xml, xml_tag, empty_tag)?;
+                        let mut date = String::new();
+                        v.extract_text(&mut date);
+                        annotation.set_date(Some(parse_datetime(date.as_bytes())?));
+                    }
+                    b"meta:date-string" => {
+                        // not used. only if dc:date is missing.
+                        read_xml(bs, b"meta:date-string", xml, xml_tag, empty_tag)?;
+                    }
+                    _ => {
+                        // text:p, text:list
+                        let v = read_xml(bs, xml_tag.name().as_ref(), xml, xml_tag, empty_tag)?;
+                        annotation.push_text(v);
+                    }
+                }
+            }
+            Event::Text(_) => (),
+            Event::End(ref e) if e.name().as_ref() == b"office:annotation" => {
+                break;
+            }
+            Event::Eof => break,
+            _ => {
+                dump_unused2("read_annotation", &evt)?;
+            }
+        }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(annotation)
+}
+
+// Takes the images anchored to the page from the table:shapes. All other
+// shapes are kept as they are.
+fn read_shapes(sheet: &mut Sheet, mut shapes: XmlTag) -> Result<(), OdsError> {
+    let mut keep = false;
+    for c in mem::take(shapes.content_mut()) {
+        if let XmlContent::Tag(t) = &c {
+            if t.name() == "draw:frame" {
+                if let Some(image) = read_image(t)? {
+                    sheet.page_images.push(image);
+                    continue;
+                }
+            }
+            keep = true;
+        }
+        shapes.content_mut().push(c);
+    }
+    if keep {
+        sheet.extra.push(shapes);
+    }
+    Ok(())
+}
+
+// Converts a draw:frame that contains a draw:image. The data is loaded
+// later from the package, or is contained as office:binary-data.
+fn read_image(frame: &XmlTag) -> Result<Option<Image>, OdsError> {
+    let draw_image = frame.content().iter().find_map(|v| match v {
+        XmlContent::Tag(t) if t.name() == "draw:image" => Some(t),
+        _ => None,
+    });
+    let draw_image = match draw_image {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let mut data = Vec::new();
+    for c in draw_image.content() {
+        if let XmlContent::Tag(t) = c {
+            if t.name() == "office:binary-data" {
+                let mut text = String::new();
+                t.extract_text(&mut text);
+                text.retain(|c| !c.is_ascii_whitespace());
+                data = parse_base64(text.as_bytes())?;
+            }
+        }
+    }
+    let href = draw_image.attrmap().attr("xlink:href");
+
+    // The data for the package images is loaded later, a generic
+    // mime-type is replaced then.
+    let mime_type = match draw_image
+        .attrmap()
+        .attr("draw:mime-type")
+        .or_else(|| draw_image.attrmap().attr("loext:mime-type"))
+    {
+        Some(v) if !v.is_empty() => v.clone(),
+        _ => guess_mime_type(href.map(|v| v.as_str()).unwrap_or_default(), &data).to_string(),
+    };
+    let mut image = Image::new(mime_type, data);
+    if let Some(href) = href {
+        image.set_href(href.as_str());
+    }
+
+    let mut x = None;
+    let mut y = None;
+    let mut width = None;
+    let mut height = None;
+    for (k, v) in frame.attrmap().iter() {
+        match k.as_ref() {
+            "draw:name" => image.set_name(v.as_str()),
+            "draw:style-name" => image.set_style(&GraphicStyleRef::from(v.as_str())),
+            "svg:x" => x = Length::parse_attr(Some(v))?,
+            "svg:y" => y = Length::parse_attr(Some(v))?,
+            "svg:width" => width = Length::parse_attr(Some(v))?,
+            "svg:height" => height = Length::parse_attr(Some(v))?,
+            _ => image.attrmap_mut().set_attr(k.as_ref(), v.clone()),
+        }
+    }
+    image.set_position(x.unwrap_or(Length::Cm(0.0)), y.unwrap_or(Length::Cm(0.0)));
+    image.set_size(width.unwrap_or_default(), height.unwrap_or_default());
+
+    for c in frame.content() {
+        if let XmlContent::Tag(t) = c {
+            let mut text = String::new();
+            t.extract_text(&mut text);
+            match t.name() {
+                "svg:title" => image.set_title(Some(text)),
+                "svg:desc" => image.set_desc(Some(text)),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(Some(image))
+}
+
+// Copies the data for the images from the package.
+// Returns the paths that were used.
+fn load_images(filebuf: &FileBuf, sheet: &mut Sheet) -> Vec<String> {
+    let mut used = Vec::new();
+    for cell in sheet.data.values_mut() {
+        if let Some(extra) = &mut cell.extra {
+            for image in extra.images.iter_mut() {
+                if load_image(filebuf, image) {
+                    used.push(image.href().to_string());
+                }
+            }
+        }
+    }
+    for image in sheet.page_images.iter_mut() {
+        if load_image(filebuf, image) {
+            used.push(image.href().to_string());
+        }
+    }
+    used
+}
+
+// Copies the data for one image. Returns true if the data was found.
+fn load_image(filebuf: &FileBuf, image: &mut Image) -> bool {
+    if !image.data().is_empty() {
+        return false;
+    }
+    match filebuf.get_file(image.href()) {
+        Some(data) => {
+            image.set_data_raw(data.to_vec());
+            if image.mime_type() == "application/octet-stream" {
+                image.set_mime_type(guess_mime_type("", image.data()));
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+// Copies the data for images whose file was already taken from the package
+// by another sheet that has been loaded before.
+fn load_shared_images(book: &WorkBook, sheet: &mut Sheet) {
+    let images = sheet
+        .data
+        .values_mut()
+        .filter_map(|v| v.extra.as_mut())
+        .flat_map(|v| v.images.iter_mut())
+        .chain(sheet.page_images.iter_mut());
+    for image in images {
+        if image.data().is_empty() {
+            let loaded = book
+                .sheets
+                .iter()
+                .filter(|v| !v.is_detached())
+                .flat_map(|v| {
+                    v.data
+                        .values()
+                        .filter_map(|v| v.extra.as_ref())
+                        .flat_map(|v| v.images.iter())
+                        .chain(v.page_images.iter())
+                })
+                .find(|v| v.href() == image.href() && !v.data().is_empty());
+            if let Some(loaded) = loaded {
+                image.set_data_raw(loaded.data().to_vec());
+            }
+        }
+    }
+}
+
+// Converts a draw:frame that contains a draw:object. The chart itself
+// is contained inline for flat files, otherwise it is loaded later from
+// the package.
+fn read_chart(frame: &XmlTag) -> Result<Option<Chart>, OdsError> {
+    let draw_object = frame.content().iter().find_map(|v| match v {
+        XmlContent::Tag(t) if t.name() == "draw:object" => Some(t),
+        _ => None,
+    });
+    let draw_object = match draw_object {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let mut chart = Chart::new(ChartType::Bar);
+    if let Some(href) = draw_object.attrmap().attr("xlink:href") {
+        chart.set_href(href.trim_start_matches("./"));
+    } else if let Some(chart_tag) = find_tag(draw_object, "chart:chart") {
+        if !read_chart_body(chart_tag, &mut chart)? {
+            return Ok(None);
+        }
+    } else {
+        return Ok(None);
+    }
+
+    let mut x = None;
+    let mut y = None;
+    let mut width = None;
+    let mut height = None;
+    for (k, v) in frame.attrmap().iter() {
+        match k.as_ref() {
+            "draw:name" => chart.set_name(v.as_str()),
+            "svg:x" => x = Length::parse_attr(Some(v))?,
+            "svg:y" => y = Length::parse_attr(Some(v))?,
+            "svg:width" => width = Length::parse_attr(Some(v))?,
+            "svg:height" => height = Length::parse_attr(Some(v))?,
+            _ => chart.attrmap_mut().set_attr(k.as_ref(), v.clone()),
+        }
+    }
+    chart.set_position(x.unwrap_or(Length::Cm(0.0)), y.unwrap_or(Length::Cm(0.0)));
+    chart.set_size(width.unwrap_or_default(), height.unwrap_or_default());
+
+    Ok(Some(chart))
+}
+
+// Reads the chart:chart element. Returns false for unsupported chart types.
+fn read_chart_body(chart_tag: &XmlTag, chart: &mut Chart) -> Result<bool, OdsError> {
+    let chart_type = chart_tag
+        .attrmap()
+        .attr("chart:class")
+        .and_then(|v| ChartType::from_class(v.as_str()));
+    match chart_type {
+        Some(chart_type) => chart.set_chart_type(chart_type),
+        None => return Ok(false),
+    }
+
+    for c in chart_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) => t,
+            XmlContent::Text(_) => continue,
+        };
+        match t.name() {
+            "chart:title" => {
+                let mut title = String::new();
+                t.extract_text(&mut title);
+                chart.set_title(Some(title));
+            }
+            "chart:legend" => {
+                let pos = t
+                    .attrmap()
+                    .attr("chart:legend-position")
+                    .map(|v| LegendPosition::parse(v.as_str()))
+                    .unwrap_or(LegendPosition::End);
+                chart.set_legend(Some(pos));
+            }
+            "chart:plot-area" => {
+                read_chart_plot_area(t, chart)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(true)
+}
+
+// Axis and series of a chart.
+fn read_chart_plot_area(plot_area: &XmlTag, chart: &mut Chart) -> Result<(), OdsError> {
+    for c in plot_area.content() {
+        let t = match c {
+            XmlContent::Tag(t) => t,
+            XmlContent::Text(_) => continue,
+        };
+        match t.name() {
+            "chart:axis" => {
+                let x_axis = t.attrmap().attr("chart:dimension").map(|v| v.as_str()) == Some("x");
+                for c in t.content() {
+                    if let XmlContent::Tag(t) = c {
+                        match t.name() {
+                            "chart:title" => {
+                                let mut title = String::new();
+                                t.extract_text(&mut title);
+                                if x_axis {
+                                    chart.set_x_axis_title(Some(title));
+                                } else {
+                                    chart.set_y_axis_title(Some(title));
+                                }
+                            }
+                            "chart:categories" => {
+                                if let Some(v) = t.attrmap().attr("table:cell-range-address") {
+                                    chart.set_categories(Some(parse_cellrange(v, &mut 0)?));
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            "chart:series" => {
+                let values = match t.attrmap().attr("chart:values-cell-range-address") {
+                    Some(v) => parse_cellrange(v, &mut 0)?,
+                    // internal data is not supported.
+                    None => continue,
+                };
+                let mut series = Series::new(values);
+                if let Some(v) = t.attrmap().attr("chart:label-cell-address") {
+                    series.set_label(Some(parse_cellref(v, &mut 0)?));
+                }
+                for c in t.content() {
+                    if let XmlContent::Tag(t) = c {
+                        if t.name() == "chart:domain" {
+                            if let Some(v) = t.attrmap().attr("table:cell-range-address") {
+                                series.set_domain(Some(parse_cellrange(v, &mut 0)?));
+                            }
+                        }
+                    }
+                }
+                chart.push_series(series);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// Depth first search for a tag.
+fn find_tag<'a>(tag: &'a XmlTag, name: &str) -> Option<&'a XmlTag> {
+    for c in tag.content() {
+        if let XmlContent::Tag(t) = c {
+            if t.name() == name {
+                return Some(t);
+            }
+            if let Some(t) = find_tag(t, name) {
+                return Some(t);
+            }
+        }
+    }
+    None
+}
+
+// Parses the chart documents from the package. Charts that can't be
+// parsed are dropped.
+// Returns the paths that were used.
+fn load_charts(
+    bs: &mut BufStack,
+    filebuf: &FileBuf,
+    sheet: &mut Sheet,
+) -> Result<Vec<String>, OdsError> {
+    let mut used = Vec::new();
+    for cell in sheet.data.values_mut() {
+        if let Some(extra) = &mut cell.extra {
+            let mut charts = Vec::new();
+            for mut chart in extra.charts.drain(..) {
+                let href = match chart.href() {
+                    Some(v) => v.clone(),
+                    None => {
+                        charts.push(chart);
+                        continue;
+                    }
+                };
+                let content = match filebuf.get_file(format!("{}/content.xml", href)) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let doc = read_xml_doc(bs, content)?;
+                if let Some(chart_tag) = doc.as_ref().and_then(|v| find_tag(v, "chart:chart")) {
+                    if read_chart_body(chart_tag, &mut chart)? {
+                        charts.push(chart);
+                        used.push(href);
+                    }
+                }
+            }
+            extra.charts = charts;
+        }
+    }
+    Ok(used)
+}
+
+// Parses a complete xml document.
+fn read_xml_doc(bs: &mut BufStack, data: &[u8]) -> Result<Option<XmlTag>, OdsError> {
+    let mut xml = quick_xml::Reader::from_reader(data);
+
+    let mut result = None;
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        match evt {
+            Event::Start(ref xml_tag) => {
+                let name = xml_tag.name().as_ref().to_vec();
+                result = Some(read_xml(bs, &name, &mut xml, xml_tag, false)?);
+                break;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(result)
+}
+
+fn append_text(new_txt: TextContent2, mut content: TextContent2) -> TextContent2 {
+    // There can be multiple text:p elements within the cell.
+    content = match content {
+        TextContent2::Empty => new_txt,
+        TextContent2::Text(txt) => {
+            // Have a destructured text:p from before.
+            // Wrap up and create list.
+            let p = TextP::new().text(txt).into_xmltag();
+            let mut vec = vec![p];
+
+            match new_txt {
+                TextContent2::Empty => {}
+                TextContent2::Text(txt) => {
+                    let p2 = TextP::new().text(txt).into_xmltag();
+                    vec.push(p2);
+                }
+                TextContent2::Xml(xml) => {
+                    vec.push(xml);
+                }
+                TextContent2::XmlVec(_) => {
+                    unreachable!();
+                }
+            }
+            TextContent2::XmlVec(vec)
+        }
+        TextContent2::Xml(xml) => {
+            let mut vec = vec![xml];
+            match new_txt {
+                TextContent2::Empty => {}
+                TextContent2::Text(txt) => {
+                    let p2 = TextP::new().text(txt).into_xmltag();
+                    vec.push(p2);
+                }
+                TextContent2::Xml(xml) => {
+                    vec.push(xml);
+                }
+                TextContent2::XmlVec(_) => {
+                    unreachable!();
+                }
+            }
+            TextContent2::XmlVec(vec)
+        }
+        TextContent2::XmlVec(mut vec) => {
+            match new_txt {
+                TextContent2::Empty => {}
+                TextContent2::Text(txt) => {
+                    let p2 = TextP::new().text(txt).into_xmltag();
+                    vec.push(p2);
+                }
+                TextContent2::Xml(xml) => {
+                    vec.push(xml);
+                }
+                TextContent2::XmlVec(_) => {
+                    unreachable!();
+                }
+            }
+            TextContent2::XmlVec(vec)
+        }
+    };
+
+    content
+}
+
+fn parse_value2(tc: ReadTableCell2, cell: &mut CellData) -> Result<(), OdsError> {
+    match tc.val_type {
+        ValueType::Empty => {
+            // noop
+        }
+        ValueType::Boolean => {
+            if let Some(v) = tc.val_bool {
+                cell.value = Value::Boolean(v);
+            } else {
+                return Err(OdsError::Parse("no boolean value".to_string()));
+            }
+        }
+        ValueType::Number => {
+            if let Some(v) = tc.val_float {
+                cell.value = Value::Number(v);
+            } else {
+                return Err(OdsError::Parse("no float value".to_string()));
+            }
+        }
+        ValueType::Percentage => {
+            if let Some(v) = tc.val_float {
+                cell.value = Value::Percentage(v);
+            } else {
+                return Err(OdsError::Parse("no float value".to_string()));
+            }
+        }
+        ValueType::Currency => {
+            if let Some(v) = tc.val_float {
+                if let Some(c) = tc.val_currency {
+                    cell.value = Value::Currency(v, c);
+                } else {
+                    return Err(OdsError::Parse("no currency value".to_string()));
+                }
+            } else {
+                return Err(OdsError::Parse("no float value".to_string()));
+            }
+        }
+        ValueType::Text => {
+            if let Some(v) = tc.val_string {
+                cell.value = Value::Text(v);
+            } else {
+                match tc.content {
+                    TextContent2::Empty => {
+                        // noop
+                    }
+                    TextContent2::Text(txt) => {
+                        cell.value = Value::Text(txt);
+                    }
+                    TextContent2::Xml(xml) => {
+                        cell.value = Value::TextXml(vec![xml]);
+                    }
+                    TextContent2::XmlVec(vec) => {
+                        cell.value = Value::TextXml(vec);
+                    }
+                }
+            }
+        }
+        ValueType::TextXml => {
+            unreachable!();
+        }
+        ValueType::DateTime => {
+            if let Some(v) = tc.val_datetime {
+                cell.value = Value::DateTime(v);
+            } else {
+                return Err(OdsError::Parse("no datetime value".to_string()));
+            }
+        }
+        ValueType::TimeDuration => {
+            if let Some(v) = tc.val_duration {
+                cell.value = Value::TimeDuration(v);
+            } else {
+                return Err(OdsError::Parse("no duration value".to_string()));
+            }
+        }
+    }
+
+    Ok(())
+}
 
+/// Reads a table-cell from an empty XML tag.
+/// There seems to be no data associated, but it can have a style and a formula.
+/// And first of all we need the repeat count for the correct placement.
+fn read_empty_table_cell(
+    cells: &mut Vec<(u32, CellData)>,
+    mut col: u32,
+    xml_tag: BytesStart<'_>,
+) -> Result<u32, OdsError> {
+    let mut cell = None;
+    // Default advance is one column.
+    let mut cell_repeat = 1;
     for attr in xml_tag.attributes().with_checks(false) {
         match attr? {
             attr if attr.key.as_ref() == b"table:number-columns-repeated" => {
                 cell_repeat = parse_u32(&attr.value)?;
             }
+            attr if attr.key.as_ref() == b"table:formula" => {
+                cell.get_or_insert_with(CellData::new).formula =
+                    Some(attr.unescape_value()?.to_string());
+            }
+            attr if attr.key.as_ref() == b"table:style-name" => {
+                cell.get_or_insert_with(CellData::new).style =
+                    Some(attr.unescape_value()?.to_string());
+            }
             attr if attr.key.as_ref() == b"table:number-rows-spanned" => {
-                cell.span.row_span = parse_u32(&attr.value)?;
+                cell.get_or_insert_with(CellData::new).span.row_span = parse_u32(&attr.value)?;
             }
             attr if attr.key.as_ref() == b"table:number-columns-spanned" => {
-                cell.span.col_span = parse_u32(&attr.value)?;
+                cell.get_or_insert_with(CellData::new).span.col_span = parse_u32(&attr.value)?;
             }
             attr if attr.key.as_ref() == b"table:content-validation-name" => {
-                cell.validation_name = Some(attr.unescape_value()?.to_string());
+                cell.get_or_insert_with(CellData::new).validation_name =
+                    Some(attr.unescape_value()?.to_string());
             }
-            attr if attr.key.as_ref() == b"calcext:value-type" => {
-                // not used. office:value-type seems to be good enough.
+
+            attr => {
+                dump_unused("read_empty_table_cell", xml_tag.name().as_ref(), &attr)?;
             }
-            attr if attr.key.as_ref() == b"office:value-type" => {
-                tc.val_type = match attr.value.as_ref() {
-                    b"string" => ValueType::Text,
-                    b"float" => ValueType::Number,
-                    b"percentage" => ValueType::Percentage,
-                    b"date" => ValueType::DateTime,
-                    b"time" => ValueType::TimeDuration,
-                    b"boolean" => ValueType::Boolean,
-                    b"currency" => ValueType::Currency,
-                    other => return Err(OdsError::Parse(format!("Unknown cell-type {:?}", other))),
+        }
+    }
+
+    if let Some(cell) = cell {
+        while cell_repeat > 1 {
+            cells.push((col, cell.clone()));
+            col += 1;
+            cell_repeat -= 1;
+        }
+        cells.push((col, cell));
+        col += 1;
+    } else {
+        col += cell_repeat;
+    }
+
+    Ok(col)
+}
+
+// reads a font-face
+fn read_fonts<R: BufRead>(
+    bs: &mut BufStack,
+    book: &mut WorkBook,
+    origin: StyleOrigin,
+    xml: &mut quick_xml::Reader<R>,
+    // no attributes
+) -> Result<(), OdsError> {
+    let mut font: FontFaceDecl = FontFaceDecl::new_empty();
+    font.set_origin(origin);
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        if DUMP_XML {
+            println!(" read_fonts {:?}", evt);
+        }
+        match evt {
+            Event::Start(ref xml_tag) | Event::Empty(ref xml_tag) => {
+                match xml_tag.name().as_ref() {
+                    b"style:font-face" => {
+                        let name = proc_style_attr(font.attrmap_mut(), xml_tag)?;
+                        font.set_name(name);
+
+                        book.add_font(font);
+
+                        font = FontFaceDecl::new_empty();
+                        font.set_origin(StyleOrigin::Content);
+                    }
+                    _ => {
+                        dump_unused2("read_fonts", &evt)?;
+                    }
                 }
             }
-            attr if attr.key.as_ref() == b"office:date-value" => {
-                tc.val_datetime = Some(parse_datetime(&attr.value)?);
-            }
-            attr if attr.key.as_ref() == b"office:time-value" => {
-                tc.val_duration = Some(parse_duration(&attr.value)?);
+            Event::End(ref e) => {
+                if e.name().as_ref() == b"office:font-face-decls" {
+                    break;
+                }
             }
-            attr if attr.key.as_ref() == b"office:value" => {
-                tc.val_float = Some(parse_f64(&attr.value)?);
+            Event::Eof => {
+                break;
             }
-            attr if attr.key.as_ref() == b"office:boolean-value" => {
-                tc.val_bool = Some(parse_bool(&attr.value)?);
+            _ => {
+                dump_unused2("read_fonts", &evt)?;
             }
-            attr if attr.key.as_ref() == b"office:string-value" => {
-                tc.val_string = Some(attr.unescape_value()?.to_string());
+        }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    Ok(())
+}
+
+// reads the page-layout tag
+fn read_page_style<R: BufRead>(
+    bs: &mut BufStack,
+    book: &mut WorkBook,
+    xml: &mut quick_xml::Reader<R>,
+    xml_tag: &BytesStart<'_>,
+) -> Result<(), OdsError> {
+    let mut pl = PageStyle::new_empty();
+    for attr in xml_tag.attributes().with_checks(false) {
+        match attr? {
+            attr if attr.key.as_ref() == b"style:name" => {
+                pl.set_name(&attr.unescape_value()?.to_string());
             }
-            attr if attr.key.as_ref() == b"office:currency" => {
-                tc.val_currency = Some(parse_currency(&attr.value)?);
+            attr if attr.key.as_ref() == b"style:page-usage" => {
+                pl.master_page_usage = Some(attr.unescape_value()?.to_string());
             }
-            attr if attr.key.as_ref() == b"table:formula" => {
-                cell.formula = Some(attr.unescape_value()?.to_string());
+            attr => {
+                dump_unused("read_page_style", xml_tag.name().as_ref(), &attr)?;
             }
-            attr if attr.key.as_ref() == b"table:style-name" => {
-                cell.style = Some(attr.unescape_value()?.to_string());
+        }
+    }
+
+    let mut headerstyle = false;
+    let mut footerstyle = false;
+
+    let mut buf = bs.get_buf();
+    loop {
+        let evt = xml.read_event_into(&mut buf)?;
+        if DUMP_XML {
+            println!(" read_page_layout {:?}", evt);
+        }
+        match evt {
+            Event::Start(ref xml_tag) | Event::Empty(ref xml_tag) => {
+                match xml_tag.name().as_ref() {
+                    b"style:page-layout-properties" => copy_attr2(pl.style_mut(), xml_tag)?,
+                    b"style:header-style" => headerstyle = true,
+                    b"style:footer-style" => footerstyle = true,
+                    b"style:header-footer-properties" => {
+                        if headerstyle {
+                            copy_attr2(pl.headerstyle_mut().style_mut(), xml_tag)?;
+                        }
+                        if footerstyle {
+                            copy_attr2(pl.footerstyle_mut().style_mut(), xml_tag)?;
+                        }
+                    }
+                    b"style:background-image" => {
+                        // noop for now. sets the background transparent.
+                    }
+                    _ => {
+                        dump_unused2("read_page_layout", &evt)?;
+                    }
+                }
             }
-            attr => {
-                dump_unused("read_table_cell2", xml_tag.name().as_ref(), &attr)?;
+            Event::Text(_) => (),
+            Event::End(ref end) => match end.name().as_ref() {
+                b"style:page-layout" => break,
+                b"style:page-layout-properties" => {}
+                b"style:header-style" => headerstyle = false,
+                b"style:footer-style" => footerstyle = false,
+                b"style:header-footer-properties" => {}
+                _ => {
+                    dump_unused2("read_page_layout", &evt)?;
+                }
+            },
+            Event::Eof => break,
+            _ => {
+                dump_unused2("read_page_layout", &evt)?;
             }
         }
+
+        buf.clear();
+    }
+    bs.push(buf);
+
+    book.add_pagestyle(pl);
+
+    Ok(())
+}
+
+// Reads the content of table:named-expressions.
+fn read_named_expressions<R: BufRead>(
+    bs: &mut BufStack,
+    xml: &mut quick_xml::Reader<R>,
+    empty_tag: bool,
+) -> Result<(Vec<NamedRange>, Vec<NamedExpression>), OdsError> {
+    let mut ranges = Vec::new();
+    let mut exprs = Vec::new();
+
+    if empty_tag {
+        return Ok((ranges, exprs));
     }
 
     let mut buf = bs.get_buf();
     loop {
         let evt = xml.read_event_into(&mut buf)?;
         if DUMP_XML {
-            println!(" read_table_cell {:?}", evt);
+            println!(" read_named_expressions {:?}", evt);
         }
-        match evt {
-            Event::Start(xml_tag) if xml_tag.name().as_ref() == b"text:p" => {
-                let new_txt = read_text_or_tag(bs, b"text:p", xml, &xml_tag, false)?;
-                tc.content = append_text(new_txt, tc.content);
-            }
-            Event::Empty(xml_tag) if xml_tag.name().as_ref() == b"text:p" => {
-                // noop
+        match &evt {
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"table:named-range" =>
+            {
+                let mut range = NamedRange::default();
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"table:name" => {
+                            range.set_name(attr.unescape_value()?.to_string());
+                        }
+                        attr if attr.key.as_ref() == b"table:cell-range-address" => {
+                            let v = attr.unescape_value()?;
+                            // a single cell is allowed too.
+                            let cr = match parse_cellrange(&v, &mut 0) {
+                                Ok(cr) => cr,
+                                Err(_) => {
+                                    let c = parse_cellref(&v, &mut 0)?;
+                                    CellRange::new_all(
+                                        c.iri().cloned(),
+                                        c.table().cloned(),
+                                        c.row_abs(),
+                                        c.row(),
+                                        c.col_abs(),
+                                        c.col(),
+                                        None,
+                                        c.row_abs(),
+                                        c.row(),
+                                        c.col_abs(),
+                                        c.col(),
+                                    )
+                                }
+                            };
+                            range.set_range(cr);
+                        }
+                        attr if attr.key.as_ref() == b"table:base-cell-address" => {
+                            let v = attr.unescape_value()?;
+                            range.set_base_cell(Some(parse_cellref(&v, &mut 0)?));
+                        }
+                        attr if attr.key.as_ref() == b"table:range-usable-as" => {
+                            range.set_usable_as(Some(attr.unescape_value()?.to_string()));
+                        }
+                        attr => {
+                            dump_unused("read_named_expressions", xml_tag.name().as_ref(), &attr)?;
+                        }
+                    }
+                }
+                ranges.push(range);
             }
-
-            Event::End(xml_tag) if xml_tag.name() == tag_name => {
-                parse_value2(tc, &mut cell)?;
-
-                while cell_repeat > 1 {
-                    sheet.add_cell_data(row, col, cell.clone());
-                    col += 1;
-                    cell_repeat -= 1;
+            Event::Start(xml_tag) | Event::Empty(xml_tag)
+                if xml_tag.name().as_ref() == b"table:named-expression" =>
+            {
+                let mut expr = NamedExpression::default();
+                for attr in xml_tag.attributes().with_checks(false) {
+                    match attr? {
+                        attr if attr.key.as_ref() == b"table:name" => {
+                            expr.set_name(attr.unescape_value()?.to_string());
+                        }
+                        attr if attr.key.as_ref() == b"table:expression" => {
+                            expr.set_expression(attr.unescape_value()?.to_string());
+                        }
+                        attr if attr.key.as_ref() == b"table:base-cell-address" => {
+                            let v = attr.unescape_value()?;
+                            expr.set_base_cell(Some(parse_cellref(&v, &mut 0)?));
+                        }
+                        attr => {
+                            dump_unused("read_named_expressions", xml_tag.name().as_ref(), &attr)?;
+                        }
+                    }
                 }
-                sheet.add_cell_data(row, col, cell);
-                col += 1;
-
+                exprs.push(expr);
+            }
+            Event::End(xml_tag)
+                if xml_tag.name().as_ref() == b"table:named-range"
+                    || xml_tag.name().as_ref() == b"table:named-expression" => {}
+            Event::End(xml_tag) if xml_tag.name().as_ref() == b"table:named-expressions" => {
                 break;
             }
-
             Event::Eof => {
                 break;
             }
-
             _ => {
-                dump_unused2("read_table_cell", &evt)?;
+                dump_unused2("read_named_expressions", &evt)?;
             }
         }
-
         buf.clear();
     }
     bs.push(buf);
 
-    Ok(col)
+    Ok((ranges, exprs))
 }
 
-fn append_text(new_txt: TextContent2, mut content: TextContent2) -> TextContent2 {
-    // There can be multiple text:p elements within the cell.
-    content = match content {
-        TextContent2::Empty => new_txt,
-        TextContent2::Text(txt) => {
-            // Have a destructured text:p from before.
-            // Wrap up and create list.
-            let p = TextP::new().text(txt).into_xmltag();
-            let mut vec = vec![p];
+// Parses table:calculation-settings.
+fn read_calculation_settings(book: &mut WorkBook, xml_tag: &XmlTag) -> Result<(), OdsError> {
+    let calc = &mut book.calc_settings;
+    for (k, v) in xml_tag.attrmap().iter() {
+        match k.as_ref() {
+            "table:case-sensitive" => calc.set_case_sensitive(v == "true"),
+            "table:precision-as-shown" => calc.set_precision_as_shown(v == "true"),
+            "table:search-criteria-must-apply-to-whole-cell" => {
+                calc.set_search_criteria_must_apply_to_whole_cell(v == "true")
+            }
+            "table:automatic-find-labels" => calc.set_automatic_find_labels(v == "true"),
+            "table:use-regular-expressions" => calc.set_use_regular_expressions(v == "true"),
+            "table:use-wildcards" => calc.set_use_wildcards(v == "true"),
+            "table:null-year" => calc.set_null_year(v.parse()?),
+            _ => {}
+        }
+    }
 
-            match new_txt {
-                TextContent2::Empty => {}
-                TextContent2::Text(txt) => {
-                    let p2 = TextP::new().text(txt).into_xmltag();
-                    vec.push(p2);
-                }
-                TextContent2::Xml(xml) => {
-                    vec.push(xml);
+    for c in xml_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) => t,
+            XmlContent::Text(_) => continue,
+        };
+        match t.name() {
+            "table:null-date" => {
+                if let Some(v) = t.attrmap().attr("table:date-value") {
+                    // may contain a time part.
+                    let v = v.get(..10).unwrap_or(v.as_str());
+                    calc.set_null_date(NaiveDate::parse_from_str(v, "%Y-%m-%d")?);
                 }
-                TextContent2::XmlVec(_) => {
-                    unreachable!();
+            }
+            "table:iteration" => {
+                for (k, v) in t.attrmap().iter() {
+                    match k.as_ref() {
+                        "table:status" => calc.set_iteration(v == "enable"),
+                        "table:steps" => calc.set_iteration_steps(v.parse()?),
+                        "table:minimum-difference" => {
+                            calc.set_iteration_minimum_difference(v.parse()?)
+                        }
+                        _ => {}
+                    }
                 }
             }
-            TextContent2::XmlVec(vec)
+            _ => {}
         }
-        TextContent2::Xml(xml) => {
-            let mut vec = vec![xml];
-            match new_txt {
-                TextContent2::Empty => {}
-                TextContent2::Text(txt) => {
-                    let p2 = TextP::new().text(txt).into_xmltag();
-                    vec.push(p2);
-                }
-                TextContent2::Xml(xml) => {
-                    vec.push(xml);
+    }
+
+    Ok(())
+}
+
+// Parses calcext:conditional-formats.
+fn read_conditional_formats(xml_tag: &XmlTag) -> Result<Vec<ConditionalFormat>, OdsError> {
+    let mut formats = Vec::new();
+    for c in xml_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) if t.name() == "calcext:conditional-format" => t,
+            _ => continue,
+        };
+
+        let ranges = match t.attrmap().attr("calcext:target-range-address") {
+            Some(v) => parse_cellranges(v, &mut 0)?.unwrap_or_default(),
+            None => Vec::new(),
+        };
+        let mut cf = ConditionalFormat::new(ranges);
+
+        for c in t.content() {
+            let t = match c {
+                XmlContent::Tag(t) => t,
+                XmlContent::Text(_) => continue,
+            };
+            match t.name() {
+                "calcext:condition" => {
+                    let mut rule = ConditionRule::default();
+                    for (k, v) in t.attrmap().iter() {
+                        match k.as_ref() {
+                            "calcext:value" => {
+                                rule.set_condition(FormatCondition::new(v.as_str()));
+                            }
+                            "calcext:apply-style-name" => {
+                                rule.set_style(&CellStyleRef::from(v.as_str()));
+                            }
+                            "calcext:base-cell-address" => {
+                                rule.set_base_cell(Some(parse_cellref(v, &mut 0)?));
+                            }
+                            _ => {}
+                        }
+                    }
+                    cf.push_condition(rule);
                 }
-                TextContent2::XmlVec(_) => {
-                    unreachable!();
+                "calcext:color-scale" => {
+                    let mut scale = ColorScale::new();
+                    for c in t.content() {
+                        if let XmlContent::Tag(t) = c {
+                            if t.name() == "calcext:color-scale-entry" {
+                                let color = match t.attrmap().attr("calcext:color") {
+                                    Some(v) => parse_color(v.as_bytes())?,
+                                    None => Rgb::new(0, 0, 0),
+                                };
+                                scale.push_entry(read_format_entry(t)?, color);
+                            }
+                        }
+                    }
+                    cf.push_color_scale(scale);
                 }
-            }
-            TextContent2::XmlVec(vec)
-        }
-        TextContent2::XmlVec(mut vec) => {
-            match new_txt {
-                TextContent2::Empty => {}
-                TextContent2::Text(txt) => {
-                    let p2 = TextP::new().text(txt).into_xmltag();
-                    vec.push(p2);
+                "calcext:data-bar" => {
+                    let mut bar = DataBar::new(Rgb::new(0, 0, 0));
+                    for (k, v) in t.attrmap().iter() {
+                        match k.as_ref() {
+                            "calcext:positive-color" => {
+                                bar.set_positive_color(parse_color(v.as_bytes())?);
+                            }
+                            "calcext:negative-color" => {
+                                bar.set_negative_color(Some(parse_color(v.as_bytes())?));
+                            }
+                            "calcext:axis-color" => {
+                                bar.set_axis_color(Some(parse_color(v.as_bytes())?));
+                            }
+                            "calcext:gradient" => bar.set_gradient(v == "true"),
+                            "calcext:show-value" => bar.set_show_value(v == "true"),
+                            _ => bar.attrmap_mut().set_attr(k.as_ref(), v.clone()),
+                        }
+                    }
+                    let mut entries = Vec::new();
+                    for c in t.content() {
+                        if let XmlContent::Tag(t) = c {
+                            if t.name() == "calcext:formatting-entry" {
+                                entries.push(read_format_entry(t)?);
+                            }
+                        }
+                    }
+                    let mut entries = entries.into_iter();
+                    if let Some(min) = entries.next() {
+                        bar.set_min(min);
+                    }
+                    if let Some(max) = entries.next() {
+                        bar.set_max(max);
+                    }
+                    cf.push_data_bar(bar);
                 }
-                TextContent2::Xml(xml) => {
-                    vec.push(xml);
+                "calcext:icon-set" => {
+                    let mut icons = IconSet::new("");
+                    for (k, v) in t.attrmap().iter() {
+                        match k.as_ref() {
+                            "calcext:icon-set-type" => icons.set_icon_set_type(v.as_str()),
+                            "calcext:show-value" => icons.set_show_value(v == "true"),
+                            _ => {}
+                        }
+                    }
+                    for c in t.content() {
+                        if let XmlContent::Tag(t) = c {
+                            if t.name() == "calcext:formatting-entry" {
+                                icons.push_entry(read_format_entry(t)?);
+                            }
+                        }
+                    }
+                    cf.push_icon_set(icons);
                 }
-                TextContent2::XmlVec(_) => {
-                    unreachable!();
+                "calcext:date-is" => {
+                    let date = match t.attrmap().attr("calcext:date") {
+                        Some(v) => DateType::parse(v)?,
+                        None => DateType::Today,
+                    };
+                    let style = t
+                        .attrmap()
+                        .attr("calcext:style")
+                        .cloned()
+                        .unwrap_or_default();
+                    cf.push_date_is(DateIs::new(date, &CellStyleRef::from(style)));
                 }
+                _ => {}
             }
-            TextContent2::XmlVec(vec)
         }
-    };
 
-    content
+        formats.push(cf);
+    }
+    Ok(formats)
 }
 
-fn parse_value2(tc: ReadTableCell2, cell: &mut CellData) -> Result<(), OdsError> {
-    match tc.val_type {
-        ValueType::Empty => {
-            // noop
-        }
-        ValueType::Boolean => {
-            if let Some(v) = tc.val_bool {
-                cell.value = Value::Boolean(v);
-            } else {
-                return Err(OdsError::Parse("no boolean value".to_string()));
-            }
-        }
-        ValueType::Number => {
-            if let Some(v) = tc.val_float {
-                cell.value = Value::Number(v);
-            } else {
-                return Err(OdsError::Parse("no float value".to_string()));
+// Parses calcext:formatting-entry and calcext:color-scale-entry.
+fn read_format_entry(xml_tag: &XmlTag) -> Result<FormatEntry, OdsError> {
+    let entry_type = match xml_tag.attrmap().attr("calcext:type") {
+        Some(v) => FormatEntryType::parse(v)?,
+        None => FormatEntryType::Number,
+    };
+    let value = xml_tag
+        .attrmap()
+        .attr("calcext:value")
+        .cloned()
+        .unwrap_or_default();
+    Ok(FormatEntry::new(entry_type, value))
+}
+
+// Parses the database ranges.
+fn read_database_ranges(xml_tag: &XmlTag) -> Result<Vec<DatabaseRange>, OdsError> {
+    let mut ranges = Vec::new();
+    for c in xml_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) if t.name() == "table:database-range" => t,
+            _ => continue,
+        };
+
+        let mut db = DatabaseRange::new("", CellRange::default());
+        for (k, v) in t.attrmap().iter() {
+            match k.as_ref() {
+                "table:name" => db.set_name(v.as_str()),
+                "table:target-range-address" => db.set_range(parse_cellrange(v, &mut 0)?),
+                "table:contains-header" => db.set_contains_header(v == "true"),
+                "table:display-filter-buttons" => db.set_display_filter_buttons(v == "true"),
+                _ => db.attrmap_mut().set_attr(k.as_ref(), v.clone()),
             }
         }
-        ValueType::Percentage => {
-            if let Some(v) = tc.val_float {
-                cell.value = Value::Percentage(v);
-            } else {
-                return Err(OdsError::Parse("no float value".to_string()));
+
+        for c in t.content() {
+            let t = match c {
+                XmlContent::Tag(t) => t,
+                XmlContent::Text(_) => continue,
+            };
+            match t.name() {
+                "table:filter" => {
+                    db.set_filter(read_filter(t)?);
+                }
+                "table:sort" => {
+                    for c in t.content() {
+                        if let XmlContent::Tag(t) = c {
+                            if t.name() == "table:sort-by" {
+                                db.push_sort_key(read_sort_key(t)?);
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    db.extra_mut().push(t.clone());
+                }
             }
         }
-        ValueType::Currency => {
-            if let Some(v) = tc.val_float {
-                if let Some(c) = tc.val_currency {
-                    cell.value = Value::Currency(v, c);
-                } else {
-                    return Err(OdsError::Parse("no currency value".to_string()));
+
+        ranges.push(db);
+    }
+    Ok(ranges)
+}
+
+// Parses table:data-pilot-tables.
+fn read_data_pilot_tables(xml_tag: &XmlTag) -> Result<Vec<DataPilotTable>, OdsError> {
+    let mut tables = Vec::new();
+    for c in xml_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) if t.name() == "table:data-pilot-table" => t,
+            _ => continue,
+        };
+
+        let mut dp = DataPilotTable::new("", CellRange::default(), CellRange::default());
+        for (k, v) in t.attrmap().iter() {
+            match k.as_ref() {
+                "table:name" => dp.set_name(v.as_str()),
+                "table:target-range-address" => dp.set_target(parse_cellrange(v, &mut 0)?),
+                "table:grand-total" => dp.set_grand_total(GrandTotal::parse(v)?),
+                "table:ignore-empty-rows" => dp.set_ignore_empty_rows(v == "true"),
+                "table:identify-categories" => dp.set_identify_categories(v == "true"),
+                "table:show-filter-button" => dp.set_show_filter_button(v == "true"),
+                "table:drill-down-on-double-click" => {
+                    dp.set_drill_down_on_double_click(v == "true")
                 }
-            } else {
-                return Err(OdsError::Parse("no float value".to_string()));
+                _ => dp.attrmap_mut().set_attr(k.as_ref(), v.clone()),
             }
         }
-        ValueType::Text => {
-            if let Some(v) = tc.val_string {
-                cell.value = Value::Text(v);
-            } else {
-                match tc.content {
-                    TextContent2::Empty => {
-                        // noop
-                    }
-                    TextContent2::Text(txt) => {
-                        cell.value = Value::Text(txt);
-                    }
-                    TextContent2::Xml(xml) => {
-                        cell.value = Value::TextXml(vec![xml]);
+
+        for c in t.content() {
+            let t = match c {
+                XmlContent::Tag(t) => t,
+                XmlContent::Text(_) => continue,
+            };
+            match t.name() {
+                "table:source-cell-range" => {
+                    if let Some(v) = t.attrmap().attr("table:cell-range-address") {
+                        dp.set_source(parse_cellrange(v, &mut 0)?);
                     }
-                    TextContent2::XmlVec(vec) => {
-                        cell.value = Value::TextXml(vec);
+                    for c in t.content() {
+                        if let XmlContent::Tag(t) = c {
+                            if t.name() == "table:filter" {
+                                dp.set_source_filter(read_filter(t)?);
+                            }
+                        }
                     }
                 }
+                "table:data-pilot-field" => {
+                    dp.push_field(read_data_pilot_field(t)?);
+                }
+                _ => {
+                    dp.extra_mut().push(t.clone());
+                }
             }
         }
-        ValueType::TextXml => {
-            unreachable!();
-        }
-        ValueType::DateTime => {
-            if let Some(v) = tc.val_datetime {
-                cell.value = Value::DateTime(v);
-            } else {
-                return Err(OdsError::Parse("no datetime value".to_string()));
-            }
-        }
-        ValueType::TimeDuration => {
-            if let Some(v) = tc.val_duration {
-                cell.value = Value::TimeDuration(v);
-            } else {
-                return Err(OdsError::Parse("no duration value".to_string()));
-            }
-        }
-    }
 
-    Ok(())
+        tables.push(dp);
+    }
+    Ok(tables)
 }
 
-/// Reads a table-cell from an empty XML tag.
-/// There seems to be no data associated, but it can have a style and a formula.
-/// And first of all we need the repeat count for the correct placement.
-fn read_empty_table_cell(
-    sheet: &mut Sheet,
-    row: u32,
-    mut col: u32,
-    xml_tag: BytesStart<'_>,
-) -> Result<u32, OdsError> {
-    let mut cell = None;
-    // Default advance is one column.
-    let mut cell_repeat = 1;
-    for attr in xml_tag.attributes().with_checks(false) {
-        match attr? {
-            attr if attr.key.as_ref() == b"table:number-columns-repeated" => {
-                cell_repeat = parse_u32(&attr.value)?;
+// Parses table:data-pilot-field.
+fn read_data_pilot_field(xml_tag: &XmlTag) -> Result<DataPilotField, OdsError> {
+    let mut field = DataPilotField::new("", DataPilotOrientation::Hidden);
+    for (k, v) in xml_tag.attrmap().iter() {
+        match k.as_ref() {
+            "table:source-field-name" => field.set_source_field_name(v.as_str()),
+            "table:orientation" => field.set_orientation(DataPilotOrientation::parse(v)?),
+            "table:function" => field.set_function(Some(DataPilotFunction::parse(v)?)),
+            "table:is-data-layout-field" => field.set_data_layout_field(v == "true"),
+            "table:selected-page" => field.set_selected_page(Some(v.clone())),
+            _ => field.attrmap_mut().set_attr(k.as_ref(), v.clone()),
+        }
+    }
+
+    for c in xml_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) => t,
+            XmlContent::Text(_) => continue,
+        };
+        match t.name() {
+            "table:data-pilot-level" => {
+                read_data_pilot_level(&mut field, t)?;
             }
-            attr if attr.key.as_ref() == b"table:formula" => {
-                cell.get_or_insert_with(CellData::new).formula =
-                    Some(attr.unescape_value()?.to_string());
+            "table:data-pilot-groups" => {
+                field.set_groups(Some(read_data_pilot_groups(t)?));
             }
-            attr if attr.key.as_ref() == b"table:style-name" => {
-                cell.get_or_insert_with(CellData::new).style =
-                    Some(attr.unescape_value()?.to_string());
+            _ => {
+                field.extra_mut().push(t.clone());
+            }
+        }
+    }
+
+    Ok(field)
+}
+
+// Parses table:data-pilot-level into the field.
+fn read_data_pilot_level(field: &mut DataPilotField, xml_tag: &XmlTag) -> Result<(), OdsError> {
+    for (k, v) in xml_tag.attrmap().iter() {
+        match k.as_ref() {
+            "table:show-empty" => field.set_show_empty(v == "true"),
+            _ => field.level_attrmap_mut().set_attr(k.as_ref(), v.clone()),
+        }
+    }
+
+    for c in xml_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) => t,
+            XmlContent::Text(_) => continue,
+        };
+        match t.name() {
+            "table:data-pilot-subtotals" => {
+                for c in t.content() {
+                    if let XmlContent::Tag(t) = c {
+                        if let Some(v) = t.attrmap().attr("table:function") {
+                            field.subtotals_mut().push(DataPilotFunction::parse(v)?);
+                        }
+                    }
+                }
             }
-            attr if attr.key.as_ref() == b"table:number-rows-spanned" => {
-                cell.get_or_insert_with(CellData::new).span.row_span = parse_u32(&attr.value)?;
+            "table:data-pilot-members" => {
+                for c in t.content() {
+                    if let XmlContent::Tag(t) = c {
+                        let mut member = DataPilotMember::new("");
+                        for (k, v) in t.attrmap().iter() {
+                            match k.as_ref() {
+                                "table:name" => member.set_name(v.as_str()),
+                                "table:display" => member.set_display(v == "true"),
+                                "table:show-details" => member.set_show_details(v == "true"),
+                                _ => {}
+                            }
+                        }
+                        field.push_member(member);
+                    }
+                }
             }
-            attr if attr.key.as_ref() == b"table:number-columns-spanned" => {
-                cell.get_or_insert_with(CellData::new).span.col_span = parse_u32(&attr.value)?;
+            "table:data-pilot-sort-info" => {
+                let order = match t.attrmap().attr("table:order") {
+                    Some(v) if v == "descending" => SortOrder::Descending,
+                    _ => SortOrder::Ascending,
+                };
+                let mode = match t.attrmap().attr("table:sort-mode").map(|v| v.as_str()) {
+                    Some("manual") => DataPilotSortMode::Manual,
+                    Some("name") => DataPilotSortMode::Name,
+                    Some("data") => DataPilotSortMode::Data(
+                        t.attrmap()
+                            .attr("table:data-field")
+                            .cloned()
+                            .unwrap_or_default(),
+                    ),
+                    _ => DataPilotSortMode::None,
+                };
+                field.set_sort(Some(DataPilotSort::new(mode, order)));
             }
-            attr if attr.key.as_ref() == b"table:content-validation-name" => {
-                cell.get_or_insert_with(CellData::new).validation_name =
-                    Some(attr.unescape_value()?.to_string());
+            _ => {
+                field.level_extra_mut().push(t.clone());
             }
+        }
+    }
 
-            attr => {
-                dump_unused("read_empty_table_cell", xml_tag.name().as_ref(), &attr)?;
-            }
+    Ok(())
+}
+
+// Parses table:data-pilot-groups.
+fn read_data_pilot_groups(xml_tag: &XmlTag) -> Result<DataPilotGroups, OdsError> {
+    let mut groups = DataPilotGroups::new();
+    for (k, v) in xml_tag.attrmap().iter() {
+        match k.as_ref() {
+            "table:source-field-name" => groups.set_source_field_name(v.as_str()),
+            "table:grouped-by" => groups.set_grouped_by(Some(DataPilotGroupedBy::parse(v)?)),
+            "table:start" if v != "auto" => groups.set_start(Some(parse_f64(v.as_bytes())?)),
+            "table:end" if v != "auto" => groups.set_end(Some(parse_f64(v.as_bytes())?)),
+            "table:step" => groups.set_step(Some(parse_f64(v.as_bytes())?)),
+            "table:start" | "table:end" => {}
+            _ => groups.attrmap_mut().set_attr(k.as_ref(), v.clone()),
         }
     }
 
-    if let Some(cell) = cell {
-        while cell_repeat > 1 {
-            sheet.add_cell_data(row, col, cell.clone());
-            col += 1;
-            cell_repeat -= 1;
+    for c in xml_tag.content() {
+        if let XmlContent::Tag(t) = c {
+            if t.name() == "table:data-pilot-group" {
+                let name = t.attrmap().attr("table:name").cloned().unwrap_or_default();
+                let mut members = Vec::new();
+                for c in t.content() {
+                    if let XmlContent::Tag(t) = c {
+                        if let Some(v) = t.attrmap().attr("table:name") {
+                            members.push(v.clone());
+                        }
+                    }
+                }
+                groups.push_group(DataPilotGroup::new(name, members));
+            }
         }
-        sheet.add_cell_data(row, col, cell);
-        col += 1;
-    } else {
-        col += cell_repeat;
     }
 
-    Ok(col)
+    Ok(groups)
 }
 
-// reads a font-face
-fn read_fonts(
-    bs: &mut BufStack,
-    book: &mut WorkBook,
-    origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
-    // no attributes
-) -> Result<(), OdsError> {
-    let mut font: FontFaceDecl = FontFaceDecl::new_empty();
-    font.set_origin(origin);
+// Parses table:tracked-changes.
+fn read_tracked_changes(xml_tag: &XmlTag) -> Result<TrackedChanges, OdsError> {
+    let mut changes = TrackedChanges::new();
+    if let Some(v) = xml_tag.attrmap().attr("table:track-changes") {
+        changes.set_track_changes(parse_bool(v.as_bytes())?);
+    }
+    for c in xml_tag.content() {
+        if let XmlContent::Tag(t) = c {
+            changes.push_change(read_tracked_change(t)?);
+        }
+    }
+    Ok(changes)
+}
 
-    let mut buf = bs.get_buf();
-    loop {
-        let evt = xml.read_event_into(&mut buf)?;
-        if DUMP_XML {
-            println!(" read_fonts {:?}", evt);
+// Parses one of the change elements.
+fn read_tracked_change(xml_tag: &XmlTag) -> Result<TrackedChange, OdsError> {
+    let attr = xml_tag.attrmap();
+    let u32_attr = |name: &str, default: u32| -> Result<u32, OdsError> {
+        match attr.attr(name) {
+            Some(v) => parse_u32(v.as_bytes()),
+            None => Ok(default),
         }
-        match evt {
-            Event::Start(ref xml_tag) | Event::Empty(ref xml_tag) => {
-                match xml_tag.name().as_ref() {
-                    b"style:font-face" => {
-                        let name = proc_style_attr(font.attrmap_mut(), xml_tag)?;
-                        font.set_name(name);
+    };
+    let change_type = || -> Result<ChangeType, OdsError> {
+        ChangeType::parse(attr.attr_def("table:type", "row"))
+    };
 
-                        book.add_font(font);
+    let mut kind = match xml_tag.name() {
+        "table:cell-content-change" => ChangeKind::CellContent {
+            address: ChangeAddress::default(),
+            previous_id: None,
+            previous: None,
+        },
+        "table:insertion" => ChangeKind::Insertion {
+            change_type: change_type()?,
+            position: u32_attr("table:position", 0)?,
+            count: u32_attr("table:count", 1)?,
+            table: u32_attr("table:table", 0)?,
+        },
+        "table:deletion" => ChangeKind::Deletion {
+            change_type: change_type()?,
+            position: u32_attr("table:position", 0)?,
+            table: u32_attr("table:table", 0)?,
+            multi_deletion_spanned: match attr.attr("table:multi-deletion-spanned") {
+                Some(v) => Some(parse_u32(v.as_bytes())?),
+                None => None,
+            },
+        },
+        "table:movement" => ChangeKind::Movement {
+            source: ChangeRange::default(),
+            target: ChangeRange::default(),
+        },
+        other => {
+            return Err(OdsError::Parse(format!("unknown tracked change {}", other)));
+        }
+    };
 
-                        font = FontFaceDecl::new_empty();
-                        font.set_origin(StyleOrigin::Content);
+    let mut extra = Vec::new();
+    let mut info = ChangeInfo::default();
+    let mut dependencies = Vec::new();
+    let mut deletions = Vec::new();
+    for c in xml_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) => t,
+            XmlContent::Text(_) => continue,
+        };
+        match (t.name(), &mut kind) {
+            ("office:change-info", _) => {
+                info = read_change_info(t);
+            }
+            ("table:dependencies", _) => {
+                for c in t.content() {
+                    if let XmlContent::Tag(t) = c {
+                        if let Some(v) = t.attrmap().attr("table:id") {
+                            dependencies.push(v.clone());
+                        }
                     }
-                    _ => {
-                        dump_unused2("read_fonts", &evt)?;
+                }
+            }
+            ("table:deletions", _) => {
+                for c in t.content() {
+                    if let XmlContent::Tag(t) = c {
+                        deletions.push(read_change_deletion(t)?);
                     }
                 }
             }
-            Event::End(ref e) => {
-                if e.name().as_ref() == b"office:font-face-decls" {
-                    break;
+            ("table:cell-address", ChangeKind::CellContent { address, .. }) => {
+                *address = read_change_address(t.attrmap(), "")?;
+            }
+            ("table:previous", ChangeKind::CellContent { previous_id, previous, .. }) => {
+                *previous_id = t.attrmap().attr("table:id").cloned();
+                for c in t.content() {
+                    if let XmlContent::Tag(t) = c {
+                        if t.name() == "table:change-track-table-cell" {
+                            *previous = read_change_cell(t)?;
+                        }
+                    }
                 }
             }
-            Event::Eof => {
-                break;
+            ("table:source-range-address", ChangeKind::Movement { source, .. }) => {
+                *source = read_change_range(t)?;
+            }
+            ("table:target-range-address", ChangeKind::Movement { target, .. }) => {
+                *target = read_change_range(t)?;
             }
             _ => {
-                dump_unused2("read_fonts", &evt)?;
+                extra.push(t.clone());
             }
         }
+    }
 
-        buf.clear();
+    let id = attr.attr("table:id").cloned().unwrap_or_default();
+    let mut change = TrackedChange::new(id, kind, info);
+    if let Some(v) = attr.attr("table:acceptance-state") {
+        change.set_acceptance_state(AcceptanceState::parse(v)?);
     }
-    bs.push(buf);
+    change.set_rejecting_change_id(attr.attr("table:rejecting-change-id").cloned());
+    *change.dependencies_mut() = dependencies;
+    *change.deletions_mut() = deletions;
+    change.extra = extra;
+    Ok(change)
+}
 
-    Ok(())
+// Parses office:change-info. An invalid date is dropped.
+fn read_change_info(xml_tag: &XmlTag) -> ChangeInfo {
+    let mut info = ChangeInfo::default();
+    let mut comment = Vec::new();
+    for c in xml_tag.content() {
+        if let XmlContent::Tag(t) = c {
+            let mut text = String::new();
+            t.extract_text(&mut text);
+            match t.name() {
+                "dc:creator" => info.set_creator(text),
+                "dc:date" => info.set_date(parse_meta_datetime(&text)),
+                "text:p" => comment.push(text),
+                _ => {}
+            }
+        }
+    }
+    info.set_comment(comment.join("\n"));
+    info
 }
 
-// reads the page-layout tag
-fn read_page_style(
-    bs: &mut BufStack,
-    book: &mut WorkBook,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
-    xml_tag: &BytesStart<'_>,
-) -> Result<(), OdsError> {
-    let mut pl = PageStyle::new_empty();
-    for attr in xml_tag.attributes().with_checks(false) {
-        match attr? {
-            attr if attr.key.as_ref() == b"style:name" => {
-                pl.set_name(&attr.unescape_value()?.to_string());
+// Parses table:change-deletion and table:cell-content-deletion.
+fn read_change_deletion(xml_tag: &XmlTag) -> Result<ChangeDeletion, OdsError> {
+    let id = xml_tag.attrmap().attr("table:id").cloned();
+    if xml_tag.name() == "table:change-deletion" {
+        return Ok(ChangeDeletion::Change(id.unwrap_or_default()));
+    }
+
+    let mut address = None;
+    let mut cell = None;
+    for c in xml_tag.content() {
+        if let XmlContent::Tag(t) = c {
+            match t.name() {
+                "table:cell-address" => address = Some(read_change_address(t.attrmap(), "")?),
+                "table:change-track-table-cell" => cell = read_change_cell(t)?,
+                _ => {}
             }
-            attr if attr.key.as_ref() == b"style:page-usage" => {
-                pl.master_page_usage = Some(attr.unescape_value()?.to_string());
+        }
+    }
+    Ok(ChangeDeletion::CellContent { id, address, cell })
+}
+
+// Parses table:column, table:row and table:table with an optional
+// prefix like "start-".
+fn read_change_address(attr: &AttrMap2, prefix: &str) -> Result<ChangeAddress, OdsError> {
+    let mut pos = [0u32; 3];
+    for (i, name) in ["table:table", "table:row", "table:column"].iter().enumerate() {
+        let name = name.replace(':', &format!(":{}", prefix));
+        if let Some(v) = attr.attr(&name) {
+            pos[i] = parse_u32(v.as_bytes())?;
+        }
+    }
+    Ok(ChangeAddress::new(pos[0], pos[1], pos[2]))
+}
+
+// Parses a range address. Either a single cell or start and end.
+fn read_change_range(xml_tag: &XmlTag) -> Result<ChangeRange, OdsError> {
+    let attr = xml_tag.attrmap();
+    if attr.attr("table:start-column").is_some() || attr.attr("table:start-row").is_some() {
+        Ok(ChangeRange::new(
+            read_change_address(attr, "start-")?,
+            read_change_address(attr, "end-")?,
+        ))
+    } else {
+        Ok(ChangeRange::cell(read_change_address(attr, "")?))
+    }
+}
+
+// Parses table:change-track-table-cell. An empty cell gives None.
+fn read_change_cell(xml_tag: &XmlTag) -> Result<Option<ChangeCell>, OdsError> {
+    let mut tc = ReadTableCell2 {
+        val_type: ValueType::Empty,
+        val_datetime: None,
+        val_duration: None,
+        val_float: None,
+        val_bool: None,
+        val_string: None,
+        val_currency: None,
+        content: TextContent2::Empty,
+    };
+    let mut cell = ChangeCell::default();
+    for (k, v) in xml_tag.attrmap().iter() {
+        match k.as_ref() {
+            "office:value-type" => {
+                tc.val_type = match v.as_str() {
+                    "string" => ValueType::Text,
+                    "float" => ValueType::Number,
+                    "percentage" => ValueType::Percentage,
+                    "date" => ValueType::DateTime,
+                    "time" => ValueType::TimeDuration,
+                    "boolean" => ValueType::Boolean,
+                    "currency" => ValueType::Currency,
+                    other => return Err(OdsError::Parse(format!("Unknown cell-type {}", other))),
+                }
             }
-            attr => {
-                dump_unused("read_page_style", xml_tag.name().as_ref(), &attr)?;
+            "office:date-value" => tc.val_datetime = Some(parse_datetime(v.as_bytes())?),
+            "office:time-value" => tc.val_duration = Some(parse_duration(v.as_bytes())?),
+            "office:value" => tc.val_float = Some(parse_f64(v.as_bytes())?),
+            "office:boolean-value" => tc.val_bool = Some(parse_bool(v.as_bytes())?),
+            "office:string-value" => tc.val_string = Some(v.clone()),
+            "office:currency" => tc.val_currency = Some(parse_currency(v.as_bytes())?),
+            "table:formula" => cell.set_formula(Some(v.clone())),
+            "calcext:value-type" => {}
+            _ => cell.attrmap_mut().set_attr(k.as_ref(), v.clone()),
+        }
+    }
+
+    let mut text = Vec::new();
+    for c in xml_tag.content() {
+        if let XmlContent::Tag(t) = c {
+            if t.name() == "text:p" {
+                let mut buf = String::new();
+                t.extract_text(&mut buf);
+                text.push(buf);
             }
         }
     }
+    if !text.is_empty() {
+        tc.content = TextContent2::Text(text.join("\n"));
+    }
 
-    let mut headerstyle = false;
-    let mut footerstyle = false;
+    if tc.val_type == ValueType::Empty && cell.formula().is_none() {
+        return Ok(None);
+    }
+    let mut data = CellData::new();
+    parse_value2(tc, &mut data)?;
+    cell.set_value(data.value);
+    Ok(Some(cell))
+}
 
-    let mut buf = bs.get_buf();
-    loop {
-        let evt = xml.read_event_into(&mut buf)?;
-        if DUMP_XML {
-            println!(" read_page_layout {:?}", evt);
+// Parses table:filter. An empty filter is dropped.
+fn read_filter(xml_tag: &XmlTag) -> Result<Option<Filter>, OdsError> {
+    let mut item = None;
+    for c in xml_tag.content() {
+        if let XmlContent::Tag(t) = c {
+            item = read_filter_item(t)?;
         }
-        match evt {
-            Event::Start(ref xml_tag) | Event::Empty(ref xml_tag) => {
-                match xml_tag.name().as_ref() {
-                    b"style:page-layout-properties" => copy_attr2(pl.style_mut(), xml_tag)?,
-                    b"style:header-style" => headerstyle = true,
-                    b"style:footer-style" => footerstyle = true,
-                    b"style:header-footer-properties" => {
-                        if headerstyle {
-                            copy_attr2(pl.headerstyle_mut().style_mut(), xml_tag)?;
-                        }
-                        if footerstyle {
-                            copy_attr2(pl.footerstyle_mut().style_mut(), xml_tag)?;
-                        }
-                    }
-                    b"style:background-image" => {
-                        // noop for now. sets the background transparent.
-                    }
-                    _ => {
-                        dump_unused2("read_page_layout", &evt)?;
+    }
+
+    if let Some(item) = item {
+        let mut filter = Filter::new(item);
+        if let Some(v) = xml_tag.attrmap().attr("table:display-duplicates") {
+            filter.set_display_duplicates(v == "true");
+        }
+        Ok(Some(filter))
+    } else {
+        Ok(None)
+    }
+}
+
+// Parses table:filter-and, table:filter-or and table:filter-condition.
+fn read_filter_item(xml_tag: &XmlTag) -> Result<Option<FilterItem>, OdsError> {
+    match xml_tag.name() {
+        "table:filter-and" | "table:filter-or" => {
+            let mut items = Vec::new();
+            for c in xml_tag.content() {
+                if let XmlContent::Tag(t) = c {
+                    if let Some(item) = read_filter_item(t)? {
+                        items.push(item);
                     }
                 }
             }
-            Event::Text(_) => (),
-            Event::End(ref end) => match end.name().as_ref() {
-                b"style:page-layout" => break,
-                b"style:page-layout-properties" => {}
-                b"style:header-style" => headerstyle = false,
-                b"style:footer-style" => footerstyle = false,
-                b"style:header-footer-properties" => {}
-                _ => {
-                    dump_unused2("read_page_layout", &evt)?;
+            if xml_tag.name() == "table:filter-and" {
+                Ok(Some(FilterItem::And(items)))
+            } else {
+                Ok(Some(FilterItem::Or(items)))
+            }
+        }
+        "table:filter-condition" => {
+            let mut cond = FilterCondition::new(0, FilterOperator::Equal, "");
+            for (k, v) in xml_tag.attrmap().iter() {
+                match k.as_ref() {
+                    "table:field-number" => cond.set_field(v.parse()?),
+                    "table:operator" => cond.set_operator(FilterOperator::parse(v)?),
+                    "table:value" => cond.set_value(v.as_str()),
+                    "table:data-type" => cond.set_data_type(FilterDataType::parse(v)?),
+                    "table:case-sensitive" => cond.set_case_sensitive(v == "true"),
+                    _ => {}
+                }
+            }
+            for c in xml_tag.content() {
+                if let XmlContent::Tag(t) = c {
+                    if t.name() == "table:filter-set-item" {
+                        if let Some(v) = t.attrmap().attr("table:value") {
+                            cond.set_items_mut().push(v.clone());
+                        }
+                    }
                 }
-            },
-            Event::Eof => break,
-            _ => {
-                dump_unused2("read_page_layout", &evt)?;
             }
+            Ok(Some(FilterItem::Condition(cond)))
         }
-
-        buf.clear();
+        _ => Ok(None),
     }
-    bs.push(buf);
-
-    book.add_pagestyle(pl);
+}
 
-    Ok(())
+// Parses table:sort-by.
+fn read_sort_key(xml_tag: &XmlTag) -> Result<SortKey, OdsError> {
+    let mut key = SortKey::new(0, SortOrder::Ascending);
+    for (k, v) in xml_tag.attrmap().iter() {
+        match k.as_ref() {
+            "table:field-number" => key.set_field(v.parse()?),
+            "table:order" if v == "descending" => key.set_order(SortOrder::Descending),
+            "table:data-type" => key.set_data_type(v.as_str()),
+            _ => {}
+        }
+    }
+    Ok(key)
 }
 
-fn read_validations(
+fn read_validations<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
 ) -> Result<(), OdsError> {
     let mut valid = Validation::new();
 
@@ -1263,11 +3989,11 @@ fn read_validations(
 }
 
 // read the master-styles tag
-fn read_master_styles(
+fn read_master_styles<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(), OdsError> {
     let mut buf = bs.get_buf();
@@ -1307,11 +4033,11 @@ fn read_master_styles(
 }
 
 // read the master-page tag
-fn read_master_page(
+fn read_master_page<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     _origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<(), OdsError> {
     let mut masterpage = MasterPage::new_empty();
@@ -1407,10 +4133,10 @@ fn read_master_page(
 }
 
 // reads any header or footer tags
-fn read_headerfooter(
+fn read_headerfooter<R: BufRead>(
     bs: &mut BufStack,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<HeaderFooter, OdsError> {
     let mut hf = HeaderFooter::new();
@@ -1489,11 +4215,12 @@ fn read_headerfooter(
 }
 
 // reads the office-styles tag
-fn read_styles_tag(
+fn read_styles_tag<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
+    opts: &ReadOptions,
     origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // not attributes
 ) -> Result<(), OdsError> {
     let mut buf = bs.get_buf();
@@ -1506,6 +4233,20 @@ fn read_styles_tag(
         match evt {
             Event::Start(ref xml_tag) | Event::Empty(ref xml_tag) => {
                 match xml_tag.name().as_ref() {
+                    b"style:style" | b"style:default-style" if opts.skip_styles => {
+                        skip_element(bs, xml, xml_tag, empty_tag)?;
+                    }
+                    b"number:boolean-style"
+                    | b"number:date-style"
+                    | b"number:time-style"
+                    | b"number:number-style"
+                    | b"number:currency-style"
+                    | b"number:percentage-style"
+                    | b"number:text-style"
+                        if opts.skip_formats =>
+                    {
+                        skip_element(bs, xml, xml_tag, empty_tag)?;
+                    }
                     b"style:style" => {
                         read_style_style(
                             bs,
@@ -1565,11 +4306,12 @@ fn read_styles_tag(
 }
 
 // read the automatic-styles tag
-fn read_auto_styles(
+fn read_auto_styles<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
+    opts: &ReadOptions,
     origin: StyleOrigin,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(), OdsError> {
     let mut buf = bs.get_buf();
@@ -1582,6 +4324,20 @@ fn read_auto_styles(
         match evt {
             Event::Start(ref xml_tag) | Event::Empty(ref xml_tag) => {
                 match xml_tag.name().as_ref() {
+                    b"style:style" | b"style:page-layout" if opts.skip_styles => {
+                        skip_element(bs, xml, xml_tag, empty_tag)?;
+                    }
+                    b"number:boolean-style"
+                    | b"number:date-style"
+                    | b"number:time-style"
+                    | b"number:number-style"
+                    | b"number:currency-style"
+                    | b"number:percentage-style"
+                    | b"number:text-style"
+                        if opts.skip_formats =>
+                    {
+                        skip_element(bs, xml, xml_tag, empty_tag)?;
+                    }
                     b"style:style" => {
                         read_style_style(
                             bs,
@@ -1632,12 +4388,12 @@ fn read_auto_styles(
 }
 
 // Reads any of the number:xxx tags
-fn read_value_format(
+fn read_value_format<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<(), OdsError> {
     match xml_tag.name().as_ref() {
@@ -1689,12 +4445,12 @@ fn read_value_format(
 }
 
 // Reads any of the number:xxx tags
-fn read_value_format_parts<T: ValueFormatTrait>(
+fn read_value_format_parts<T: ValueFormatTrait, R: BufRead>(
     bs: &mut BufStack,
     origin: StyleOrigin,
     styleuse: StyleUse,
     valuestyle: &mut T,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
 ) -> Result<(), OdsError> {
     valuestyle.set_origin(origin);
@@ -1859,9 +4615,9 @@ fn read_value_format_parts<T: ValueFormatTrait>(
     Ok(())
 }
 
-fn read_part(
+fn read_part<R: BufRead>(
     _bs: &mut BufStack,
-    _xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    _xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     part_type: FormatPartType,
 ) -> Result<FormatPart, OdsError> {
@@ -1920,13 +4676,13 @@ fn read_part(
 
 #[allow(clippy::too_many_arguments)]
 // style:style tag
-fn read_style_style(
+fn read_style_style<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -1975,13 +4731,13 @@ fn read_style_style(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_tablestyle(
+fn read_tablestyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2035,13 +4791,13 @@ fn read_tablestyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_rowstyle(
+fn read_rowstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2094,13 +4850,13 @@ fn read_rowstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_colstyle(
+fn read_colstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2154,13 +4910,13 @@ fn read_colstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_cellstyle(
+fn read_cellstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2229,13 +4985,13 @@ fn read_cellstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_paragraphstyle(
+fn read_paragraphstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2303,13 +5059,13 @@ fn read_paragraphstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_textstyle(
+fn read_textstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2362,13 +5118,13 @@ fn read_textstyle(
 // style:style tag
 #[allow(clippy::collapsible_else_if)]
 #[allow(clippy::too_many_arguments)]
-fn read_graphicstyle(
+fn read_graphicstyle<R: BufRead>(
     bs: &mut BufStack,
     book: &mut WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<(), OdsError> {
@@ -2479,12 +5235,13 @@ fn copy_attr2(attrmap: &mut AttrMap2, xml_tag: &BytesStart<'_>) -> Result<(), Od
     Ok(())
 }
 
-fn read_styles(
+fn read_styles<R: Read>(
     bs: &mut BufStack,
     book: &mut WorkBook,
-    zip_file: &mut ZipFile<'_>,
+    opts: &ReadOptions,
+    read: R,
 ) -> Result<(), OdsError> {
-    let mut xml = quick_xml::Reader::from_reader(BufReader::new(zip_file));
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(read));
     // Do not trim text data. All text read contains significant whitespace.
     // The rest is ignored anyway.
     //
@@ -2507,19 +5264,27 @@ fn read_styles(
             }
 
             Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:font-face-decls" => {
-                read_fonts(bs, book, StyleOrigin::Styles, &mut xml)?
+                if opts.skip_styles {
+                    skip_element(bs, &mut xml, &xml_tag, false)?;
+                } else {
+                    read_fonts(bs, book, StyleOrigin::Styles, &mut xml)?;
+                }
             }
 
             Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:styles" => {
-                read_styles_tag(bs, book, StyleOrigin::Styles, &mut xml)?
+                read_styles_tag(bs, book, opts, StyleOrigin::Styles, &mut xml)?
             }
 
             Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:automatic-styles" => {
-                read_auto_styles(bs, book, StyleOrigin::Styles, &mut xml)?
+                read_auto_styles(bs, book, opts, StyleOrigin::Styles, &mut xml)?
             }
 
             Event::Start(xml_tag) if xml_tag.name().as_ref() == b"office:master-styles" => {
-                read_master_styles(bs, book, StyleOrigin::Styles, &mut xml)?
+                if opts.skip_styles {
+                    skip_element(bs, &mut xml, &xml_tag, false)?;
+                } else {
+                    read_master_styles(bs, book, StyleOrigin::Styles, &mut xml)?;
+                }
             }
 
             Event::Eof => {
@@ -2628,12 +5393,8 @@ pub(crate) fn default_settings() -> Detach<Config> {
     dc
 }
 
-fn read_settings(
-    bs: &mut BufStack,
-    book: &mut WorkBook,
-    zip_file: &mut ZipFile<'_>,
-) -> Result<(), OdsError> {
-    let mut xml = quick_xml::Reader::from_reader(BufReader::new(zip_file));
+fn read_settings<R: Read>(bs: &mut BufStack, book: &mut WorkBook, read: R) -> Result<(), OdsError> {
+    let mut xml = quick_xml::Reader::from_reader(BufReader::new(read));
     // Do not trim text data. All text read contains significant whitespace.
     // The rest is ignored anyway.
     //
@@ -2675,10 +5436,96 @@ fn read_settings(
     Ok(())
 }
 
+fn read_meta<R: Read>(bs: &mut BufStack, book: &mut WorkBook, read: R) -> Result<(), OdsError> {
+    let mut data = Vec::new();
+    BufReader::new(read).read_to_end(&mut data)?;
+    let doc = read_xml_doc(bs, &data)?;
+    if let Some(meta) = doc.as_ref().and_then(|v| find_tag(v, "office:meta")) {
+        book.metadata = read_office_meta(meta)?;
+    }
+    Ok(())
+}
+
+// Parses office:meta. Invalid dates and numbers are dropped, they are
+// rewritten anyway. Unknown elements are kept as xml.
+fn read_office_meta(xml_tag: &XmlTag) -> Result<Metadata, OdsError> {
+    let mut meta = Metadata::new();
+    for c in xml_tag.content() {
+        let t = match c {
+            XmlContent::Tag(t) => t,
+            XmlContent::Text(_) => continue,
+        };
+        let mut text = String::new();
+        t.extract_text(&mut text);
+
+        match t.name() {
+            "meta:generator" => meta.set_generator(text),
+            "dc:title" => meta.set_title(text),
+            "dc:subject" => meta.set_subject(text),
+            "dc:description" => meta.set_description(text),
+            "meta:keyword" => meta.push_keyword(text),
+            "meta:initial-creator" => meta.set_initial_creator(text),
+            "dc:creator" => meta.set_creator(text),
+            "meta:printed-by" => meta.set_printed_by(text),
+            "dc:language" => meta.set_language(text),
+            "meta:creation-date" | "dc:date" | "meta:print-date" => {
+                match parse_meta_datetime(&text) {
+                    Some(v) if t.name() == "meta:creation-date" => {
+                        meta.set_creation_date(Some(v));
+                    }
+                    Some(v) if t.name() == "dc:date" => meta.set_modification_date(Some(v)),
+                    Some(v) => meta.set_print_date(Some(v)),
+                    None => {}
+                }
+            }
+            "meta:editing-cycles" => {
+                if let Ok(v) = parse_u32(text.trim().as_bytes()) {
+                    meta.set_editing_cycles(v);
+                }
+            }
+            "meta:editing-duration" => {
+                if let Ok(v) = parse_duration(text.trim().as_bytes()) {
+                    meta.set_editing_duration(v);
+                }
+            }
+            "meta:user-defined" => match read_meta_user_defined(t, &text) {
+                Some((name, value)) => meta.set_user_defined(name, value),
+                None => meta.extra.push(t.clone()),
+            },
+            _ => meta.extra.push(t.clone()),
+        }
+    }
+    Ok(meta)
+}
+
+// Dates in meta.xml may carry a timezone, which is dropped.
+fn parse_meta_datetime(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim().trim_end_matches('Z');
+    let text = match text.rfind(['+', '-']) {
+        Some(idx) if idx > 10 => &text[..idx],
+        _ => text,
+    };
+    parse_datetime(text.as_bytes()).ok()
+}
+
+// Parses meta:user-defined. Returns None if the value can't be parsed.
+fn read_meta_user_defined(xml_tag: &XmlTag, text: &str) -> Option<(String, MetaValue)> {
+    let name = xml_tag.attrmap().attr("meta:name")?.clone();
+    let value = match xml_tag.attrmap().attr("meta:value-type").map(|v| v.as_str()) {
+        Some("boolean") => MetaValue::Boolean(parse_bool(text.trim().as_bytes()).ok()?),
+        Some("date") => MetaValue::DateTime(parse_meta_datetime(text)?),
+        Some("float") => MetaValue::Float(parse_f64(text.trim().as_bytes()).ok()?),
+        Some("time") => MetaValue::TimeDuration(parse_duration(text.trim().as_bytes()).ok()?),
+        Some("string") | None => MetaValue::String(text.to_string()),
+        Some(_) => return None,
+    };
+    Some((name, value))
+}
+
 // read the automatic-styles tag
-fn read_office_settings(
+fn read_office_settings<R: BufRead>(
     bs: &mut BufStack,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<Config, OdsError> {
     let mut config = Config::new();
@@ -2711,10 +5558,10 @@ fn read_office_settings(
 }
 
 // read the automatic-styles tag
-fn read_config_item_set(
+fn read_config_item_set<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(String, ConfigItem), OdsError> {
     let mut name = None;
@@ -2781,10 +5628,10 @@ fn read_config_item_set(
 }
 
 // read the automatic-styles tag
-fn read_config_item_map_indexed(
+fn read_config_item_map_indexed<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(String, ConfigItem), OdsError> {
     let mut name = None;
@@ -2846,10 +5693,10 @@ fn read_config_item_map_indexed(
 }
 
 // read the automatic-styles tag
-fn read_config_item_map_named(
+fn read_config_item_map_named<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(String, ConfigItem), OdsError> {
     let mut name = None;
@@ -2913,10 +5760,10 @@ fn read_config_item_map_named(
 }
 
 // read the automatic-styles tag
-fn read_config_item_map_entry(
+fn read_config_item_map_entry<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(Option<String>, ConfigItem), OdsError> {
     let mut name = None;
@@ -2977,10 +5824,10 @@ fn read_config_item_map_entry(
 }
 
 // read the automatic-styles tag
-fn read_config_item(
+fn read_config_item<R: BufRead>(
     bs: &mut BufStack,
     xml_tag: &BytesStart<'_>,
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     // no attributes
 ) -> Result<(String, ConfigValue), OdsError> {
     #[derive(PartialEq)]
@@ -3110,10 +5957,10 @@ fn read_config_item(
 }
 
 // Reads a part of the XML as XmlTag's.
-fn read_xml(
+fn read_xml<R: BufRead>(
     bs: &mut BufStack,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<XmlTag, OdsError> {
@@ -3191,10 +6038,10 @@ fn read_xml(
     Ok(stack.pop().unwrap())
 }
 
-fn read_text_or_tag(
+fn read_text_or_tag<R: BufRead>(
     bs: &mut BufStack,
     end_tag: &[u8],
-    xml: &mut quick_xml::Reader<BufReader<&mut ZipFile<'_>>>,
+    xml: &mut quick_xml::Reader<R>,
     xml_tag: &BytesStart<'_>,
     empty_tag: bool,
 ) -> Result<TextContent2, OdsError> {
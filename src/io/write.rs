@@ -1,31 +1,54 @@
-use std::collections::HashMap;
-use std::fs::File;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::fs::{File, OpenOptions};
 use std::io;
-use std::io::{Cursor, Seek, Write};
-use std::path::Path;
+use std::io::{BufWriter, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use chrono::NaiveDateTime;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{Duration, NaiveDateTime};
 use zip::write::FileOptions;
+use zip::ZipArchive;
 
+use crate::annotation::Annotation;
+use crate::calcsettings::CalculationSettings;
+use crate::chart::{Chart, ChartType};
+use crate::condformat::{ConditionalEntry, ConditionalFormat, FormatEntry};
 use crate::config::{ConfigItem, ConfigItemType, ConfigValue};
+use crate::database::{DatabaseRange, FilterItem, SortOrder};
+use crate::datapilot::{DataPilotField, DataPilotSortMode, DataPilotTable, GrandTotal};
+use crate::draw::{guess_mime_type, Image};
 use crate::error::OdsError;
 use crate::format::FormatPartType;
-use crate::io::filebuf::FileBufEntry;
+use crate::io::crypt::{EncryptionData, EncryptionKey};
+use crate::io::filebuf::{FileBuf, FileBufEntry};
 use crate::io::xmlwriter::XmlWriter;
-use crate::io::zip_out::{ZipOut, ZipWrite};
+use crate::io::zip_out::ZipOut;
+use crate::metadata::{MetaValue, Metadata};
+use crate::named::{NamedExpression, NamedRange};
+use crate::protection::ProtectionOptions;
 use crate::refs::{cellranges_string, CellRange};
+use crate::scenario::Scenario;
 use crate::style::{
-    CellStyle, ColStyle, FontFaceDecl, GraphicStyle, HeaderFooter, MasterPage, PageStyle,
-    ParagraphStyle, RowStyle, StyleOrigin, StyleUse, TableStyle, TextStyle,
+    color_string, CellStyle, ColStyle, FontFaceDecl, GraphicStyle, HeaderFooter, MasterPage,
+    PageStyle, ParagraphStyle, RowStyle, StyleOrigin, StyleUse, TableStyle, TextStyle,
+};
+use crate::trackedchanges::{
+    AcceptanceState, ChangeAddress, ChangeCell, ChangeDeletion, ChangeInfo, ChangeKind,
+    ChangeRange, ChangeType, TrackedChange, TrackedChanges,
 };
 use crate::validation::ValidationDisplay;
 use crate::xmltree::{XmlContent, XmlTag};
 use crate::{
-    CellContentRef, Length, Sheet, Value, ValueFormatTrait, ValueType, Visibility, WorkBook,
+    CellContent, CellContentRef, Grouped, Length, Sheet, Value, ValueFormatTrait, ValueType,
+    Visibility, WorkBook,
 };
 
 type OdsWriter<W> = ZipOut<W>;
-type XmlOdsWriter<'a, W> = XmlWriter<ZipWrite<'a, W>>;
 
 /// Writes the ODS file into a supplied buffer.
 pub fn write_ods_buf_uncompressed(book: &mut WorkBook, buf: Vec<u8>) -> Result<Vec<u8>, OdsError> {
@@ -41,7 +64,8 @@ pub fn write_ods_buf(book: &mut WorkBook, buf: Vec<u8>) -> Result<Vec<u8>, OdsEr
 
 /// Writes the ODS file.
 ///
-/// All the parts are written to a temp directory and then zipped together.
+/// The zip is written directly to the target file. If an error occurs
+/// the file is left incomplete, use [write_ods_atomic] if this matters.
 ///
 pub fn write_ods<P: AsRef<Path>>(book: &mut WorkBook, ods_path: P) -> Result<(), OdsError> {
     let zip_writer = ZipOut::<File>::new_file(ods_path.as_ref())?;
@@ -51,36 +75,571 @@ pub fn write_ods<P: AsRef<Path>>(book: &mut WorkBook, ods_path: P) -> Result<(),
 
 /// Writes the ODS file.
 ///
-/// All the parts are written to a temp directory and then zipped together.
+/// The zip is written to a temporary file next to the target, which
+/// is renamed to the target after it is complete. Readers of the target
+/// never see a partially written file. If an error occurs the temporary
+/// file is removed and the target is left untouched.
+///
+pub fn write_ods_atomic<P: AsRef<Path>>(book: &mut WorkBook, ods_path: P) -> Result<(), OdsError> {
+    let ods_path = ods_path.as_ref();
+    let file_name = match ods_path.file_name() {
+        Some(v) => v,
+        None => return Err(OdsError::Ods(format!("Not a file name: {:?}", ods_path))),
+    };
+    let (tmp_path, tmp_file) = create_tmp_file(ods_path, file_name)?;
+
+    let res = write_ods_impl(book, ZipOut::new(tmp_file))
+        .and_then(|f| Ok(f.sync_all()?))
+        .and_then(|_| Ok(fs::rename(&tmp_path, ods_path)?));
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    res
+}
+
+// Creates a new temporary file next to the target. The name contains the
+// process id and a counter, and the file is opened with create_new. No two
+// writers ever share a temporary file, even within the same process.
+fn create_tmp_file(ods_path: &Path, file_name: &OsStr) -> Result<(PathBuf, File), OdsError> {
+    static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    loop {
+        let mut tmp_name = OsString::from(".");
+        tmp_name.push(file_name);
+        tmp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let tmp_path = ods_path.with_file_name(tmp_name);
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+        {
+            Ok(f) => return Ok((tmp_path, f)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Writes an encrypted ODS file.
+///
+/// All parts except the mimetype are encrypted with AES-256-CBC, the key is
+/// derived from the password with PBKDF2. This is the same encryption as
+/// LibreOffice uses when saving with a password.
+///
+/// The package is assembled in memory before it is encrypted.
+///
+pub fn write_ods_encrypted<P: AsRef<Path>>(
+    book: &mut WorkBook,
+    ods_path: P,
+    password: &str,
+) -> Result<(), OdsError> {
+    let zip_writer = ZipOut::new_uncompressed(File::create(ods_path)?);
+    write_ods_encrypted_impl(book, zip_writer, password)?;
+    Ok(())
+}
+
+/// Writes an encrypted ODS file into a supplied buffer.
+pub fn write_ods_buf_encrypted(
+    book: &mut WorkBook,
+    buf: Vec<u8>,
+    password: &str,
+) -> Result<Vec<u8>, OdsError> {
+    let zip_writer = ZipOut::new_uncompressed(Cursor::new(buf));
+    Ok(write_ods_encrypted_impl(book, zip_writer, password)?.into_inner())
+}
+
+// Writes the plain package into a buffer, then encrypts it part by part.
+fn write_ods_encrypted_impl<W: Write + Seek>(
+    book: &mut WorkBook,
+    mut zip_writer: OdsWriter<W>,
+    password: &str,
+) -> Result<W, OdsError> {
+    let plain = ZipOut::<Cursor<Vec<u8>>>::new_buf_uncompressed(Vec::new())?;
+    let plain = write_ods_impl(book, plain)?;
+    let mut zip_in = ZipArchive::new(plain)?;
+
+    let key = EncryptionKey::new(password)?;
+    let mut encrypted = HashMap::new();
+
+    // mimetype must be the first entry and is never encrypted.
+    let mut buf = Vec::new();
+    zip_in.by_name("mimetype")?.read_to_end(&mut buf)?;
+    zip_writer
+        .start_file("mimetype", FileOptions::default())?
+        .write_all(&buf)?;
+
+    for idx in 0..zip_in.len() {
+        let mut ze = zip_in.by_index(idx)?;
+        if matches!(ze.name(), "mimetype" | "META-INF/manifest.xml") {
+            continue;
+        }
+
+        if ze.is_dir() {
+            zip_writer.add_directory(ze.name(), FileOptions::default())?;
+        } else {
+            let mut buf = Vec::new();
+            ze.read_to_end(&mut buf)?;
+            let (enc, buf) = key.encrypt(&buf)?;
+            zip_writer
+                .start_file(ze.name(), FileOptions::default())?
+                .write_all(&buf)?;
+            encrypted.insert(ze.name().to_string(), (ze.size(), enc));
+        }
+    }
+
+    write_manifest_encrypted(book, &encrypted, &mut zip_writer)?;
+
+    Ok(zip_writer.zip()?)
+}
+
+/// Writes the ODS file to any seekable target.
 ///
+/// Returns the target after the zip has been finished.
+pub fn write_ods_to<W: Write + Seek>(book: &mut WorkBook, write: W) -> Result<W, OdsError> {
+    write_ods_impl(book, ZipOut::new(write))
+}
+
+/// Writes the ODS file.
 fn write_ods_impl<W: Write + Seek>(
     book: &mut WorkBook,
     mut zip_writer: OdsWriter<W>,
 ) -> Result<W, OdsError> {
+    write_ods_parts(book, &mut zip_writer)?;
+    write_ods_content(book, &mut zip_writer)?;
+
+    Ok(zip_writer.zip()?)
+}
+
+/// Writes everything except the content.xml.
+fn write_ods_parts<W: Write + Seek>(
+    book: &mut WorkBook,
+    zip_writer: &mut OdsWriter<W>,
+) -> Result<(), OdsError> {
     sanity_checks(book)?;
 
     store_derived(book)?;
+    assign_chart_hrefs(book);
 
     // copy all buffered data from the original.
-    copy_workbook(book, &mut zip_writer)?;
+    copy_workbook(book, zip_writer)?;
+    write_images(book, zip_writer)?;
+    write_charts(book, zip_writer)?;
     // write the rest, if necessary.
-    write_mimetype(book, &mut zip_writer)?;
-    write_manifest(book, &mut zip_writer)?;
-    write_manifest_rdf(book, &mut zip_writer)?;
-    write_meta(book, &mut zip_writer)?;
+    write_mimetype(book, zip_writer)?;
+    write_manifest(book, zip_writer)?;
+    write_manifest_rdf(book, zip_writer)?;
+    write_meta(book, zip_writer)?;
     // not in use any more, just ignore
     // write_configurations(&mut zip_writer, &mut file_set)?;
-    write_settings(book, &mut zip_writer)?;
-    write_ods_styles(book, &mut zip_writer)?;
-    write_ods_content(book, &mut zip_writer)?;
+    write_settings(book, zip_writer)?;
+    write_ods_styles(book, zip_writer)?;
 
-    Ok(zip_writer.zip()?)
+    Ok(())
+}
+
+/// Writes the workbook as a flat ODS file (.fods) into a supplied buffer.
+///
+/// Everything is written into one single xml document. Pictures are
+/// embedded as base64 encoded office:binary-data.
+pub fn write_fods_buf(book: &mut WorkBook, buf: Vec<u8>) -> Result<Vec<u8>, OdsError> {
+    write_fods_impl(book, buf)
+}
+
+/// Writes the workbook as a flat ODS file (.fods).
+///
+/// Everything is written into one single xml document. Pictures are
+/// embedded as base64 encoded office:binary-data.
+pub fn write_fods<P: AsRef<Path>>(book: &mut WorkBook, fods_path: P) -> Result<(), OdsError> {
+    let mut write = write_fods_impl(book, BufWriter::new(File::create(fods_path)?))?;
+    write.flush()?;
+    Ok(())
+}
+
+fn write_fods_impl<W: Write>(book: &mut WorkBook, write: W) -> Result<W, OdsError> {
+    sanity_checks(book)?;
+
+    store_derived(book)?;
+
+    let mut xml_out = XmlWriter::new(write);
+
+    xml_out.dtd("UTF-8")?;
+
+    xml_out.elem("office:document")?;
+    write_content_namespaces(&mut xml_out)?;
+    xml_out.attr(
+        "xmlns:config",
+        "urn:oasis:names:tc:opendocument:xmlns:config:1.0",
+    )?;
+    xml_out.attr("office:version", book.version())?;
+    xml_out.attr(
+        "office:mimetype",
+        "application/vnd.oasis.opendocument.spreadsheet",
+    )?;
+
+    write_office_meta(book.metadata(), &mut xml_out)?;
+    write_office_settings(book, &mut xml_out)?;
+
+    xml_out.empty("office:scripts")?;
+
+    xml_out.elem("office:font-face-decls")?;
+    write_font_decl(&book.fonts, StyleOrigin::Styles, &mut xml_out)?;
+    write_font_decl(&book.fonts, StyleOrigin::Content, &mut xml_out)?;
+    xml_out.end_elem("office:font-face-decls")?;
+
+    write_office_styles(book, &mut xml_out)?;
+
+    xml_out.elem("office:automatic-styles")?;
+    write_pagestyles(&book.pagestyles, &mut xml_out)?;
+    write_automatic_styles(book, StyleOrigin::Styles, &mut xml_out)?;
+    write_automatic_styles(book, StyleOrigin::Content, &mut xml_out)?;
+    xml_out.end_elem("office:automatic-styles")?;
+
+    xml_out.elem("office:master-styles")?;
+    write_masterpage(&book.masterpages, &mut xml_out)?;
+    xml_out.end_elem("office:master-styles")?;
+
+    xml_out.elem("office:body")?;
+    xml_out.elem("office:spreadsheet")?;
+    write_spreadsheet_start(book, &mut xml_out)?;
+    for sheet in &book.sheets {
+        write_sheet(book, sheet, Some(&book.filebuf), &mut xml_out)?;
+    }
+    write_spreadsheet_end(book, &mut xml_out)?;
+    xml_out.end_elem("office:spreadsheet")?;
+    xml_out.end_elem("office:body")?;
+
+    xml_out.end_elem("office:document")?;
+
+    xml_out.close()?;
+
+    Ok(xml_out.into_inner())
+}
+
+/// Writes an ODS-file row by row.
+///
+/// Everything but the cell data is taken from the workbook and written
+/// up-front. This includes all the styles and value formats, so they must
+/// be added to the workbook before the writer is created.
+///
+/// The sheets of the workbook are started in order with
+/// [OdsStreamWriter::start_sheet]. Name, column headers, row headers,
+/// header rows/columns and print ranges are taken from the sheet, but any
+/// cell data it contains is ignored. The rows are written as they come and
+/// are never held in memory. Sheets that are not streamed are written with
+/// all their data by [OdsStreamWriter::finish].
+///
+/// The file is only complete after calling finish.
+///
+/// ```
+/// use spreadsheet_ods::{CellContent, OdsError, OdsStreamWriter, Sheet, WorkBook};
+/// use icu_locid::locale;
+///
+/// let mut book = WorkBook::new(locale!("en_US"));
+/// book.push_sheet(Sheet::new("data"));
+///
+/// let mut ods = OdsStreamWriter::create(book, "test_out/stream_doc.ods")?;
+/// ods.start_sheet()?;
+/// for row in 0..100 {
+///     let mut cell = CellContent::new();
+///     cell.set_value(row * 2);
+///     ods.write_row(row, &[(0, cell)])?;
+/// }
+/// ods.finish()?;
+/// # Ok::<(), OdsError>(())
+/// ```
+pub struct OdsStreamWriter<W: Write + Seek> {
+    book: WorkBook,
+    xml_out: XmlWriter<OdsWriter<W>>,
+    // Index of the next sheet.
+    next_sheet: usize,
+    // Current sheet.
+    sheet: Option<StreamSheet>,
+}
+
+// Row state of the current sheet.
+#[derive(Debug)]
+struct StreamSheet {
+    idx: usize,
+    max_cols: u32,
+    // list of current spans
+    spans: Vec<CellRange>,
+    first_row: bool,
+    last_r: u32,
+    last_r_repeat: u32,
+}
+
+impl<W: Write + Seek> fmt::Debug for OdsStreamWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OdsStreamWriter")
+            .field("book", &self.book)
+            .field("next_sheet", &self.next_sheet)
+            .field("sheet", &self.sheet)
+            .finish()
+    }
+}
+
+impl OdsStreamWriter<File> {
+    /// Creates the ODS-file and writes everything up to the first sheet.
+    pub fn create<P: AsRef<Path>>(book: WorkBook, ods_path: P) -> Result<Self, OdsError> {
+        Self::new(book, File::create(ods_path.as_ref())?)
+    }
+}
+
+impl<W: Write + Seek> OdsStreamWriter<W> {
+    /// Writes everything up to the first sheet.
+    pub fn new(mut book: WorkBook, write: W) -> Result<Self, OdsError> {
+        let mut zip_writer = ZipOut::new(write);
+
+        write_ods_parts(&mut book, &mut zip_writer)?;
+
+        zip_writer.start_file("content.xml", FileOptions::default())?;
+        let mut xml_out = XmlWriter::new(zip_writer);
+        write_content_start(&book, &mut xml_out)?;
+
+        Ok(Self {
+            book,
+            xml_out,
+            next_sheet: 0,
+            sheet: None,
+        })
+    }
+
+    /// The workbook.
+    pub fn book(&self) -> &WorkBook {
+        &self.book
+    }
+
+    /// Ends the current sheet and starts the next sheet of the workbook.
+    pub fn start_sheet(&mut self) -> Result<(), OdsError> {
+        self.end_sheet()?;
+
+        if self.next_sheet >= self.book.num_sheets() {
+            return Err(OdsError::Ods("No more sheets in the workbook.".to_string()));
+        }
+
+        let idx = self.next_sheet;
+        self.next_sheet += 1;
+
+        let sheet = self.book.sheet(idx);
+        let max_cols = sheet.used_cols();
+        write_sheet_start(sheet, max_cols, None, &mut self.xml_out)?;
+
+        self.sheet = Some(StreamSheet {
+            idx,
+            max_cols,
+            spans: Vec::new(),
+            first_row: true,
+            last_r: 0,
+            last_r_repeat: 1,
+        });
+
+        Ok(())
+    }
+
+    /// Writes one row of the current sheet. The rows must be written in
+    /// ascending order, and the cells must be sorted by column.
+    /// Gaps between the rows are filled with empty rows. A row without
+    /// cells is written as an empty row.
+    ///
+    /// Images and charts can't be streamed, a cell with images or charts
+    /// is an error.
+    pub fn write_row(&mut self, row: u32, cells: &[(u32, CellContent)]) -> Result<(), OdsError> {
+        let st = if let Some(st) = &mut self.sheet {
+            st
+        } else {
+            return Err(OdsError::Ods("No sheet started.".to_string()));
+        };
+        let sheet = self.book.sheet(st.idx);
+        let xml_out = &mut self.xml_out;
+
+        if !st.first_row && row < st.last_r + st.last_r_repeat {
+            return Err(OdsError::Ods(format!(
+                "Row {} is written out of order after row {}.",
+                row, st.last_r
+            )));
+        }
+        for w in cells.windows(2) {
+            if w[0].0 >= w[1].0 {
+                return Err(OdsError::Ods(format!(
+                    "Cells in row {} are not sorted by column.",
+                    row
+                )));
+            }
+        }
+        // The image data and charts would be needed before the content.
+        for (col, cell) in cells {
+            if !cell.images.is_empty() || !cell.charts.is_empty() {
+                return Err(OdsError::Ods(format!(
+                    "Cell {},{} has images or charts, they can't be streamed.",
+                    row, col
+                )));
+            }
+        }
+
+        // Any empty rows before this one?
+        let backward_dr = row - st.last_r;
+        if backward_dr > 0 {
+            // If the last row had a repeat counter the distance is reduced.
+            // We should not add any extra empty rows.
+            if st.last_r_repeat - 1 < backward_dr {
+                write_empty_rows_before(
+                    sheet,
+                    row,
+                    st.first_row,
+                    backward_dr - st.last_r_repeat + 1,
+                    (0, st.max_cols),
+                    xml_out,
+                )?;
+            }
+        }
+
+        let repeat = if let Some(row_header) = sheet.row_header.get(&row) {
+            row_header.repeat
+        } else {
+            1
+        };
+
+        if let Some((first_col, _)) = cells.first() {
+            write_start_current_row(sheet, row, *first_col, xml_out)?;
+        } else {
+            write_empty_rows(
+                sheet,
+                row,
+                row.saturating_add(repeat),
+                (0, st.max_cols),
+                xml_out,
+            )?;
+        }
+
+        for (i, (col, cell)) in cells.iter().enumerate() {
+            // Blank cells until the next one, or fill up to max used columns.
+            let forward_dc = if let Some((next_c, _)) = cells.get(i + 1) {
+                next_c - col
+            } else {
+                st.max_cols.saturating_sub(*col)
+            };
+
+            // Remove no longer usefull cell-spans.
+            remove_outlooped(&mut st.spans, row, *col);
+
+            // Current cell is hidden?
+            let (is_hidden, hidden_cols) = check_hidden(&st.spans, row, *col);
+
+            let cell: CellContentRef<'_> = cell.into();
+            write_cell(&self.book, &cell, is_hidden, false, xml_out)?;
+
+            if forward_dc > 1 {
+                write_empty_cells(forward_dc, hidden_cols, xml_out)?;
+            }
+
+            if let Some(span) = cell.span {
+                if !is_hidden && (span.row_span > 1 || span.col_span > 1) {
+                    st.spans
+                        .push(CellRange::origin_span(row, *col, span.into()));
+                }
+            }
+        }
+
+        if !cells.is_empty() {
+            write_end_current_row(sheet, row, xml_out)?;
+        }
+
+        st.first_row = false;
+        st.last_r = row;
+        st.last_r_repeat = repeat;
+
+        Ok(())
+    }
+
+    // Ends the current sheet, if any.
+    fn end_sheet(&mut self) -> Result<(), OdsError> {
+        if let Some(st) = self.sheet.take() {
+            let sheet = self.book.sheet(st.idx);
+            if !st.first_row {
+                write_empty_rows_after(
+                    sheet,
+                    st.last_r.saturating_add(st.last_r_repeat),
+                    (0, st.max_cols),
+                    &mut self.xml_out,
+                )?;
+            }
+            write_sheet_end(sheet, &mut self.xml_out)?;
+        }
+        Ok(())
+    }
+
+    /// Ends the current sheet, writes all the sheets that have not been
+    /// started, and finishes the ODS-file.
+    pub fn finish(mut self) -> Result<W, OdsError> {
+        self.end_sheet()?;
+
+        for sheet in &self.book.sheets[self.next_sheet..] {
+            write_sheet(&self.book, sheet, None, &mut self.xml_out)?;
+        }
+        write_content_end(&self.book, &mut self.xml_out)?;
+
+        self.xml_out.close()?;
+
+        Ok(self.xml_out.into_inner().zip()?)
+    }
 }
 
 fn sanity_checks(book: &mut WorkBook) -> Result<(), OdsError> {
     if book.sheets.is_empty() {
         return Err(OdsError::Ods("Workbook contains no sheets.".to_string()));
     }
+    for sheet in &book.sheets {
+        if let Some(header_rows) = &sheet.header_rows {
+            check_header_groups(
+                sheet,
+                "rows",
+                header_rows.row(),
+                header_rows.to_row(),
+                &sheet.group_rows,
+            )?;
+        }
+        if let Some(header_cols) = &sheet.header_cols {
+            check_header_groups(
+                sheet,
+                "columns",
+                header_cols.col(),
+                header_cols.to_col(),
+                &sheet.group_cols,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// The header rows/columns can be nested inside a group, but a group
+// can't start or end within the header.
+fn check_header_groups(
+    sheet: &Sheet,
+    kind: &str,
+    from: u32,
+    to: u32,
+    groups: &[Grouped],
+) -> Result<(), OdsError> {
+    for group in groups {
+        let disjoint = group.to() < from || group.from() > to;
+        let contains = group.from() <= from && group.to() >= to;
+        if !disjoint && !contains {
+            return Err(OdsError::Ods(format!(
+                "Group {}..{} crosses the header {} {}..{} of sheet {}.",
+                group.from(),
+                group.to(),
+                kind,
+                from,
+                to,
+                sheet.name()
+            )));
+        }
+    }
     Ok(())
 }
 
@@ -191,11 +750,25 @@ fn copy_workbook<W: Write + Seek>(
     book: &WorkBook,
     zip_writer: &mut OdsWriter<W>,
 ) -> Result<(), OdsError> {
+    let images = image_files(book);
+    let charts = chart_dirs(book);
+
     for filebuf in book.filebuf.iter() {
         match filebuf {
+            FileBufEntry::Dir(name) | FileBufEntry::File(name, _)
+                if charts.iter().any(|v| name.starts_with(v.as_str())) =>
+            {
+                // written with the charts.
+            }
             FileBufEntry::Dir(name) => {
                 zip_writer.add_directory(name, FileOptions::default())?;
             }
+            FileBufEntry::File(name, _) if name == "META-INF/manifest.xml" => {
+                // always rewritten, as the images may have changed.
+            }
+            FileBufEntry::File(name, _) if images.contains_key(name.as_str()) => {
+                // written with the images.
+            }
             FileBufEntry::File(name, buf) => {
                 let mut wr = zip_writer.start_file(name, FileOptions::default())?;
                 wr.write_all(buf.as_slice())?;
@@ -206,83 +779,387 @@ fn copy_workbook<W: Write + Seek>(
     Ok(())
 }
 
-fn write_mimetype<W: Write + Seek>(
-    book: &WorkBook,
-    zip_out: &mut OdsWriter<W>,
-) -> Result<(), io::Error> {
-    if !book.filebuf.contains("mimetype") {
-        let mut w = zip_out.start_file(
-            "mimetype",
-            FileOptions::default().compression_method(zip::CompressionMethod::Stored),
-        )?;
-
-        let mime = "application/vnd.oasis.opendocument.spreadsheet";
-        w.write_all(mime.as_bytes())?;
+// All images with data, by path. Images with the same path are
+// expected to contain the same data.
+fn image_files(book: &WorkBook) -> HashMap<&str, &Image> {
+    let mut images = HashMap::new();
+    for sheet in book.sheets.iter() {
+        let cell_images = sheet.data.values().flat_map(|v| v.images());
+        for image in cell_images.chain(sheet.page_images.iter()) {
+            if !image.data().is_empty() {
+                images.entry(image.href()).or_insert(image);
+            }
+        }
     }
+    images
+}
 
-    Ok(())
+// Mime-type of the image, guessed if none was set.
+fn image_mime_type(image: &Image) -> &str {
+    if image.mime_type().is_empty() {
+        guess_mime_type(image.href(), image.data())
+    } else {
+        image.mime_type()
+    }
 }
 
-fn write_manifest<W: Write + Seek>(
+fn write_images<W: Write + Seek>(
     book: &WorkBook,
-    zip_out: &mut OdsWriter<W>,
+    zip_writer: &mut OdsWriter<W>,
 ) -> Result<(), OdsError> {
-    if !book.filebuf.contains("META-INF/manifest.xml") {
-        zip_out.add_directory("META-INF", FileOptions::default())?;
-        let w = zip_out.start_file("META-INF/manifest.xml", FileOptions::default())?;
-
-        let mut xml_out = XmlWriter::new(w);
+    let mut images: Vec<_> = image_files(book).into_iter().collect();
+    images.sort_by_key(|v| v.0);
 
-        xml_out.dtd("UTF-8")?;
+    for (href, image) in images {
+        let mut wr = zip_writer.start_file(href, FileOptions::default())?;
+        wr.write_all(image.data())?;
+    }
 
-        xml_out.elem("manifest:manifest")?;
-        xml_out.attr(
-            "xmlns:manifest",
-            "urn:oasis:names:tc:opendocument:xmlns:manifest:1.0",
-        )?;
-        xml_out.attr("manifest:version", book.version())?;
+    Ok(())
+}
 
-        xml_out.empty("manifest:file-entry")?;
-        xml_out.attr("manifest:full-path", "/")?;
-        xml_out.attr("manifest:version", book.version())?;
-        xml_out.attr(
-            "manifest:media-type",
-            "application/vnd.oasis.opendocument.spreadsheet",
-        )?;
+// Assigns a path within the package to all new charts and to
+// charts that share a path with another one.
+fn assign_chart_hrefs(book: &mut WorkBook) {
+    let mut used = HashSet::new();
+    for filebuf in book.filebuf.iter() {
+        let name = match filebuf {
+            FileBufEntry::Dir(name) => name,
+            FileBufEntry::File(name, _) => name,
+        };
+        if let Some((dir, _)) = name.split_once('/') {
+            used.insert(dir.to_string());
+        }
+    }
+    for sheet in book.sheets.iter() {
+        for cell in sheet.data.values() {
+            for chart in cell.charts() {
+                if let Some(href) = chart.href() {
+                    used.insert(href.clone());
+                }
+            }
+        }
+    }
 
-        //        xml_out.write_event(xml_empty_a("manifest:file-entry", vec![
-        //            ("manifest:full-path", String::from("Configurations2/")),
-        //            ("manifest:media-type", String::from("application/vnd.sun.xml.ui.configuration")),
-        //        ]))?;
+    let mut seen = HashSet::new();
+    let mut n = 1;
+    for sheet in book.sheets.iter_mut() {
+        for cell in sheet.data.values_mut() {
+            if let Some(extra) = &mut cell.extra {
+                for chart in extra.charts.iter_mut() {
+                    if let Some(href) = chart.href() {
+                        if seen.insert(href.clone()) {
+                            continue;
+                        }
+                    }
+                    let href = loop {
+                        let href = format!("Object {}", n);
+                        n += 1;
+                        if !used.contains(&href) {
+                            break href;
+                        }
+                    };
+                    used.insert(href.clone());
+                    seen.insert(href.clone());
+                    chart.set_href(href);
+                }
+            }
+        }
+    }
+}
 
-        xml_out.empty("manifest:file-entry")?;
-        xml_out.attr("manifest:full-path", "manifest.rdf")?;
-        xml_out.attr("manifest:media-type", "application/rdf+xml")?;
+// All charts with their path.
+fn chart_files(book: &WorkBook) -> Vec<(&str, &Chart)> {
+    let mut charts = Vec::new();
+    for sheet in book.sheets.iter() {
+        for cell in sheet.data.values() {
+            for chart in cell.charts() {
+                if let Some(href) = chart.href() {
+                    charts.push((href.as_str(), chart));
+                }
+            }
+        }
+    }
+    charts
+}
 
-        xml_out.empty("manifest:file-entry")?;
-        xml_out.attr("manifest:full-path", "styles.xml")?;
-        xml_out.attr("manifest:media-type", "text/xml")?;
+// Directories of the charts, with a trailing '/'.
+fn chart_dirs(book: &WorkBook) -> Vec<String> {
+    chart_files(book)
+        .into_iter()
+        .map(|(href, _)| format!("{}/", href))
+        .collect()
+}
 
-        xml_out.empty("manifest:file-entry")?;
-        xml_out.attr("manifest:full-path", "meta.xml")?;
-        xml_out.attr("manifest:media-type", "text/xml")?;
+fn write_charts<W: Write + Seek>(
+    book: &WorkBook,
+    zip_writer: &mut OdsWriter<W>,
+) -> Result<(), OdsError> {
+    for (href, chart) in chart_files(book) {
+        zip_writer.add_directory(href, FileOptions::default())?;
 
-        xml_out.empty("manifest:file-entry")?;
-        xml_out.attr("manifest:full-path", "content.xml")?;
-        xml_out.attr("manifest:media-type", "text/xml")?;
+        let w = zip_writer.start_file(format!("{}/content.xml", href), FileOptions::default())?;
+        let mut xml_out = XmlWriter::new(w);
+        xml_out.dtd("UTF-8")?;
+        xml_out.elem("office:document-content")?;
+        write_content_namespaces(&mut xml_out)?;
+        xml_out.attr("office:version", book.version())?;
+        xml_out.elem("office:body")?;
+        xml_out.elem("office:chart")?;
+        write_chart_body(chart, &mut xml_out)?;
+        xml_out.end_elem("office:chart")?;
+        xml_out.end_elem("office:body")?;
+        xml_out.end_elem("office:document-content")?;
+        xml_out.close()?;
+
+        let w = zip_writer.start_file(format!("{}/styles.xml", href), FileOptions::default())?;
+        let mut xml_out = XmlWriter::new(w);
+        xml_out.dtd("UTF-8")?;
+        xml_out.elem("office:document-styles")?;
+        write_content_namespaces(&mut xml_out)?;
+        xml_out.attr("office:version", book.version())?;
+        xml_out.empty("office:styles")?;
+        xml_out.end_elem("office:document-styles")?;
+        xml_out.close()?;
+    }
+
+    Ok(())
+}
+
+fn write_mimetype<W: Write + Seek>(
+    book: &WorkBook,
+    zip_out: &mut OdsWriter<W>,
+) -> Result<(), io::Error> {
+    if !book.filebuf.contains("mimetype") {
+        let mut w = zip_out.start_file(
+            "mimetype",
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored),
+        )?;
+
+        let mime = "application/vnd.oasis.opendocument.spreadsheet";
+        w.write_all(mime.as_bytes())?;
+    }
 
+    Ok(())
+}
+
+// One file-entry of the manifest.
+struct ManifestEntry {
+    full_path: String,
+    media_type: String,
+    // Sub-documents have their own version.
+    version: bool,
+}
+
+impl ManifestEntry {
+    fn new<S: Into<String>, T: Into<String>>(full_path: S, media_type: T) -> Self {
+        Self {
+            full_path: full_path.into(),
+            media_type: media_type.into(),
+            version: false,
+        }
+    }
+
+    fn new_doc<S: Into<String>, T: Into<String>>(full_path: S, media_type: T) -> Self {
+        Self {
+            full_path: full_path.into(),
+            media_type: media_type.into(),
+            version: true,
+        }
+    }
+}
+
+// All file-entries of the manifest.
+fn manifest_entries(book: &WorkBook) -> Vec<ManifestEntry> {
+    let mut entries = vec![
+        ManifestEntry::new_doc("/", "application/vnd.oasis.opendocument.spreadsheet"),
+        ManifestEntry::new("manifest.rdf", "application/rdf+xml"),
+        ManifestEntry::new("styles.xml", "text/xml"),
+        ManifestEntry::new("meta.xml", "text/xml"),
+        ManifestEntry::new("content.xml", "text/xml"),
+        ManifestEntry::new("settings.xml", "text/xml"),
+    ];
+
+    // everything else in the package.
+    let images = image_files(book);
+    let charts = chart_dirs(book);
+    for filebuf in book.filebuf.iter() {
+        if let FileBufEntry::File(name, _) = filebuf {
+            if !matches!(
+                name.as_str(),
+                "mimetype" | "META-INF/manifest.xml" | "manifest.rdf" | "meta.xml"
+            ) && !images.contains_key(name.as_str())
+                && !charts.iter().any(|v| name.starts_with(v.as_str()))
+            {
+                entries.push(ManifestEntry::new(name.as_str(), media_type(name)));
+            }
+        }
+    }
+    let mut images: Vec<_> = images.into_iter().collect();
+    images.sort_by_key(|v| v.0);
+    for (href, image) in images {
+        entries.push(ManifestEntry::new(href, image_mime_type(image)));
+    }
+    for (href, _) in chart_files(book) {
+        entries.push(ManifestEntry::new_doc(
+            format!("{}/", href),
+            "application/vnd.oasis.opendocument.chart",
+        ));
+        entries.push(ManifestEntry::new(
+            format!("{}/content.xml", href),
+            "text/xml",
+        ));
+        entries.push(ManifestEntry::new(
+            format!("{}/styles.xml", href),
+            "text/xml",
+        ));
+    }
+
+    entries
+}
+
+fn write_manifest<W: Write + Seek>(
+    book: &WorkBook,
+    zip_out: &mut OdsWriter<W>,
+) -> Result<(), OdsError> {
+    if !book.filebuf.contains("META-INF/") {
+        zip_out.add_directory("META-INF", FileOptions::default())?;
+    }
+    let w = zip_out.start_file("META-INF/manifest.xml", FileOptions::default())?;
+
+    let mut xml_out = XmlWriter::new(w);
+
+    xml_out.dtd("UTF-8")?;
+
+    xml_out.elem("manifest:manifest")?;
+    xml_out.attr(
+        "xmlns:manifest",
+        "urn:oasis:names:tc:opendocument:xmlns:manifest:1.0",
+    )?;
+    xml_out.attr("manifest:version", book.version())?;
+
+    for entry in manifest_entries(book) {
         xml_out.empty("manifest:file-entry")?;
-        xml_out.attr("manifest:full-path", "settings.xml")?;
-        xml_out.attr("manifest:media-type", "text/xml")?;
+        write_manifest_entry(book, &entry, &mut xml_out)?;
+    }
 
-        xml_out.end_elem("manifest:manifest")?;
+    xml_out.end_elem("manifest:manifest")?;
 
-        xml_out.close()?;
+    xml_out.close()?;
+
+    Ok(())
+}
+
+fn write_manifest_entry<W: Write>(
+    book: &WorkBook,
+    entry: &ManifestEntry,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.attr_esc("manifest:full-path", &entry.full_path)?;
+    if entry.version {
+        xml_out.attr("manifest:version", book.version())?;
+    }
+    xml_out.attr_esc("manifest:media-type", &entry.media_type)?;
+    Ok(())
+}
+
+// Manifest for an encrypted package. Contains the same entries as the
+// plain manifest, with the encryption-data for each encrypted file.
+fn write_manifest_encrypted<W: Write + Seek>(
+    book: &WorkBook,
+    encrypted: &HashMap<String, (u64, EncryptionData)>,
+    zip_out: &mut OdsWriter<W>,
+) -> Result<(), OdsError> {
+    zip_out.add_directory("META-INF", FileOptions::default())?;
+    let w = zip_out.start_file("META-INF/manifest.xml", FileOptions::default())?;
+
+    let mut xml_out = XmlWriter::new(w);
+
+    xml_out.dtd("UTF-8")?;
+
+    xml_out.elem("manifest:manifest")?;
+    xml_out.attr(
+        "xmlns:manifest",
+        "urn:oasis:names:tc:opendocument:xmlns:manifest:1.0",
+    )?;
+    xml_out.attr("manifest:version", book.version())?;
+
+    let mut entries = manifest_entries(book);
+    // files that are in the package but not in the plain manifest.
+    let mut missing: Vec<_> = encrypted
+        .keys()
+        .filter(|name| !entries.iter().any(|v| &v.full_path == *name))
+        .collect();
+    missing.sort();
+    for name in missing {
+        entries.push(ManifestEntry::new(name.as_str(), media_type(name)));
     }
 
+    for entry in entries {
+        match encrypted.get(&entry.full_path) {
+            Some((size, enc)) => {
+                xml_out.elem("manifest:file-entry")?;
+                write_manifest_entry(book, &entry, &mut xml_out)?;
+                xml_out.attr("manifest:size", size.to_string())?;
+                write_encryption_data(enc, &mut xml_out)?;
+                xml_out.end_elem("manifest:file-entry")?;
+            }
+            None => {
+                xml_out.empty("manifest:file-entry")?;
+                write_manifest_entry(book, &entry, &mut xml_out)?;
+            }
+        }
+    }
+
+    xml_out.end_elem("manifest:manifest")?;
+
+    xml_out.close()?;
+
     Ok(())
 }
 
+fn write_encryption_data<W: Write>(
+    enc: &EncryptionData,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("manifest:encryption-data")?;
+    xml_out.attr("manifest:checksum-type", &enc.checksum_type)?;
+    xml_out.attr("manifest:checksum", STANDARD.encode(&enc.checksum))?;
+
+    xml_out.empty("manifest:algorithm")?;
+    xml_out.attr("manifest:algorithm-name", &enc.algorithm)?;
+    xml_out.attr("manifest:initialisation-vector", STANDARD.encode(&enc.iv))?;
+
+    xml_out.empty("manifest:start-key-generation")?;
+    xml_out.attr(
+        "manifest:start-key-generation-name",
+        &enc.start_key_generation,
+    )?;
+    xml_out.attr("manifest:key-size", enc.start_key_size.to_string())?;
+
+    xml_out.empty("manifest:key-derivation")?;
+    xml_out.attr("manifest:key-derivation-name", &enc.key_derivation)?;
+    xml_out.attr("manifest:key-size", enc.key_size.to_string())?;
+    xml_out.attr("manifest:iteration-count", enc.iteration_count.to_string())?;
+    xml_out.attr("manifest:salt", STANDARD.encode(&enc.salt))?;
+
+    xml_out.end_elem("manifest:encryption-data")?;
+    Ok(())
+}
+
+// Media-type for the manifest.
+fn media_type(name: &str) -> &'static str {
+    match name.rsplit_once('.').map(|v| v.1) {
+        Some("xml") => "text/xml",
+        Some("rdf") => "application/rdf+xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        _ => "",
+    }
+}
+
 fn write_manifest_rdf<W: Write + Seek>(
     book: &WorkBook,
     zip_out: &mut OdsWriter<W>,
@@ -359,31 +1236,95 @@ fn write_meta<W: Write + Seek>(
             "xmlns:office",
             "urn:oasis:names:tc:opendocument:xmlns:office:1.0",
         )?;
+        xml_out.attr("xmlns:dc", "http://purl.org/dc/elements/1.1/")?;
+        xml_out.attr("xmlns:xlink", "http://www.w3.org/1999/xlink")?;
         xml_out.attr("office:version", book.version())?;
 
-        xml_out.elem("office:meta")?;
+        write_office_meta(book.metadata(), &mut xml_out)?;
 
-        xml_out.elem_text("meta:generator", "spreadsheet-ods 0.15.0")?;
-        let s = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
-        let d = NaiveDateTime::from_timestamp_opt(s.as_secs() as i64, 0);
-        if let Some(d) = d {
-            xml_out.elem_text(
-                "meta:creation-date",
-                &d.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
-            )?;
-        }
-        xml_out.elem_text("meta:editing-duration", "P0D")?;
-        xml_out.elem_text("meta:editing-cycles", "1")?;
-        // xml_out.elem_text_esc("meta:initial-creator", &username::get_user_name().unwrap())?;
+        xml_out.end_elem("office:document-meta")?;
 
-        // TODO: allow to set this data.
+        xml_out.close()?;
+    }
 
-        xml_out.end_elem("office:meta")?;
+    Ok(())
+}
 
-        xml_out.end_elem("office:document-meta")?;
+// The office:meta element.
+fn write_office_meta<W: Write>(
+    meta: &Metadata,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("office:meta")?;
 
-        xml_out.close()?;
+    xml_out.elem_text(
+        "meta:generator",
+        concat!("spreadsheet-ods ", env!("CARGO_PKG_VERSION")),
+    )?;
+    if !meta.title().is_empty() {
+        xml_out.elem_text_esc("dc:title", meta.title())?;
+    }
+    if !meta.description().is_empty() {
+        xml_out.elem_text_esc("dc:description", meta.description())?;
+    }
+    if !meta.subject().is_empty() {
+        xml_out.elem_text_esc("dc:subject", meta.subject())?;
+    }
+    for keyword in meta.keywords() {
+        xml_out.elem_text_esc("meta:keyword", keyword)?;
+    }
+    if !meta.initial_creator().is_empty() {
+        xml_out.elem_text_esc("meta:initial-creator", meta.initial_creator())?;
+    }
+    if !meta.creator().is_empty() {
+        xml_out.elem_text_esc("dc:creator", meta.creator())?;
+    }
+    if !meta.printed_by().is_empty() {
+        xml_out.elem_text_esc("meta:printed-by", meta.printed_by())?;
+    }
+    if let Some(d) = meta.creation_date() {
+        xml_out.elem_text("meta:creation-date", datetime_value(&d))?;
+    } else {
+        let s = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?;
+        if let Some(d) = NaiveDateTime::from_timestamp_opt(s.as_secs() as i64, 0) {
+            xml_out.elem_text("meta:creation-date", datetime_value(&d))?;
+        }
+    }
+    if let Some(d) = meta.modification_date() {
+        xml_out.elem_text("dc:date", datetime_value(&d))?;
+    }
+    if let Some(d) = meta.print_date() {
+        xml_out.elem_text("meta:print-date", datetime_value(&d))?;
+    }
+    if !meta.language().is_empty() {
+        xml_out.elem_text_esc("dc:language", meta.language())?;
     }
+    xml_out.elem_text(
+        "meta:editing-cycles",
+        meta.editing_cycles().to_string().as_str(),
+    )?;
+    xml_out.elem_text(
+        "meta:editing-duration",
+        duration_value(&meta.editing_duration()),
+    )?;
+    for tag in &meta.extra {
+        write_xmltag(tag, xml_out)?;
+    }
+    for user in meta.iter_user_defined() {
+        xml_out.elem("meta:user-defined")?;
+        xml_out.attr_esc("meta:name", user.name())?;
+        xml_out.attr("meta:value-type", user.value().value_type())?;
+        match user.value() {
+            MetaValue::Boolean(v) => xml_out.text(v.to_string())?,
+            MetaValue::DateTime(v) => xml_out.text(datetime_value(v))?,
+            MetaValue::Float(v) => xml_out.text(v.to_string())?,
+            MetaValue::TimeDuration(v) => xml_out.text(duration_value(v))?,
+            MetaValue::String(v) => xml_out.text_esc(v)?,
+        }
+        xml_out.end_elem("meta:user-defined")?;
+    }
+
+    xml_out.end_elem("office:meta")?;
 
     Ok(())
 }
@@ -409,6 +1350,19 @@ fn write_settings<W: Write + Seek>(
         "urn:oasis:names:tc:opendocument:xmlns:config:1.0",
     )?;
     xml_out.attr("office:version", book.version())?;
+    write_office_settings(book, &mut xml_out)?;
+    xml_out.end_elem("office:document-settings")?;
+
+    xml_out.close()?;
+
+    Ok(())
+}
+
+// The office:settings element.
+fn write_office_settings<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
     xml_out.elem("office:settings")?;
 
     for (name, item) in book.config.iter() {
@@ -416,7 +1370,7 @@ fn write_settings<W: Write + Seek>(
             ConfigItem::Value(_) => {
                 panic!("office-settings must not contain config-item");
             }
-            ConfigItem::Set(_) => write_config_item_set(name, item, &mut xml_out)?,
+            ConfigItem::Set(_) => write_config_item_set(name, item, xml_out)?,
             ConfigItem::Vec(_) => {
                 panic!("office-settings must not contain config-item-map-index")
             }
@@ -430,17 +1384,14 @@ fn write_settings<W: Write + Seek>(
     }
 
     xml_out.end_elem("office:settings")?;
-    xml_out.end_elem("office:document-settings")?;
-
-    xml_out.close()?;
 
     Ok(())
 }
 
-fn write_config_item_set<W: Write + Seek>(
+fn write_config_item_set<W: Write>(
     name: &str,
     set: &ConfigItem,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("config:config-item-set")?;
     xml_out.attr("config:name", name)?;
@@ -462,10 +1413,10 @@ fn write_config_item_set<W: Write + Seek>(
     Ok(())
 }
 
-fn write_config_item_map_indexed<W: Write + Seek>(
+fn write_config_item_map_indexed<W: Write>(
     name: &str,
     vec: &ConfigItem,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("config:config-item-map-indexed")?;
     xml_out.attr("config:name", name)?;
@@ -499,10 +1450,10 @@ fn write_config_item_map_indexed<W: Write + Seek>(
     Ok(())
 }
 
-fn write_config_item_map_named<W: Write + Seek>(
+fn write_config_item_map_named<W: Write>(
     name: &str,
     map: &ConfigItem,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("config:config-item-map-named")?;
     xml_out.attr("config:name", name)?;
@@ -528,10 +1479,10 @@ fn write_config_item_map_named<W: Write + Seek>(
     Ok(())
 }
 
-fn write_config_item_map_entry<W: Write + Seek>(
+fn write_config_item_map_entry<W: Write>(
     name: Option<&String>,
     map_entry: &ConfigItem,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("config:config-item-map-entry")?;
     if let Some(name) = name {
@@ -555,10 +1506,10 @@ fn write_config_item_map_entry<W: Write + Seek>(
     Ok(())
 }
 
-fn write_config_item<W: Write + Seek>(
+fn write_config_item<W: Write>(
     name: &str,
     value: &ConfigValue,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     let is_empty = match value {
         ConfigValue::Base64Binary(t) => t.is_empty(),
@@ -745,150 +1696,146 @@ fn write_ods_styles<W: Write + Seek>(
     write_font_decl(&book.fonts, StyleOrigin::Styles, &mut xml_out)?;
     xml_out.end_elem("office:font-face-decls")?;
 
+    write_office_styles(book, &mut xml_out)?;
+
+    xml_out.elem("office:automatic-styles")?;
+    write_pagestyles(&book.pagestyles, &mut xml_out)?;
+    write_automatic_styles(book, StyleOrigin::Styles, &mut xml_out)?;
+    xml_out.end_elem("office:automatic-styles")?;
+
+    xml_out.elem("office:master-styles")?;
+    write_masterpage(&book.masterpages, &mut xml_out)?;
+    xml_out.end_elem("office:master-styles")?;
+
+    xml_out.end_elem("office:document-styles")?;
+
+    xml_out.close()?;
+
+    Ok(())
+}
+
+// The office:styles element.
+fn write_office_styles<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
     xml_out.elem("office:styles")?;
-    write_styles(book, StyleOrigin::Styles, StyleUse::Default, &mut xml_out)?;
-    write_styles(book, StyleOrigin::Styles, StyleUse::Named, &mut xml_out)?;
+    write_styles(book, StyleOrigin::Styles, StyleUse::Default, xml_out)?;
+    write_styles(book, StyleOrigin::Styles, StyleUse::Named, xml_out)?;
     write_valuestyles(
         &book.formats_boolean,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_currency,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_datetime,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_number,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_percentage,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_text,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_timeduration,
         StyleOrigin::Styles,
         StyleUse::Named,
-        &mut xml_out,
+        xml_out,
     )?;
 
     write_valuestyles(
         &book.formats_boolean,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_currency,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_datetime,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_number,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_percentage,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_text,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     write_valuestyles(
         &book.formats_timeduration,
         StyleOrigin::Styles,
         StyleUse::Default,
-        &mut xml_out,
+        xml_out,
     )?;
     xml_out.end_elem("office:styles")?;
 
-    xml_out.elem("office:automatic-styles")?;
-    write_pagestyles(&book.pagestyles, &mut xml_out)?;
-    write_styles(book, StyleOrigin::Styles, StyleUse::Automatic, &mut xml_out)?;
-    write_valuestyles(
-        &book.formats_boolean,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_currency,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_datetime,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_number,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
+    Ok(())
+}
+
+// Automatic styles and value formats for the given origin.
+fn write_automatic_styles<W: Write>(
+    book: &WorkBook,
+    origin: StyleOrigin,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    write_styles(book, origin, StyleUse::Automatic, xml_out)?;
+    write_valuestyles(&book.formats_boolean, origin, StyleUse::Automatic, xml_out)?;
+    write_valuestyles(&book.formats_currency, origin, StyleUse::Automatic, xml_out)?;
+    write_valuestyles(&book.formats_datetime, origin, StyleUse::Automatic, xml_out)?;
+    write_valuestyles(&book.formats_number, origin, StyleUse::Automatic, xml_out)?;
     write_valuestyles(
         &book.formats_percentage,
-        StyleOrigin::Styles,
+        origin,
         StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_text,
-        StyleOrigin::Styles,
-        StyleUse::Automatic,
-        &mut xml_out,
+        xml_out,
     )?;
+    write_valuestyles(&book.formats_text, origin, StyleUse::Automatic, xml_out)?;
     write_valuestyles(
         &book.formats_timeduration,
-        StyleOrigin::Styles,
+        origin,
         StyleUse::Automatic,
-        &mut xml_out,
+        xml_out,
     )?;
-    xml_out.end_elem("office:automatic-styles")?;
-
-    xml_out.elem("office:master-styles")?;
-    write_masterpage(&book.masterpages, &mut xml_out)?;
-    xml_out.end_elem("office:master-styles")?;
-
-    xml_out.end_elem("office:document-styles")?;
-
-    xml_out.close()?;
 
     Ok(())
 }
@@ -900,9 +1847,62 @@ fn write_ods_content<W: Write + Seek>(
     let w = zip_out.start_file("content.xml", FileOptions::default())?;
     let mut xml_out = XmlWriter::new(w);
 
+    write_content_start(book, &mut xml_out)?;
+    for sheet in &book.sheets {
+        write_sheet(book, sheet, None, &mut xml_out)?;
+    }
+    write_content_end(book, &mut xml_out)?;
+
+    xml_out.close()?;
+
+    Ok(())
+}
+
+// Everything in content.xml before the first sheet.
+fn write_content_start<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
     xml_out.dtd("UTF-8")?;
 
     xml_out.elem("office:document-content")?;
+    write_content_namespaces(xml_out)?;
+    xml_out.attr("office:version", book.version())?;
+
+    xml_out.empty("office:scripts")?;
+
+    xml_out.elem("office:font-face-decls")?;
+    write_font_decl(&book.fonts, StyleOrigin::Content, xml_out)?;
+    xml_out.end_elem("office:font-face-decls")?;
+
+    xml_out.elem("office:automatic-styles")?;
+    write_automatic_styles(book, StyleOrigin::Content, xml_out)?;
+    xml_out.end_elem("office:automatic-styles")?;
+
+    xml_out.elem("office:body")?;
+    xml_out.elem("office:spreadsheet")?;
+
+    write_spreadsheet_start(book, xml_out)?;
+
+    Ok(())
+}
+
+// Everything in content.xml after the last sheet.
+fn write_content_end<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    write_spreadsheet_end(book, xml_out)?;
+
+    xml_out.end_elem("office:spreadsheet")?;
+    xml_out.end_elem("office:body")?;
+    xml_out.end_elem("office:document-content")?;
+
+    Ok(())
+}
+
+// Namespaces for content.xml.
+fn write_content_namespaces<W: Write>(xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     xml_out.attr(
         "xmlns:meta",
         "urn:oasis:names:tc:opendocument:xmlns:meta:1.0",
@@ -993,114 +1993,721 @@ fn write_ods_content<W: Write + Seek>(
         "urn:oasis:names:tc:opendocument:xmlns:presentation:1.0",
     )?;
 
-    xml_out.attr("office:version", book.version())?;
+    Ok(())
+}
+
+// Everything in office:spreadsheet before the first sheet.
+fn write_spreadsheet_start<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    // extra tags. pass through only
+    for tag in &book.extra {
+        if tag.name() == "office:scripts" {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
+
+    if let Some(tracked_changes) = &book.tracked_changes {
+        write_tracked_changes(tracked_changes, xml_out)?;
+    }
+
+    for tag in &book.extra {
+        if tag.name() == "text:variable-decls"
+            || tag.name() == "text:sequence-decls"
+            || tag.name() == "text:user-field-decls"
+            || tag.name() == "text:dde-connection-decls"
+        {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
+
+    write_calculation_settings(book, xml_out)?;
+    write_content_validations(book, xml_out)?;
+
+    for tag in &book.extra {
+        if tag.name() == "table:label-ranges" {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_tracked_changes<W: Write>(
+    changes: &TrackedChanges,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    if changes.changes().is_empty() {
+        xml_out.empty("table:tracked-changes")?;
+    } else {
+        xml_out.elem("table:tracked-changes")?;
+    }
+    if !changes.track_changes() {
+        xml_out.attr("table:track-changes", "false")?;
+    }
+    for change in changes.iter() {
+        write_tracked_change(change, xml_out)?;
+    }
+    if !changes.changes().is_empty() {
+        xml_out.end_elem("table:tracked-changes")?;
+    }
+    Ok(())
+}
+
+fn write_tracked_change<W: Write>(
+    change: &TrackedChange,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let tag = match change.kind() {
+        ChangeKind::CellContent { .. } => "table:cell-content-change",
+        ChangeKind::Insertion { .. } => "table:insertion",
+        ChangeKind::Deletion { .. } => "table:deletion",
+        ChangeKind::Movement { .. } => "table:movement",
+    };
+    xml_out.elem(tag)?;
+    xml_out.attr_esc("table:id", change.id())?;
+    if change.acceptance_state() != AcceptanceState::Pending {
+        xml_out.attr("table:acceptance-state", change.acceptance_state().as_str())?;
+    }
+    if let Some(id) = change.rejecting_change_id() {
+        xml_out.attr_esc("table:rejecting-change-id", id)?;
+    }
+    match change.kind() {
+        ChangeKind::Insertion {
+            change_type,
+            position,
+            count,
+            table,
+        } => {
+            xml_out.attr("table:type", change_type.as_str())?;
+            xml_out.attr("table:position", position.to_string().as_str())?;
+            if *count != 1 {
+                xml_out.attr("table:count", count.to_string().as_str())?;
+            }
+            if *change_type != ChangeType::Table {
+                xml_out.attr("table:table", table.to_string().as_str())?;
+            }
+        }
+        ChangeKind::Deletion {
+            change_type,
+            position,
+            table,
+            multi_deletion_spanned,
+        } => {
+            xml_out.attr("table:type", change_type.as_str())?;
+            xml_out.attr("table:position", position.to_string().as_str())?;
+            if *change_type != ChangeType::Table {
+                xml_out.attr("table:table", table.to_string().as_str())?;
+            }
+            if let Some(v) = multi_deletion_spanned {
+                xml_out.attr("table:multi-deletion-spanned", v.to_string().as_str())?;
+            }
+        }
+        _ => {}
+    }
+
+    match change.kind() {
+        ChangeKind::CellContent { address, .. } => {
+            write_change_address("table:cell-address", address, xml_out)?;
+        }
+        ChangeKind::Movement { source, target } => {
+            write_change_range("table:source-range-address", source, xml_out)?;
+            write_change_range("table:target-range-address", target, xml_out)?;
+        }
+        _ => {}
+    }
+
+    write_change_info(change.info(), xml_out)?;
+
+    if !change.dependencies().is_empty() {
+        xml_out.elem("table:dependencies")?;
+        for id in change.dependencies() {
+            xml_out.empty("table:dependency")?;
+            xml_out.attr_esc("table:id", id)?;
+        }
+        xml_out.end_elem("table:dependencies")?;
+    }
+
+    if !change.deletions().is_empty() {
+        xml_out.elem("table:deletions")?;
+        for deletion in change.deletions() {
+            match deletion {
+                ChangeDeletion::Change(id) => {
+                    xml_out.empty("table:change-deletion")?;
+                    xml_out.attr_esc("table:id", id)?;
+                }
+                ChangeDeletion::CellContent { id, address, cell } => {
+                    xml_out.elem("table:cell-content-deletion")?;
+                    if let Some(id) = id {
+                        xml_out.attr_esc("table:id", id)?;
+                    }
+                    if let Some(address) = address {
+                        write_change_address("table:cell-address", address, xml_out)?;
+                    }
+                    if let Some(cell) = cell {
+                        write_change_cell(Some(cell), xml_out)?;
+                    }
+                    xml_out.end_elem("table:cell-content-deletion")?;
+                }
+            }
+        }
+        xml_out.end_elem("table:deletions")?;
+    }
+
+    if let ChangeKind::CellContent {
+        previous_id,
+        previous,
+        ..
+    } = change.kind()
+    {
+        xml_out.elem("table:previous")?;
+        if let Some(id) = previous_id {
+            xml_out.attr_esc("table:id", id)?;
+        }
+        write_change_cell(previous.as_ref(), xml_out)?;
+        xml_out.end_elem("table:previous")?;
+    }
+
+    // cut-offs
+    for tag in &change.extra {
+        write_xmltag(tag, xml_out)?;
+    }
+
+    xml_out.end_elem(tag)?;
+    Ok(())
+}
+
+fn write_change_info<W: Write>(
+    info: &ChangeInfo,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("office:change-info")?;
+    xml_out.elem_text_esc("dc:creator", info.creator())?;
+    if let Some(d) = info.date() {
+        xml_out.elem_text("dc:date", datetime_value(&d))?;
+    }
+    if !info.comment().is_empty() {
+        for l in info.comment().split('\n') {
+            xml_out.elem_text_esc("text:p", l)?;
+        }
+    }
+    xml_out.end_elem("office:change-info")?;
+    Ok(())
+}
+
+fn write_change_address<W: Write>(
+    tag: &str,
+    address: &ChangeAddress,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.empty(tag)?;
+    xml_out.attr("table:column", address.col().to_string().as_str())?;
+    xml_out.attr("table:row", address.row().to_string().as_str())?;
+    xml_out.attr("table:table", address.table().to_string().as_str())?;
+    Ok(())
+}
+
+fn write_change_range<W: Write>(
+    tag: &str,
+    range: &ChangeRange,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    if range.from() == range.to() {
+        return write_change_address(tag, &range.from(), xml_out);
+    }
+    xml_out.empty(tag)?;
+    xml_out.attr(
+        "table:start-column",
+        range.from().col().to_string().as_str(),
+    )?;
+    xml_out.attr("table:start-row", range.from().row().to_string().as_str())?;
+    xml_out.attr(
+        "table:start-table",
+        range.from().table().to_string().as_str(),
+    )?;
+    xml_out.attr("table:end-column", range.to().col().to_string().as_str())?;
+    xml_out.attr("table:end-row", range.to().row().to_string().as_str())?;
+    xml_out.attr("table:end-table", range.to().table().to_string().as_str())?;
+    Ok(())
+}
+
+// None is written as an empty cell.
+fn write_change_cell<W: Write>(
+    cell: Option<&ChangeCell>,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let is_empty = match cell {
+        Some(cell) => matches!(cell.value(), Value::Empty),
+        None => true,
+    };
+    if is_empty {
+        xml_out.empty("table:change-track-table-cell")?;
+    } else {
+        xml_out.elem("table:change-track-table-cell")?;
+    }
+    if let Some(cell) = cell {
+        if let Some(formula) = cell.formula() {
+            xml_out.attr_esc("table:formula", formula)?;
+        }
+        for (k, v) in cell.attrmap().iter() {
+            xml_out.attr_esc(k.as_ref(), v)?;
+        }
+        write_value_attr(cell.value(), xml_out)?;
+        write_value_text(cell.value(), xml_out)?;
+    }
+    if !is_empty {
+        xml_out.end_elem("table:change-track-table-cell")?;
+    }
+    Ok(())
+}
+
+// Only the values that differ from the default are written.
+fn write_calculation_settings<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let calc = &book.calc_settings;
+    let def = CalculationSettings::default();
+    if *calc == def {
+        return Ok(());
+    }
+
+    let null_date = calc.null_date() != def.null_date();
+    let iteration = calc.iteration() != def.iteration()
+        || calc.iteration_steps() != def.iteration_steps()
+        || calc.iteration_minimum_difference() != def.iteration_minimum_difference();
+
+    if null_date || iteration {
+        xml_out.elem("table:calculation-settings")?;
+    } else {
+        xml_out.empty("table:calculation-settings")?;
+    }
+    if calc.case_sensitive() != def.case_sensitive() {
+        xml_out.attr("table:case-sensitive", calc.case_sensitive().to_string())?;
+    }
+    if calc.precision_as_shown() != def.precision_as_shown() {
+        xml_out.attr(
+            "table:precision-as-shown",
+            calc.precision_as_shown().to_string(),
+        )?;
+    }
+    if calc.search_criteria_must_apply_to_whole_cell()
+        != def.search_criteria_must_apply_to_whole_cell()
+    {
+        xml_out.attr(
+            "table:search-criteria-must-apply-to-whole-cell",
+            calc.search_criteria_must_apply_to_whole_cell().to_string(),
+        )?;
+    }
+    if calc.automatic_find_labels() != def.automatic_find_labels() {
+        xml_out.attr(
+            "table:automatic-find-labels",
+            calc.automatic_find_labels().to_string(),
+        )?;
+    }
+    if calc.use_regular_expressions() != def.use_regular_expressions() {
+        xml_out.attr(
+            "table:use-regular-expressions",
+            calc.use_regular_expressions().to_string(),
+        )?;
+    }
+    if calc.use_wildcards() != def.use_wildcards() {
+        xml_out.attr("table:use-wildcards", calc.use_wildcards().to_string())?;
+    }
+    if calc.null_year() != def.null_year() {
+        xml_out.attr("table:null-year", calc.null_year().to_string())?;
+    }
+
+    if null_date {
+        xml_out.empty("table:null-date")?;
+        xml_out.attr("table:value-type", "date")?;
+        xml_out.attr(
+            "table:date-value",
+            calc.null_date().format("%Y-%m-%d").to_string(),
+        )?;
+    }
+    if iteration {
+        xml_out.empty("table:iteration")?;
+        if calc.iteration() {
+            xml_out.attr("table:status", "enable")?;
+        }
+        xml_out.attr("table:steps", calc.iteration_steps().to_string())?;
+        xml_out.attr(
+            "table:minimum-difference",
+            calc.iteration_minimum_difference().to_string(),
+        )?;
+    }
+
+    if null_date || iteration {
+        xml_out.end_elem("table:calculation-settings")?;
+    }
+
+    Ok(())
+}
+
+// Everything in office:spreadsheet after the last sheet.
+fn write_spreadsheet_end<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    write_named_expressions(&book.named_ranges, &book.named_expressions, xml_out)?;
+    write_database_ranges(book, xml_out)?;
+    write_data_pilot_tables(book, xml_out)?;
+
+    // extra tags. pass through only
+    for tag in &book.extra {
+        if tag.name() == "table:consolidation" || tag.name() == "table:dde-links" {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Database ranges, sorted by name.
+fn write_database_ranges<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    if book.database_ranges.is_empty() {
+        return Ok(());
+    }
+
+    xml_out.elem("table:database-ranges")?;
+
+    let mut ranges: Vec<_> = book.database_ranges.values().collect();
+    ranges.sort_by(|a, b| a.name().cmp(b.name()));
+    for db in ranges {
+        write_database_range(db, xml_out)?;
+    }
+
+    xml_out.end_elem("table:database-ranges")?;
+
+    Ok(())
+}
+
+fn write_database_range<W: Write>(
+    db: &DatabaseRange,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("table:database-range")?;
+    xml_out.attr_esc("table:name", db.name())?;
+    xml_out.attr_esc("table:target-range-address", db.range().to_string())?;
+    if !db.contains_header() {
+        xml_out.attr("table:contains-header", "false")?;
+    }
+    if db.display_filter_buttons() {
+        xml_out.attr("table:display-filter-buttons", "true")?;
+    }
+    for (k, v) in db.attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
+
+    for tag in db.extra() {
+        if tag.name() != "table:subtotal-rules" {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
+
+    if let Some(filter) = db.filter() {
+        xml_out.elem("table:filter")?;
+        if !filter.display_duplicates() {
+            xml_out.attr("table:display-duplicates", "false")?;
+        }
+        write_filter_item(filter.item(), xml_out)?;
+        xml_out.end_elem("table:filter")?;
+    }
+
+    if !db.sort_keys().is_empty() {
+        xml_out.elem("table:sort")?;
+        for key in db.sort_keys() {
+            xml_out.empty("table:sort-by")?;
+            xml_out.attr("table:field-number", key.field().to_string())?;
+            xml_out.attr_esc("table:data-type", key.data_type())?;
+            if key.order() == SortOrder::Descending {
+                xml_out.attr("table:order", "descending")?;
+            }
+        }
+        xml_out.end_elem("table:sort")?;
+    }
+
+    for tag in db.extra() {
+        if tag.name() == "table:subtotal-rules" {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
+
+    xml_out.end_elem("table:database-range")?;
+
+    Ok(())
+}
+
+// Pivot tables, sorted by name.
+fn write_data_pilot_tables<W: Write>(
+    book: &WorkBook,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    if book.data_pilot_tables.is_empty() {
+        return Ok(());
+    }
+
+    xml_out.elem("table:data-pilot-tables")?;
+
+    let mut tables: Vec<_> = book.data_pilot_tables.values().collect();
+    tables.sort_by(|a, b| a.name().cmp(b.name()));
+    for dp in tables {
+        write_data_pilot_table(dp, xml_out)?;
+    }
+
+    xml_out.end_elem("table:data-pilot-tables")?;
+
+    Ok(())
+}
+
+fn write_data_pilot_table<W: Write>(
+    dp: &DataPilotTable,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("table:data-pilot-table")?;
+    xml_out.attr_esc("table:name", dp.name())?;
+    xml_out.attr_esc("table:target-range-address", dp.target().to_string())?;
+    if dp.grand_total() != GrandTotal::Both {
+        xml_out.attr("table:grand-total", dp.grand_total().as_str())?;
+    }
+    if dp.ignore_empty_rows() {
+        xml_out.attr("table:ignore-empty-rows", "true")?;
+    }
+    if dp.identify_categories() {
+        xml_out.attr("table:identify-categories", "true")?;
+    }
+    if !dp.show_filter_button() {
+        xml_out.attr("table:show-filter-button", "false")?;
+    }
+    if !dp.drill_down_on_double_click() {
+        xml_out.attr("table:drill-down-on-double-click", "false")?;
+    }
+    for (k, v) in dp.attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
+
+    // Other sources than a cell-range are kept as xml.
+    let has_source = dp
+        .extra()
+        .iter()
+        .any(|v| v.name().starts_with("table:database-source"));
+    if has_source {
+        for tag in dp.extra() {
+            if tag.name().starts_with("table:database-source") {
+                write_xmltag(tag, xml_out)?;
+            }
+        }
+    } else {
+        xml_out.elem("table:source-cell-range")?;
+        xml_out.attr_esc("table:cell-range-address", dp.source().to_string())?;
+        if let Some(filter) = dp.source_filter() {
+            xml_out.elem("table:filter")?;
+            if !filter.display_duplicates() {
+                xml_out.attr("table:display-duplicates", "false")?;
+            }
+            write_filter_item(filter.item(), xml_out)?;
+            xml_out.end_elem("table:filter")?;
+        }
+        xml_out.end_elem("table:source-cell-range")?;
+    }
+
+    for field in dp.fields() {
+        write_data_pilot_field(field, xml_out)?;
+    }
 
-    xml_out.empty("office:scripts")?;
+    for tag in dp.extra() {
+        if !tag.name().starts_with("table:database-source") {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
 
-    xml_out.elem("office:font-face-decls")?;
-    write_font_decl(&book.fonts, StyleOrigin::Content, &mut xml_out)?;
-    xml_out.end_elem("office:font-face-decls")?;
+    xml_out.end_elem("table:data-pilot-table")?;
 
-    xml_out.elem("office:automatic-styles")?;
-    write_styles(
-        book,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_boolean,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_currency,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_datetime,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_number,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_percentage,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_text,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    write_valuestyles(
-        &book.formats_timeduration,
-        StyleOrigin::Content,
-        StyleUse::Automatic,
-        &mut xml_out,
-    )?;
-    xml_out.end_elem("office:automatic-styles")?;
+    Ok(())
+}
 
-    xml_out.elem("office:body")?;
-    xml_out.elem("office:spreadsheet")?;
+fn write_data_pilot_field<W: Write>(
+    field: &DataPilotField,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("table:data-pilot-field")?;
+    xml_out.attr_esc("table:source-field-name", field.source_field_name())?;
+    if field.is_data_layout_field() {
+        xml_out.attr("table:is-data-layout-field", "true")?;
+    }
+    xml_out.attr("table:orientation", field.orientation().as_str())?;
+    if let Some(function) = field.function() {
+        xml_out.attr("table:function", function.as_str())?;
+    }
+    if let Some(selected_page) = field.selected_page() {
+        xml_out.attr_esc("table:selected-page", selected_page)?;
+    }
+    for (k, v) in field.attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
 
-    // extra tags. pass through only
-    for tag in &book.extra {
-        if tag.name() == "office:scripts" ||
-            tag.name() == "table:tracked-changes" ||
-            tag.name() == "text:variable-decls" ||
-            tag.name() == "text:sequence-decls" ||
-            tag.name() == "text:user-field-decls" ||
-            tag.name() == "text:dde-connection-decls" ||
-            // tag.name() == "text:alphabetical-index-auto-mark-file" ||
-            tag.name() == "table:calculation-settings" ||
-            tag.name() == "table:label-ranges"
-        {
-            write_xmltag(tag, &mut xml_out)?;
+    xml_out.elem("table:data-pilot-level")?;
+    xml_out.attr(
+        "table:show-empty",
+        if field.show_empty() { "true" } else { "false" },
+    )?;
+    for (k, v) in field.level_attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
+    if !field.subtotals().is_empty() {
+        xml_out.elem("table:data-pilot-subtotals")?;
+        for function in field.subtotals() {
+            xml_out.empty("table:data-pilot-subtotal")?;
+            xml_out.attr("table:function", function.as_str())?;
         }
+        xml_out.end_elem("table:data-pilot-subtotals")?;
     }
+    if !field.members().is_empty() {
+        xml_out.elem("table:data-pilot-members")?;
+        for member in field.members() {
+            xml_out.empty("table:data-pilot-member")?;
+            xml_out.attr_esc("table:name", member.name())?;
+            xml_out.attr(
+                "table:display",
+                if member.display() { "true" } else { "false" },
+            )?;
+            xml_out.attr(
+                "table:show-details",
+                if member.show_details() {
+                    "true"
+                } else {
+                    "false"
+                },
+            )?;
+        }
+        xml_out.end_elem("table:data-pilot-members")?;
+    }
+    for tag in field.level_extra() {
+        if tag.name() == "table:data-pilot-display-info" {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
+    if let Some(sort) = field.sort() {
+        xml_out.empty("table:data-pilot-sort-info")?;
+        match sort.mode() {
+            DataPilotSortMode::None => xml_out.attr("table:sort-mode", "none")?,
+            DataPilotSortMode::Manual => xml_out.attr("table:sort-mode", "manual")?,
+            DataPilotSortMode::Name => xml_out.attr("table:sort-mode", "name")?,
+            DataPilotSortMode::Data(data_field) => {
+                xml_out.attr("table:sort-mode", "data")?;
+                xml_out.attr_esc("table:data-field", data_field)?;
+            }
+        }
+        xml_out.attr(
+            "table:order",
+            match sort.order() {
+                SortOrder::Ascending => "ascending",
+                SortOrder::Descending => "descending",
+            },
+        )?;
+    }
+    for tag in field.level_extra() {
+        if tag.name() != "table:data-pilot-display-info" {
+            write_xmltag(tag, xml_out)?;
+        }
+    }
+    xml_out.end_elem("table:data-pilot-level")?;
 
-    write_content_validations(book, &mut xml_out)?;
-
-    for sheet in &book.sheets {
-        write_sheet(book, sheet, &mut xml_out)?;
+    for tag in field.extra() {
+        write_xmltag(tag, xml_out)?;
     }
 
-    // extra tags. pass through only
-    for tag in &book.extra {
-        if tag.name() == "table:named-expressions"
-            || tag.name() == "table:database-ranges"
-            || tag.name() == "table:data-pilot-tables"
-            || tag.name() == "table:consolidation"
-            || tag.name() == "table:dde-links"
-        {
-            write_xmltag(tag, &mut xml_out)?;
+    if let Some(groups) = field.groups() {
+        xml_out.elem("table:data-pilot-groups")?;
+        if groups.source_field_name().is_empty() {
+            xml_out.attr_esc("table:source-field-name", field.source_field_name())?;
+        } else {
+            xml_out.attr_esc("table:source-field-name", groups.source_field_name())?;
+        }
+        if let Some(grouped_by) = groups.grouped_by() {
+            xml_out.attr("table:grouped-by", grouped_by.as_str())?;
+        }
+        if groups.grouped_by().is_none() || groups.start().is_some() {
+            match groups.start() {
+                Some(v) => xml_out.attr("table:start", v.to_string())?,
+                None => xml_out.attr("table:start", "auto")?,
+            }
         }
+        if groups.grouped_by().is_none() || groups.end().is_some() {
+            match groups.end() {
+                Some(v) => xml_out.attr("table:end", v.to_string())?,
+                None => xml_out.attr("table:end", "auto")?,
+            }
+        }
+        if let Some(step) = groups.step() {
+            xml_out.attr("table:step", step.to_string())?;
+        }
+        for (k, v) in groups.attrmap().iter() {
+            xml_out.attr_esc(k.as_ref(), v.as_str())?;
+        }
+        for group in groups.groups() {
+            xml_out.elem("table:data-pilot-group")?;
+            xml_out.attr_esc("table:name", group.name())?;
+            for member in group.members() {
+                xml_out.empty("table:data-pilot-group-member")?;
+                xml_out.attr_esc("table:name", member)?;
+            }
+            xml_out.end_elem("table:data-pilot-group")?;
+        }
+        xml_out.end_elem("table:data-pilot-groups")?;
     }
 
-    xml_out.end_elem("office:spreadsheet")?;
-    xml_out.end_elem("office:body")?;
-    xml_out.end_elem("office:document-content")?;
+    xml_out.end_elem("table:data-pilot-field")?;
 
-    xml_out.close()?;
+    Ok(())
+}
 
+fn write_filter_item<W: Write>(
+    item: &FilterItem,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    match item {
+        FilterItem::And(items) => {
+            xml_out.elem("table:filter-and")?;
+            for item in items {
+                write_filter_item(item, xml_out)?;
+            }
+            xml_out.end_elem("table:filter-and")?;
+        }
+        FilterItem::Or(items) => {
+            xml_out.elem("table:filter-or")?;
+            for item in items {
+                write_filter_item(item, xml_out)?;
+            }
+            xml_out.end_elem("table:filter-or")?;
+        }
+        FilterItem::Condition(cond) => {
+            xml_out.elem("table:filter-condition")?;
+            xml_out.attr("table:field-number", cond.field().to_string())?;
+            xml_out.attr_esc("table:value", cond.value())?;
+            xml_out.attr_esc("table:operator", cond.operator().as_str())?;
+            xml_out.attr("table:data-type", cond.data_type().as_str())?;
+            if cond.case_sensitive() {
+                xml_out.attr("table:case-sensitive", "true")?;
+            }
+            for v in cond.set_items() {
+                xml_out.empty("table:filter-set-item")?;
+                xml_out.attr_esc("table:value", v)?;
+            }
+            xml_out.end_elem("table:filter-condition")?;
+        }
+    }
     Ok(())
 }
 
-fn write_content_validations<W: Write + Seek>(
+fn write_content_validations<W: Write>(
     book: &WorkBook,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if !book.validations.is_empty() {
         xml_out.elem("table:content-validations")?;
@@ -1186,42 +2793,16 @@ pub(crate) fn remove_outlooped(ranges: &mut Vec<CellRange>, row: u32, col: u32)
         .collect();
 }
 
-fn write_sheet<W: Write + Seek>(
+// With embed set, pictures from the FileBuf are written as office:binary-data.
+fn write_sheet<W: Write>(
     book: &WorkBook,
     sheet: &Sheet,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    embed: Option<&FileBuf>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
-    xml_out.elem("table:table")?;
-    xml_out.attr_esc("table:name", &*sheet.name)?;
-    if let Some(style) = &sheet.style {
-        xml_out.attr_esc("table:style-name", style.as_str())?;
-    }
-    if let Some(print_ranges) = &sheet.print_ranges {
-        xml_out.attr_esc("table:print-ranges", &cellranges_string(print_ranges))?;
-    }
-    if !sheet.print() {
-        xml_out.attr("table:print", "false")?;
-    }
-    if !sheet.display() {
-        xml_out.attr("table:display", "false")?;
-    }
-
     let max_cell = sheet.used_grid_size();
 
-    for tag in &sheet.extra {
-        if tag.name() == "table:title"
-            || tag.name() == "table:desc"
-            || tag.name() == "table:table-source"
-            || tag.name() == "office:dde-source"
-            || tag.name() == "table:scenario"
-            || tag.name() == "office:forms"
-            || tag.name() == "table:shapes"
-        {
-            write_xmltag(tag, xml_out)?;
-        }
-    }
-
-    write_table_columns(sheet, max_cell, xml_out)?;
+    write_sheet_start(sheet, max_cell.1, embed, xml_out)?;
 
     // list of current spans
     let mut spans = Vec::<CellRange>::new();
@@ -1301,7 +2882,7 @@ fn write_sheet<W: Write + Seek>(
         let (is_hidden, hidden_cols) = check_hidden(&spans, cur_row, cur_col);
 
         // And now to something completely different ...
-        write_cell(book, &cell, is_hidden, xml_out)?;
+        write_cell(book, &cell, is_hidden, embed.is_some(), xml_out)?;
 
         // There may be some blank cells until the next one, but only one less the forward.
         if forward_dc > 1 {
@@ -1332,21 +2913,346 @@ fn write_sheet<W: Write + Seek>(
         last_c = cur_col;
     }
 
-    xml_out.end_elem("table:table")?;
+    if !first_cell {
+        write_empty_rows_after(
+            sheet,
+            last_r.saturating_add(last_r_repeat),
+            max_cell,
+            xml_out,
+        )?;
+    }
+
+    write_sheet_end(sheet, xml_out)?;
+
+    Ok(())
+}
+
+// Writes the table tag and everything before the first row.
+fn write_sheet_start<W: Write>(
+    sheet: &Sheet,
+    max_cols: u32,
+    embed: Option<&FileBuf>,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("table:table")?;
+    xml_out.attr_esc("table:name", &*sheet.name)?;
+    if let Some(style) = &sheet.style {
+        xml_out.attr_esc("table:style-name", style.as_str())?;
+    }
+    if let Some(print_ranges) = &sheet.print_ranges {
+        xml_out.attr_esc("table:print-ranges", &cellranges_string(print_ranges))?;
+    }
+    if !sheet.print() {
+        xml_out.attr("table:print", "false")?;
+    }
+    if !sheet.display() {
+        xml_out.attr("table:display", "false")?;
+    }
+    if let Some(protection) = &sheet.protection {
+        xml_out.attr("table:protected", "true")?;
+        if protection.has_password() {
+            xml_out.attr_esc("table:protection-key", protection.key())?;
+        }
+        if let Some(digest_algorithm) = protection.digest_algorithm() {
+            xml_out.attr_esc(
+                "table:protection-key-digest-algorithm",
+                digest_algorithm.as_str(),
+            )?;
+        }
+    }
+
+    for tag in &sheet.extra {
+        if tag.name() == "table:title" || tag.name() == "table:desc" {
+            if let Some(filebuf) = embed {
+                write_xmltag_embedded(tag, filebuf, xml_out)?;
+            } else {
+                write_xmltag(tag, xml_out)?;
+            }
+        }
+    }
+
+    if let Some(protection) = &sheet.protection {
+        write_table_protection(protection.options(), xml_out)?;
+    }
+
+    for tag in &sheet.extra {
+        if tag.name() == "table:table-source" || tag.name() == "office:dde-source" {
+            if let Some(filebuf) = embed {
+                write_xmltag_embedded(tag, filebuf, xml_out)?;
+            } else {
+                write_xmltag(tag, xml_out)?;
+            }
+        }
+    }
+
+    if let Some(scenario) = &sheet.scenario {
+        write_scenario(scenario, xml_out)?;
+    }
+
+    for tag in &sheet.extra {
+        if tag.name() == "office:forms" {
+            if let Some(filebuf) = embed {
+                write_xmltag_embedded(tag, filebuf, xml_out)?;
+            } else {
+                write_xmltag(tag, xml_out)?;
+            }
+        }
+    }
+
+    write_shapes(sheet, embed, xml_out)?;
+
+    write_table_columns(sheet, max_cols, xml_out)?;
+
+    Ok(())
+}
+
+// Writes table:shapes with the images anchored to the page, followed by
+// all the other shapes as they were read.
+fn write_shapes<W: Write>(
+    sheet: &Sheet,
+    embed: Option<&FileBuf>,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let shapes: Vec<_> = sheet
+        .extra
+        .iter()
+        .filter(|v| v.name() == "table:shapes")
+        .collect();
+    if sheet.page_images.is_empty() && shapes.is_empty() {
+        return Ok(());
+    }
+
+    xml_out.elem("table:shapes")?;
+    for image in &sheet.page_images {
+        write_image(image, embed.is_some(), xml_out)?;
+    }
+    for tag in shapes {
+        for c in tag.content() {
+            match c {
+                XmlContent::Text(t) => {
+                    xml_out.text_esc(t)?;
+                }
+                XmlContent::Tag(t) => {
+                    if let Some(filebuf) = embed {
+                        write_xmltag_embedded(t, filebuf, xml_out)?;
+                    } else {
+                        write_xmltag(t, xml_out)?;
+                    }
+                }
+            }
+        }
+    }
+    xml_out.end_elem("table:shapes")?;
+
+    Ok(())
+}
+
+// Writes table:scenario. The flags are only written if they differ from
+// the default.
+fn write_scenario<W: Write>(
+    scenario: &Scenario,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.empty("table:scenario")?;
+    if !scenario.display_border() {
+        xml_out.attr("table:display-border", "false")?;
+    }
+    if let Some(color) = scenario.border_color() {
+        xml_out.attr("table:border-color", color_string(color))?;
+    }
+    if !scenario.copy_back() {
+        xml_out.attr("table:copy-back", "false")?;
+    }
+    if !scenario.copy_styles() {
+        xml_out.attr("table:copy-styles", "false")?;
+    }
+    if !scenario.copy_formulas() {
+        xml_out.attr("table:copy-formulas", "false")?;
+    }
+    xml_out.attr("table:is-active", scenario.active().to_string())?;
+    xml_out.attr_esc(
+        "table:scenario-ranges",
+        cellranges_string(scenario.ranges()),
+    )?;
+    if !scenario.comment().is_empty() {
+        xml_out.attr_esc("table:comment", scenario.comment())?;
+    }
+    if scenario.protected() {
+        xml_out.attr("table:protected", "true")?;
+    }
+    Ok(())
+}
+
+// Writes the LibreOffice extension loext:table-protection.
+// Only the allowed actions are written, as LibreOffice does.
+fn write_table_protection<W: Write>(
+    options: &ProtectionOptions,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.empty("loext:table-protection")?;
+    if options.select_protected_cells() {
+        xml_out.attr("loext:select-protected-cells", "true")?;
+    }
+    if options.select_unprotected_cells() {
+        xml_out.attr("loext:select-unprotected-cells", "true")?;
+    }
+    if options.insert_columns() {
+        xml_out.attr("loext:insert-columns", "true")?;
+    }
+    if options.insert_rows() {
+        xml_out.attr("loext:insert-rows", "true")?;
+    }
+    if options.delete_columns() {
+        xml_out.attr("loext:delete-columns", "true")?;
+    }
+    if options.delete_rows() {
+        xml_out.attr("loext:delete-rows", "true")?;
+    }
+    Ok(())
+}
+
+// Ends the table.
+fn write_sheet_end<W: Write>(sheet: &Sheet, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+    write_named_expressions(&sheet.named_ranges, &sheet.named_expressions, xml_out)?;
+    write_conditional_formats(&sheet.conditional_formats, xml_out)?;
+
+    xml_out.end_elem("table:table")?;
+
+    Ok(())
+}
+
+fn write_conditional_formats<W: Write>(
+    formats: &[ConditionalFormat],
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    if formats.is_empty() {
+        return Ok(());
+    }
+
+    xml_out.elem("calcext:conditional-formats")?;
+    for cf in formats {
+        xml_out.elem("calcext:conditional-format")?;
+        xml_out.attr_esc(
+            "calcext:target-range-address",
+            cellranges_string(cf.ranges()),
+        )?;
+        for entry in cf.entries() {
+            match entry {
+                ConditionalEntry::Condition(rule) => {
+                    xml_out.empty("calcext:condition")?;
+                    xml_out.attr_esc("calcext:apply-style-name", rule.style())?;
+                    xml_out.attr_esc("calcext:value", rule.condition().to_string())?;
+                    if let Some(base_cell) = rule.base_cell() {
+                        xml_out.attr_esc("calcext:base-cell-address", base_cell.to_string())?;
+                    }
+                }
+                ConditionalEntry::ColorScale(scale) => {
+                    xml_out.elem("calcext:color-scale")?;
+                    for (entry, color) in scale.entries() {
+                        xml_out.empty("calcext:color-scale-entry")?;
+                        xml_out.attr_esc("calcext:value", entry.value())?;
+                        xml_out.attr("calcext:type", entry.entry_type().as_str())?;
+                        xml_out.attr("calcext:color", color_string(*color))?;
+                    }
+                    xml_out.end_elem("calcext:color-scale")?;
+                }
+                ConditionalEntry::DataBar(bar) => {
+                    xml_out.elem("calcext:data-bar")?;
+                    xml_out.attr("calcext:positive-color", color_string(bar.positive_color()))?;
+                    if let Some(color) = bar.negative_color() {
+                        xml_out.attr("calcext:negative-color", color_string(color))?;
+                    }
+                    if let Some(color) = bar.axis_color() {
+                        xml_out.attr("calcext:axis-color", color_string(color))?;
+                    }
+                    xml_out.attr("calcext:gradient", bar.gradient().to_string())?;
+                    xml_out.attr("calcext:show-value", bar.show_value().to_string())?;
+                    for (k, v) in bar.attrmap().iter() {
+                        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+                    }
+                    write_format_entry(bar.min(), xml_out)?;
+                    write_format_entry(bar.max(), xml_out)?;
+                    xml_out.end_elem("calcext:data-bar")?;
+                }
+                ConditionalEntry::IconSet(icons) => {
+                    xml_out.elem("calcext:icon-set")?;
+                    xml_out.attr_esc("calcext:icon-set-type", icons.icon_set_type())?;
+                    xml_out.attr("calcext:show-value", icons.show_value().to_string())?;
+                    for entry in icons.entries() {
+                        write_format_entry(entry, xml_out)?;
+                    }
+                    xml_out.end_elem("calcext:icon-set")?;
+                }
+                ConditionalEntry::DateIs(date_is) => {
+                    xml_out.empty("calcext:date-is")?;
+                    xml_out.attr("calcext:date", date_is.date().as_str())?;
+                    xml_out.attr_esc("calcext:style", date_is.style())?;
+                }
+            }
+        }
+        xml_out.end_elem("calcext:conditional-format")?;
+    }
+    xml_out.end_elem("calcext:conditional-formats")?;
+
+    Ok(())
+}
+
+fn write_format_entry<W: Write>(
+    entry: &FormatEntry,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.empty("calcext:formatting-entry")?;
+    xml_out.attr_esc("calcext:value", entry.value())?;
+    xml_out.attr("calcext:type", entry.entry_type().as_str())?;
+    Ok(())
+}
+
+// Named ranges and expressions, sorted by name.
+fn write_named_expressions<W: Write>(
+    ranges: &HashMap<String, NamedRange>,
+    exprs: &HashMap<String, NamedExpression>,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    if ranges.is_empty() && exprs.is_empty() {
+        return Ok(());
+    }
+
+    xml_out.elem("table:named-expressions")?;
 
-    for tag in &sheet.extra {
-        if tag.name() == "table:named-expressions" || tag.name() == "calcext:conditional-formats" {
-            write_xmltag(tag, xml_out)?;
+    let mut ranges: Vec<_> = ranges.values().collect();
+    ranges.sort_by(|a, b| a.name().cmp(b.name()));
+    for range in ranges {
+        xml_out.empty("table:named-range")?;
+        xml_out.attr_esc("table:name", range.name())?;
+        if let Some(base_cell) = range.base_cell() {
+            xml_out.attr_esc("table:base-cell-address", base_cell.to_string())?;
+        }
+        xml_out.attr_esc("table:cell-range-address", range.range().to_string())?;
+        if let Some(usable_as) = range.usable_as() {
+            xml_out.attr_esc("table:range-usable-as", usable_as)?;
         }
     }
 
+    let mut exprs: Vec<_> = exprs.values().collect();
+    exprs.sort_by(|a, b| a.name().cmp(b.name()));
+    for expr in exprs {
+        xml_out.empty("table:named-expression")?;
+        xml_out.attr_esc("table:name", expr.name())?;
+        if let Some(base_cell) = expr.base_cell() {
+            xml_out.attr_esc("table:base-cell-address", base_cell.to_string())?;
+        }
+        xml_out.attr_esc("table:expression", expr.expression())?;
+    }
+
+    xml_out.end_elem("table:named-expressions")?;
+
     Ok(())
 }
 
-fn write_empty_cells<W: Write + Seek>(
+fn write_empty_cells<W: Write>(
     mut forward_dc: u32,
     hidden_cols: u32,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     // split between hidden and regular cells.
     if hidden_cols >= forward_dc {
@@ -1372,18 +3278,14 @@ fn write_empty_cells<W: Write + Seek>(
     Ok(())
 }
 
-fn write_start_current_row<W: Write + Seek>(
+fn write_start_current_row<W: Write>(
     sheet: &Sheet,
     cur_row: u32,
     backward_dc: u32,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
-    // Start of headers
-    if let Some(header_rows) = &sheet.header_rows {
-        if header_rows.row() == cur_row {
-            xml_out.elem("table:table-header-rows")?;
-        }
-    }
+    // Start of headers and groups
+    write_rows_start(sheet, cur_row, xml_out)?;
 
     xml_out.elem("table:table-row")?;
     if let Some(row_header) = sheet.row_header.get(&cur_row) {
@@ -1414,111 +3316,162 @@ fn write_start_current_row<W: Write + Seek>(
     Ok(())
 }
 
-fn write_end_last_row<W: Write + Seek>(
+fn write_end_last_row<W: Write>(
     sheet: &Sheet,
     cur_row: u32,
     backward_dr: u32,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.end_elem("table:table-row")?;
 
-    // This row was the end of the header.
-    if let Some(header_rows) = &sheet.header_rows {
-        let last_row = cur_row - backward_dr;
-        if header_rows.to_row() == last_row {
-            xml_out.end_elem("table:table-header-rows")?;
-        }
-    }
+    // This row was the end of the header or a group.
+    write_rows_end(sheet, last_row_end(sheet, cur_row - backward_dr), xml_out)?;
 
     Ok(())
 }
 
-fn write_end_current_row<W: Write + Seek>(
+fn write_end_current_row<W: Write>(
     sheet: &Sheet,
     cur_row: u32,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.end_elem("table:table-row")?;
 
-    // This row was the end of the header.
+    // This row was the end of the header or a group.
+    write_rows_end(sheet, last_row_end(sheet, cur_row), xml_out)?;
+
+    Ok(())
+}
+
+// Last row covered by a row with a repeat count.
+fn last_row_end(sheet: &Sheet, row: u32) -> u32 {
+    if let Some(row_header) = sheet.row_header.get(&row) {
+        row + row_header.repeat - 1
+    } else {
+        row
+    }
+}
+
+// Starts all groups and the header rows that begin with this row.
+fn write_rows_start<W: Write>(
+    sheet: &Sheet,
+    row: u32,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    // outer groups come first.
+    for group in sheet.group_rows.iter().filter(|v| v.from() == row) {
+        xml_out.elem("table:table-row-group")?;
+        if !group.display() {
+            xml_out.attr("table:display", "false")?;
+        }
+    }
+    if let Some(header_rows) = &sheet.header_rows {
+        if header_rows.row() == row {
+            xml_out.elem("table:table-header-rows")?;
+        }
+    }
+    Ok(())
+}
+
+// Ends the header rows and all groups that end with this row.
+fn write_rows_end<W: Write>(
+    sheet: &Sheet,
+    row: u32,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
     if let Some(header_rows) = &sheet.header_rows {
-        if header_rows.to_row() == cur_row {
+        if header_rows.to_row() == row {
             xml_out.end_elem("table:table-header-rows")?;
         }
     }
-
+    // inner groups end first.
+    for _ in sheet.group_rows.iter().filter(|v| v.to() == row) {
+        xml_out.end_elem("table:table-row-group")?;
+    }
     Ok(())
 }
 
-fn write_empty_rows_before<W: Write + Seek>(
+// The next row after row, where a header or group starts, or
+// after the end of one. At most until.
+fn next_rows_boundary(sheet: &Sheet, row: u32, until: u32) -> u32 {
+    let mut next = until;
+    let mut check = |v: u32| {
+        if v > row && v < next {
+            next = v;
+        }
+    };
+    if let Some(header_rows) = &sheet.header_rows {
+        check(header_rows.row());
+        check(header_rows.to_row().saturating_add(1));
+    }
+    for group in &sheet.group_rows {
+        check(group.from());
+        check(group.to().saturating_add(1));
+    }
+    next
+}
+
+fn write_empty_rows_before<W: Write>(
     sheet: &Sheet,
     cur_row: u32,
     first_cell: bool,
-    mut backward_dr: u32,
+    backward_dr: u32,
     max_cell: (u32, u32),
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     // Empty rows in between are 1 less than the delta, except at the very start.
-    #[allow(clippy::bool_to_int_with_if)]
-    let mut corr = if first_cell { 0u32 } else { 1u32 };
-
-    // Only deltas greater 1 are relevant.
-    // Or if this is the very start.
-    if backward_dr > 1 || first_cell && backward_dr > 0 {
-        // split up the empty rows, if there is some header stuff.
-        if let Some(header_rows) = &sheet.header_rows {
-            // What was the last_row? Was there a header start since?
-            let last_row = cur_row - backward_dr;
-            if header_rows.row() < cur_row && header_rows.row() > last_row {
-                write_empty_row(
-                    sheet,
-                    last_row,
-                    header_rows.row() - last_row - corr,
-                    max_cell,
-                    xml_out,
-                )?;
-                xml_out.elem("table:table-header-rows")?;
-                // Don't write the empty line for the first header-row, we can
-                // collapse it with the rest. corr suits fine for this.
-                corr = 0;
-                // We correct the empty line count.
-                backward_dr = cur_row - header_rows.row();
-            }
-
-            // What was the last row here? Was there a header end since?
-            let last_row = cur_row - backward_dr;
-            if header_rows.to_row() < cur_row && header_rows.to_row() > cur_row - backward_dr {
-                // Empty lines, including the current line that marks
-                // the end of the header.
-                write_empty_row(
-                    sheet,
-                    last_row,
-                    header_rows.to_row() - last_row - corr + 1,
-                    max_cell,
-                    xml_out,
-                )?;
-                xml_out.end_elem("table:table-header-rows")?;
-                // Correction for table start is no longer needed.
-                corr = 1;
-                // We correct the empty line count.
-                backward_dr = cur_row - header_rows.to_row();
-            }
-        }
+    let first_row = if first_cell {
+        cur_row - backward_dr
+    } else {
+        cur_row - backward_dr + 1
+    };
+    write_empty_rows(sheet, first_row, cur_row, max_cell, xml_out)
+}
 
-        // Write out the empty lines.
-        let last_row = cur_row - backward_dr;
-        write_empty_row(sheet, last_row, backward_dr - corr, max_cell, xml_out)?;
+// Writes empty rows up to the end of any header or group that
+// extends beyond the last written row.
+fn write_empty_rows_after<W: Write>(
+    sheet: &Sheet,
+    next_row: u32,
+    max_cell: (u32, u32),
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let mut until = next_row;
+    if let Some(header_rows) = &sheet.header_rows {
+        until = until.max(header_rows.to_row().saturating_add(1));
     }
+    for group in &sheet.group_rows {
+        until = until.max(group.to().saturating_add(1));
+    }
+    write_empty_rows(sheet, next_row, until, max_cell, xml_out)
+}
 
+// Writes the empty rows from first_row up to, but excluding, until.
+// The rows are split where a header or a group starts or ends.
+fn write_empty_rows<W: Write>(
+    sheet: &Sheet,
+    first_row: u32,
+    until: u32,
+    max_cell: (u32, u32),
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let mut row = first_row;
+    while row < until {
+        let next = next_rows_boundary(sheet, row, until);
+        write_rows_start(sheet, row, xml_out)?;
+        write_empty_row(sheet, row, next - row, max_cell, xml_out)?;
+        write_rows_end(sheet, next - 1, xml_out)?;
+        row = next;
+    }
     Ok(())
 }
 
-fn write_empty_row<W: Write + Seek>(
+fn write_empty_row<W: Write>(
     sheet: &Sheet,
     cur_row: u32,
     empty_count: u32,
     max_cell: (u32, u32),
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     xml_out.elem("table:table-row")?;
     xml_out.attr("table:number-rows-repeated", &empty_count.to_string())?;
@@ -1547,10 +3500,7 @@ fn write_empty_row<W: Write + Seek>(
     Ok(())
 }
 
-fn write_xmltag<W: Write + Seek>(
-    x: &XmlTag,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
+fn write_xmltag<W: Write>(x: &XmlTag, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     if x.is_empty() {
         xml_out.empty(x.name())?;
     } else {
@@ -1578,13 +3528,79 @@ fn write_xmltag<W: Write + Seek>(
     Ok(())
 }
 
-fn write_table_columns<W: Write + Seek>(
+// Same as write_xmltag, but any draw:image that references a file in the
+// FileBuf gets the data inlined as office:binary-data.
+fn write_xmltag_embedded<W: Write>(
+    x: &XmlTag,
+    filebuf: &FileBuf,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    let data = if x.name() == "draw:image" {
+        x.attrmap()
+            .attr("xlink:href")
+            .and_then(|href| filebuf.get_file(href))
+    } else {
+        None
+    };
+
+    if x.is_empty() && data.is_none() {
+        xml_out.empty(x.name())?;
+    } else {
+        xml_out.elem(x.name())?;
+    }
+    for (k, v) in x.attrmap().iter() {
+        if data.is_some() && k.as_ref().starts_with("xlink:") {
+            continue;
+        }
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
+
+    if let Some(data) = data {
+        xml_out.elem_text("office:binary-data", STANDARD.encode(data))?;
+    }
+
+    for c in x.content() {
+        match c {
+            XmlContent::Text(t) => {
+                xml_out.text_esc(t)?;
+            }
+            XmlContent::Tag(t) => {
+                write_xmltag_embedded(t, filebuf, xml_out)?;
+            }
+        }
+    }
+
+    if !x.is_empty() || data.is_some() {
+        xml_out.end_elem(x.name())?;
+    }
+
+    Ok(())
+}
+
+fn write_table_columns<W: Write>(
     sheet: &Sheet,
-    max_cell: (u32, u32),
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    max_cols: u32,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
+    // Groups and header may extend beyond the used columns.
+    let mut max_cols = max_cols;
+    if let Some(header_cols) = &sheet.header_cols {
+        max_cols = max_cols.max(header_cols.to_col().saturating_add(1));
+    }
+    for group in &sheet.group_cols {
+        max_cols = max_cols.max(group.to().saturating_add(1));
+    }
+
     // table:table-column
-    for c in 0..max_cell.1 {
+    for c in 0..max_cols {
+        // outer groups come first.
+        for group in sheet.group_cols.iter().filter(|v| v.from() == c) {
+            xml_out.elem("table:table-column-group")?;
+            if !group.display() {
+                xml_out.attr("table:display", "false")?;
+            }
+        }
+
         // markup header columns
         if let Some(header_cols) = &sheet.header_cols {
             if header_cols.col() == c {
@@ -1614,17 +3630,23 @@ fn write_table_columns<W: Write + Seek>(
                 xml_out.end_elem("table:table-header-columns")?;
             }
         }
+
+        // inner groups end first.
+        for _ in sheet.group_cols.iter().filter(|v| v.to() == c) {
+            xml_out.end_elem("table:table-column-group")?;
+        }
     }
 
     Ok(())
 }
 
 #[allow(clippy::single_char_add_str)]
-fn write_cell<W: Write + Seek>(
+fn write_cell<W: Write>(
     book: &WorkBook,
     cell: &CellContentRef<'_>,
     is_hidden: bool,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    embed: bool,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     let tag = if is_hidden {
         "table:covered-table-cell"
@@ -1632,9 +3654,15 @@ fn write_cell<W: Write + Seek>(
         "table:table-cell"
     };
 
-    match cell.value {
-        None | Some(Value::Empty) => xml_out.empty(tag)?,
-        _ => xml_out.elem(tag)?,
+    // Annotations, images and charts are content too.
+    let is_empty = matches!(cell.value, None | Some(Value::Empty))
+        && cell.annotation.is_none()
+        && cell.images().is_empty()
+        && cell.charts().is_empty();
+    if is_empty {
+        xml_out.empty(tag)?;
+    } else {
+        xml_out.elem(tag)?;
     }
 
     if let Some(formula) = cell.formula {
@@ -1681,97 +3709,382 @@ fn write_cell<W: Write + Seek>(
     //     None
     // };
 
-    match cell.value {
-        None | Some(Value::Empty) => {}
-        Some(Value::Text(s)) => {
+    if let Some(value) = cell.value {
+        write_value_attr(value, xml_out)?;
+    }
+
+    // The annotation must precede the text.
+    if let Some(annotation) = cell.annotation {
+        write_annotation(annotation, xml_out)?;
+    }
+    for image in cell.images() {
+        write_image(image, embed, xml_out)?;
+    }
+    for chart in cell.charts() {
+        write_chart(book, chart, embed, xml_out)?;
+    }
+
+    if let Some(value) = cell.value {
+        write_value_text(value, xml_out)?;
+    }
+
+    if !is_empty {
+        xml_out.end_elem(tag)?;
+    }
+
+    Ok(())
+}
+
+// Writes office:value-type and the value attribute.
+fn write_value_attr<W: Write>(value: &Value, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+    match value {
+        Value::Empty => {}
+        Value::Text(_) | Value::TextXml(_) => {
             xml_out.attr("office:value-type", "string")?;
+        }
+        Value::DateTime(d) => {
+            xml_out.attr("office:value-type", "date")?;
+            xml_out.attr("office:date-value", datetime_value(d).as_str())?;
+        }
+        Value::TimeDuration(d) => {
+            xml_out.attr("office:value-type", "time")?;
+            xml_out.attr("office:time-value", duration_value(d).as_str())?;
+        }
+        Value::Boolean(b) => {
+            xml_out.attr("office:value-type", "boolean")?;
+            xml_out.attr("office:boolean-value", if *b { "true" } else { "false" })?;
+        }
+        Value::Currency(v, c) => {
+            xml_out.attr("office:value-type", "currency")?;
+            xml_out.attr_esc("office:currency", c)?;
+            xml_out.attr("office:value", v.to_string().as_str())?;
+        }
+        Value::Number(v) => {
+            xml_out.attr("office:value-type", "float")?;
+            xml_out.attr("office:value", v.to_string().as_str())?;
+        }
+        Value::Percentage(v) => {
+            xml_out.attr("office:value-type", "percentage")?;
+            xml_out.attr("office:value", v.to_string().as_str())?;
+        }
+    }
+    Ok(())
+}
+
+// Writes the value as text:p.
+fn write_value_text<W: Write>(value: &Value, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+    match value {
+        Value::Empty => {}
+        Value::Text(s) => {
             for l in s.split('\n') {
                 xml_out.elem("text:p")?;
                 xml_out.text_esc(l)?;
                 xml_out.end_elem("text:p")?;
             }
         }
-        Some(Value::TextXml(t)) => {
-            xml_out.attr("office:value-type", "string")?;
+        Value::TextXml(t) => {
             for tt in t.iter() {
                 write_xmltag(tt, xml_out)?;
             }
         }
-        Some(Value::DateTime(d)) => {
-            xml_out.attr("office:value-type", "date")?;
-            let value = d.format("%Y-%m-%dT%H:%M:%S%.f").to_string();
-            xml_out.attr("office:date-value", value.as_str())?;
+        Value::DateTime(d) => {
             xml_out.elem("text:p")?;
-            xml_out.text_esc(value)?;
+            xml_out.text_esc(datetime_value(d))?;
             xml_out.end_elem("text:p")?;
         }
-        Some(Value::TimeDuration(d)) => {
-            xml_out.attr("office:value-type", "time")?;
-
-            let mut value = String::from("PT");
-            value.push_str(&d.num_hours().to_string());
-            value.push_str("H");
-            value.push_str(&(d.num_minutes() % 60).to_string());
-            value.push_str("M");
-            value.push_str(&(d.num_seconds() % 60).to_string());
-            value.push_str(".");
-            value.push_str(&(d.num_milliseconds() % 1000).to_string());
-            value.push_str("S");
-
-            xml_out.attr("office:time-value", value.as_str())?;
-
+        Value::TimeDuration(d) => {
             xml_out.elem("text:p")?;
-            xml_out.text(value)?;
+            xml_out.text(duration_value(d))?;
             xml_out.end_elem("text:p")?;
         }
-        Some(Value::Boolean(b)) => {
-            xml_out.attr("office:value-type", "boolean")?;
-            xml_out.attr("office:boolean-value", if *b { "true" } else { "false" })?;
+        Value::Boolean(b) => {
             xml_out.elem("text:p")?;
             xml_out.text(if *b { "true" } else { "false" })?;
             xml_out.end_elem("text:p")?;
         }
-        Some(Value::Currency(v, c)) => {
-            xml_out.attr("office:value-type", "currency")?;
-            xml_out.attr_esc("office:currency", c)?;
-            let value = v.to_string();
-            xml_out.attr("office:value", value.as_str())?;
+        Value::Currency(v, c) => {
             xml_out.elem("text:p")?;
             xml_out.text(c)?;
             xml_out.text(" ")?;
-            xml_out.text(value)?;
+            xml_out.text(v.to_string())?;
             xml_out.end_elem("text:p")?;
         }
-        Some(Value::Number(v)) => {
-            xml_out.attr("office:value-type", "float")?;
-            let value = v.to_string();
-            xml_out.attr("office:value", value.as_str())?;
+        Value::Number(v) | Value::Percentage(v) => {
             xml_out.elem("text:p")?;
-            xml_out.text(value)?;
+            xml_out.text(v.to_string())?;
             xml_out.end_elem("text:p")?;
         }
-        Some(Value::Percentage(v)) => {
-            xml_out.attr("office:value-type", "percentage")?;
-            let value = v.to_string();
-            xml_out.attr("office:value", value.as_str())?;
-            xml_out.elem("text:p")?;
-            xml_out.text(value)?;
-            xml_out.end_elem("text:p")?;
+    }
+    Ok(())
+}
+
+fn datetime_value(d: &NaiveDateTime) -> String {
+    d.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+fn duration_value(d: &Duration) -> String {
+    let mut value = String::from("PT");
+    value.push_str(&d.num_hours().to_string());
+    value.push('H');
+    value.push_str(&(d.num_minutes() % 60).to_string());
+    value.push('M');
+    value.push_str(&(d.num_seconds() % 60).to_string());
+    value.push('.');
+    value.push_str(&(d.num_milliseconds() % 1000).to_string());
+    value.push('S');
+    value
+}
+
+// Writes the draw:frame for an image. The data is either embedded as
+// office:binary-data or written to the package separately.
+fn write_image<W: Write>(
+    image: &Image,
+    embed: bool,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("draw:frame")?;
+    if let Some(name) = image.name() {
+        xml_out.attr_esc("draw:name", name.as_str())?;
+    }
+    if let Some(style) = image.style() {
+        xml_out.attr_esc("draw:style-name", style.as_str())?;
+    }
+    let (x, y) = image.position();
+    xml_out.attr("svg:x", x.to_string())?;
+    xml_out.attr("svg:y", y.to_string())?;
+    let (width, height) = image.size();
+    if width != Length::Default {
+        xml_out.attr("svg:width", width.to_string())?;
+    }
+    if height != Length::Default {
+        xml_out.attr("svg:height", height.to_string())?;
+    }
+    for (k, v) in image.attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
+
+    xml_out.elem("draw:image")?;
+    if embed {
+        xml_out.attr_esc("draw:mime-type", image_mime_type(image))?;
+        xml_out.elem("office:binary-data")?;
+        xml_out.text(STANDARD.encode(image.data()))?;
+        xml_out.end_elem("office:binary-data")?;
+    } else {
+        xml_out.attr_esc("xlink:href", image.href())?;
+        xml_out.attr("xlink:type", "simple")?;
+        xml_out.attr("xlink:show", "embed")?;
+        xml_out.attr("xlink:actuate", "onLoad")?;
+        xml_out.attr_esc("draw:mime-type", image_mime_type(image))?;
+    }
+    xml_out.end_elem("draw:image")?;
+
+    if let Some(title) = image.title() {
+        xml_out.elem("svg:title")?;
+        xml_out.text_esc(title)?;
+        xml_out.end_elem("svg:title")?;
+    }
+    if let Some(desc) = image.desc() {
+        xml_out.elem("svg:desc")?;
+        xml_out.text_esc(desc)?;
+        xml_out.end_elem("svg:desc")?;
+    }
+
+    xml_out.end_elem("draw:frame")?;
+
+    Ok(())
+}
+
+// Writes the draw:frame for a chart. The chart document is either
+// embedded or written to the package separately.
+fn write_chart<W: Write>(
+    book: &WorkBook,
+    chart: &Chart,
+    embed: bool,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("draw:frame")?;
+    if let Some(name) = chart.name() {
+        xml_out.attr_esc("draw:name", name.as_str())?;
+    }
+    let (x, y) = chart.position();
+    xml_out.attr("svg:x", x.to_string())?;
+    xml_out.attr("svg:y", y.to_string())?;
+    let (width, height) = chart.size();
+    if width != Length::Default {
+        xml_out.attr("svg:width", width.to_string())?;
+    }
+    if height != Length::Default {
+        xml_out.attr("svg:height", height.to_string())?;
+    }
+    for (k, v) in chart.attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
+    }
+
+    xml_out.elem("draw:object")?;
+    let ranges: Vec<_> = chart.ranges().iter().map(chart_range).collect();
+    xml_out.attr_esc(
+        "draw:notify-on-update-of-ranges",
+        cellranges_string(&ranges),
+    )?;
+    if embed {
+        xml_out.elem("office:document")?;
+        xml_out.attr(
+            "office:mimetype",
+            "application/vnd.oasis.opendocument.chart",
+        )?;
+        xml_out.attr("office:version", book.version())?;
+        xml_out.elem("office:body")?;
+        xml_out.elem("office:chart")?;
+        write_chart_body(chart, xml_out)?;
+        xml_out.end_elem("office:chart")?;
+        xml_out.end_elem("office:body")?;
+        xml_out.end_elem("office:document")?;
+    } else if let Some(href) = chart.href() {
+        xml_out.attr_esc("xlink:href", format!("./{}", href))?;
+        xml_out.attr("xlink:type", "simple")?;
+        xml_out.attr("xlink:show", "embed")?;
+        xml_out.attr("xlink:actuate", "onLoad")?;
+    }
+    xml_out.end_elem("draw:object")?;
+
+    xml_out.end_elem("draw:frame")?;
+
+    Ok(())
+}
+
+// Writes the chart:chart element of a chart document.
+fn write_chart_body<W: Write>(chart: &Chart, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+    xml_out.elem("chart:chart")?;
+    let (width, height) = chart.size();
+    if width != Length::Default {
+        xml_out.attr("svg:width", width.to_string())?;
+    }
+    if height != Length::Default {
+        xml_out.attr("svg:height", height.to_string())?;
+    }
+    xml_out.attr("chart:class", chart.chart_type().as_class())?;
+
+    if let Some(title) = chart.title() {
+        write_chart_title(title, xml_out)?;
+    }
+    if let Some(legend) = chart.legend() {
+        xml_out.empty("chart:legend")?;
+        xml_out.attr("chart:legend-position", legend.as_str())?;
+    }
+
+    xml_out.elem("chart:plot-area")?;
+    let ranges: Vec<_> = chart.ranges().iter().map(chart_range).collect();
+    if !ranges.is_empty() {
+        xml_out.attr_esc("table:cell-range-address", cellranges_string(&ranges))?;
+    }
+
+    // A pie has no axis, but the categories are still needed.
+    let is_pie = chart.chart_type() == ChartType::Pie;
+    if !is_pie || chart.categories().is_some() {
+        xml_out.elem("chart:axis")?;
+        xml_out.attr("chart:dimension", "x")?;
+        xml_out.attr("chart:name", "primary-x")?;
+        if let Some(title) = chart.x_axis_title() {
+            write_chart_title(title, xml_out)?;
         }
+        if let Some(categories) = chart.categories() {
+            xml_out.empty("chart:categories")?;
+            xml_out.attr_esc(
+                "table:cell-range-address",
+                chart_range(categories).to_string(),
+            )?;
+        }
+        xml_out.end_elem("chart:axis")?;
+    }
+    if !is_pie {
+        xml_out.elem("chart:axis")?;
+        xml_out.attr("chart:dimension", "y")?;
+        xml_out.attr("chart:name", "primary-y")?;
+        if let Some(title) = chart.y_axis_title() {
+            write_chart_title(title, xml_out)?;
+        }
+        xml_out.end_elem("chart:axis")?;
+    }
+
+    for series in chart.series() {
+        xml_out.elem("chart:series")?;
+        xml_out.attr_esc(
+            "chart:values-cell-range-address",
+            chart_range(series.values()).to_string(),
+        )?;
+        if let Some(label) = series.label() {
+            xml_out.attr_esc("chart:label-cell-address", label.to_string())?;
+        }
+        xml_out.attr("chart:class", chart.chart_type().as_class())?;
+        if let Some(domain) = series.domain() {
+            xml_out.empty("chart:domain")?;
+            xml_out.attr_esc("table:cell-range-address", chart_range(domain).to_string())?;
+        }
+        xml_out.end_elem("chart:series")?;
+    }
+
+    xml_out.end_elem("chart:plot-area")?;
+    xml_out.end_elem("chart:chart")?;
+
+    Ok(())
+}
+
+fn write_chart_title<W: Write>(title: &str, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
+    xml_out.elem("chart:title")?;
+    xml_out.elem("text:p")?;
+    xml_out.text_esc(title)?;
+    xml_out.end_elem("text:p")?;
+    xml_out.end_elem("chart:title")?;
+    Ok(())
+}
+
+// Charts need the sheet name for both ends of a range.
+fn chart_range(range: &CellRange) -> CellRange {
+    let mut range = range.clone();
+    if range.to_table().is_none() {
+        if let Some(table) = range.table().cloned() {
+            range.set_to_table(table);
+        }
+    }
+    range
+}
+
+fn write_annotation<W: Write>(
+    annotation: &Annotation,
+    xml_out: &mut XmlWriter<W>,
+) -> Result<(), OdsError> {
+    xml_out.elem("office:annotation")?;
+    if let Some(name) = annotation.name() {
+        xml_out.attr_esc("office:name", name.as_str())?;
+    }
+    xml_out.attr("office:display", annotation.display().to_string())?;
+    for (k, v) in annotation.attrmap().iter() {
+        xml_out.attr_esc(k.as_ref(), v.as_str())?;
     }
 
-    match cell.value {
-        None | Some(Value::Empty) => {}
-        _ => xml_out.end_elem(tag)?,
+    if let Some(creator) = annotation.creator() {
+        xml_out.elem("dc:creator")?;
+        xml_out.text_esc(creator)?;
+        xml_out.end_elem("dc:creator")?;
+    }
+    if let Some(date) = annotation.date() {
+        xml_out.elem("dc:date")?;
+        xml_out.text_esc(datetime_value(date))?;
+        xml_out.end_elem("dc:date")?;
+    }
+    for t in annotation.text() {
+        write_xmltag(t, xml_out)?;
     }
 
+    xml_out.end_elem("office:annotation")?;
+
     Ok(())
 }
 
-fn write_font_decl<W: Write + Seek>(
+fn write_font_decl<W: Write>(
     fonts: &HashMap<String, FontFaceDecl>,
     origin: StyleOrigin,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for font in fonts.values().filter(|s| s.origin() == origin) {
         xml_out.empty("style:font-face")?;
@@ -1783,11 +4096,11 @@ fn write_font_decl<W: Write + Seek>(
     Ok(())
 }
 
-fn write_styles<W: Write + Seek>(
+fn write_styles<W: Write>(
     book: &WorkBook,
     origin: StyleOrigin,
     styleuse: StyleUse,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for style in book.tablestyles.values() {
         if style.origin() == origin && style.styleuse() == styleuse {
@@ -1837,9 +4150,9 @@ fn write_styles<W: Write + Seek>(
     Ok(())
 }
 
-fn write_tablestyle<W: Write + Seek>(
+fn write_tablestyle<W: Write>(
     style: &TableStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -1873,10 +4186,7 @@ fn write_tablestyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_rowstyle<W: Write + Seek>(
-    style: &RowStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
+fn write_rowstyle<W: Write>(style: &RowStyle, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
     } else {
@@ -1909,10 +4219,7 @@ fn write_rowstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_colstyle<W: Write + Seek>(
-    style: &ColStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
+fn write_colstyle<W: Write>(style: &ColStyle, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
     } else {
@@ -1945,9 +4252,9 @@ fn write_colstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_cellstyle<W: Write + Seek>(
+fn write_cellstyle<W: Write>(
     style: &CellStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -2001,9 +4308,9 @@ fn write_cellstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_paragraphstyle<W: Write + Seek>(
+fn write_paragraphstyle<W: Write>(
     style: &ParagraphStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -2061,9 +4368,9 @@ fn write_paragraphstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_textstyle<W: Write + Seek>(
+fn write_textstyle<W: Write>(
     style: &TextStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -2097,9 +4404,9 @@ fn write_textstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_graphicstyle<W: Write + Seek>(
+fn write_graphicstyle<W: Write>(
     style: &GraphicStyle,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     if style.styleuse() == StyleUse::Default {
         xml_out.elem("style:default-style")?;
@@ -2134,11 +4441,11 @@ fn write_graphicstyle<W: Write + Seek>(
     Ok(())
 }
 
-fn write_valuestyles<W: Write + Seek, T: ValueFormatTrait>(
+fn write_valuestyles<W: Write, T: ValueFormatTrait>(
     value_formats: &HashMap<String, T>,
     origin: StyleOrigin,
     styleuse: StyleUse,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for value_format in value_formats
         .values()
@@ -2247,9 +4554,9 @@ fn write_valuestyles<W: Write + Seek, T: ValueFormatTrait>(
     Ok(())
 }
 
-fn write_pagestyles<W: Write + Seek>(
+fn write_pagestyles<W: Write>(
     styles: &HashMap<String, PageStyle>,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for style in styles.values() {
         xml_out.elem("style:page-layout")?;
@@ -2289,9 +4596,9 @@ fn write_pagestyles<W: Write + Seek>(
     Ok(())
 }
 
-fn write_masterpage<W: Write + Seek>(
+fn write_masterpage<W: Write>(
     styles: &HashMap<String, MasterPage>,
-    xml_out: &mut XmlOdsWriter<'_, W>,
+    xml_out: &mut XmlWriter<W>,
 ) -> Result<(), OdsError> {
     for style in styles.values() {
         xml_out.elem("style:master-page")?;
@@ -2350,10 +4657,7 @@ fn write_masterpage<W: Write + Seek>(
     Ok(())
 }
 
-fn write_regions<W: Write + Seek>(
-    hf: &HeaderFooter,
-    xml_out: &mut XmlOdsWriter<'_, W>,
-) -> Result<(), OdsError> {
+fn write_regions<W: Write>(hf: &HeaderFooter, xml_out: &mut XmlWriter<W>) -> Result<(), OdsError> {
     for left in hf.left() {
         xml_out.elem("style:region-left")?;
         write_xmltag(left, xml_out)?;
@@ -19,6 +19,20 @@ pub(crate) struct ZipWrite<'a, W: Write + Seek> {
 
 #[allow(dead_code)]
 impl<W: Write + Seek> ZipOut<W> {
+    pub(crate) fn new(write: W) -> ZipOut<W> {
+        ZipOut {
+            zip: ZipWriter::new(write),
+            compression: CompressionMethod::Deflated,
+        }
+    }
+
+    pub(crate) fn new_uncompressed(write: W) -> ZipOut<W> {
+        ZipOut {
+            zip: ZipWriter::new(write),
+            compression: CompressionMethod::Stored,
+        }
+    }
+
     pub(crate) fn new_file(zip_file: &Path) -> Result<ZipOut<File>, std::io::Error> {
         let f = File::create(zip_file)?;
         Ok(ZipOut {
@@ -77,3 +91,14 @@ impl<W: Write + Seek> Write for ZipWrite<'_, W> {
         self.write.flush()
     }
 }
+
+/// Writes to the last started file.
+impl<W: Write + Seek> Write for ZipOut<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, IOError> {
+        self.zip.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), IOError> {
+        self.zip.flush()
+    }
+}
@@ -73,11 +73,34 @@
 //!     * Only as strings, but support functions for cell/range references.
 //!   * Row/Column spans
 //!   * Header rows/columns, print ranges
+//!   * Row and column groups
+//!   * Named ranges and named expressions.
+//!   * Database ranges with autofilter and sort.
+//!   * Conditional formats with conditions, color scales, data bars,
+//!     icon sets and dates.
+//!   * Calculation settings.
+//!   * Sheet protection and cell protection.
+//!   * Document metadata.
+//!   * Pivot tables (data pilot tables).
+//!   * Tracked changes.
+//!   * Scenarios.
+//!   * Inserting and deleting rows and columns.
 //!   * Formatted text as xml text.
+//!   * Cell annotations.
+//!   * Images anchored to cells or to the page.
+//!   * Charts anchored to cells. Bar, line, pie and scatter charts.
+//!   * Reading row by row with OdsReader.
+//!   * Loading sheets on demand with LazyWorkBook.
+//!   * Reading only values or selected sheets with ReadOptions.
+//!   * Writing row by row with OdsStreamWriter.
+//!   * Reading and writing flat ODS-files (.fods).
+//!   * Reading and writing password protected ODS-files.
 //!
 //! * Formulas
 //!   * Only as strings.
 //!   * Utilities for cell/range references.
+//!   * References are changed when rows/columns are inserted, deleted
+//!     or a range is moved.
 //!
 //! * Styles
 //!   * Default styles per data type.
@@ -110,40 +133,24 @@
 //!   to read the contents correctly. LibreOffice seems to ignore this completely
 //!   and display everything correctly.
 //!
-//! What is not supported:
-//! * Spreadsheets
-//!   * Row and column grouping
-//!
-//! Next on the TO-DO list:
-//! * Row and column grouping.
-//! * Calculation settings.
-//! * Named expressions.
-//!
 //! There are a number of features that are not parsed to a structure,
 //! but which are stored as a XML. This might work as long as
 //! these features don't refer to data that is no longer valid after
 //! some modification. But they are written back to the ods.
 //!
 //! Anyway those are:
-//! * tracked-changes
 //! * variable-decls
 //! * sequence-decls
 //! * user-field-decls
 //! * dde-connection-decls
-//! * calculation-settings  
 //! * label-ranges  
-//! * named-expressions  
-//! * database-ranges
-//! * data-pilot-tables
 //! * consolidation
 //! * dde-links
 //! * table:desc
 //! * table-source
 //! * dde-source
-//! * scenario
 //! * forms
 //! * shapes
-//! * calcext:conditional-formats
 //!
 //! When storing a previously read ODS file, all the contained files
 //! are copied to the new file, except settings.xml, styles.xml and content.xml.
@@ -189,14 +196,34 @@ pub use crate::format::{
     ValueFormatBoolean, ValueFormatCurrency, ValueFormatDateTime, ValueFormatNumber,
     ValueFormatPercentage, ValueFormatRef, ValueFormatText, ValueFormatTimeDuration,
 };
-pub use crate::io::read::{read_ods, read_ods_buf};
-pub use crate::io::write::{write_ods, write_ods_buf, write_ods_buf_uncompressed};
+pub use crate::io::read::{
+    read_fods, read_fods_buf, read_ods, read_ods_buf, read_ods_buf_with_password, read_ods_from,
+    read_ods_with_password, LazyWorkBook, OdsContentReader, OdsReader, ReadOptions,
+};
+pub use crate::io::write::{
+    write_fods, write_fods_buf, write_ods, write_ods_atomic, write_ods_buf,
+    write_ods_buf_encrypted, write_ods_buf_uncompressed, write_ods_encrypted, write_ods_to,
+    OdsStreamWriter,
+};
 pub use crate::refs::{CellRange, CellRef, ColRange, RowRange};
 pub use crate::style::units::{Angle, Length};
 pub use crate::style::{CellStyle, CellStyleRef};
 
+use crate::annotation::Annotation;
+use crate::chart::Chart;
 use crate::config::Config;
 use crate::defaultstyles::{DefaultFormat, DefaultStyle};
+use crate::calcsettings::CalculationSettings;
+use crate::condformat::ConditionalFormat;
+use crate::database::DatabaseRange;
+use crate::datapilot::DataPilotTable;
+use crate::trackedchanges::TrackedChanges;
+use crate::draw::Image;
+use crate::metadata::Metadata;
+use crate::named::{NamedExpression, NamedRange};
+use crate::protection::{ProtectionOptions, SheetProtection};
+use crate::scenario::Scenario;
+use crate::shift::{move_range, shift_book, shift_sheet, Shift};
 use crate::ds::detach::Detach;
 use crate::ds::detach::Detached;
 use crate::format::ValueFormatTrait;
@@ -242,15 +269,28 @@ mod ds;
 mod io;
 mod locale;
 
+pub mod annotation;
+pub mod chart;
+pub mod calcsettings;
+pub mod condformat;
 pub mod condition;
+pub mod database;
+pub mod datapilot;
 pub mod defaultstyles;
+pub mod draw;
 pub mod error;
 pub mod format;
 pub mod formula;
+pub mod metadata;
+pub mod named;
+pub mod protection;
 pub mod refs;
 mod refs_impl;
+pub mod scenario;
+mod shift;
 pub mod style;
 pub mod text;
+pub mod trackedchanges;
 pub mod validation;
 pub mod xmltree;
 
@@ -299,6 +339,25 @@ pub struct WorkBook {
     /// Validations.
     validations: HashMap<String, Validation>,
 
+    /// Workbook wide named ranges and expressions.
+    named_ranges: HashMap<String, NamedRange>,
+    named_expressions: HashMap<String, NamedExpression>,
+
+    /// Database ranges with filter and sort.
+    database_ranges: HashMap<String, DatabaseRange>,
+
+    /// Pivot tables.
+    data_pilot_tables: HashMap<String, DataPilotTable>,
+
+    /// Tracked changes.
+    tracked_changes: Option<TrackedChanges>,
+
+    /// Settings for the formula calculation.
+    calc_settings: CalculationSettings,
+
+    /// Document metadata from meta.xml.
+    metadata: Metadata,
+
     /// Configuration data. Internal cache for all values.
     /// Mapped into WorkBookConfig, SheetConfig.
     config: Detach<Config>,
@@ -372,6 +431,23 @@ impl fmt::Debug for WorkBook {
         for s in self.validations.values() {
             writeln!(f, "{:?}", s)?;
         }
+        for s in self.named_ranges.values() {
+            writeln!(f, "{:?}", s)?;
+        }
+        for s in self.named_expressions.values() {
+            writeln!(f, "{:?}", s)?;
+        }
+        for s in self.database_ranges.values() {
+            writeln!(f, "{:?}", s)?;
+        }
+        for s in self.data_pilot_tables.values() {
+            writeln!(f, "{:?}", s)?;
+        }
+        if let Some(tracked_changes) = &self.tracked_changes {
+            writeln!(f, "{:?}", tracked_changes)?;
+        }
+        writeln!(f, "{:?}", self.calc_settings)?;
+        writeln!(f, "{:?}", self.metadata)?;
         for xtr in &self.extra {
             writeln!(f, "extras {:?}", xtr)?;
         }
@@ -434,6 +510,13 @@ impl WorkBook {
             pagestyles: Default::default(),
             masterpages: Default::default(),
             validations: Default::default(),
+            named_ranges: Default::default(),
+            named_expressions: Default::default(),
+            database_ranges: Default::default(),
+            data_pilot_tables: Default::default(),
+            tracked_changes: None,
+            calc_settings: Default::default(),
+            metadata: Default::default(),
             config: default_settings(),
             workbook_config: Default::default(),
             extra: vec![],
@@ -611,6 +694,134 @@ impl WorkBook {
         self.sheets.remove(n).take()
     }
 
+    /// Adds a scenario for the sheet at index base. The new scenario
+    /// sheet is inserted after the base sheet and its existing scenarios
+    /// and gets a copy of the cells in the scenario ranges. It is hidden,
+    /// as LibreOffice does.
+    ///
+    /// Returns the index of the scenario sheet.
+    ///
+    /// Panics
+    ///
+    /// Panics if base is out of bounds or a sheet was detached.
+    pub fn add_scenario<S: Into<String>>(
+        &mut self,
+        base: usize,
+        name: S,
+        scenario: Scenario,
+    ) -> usize {
+        let base_sheet = self.sheet(base);
+        let mut sheet = base_sheet.clone_no_data();
+        sheet.set_name(name);
+        sheet.set_display(false);
+        sheet.protection = None;
+        for range in scenario.ranges() {
+            for ((row, col), cell) in base_sheet
+                .data
+                .range((range.row(), range.col())..=(range.to_row(), range.to_col()))
+            {
+                if *col >= range.col() && *col <= range.to_col() {
+                    sheet.data.insert((*row, *col), cell.clone());
+                }
+            }
+        }
+        sheet.scenario = Some(scenario);
+
+        let idx = base + 1 + self.scenarios(base).len();
+        self.insert_sheet(idx, sheet);
+        idx
+    }
+
+    /// Inserts n empty rows before row in the sheet at index idx.
+    /// See Sheet::insert_rows().
+    ///
+    /// The references to this sheet are changed in all formulas,
+    /// validations, stylemaps, conditional formats, named ranges, database
+    /// ranges, data pilot tables and charts. Ranges that are deleted
+    /// completely are removed. The scenario sheets of the sheet change
+    /// too.
+    ///
+    /// Panics
+    ///
+    /// Panics if idx is out of bounds or the sheet was detached.
+    pub fn insert_rows(&mut self, idx: usize, row: u32, n: u32) {
+        shift_book(self, idx, Shift::new(true, true, row, n));
+    }
+
+    /// Deletes n rows starting with row in the sheet at index idx.
+    /// See Sheet::delete_rows().
+    ///
+    /// The references to this sheet are changed in all formulas,
+    /// validations, stylemaps, conditional formats, named ranges, database
+    /// ranges, data pilot tables and charts. Ranges that are deleted
+    /// completely are removed. The scenario sheets of the sheet change
+    /// too.
+    ///
+    /// Panics
+    ///
+    /// Panics if idx is out of bounds or the sheet was detached.
+    pub fn delete_rows(&mut self, idx: usize, row: u32, n: u32) {
+        shift_book(self, idx, Shift::new(true, false, row, n));
+    }
+
+    /// Inserts n empty columns before col in the sheet at index idx.
+    /// See Sheet::insert_cols().
+    ///
+    /// The references to this sheet are changed in all formulas,
+    /// validations, stylemaps, conditional formats, named ranges, database
+    /// ranges, data pilot tables and charts. Ranges that are deleted
+    /// completely are removed. The scenario sheets of the sheet change
+    /// too.
+    ///
+    /// Panics
+    ///
+    /// Panics if idx is out of bounds or the sheet was detached.
+    pub fn insert_cols(&mut self, idx: usize, col: u32, n: u32) {
+        shift_book(self, idx, Shift::new(false, true, col, n));
+    }
+
+    /// Deletes n columns starting with col in the sheet at index idx.
+    /// See Sheet::delete_cols().
+    ///
+    /// The references to this sheet are changed in all formulas,
+    /// validations, stylemaps, conditional formats, named ranges, database
+    /// ranges, data pilot tables and charts. Ranges that are deleted
+    /// completely are removed. The scenario sheets of the sheet change
+    /// too.
+    ///
+    /// Panics
+    ///
+    /// Panics if idx is out of bounds or the sheet was detached.
+    pub fn delete_cols(&mut self, idx: usize, col: u32, n: u32) {
+        shift_book(self, idx, Shift::new(false, false, col, n));
+    }
+
+    /// Moves the cells of the range in the sheet at index idx to the sheet
+    /// at index to_idx. The top left cell is moved to row/col, the cells
+    /// that were there before are replaced. The table of the range is
+    /// ignored.
+    ///
+    /// References to the moved cells are changed in all formulas,
+    /// validations, stylemaps, conditional formats, named ranges, database
+    /// ranges, data pilot tables and charts. A range is only changed if it
+    /// is moved completely. References to the replaced cells become #REF!.
+    ///
+    /// Panics
+    ///
+    /// Panics if idx or to_idx are out of bounds or the sheets were
+    /// detached.
+    pub fn move_range(&mut self, idx: usize, range: &CellRange, to_idx: usize, row: u32, col: u32) {
+        move_range(self, idx, range, to_idx, row, col);
+    }
+
+    /// Indices of the scenario sheets of the sheet at index base.
+    /// These are the scenario sheets directly following the base sheet.
+    pub fn scenarios(&self, base: usize) -> Vec<usize> {
+        (base + 1..self.sheets.len())
+            .take_while(|v| self.sheet(*v).is_scenario())
+            .collect()
+    }
+
     /// Adds a default-style for all new values.
     /// This information is only used when writing the data to the ODS file.
     pub fn add_def_style(&mut self, value_type: ValueType, style: &CellStyleRef) {
@@ -1124,6 +1335,194 @@ impl WorkBook {
     pub fn validation_mut(&mut self, name: &str) -> Option<&mut Validation> {
         self.validations.get_mut(name)
     }
+
+    /// Adds a workbook wide named range. Replaces any existing range
+    /// with the same name.
+    pub fn add_named_range(&mut self, range: NamedRange) {
+        self.named_ranges.insert(range.name().clone(), range);
+    }
+
+    /// Removes a named range.
+    pub fn remove_named_range(&mut self, name: &str) -> Option<NamedRange> {
+        self.named_ranges.remove(name)
+    }
+
+    /// Returns the named range.
+    pub fn named_range(&self, name: &str) -> Option<&NamedRange> {
+        self.named_ranges.get(name)
+    }
+
+    /// Returns a mutable named range.
+    pub fn named_range_mut(&mut self, name: &str) -> Option<&mut NamedRange> {
+        self.named_ranges.get_mut(name)
+    }
+
+    /// All workbook wide named ranges.
+    pub fn iter_named_ranges(&self) -> impl Iterator<Item = &NamedRange> {
+        self.named_ranges.values()
+    }
+
+    /// Adds a workbook wide named expression. Replaces any existing
+    /// expression with the same name.
+    pub fn add_named_expression(&mut self, expr: NamedExpression) {
+        self.named_expressions.insert(expr.name().clone(), expr);
+    }
+
+    /// Removes a named expression.
+    pub fn remove_named_expression(&mut self, name: &str) -> Option<NamedExpression> {
+        self.named_expressions.remove(name)
+    }
+
+    /// Returns the named expression.
+    pub fn named_expression(&self, name: &str) -> Option<&NamedExpression> {
+        self.named_expressions.get(name)
+    }
+
+    /// Returns a mutable named expression.
+    pub fn named_expression_mut(&mut self, name: &str) -> Option<&mut NamedExpression> {
+        self.named_expressions.get_mut(name)
+    }
+
+    /// All workbook wide named expressions.
+    pub fn iter_named_expressions(&self) -> impl Iterator<Item = &NamedExpression> {
+        self.named_expressions.values()
+    }
+
+    /// Adds a database range. The sheet is given by the range.
+    /// Replaces any existing database range with the same name.
+    pub fn add_database_range(&mut self, range: DatabaseRange) {
+        self.database_ranges.insert(range.name().clone(), range);
+    }
+
+    /// Removes a database range.
+    pub fn remove_database_range(&mut self, name: &str) -> Option<DatabaseRange> {
+        self.database_ranges.remove(name)
+    }
+
+    /// Returns the database range.
+    pub fn database_range(&self, name: &str) -> Option<&DatabaseRange> {
+        self.database_ranges.get(name)
+    }
+
+    /// Returns a mutable database range.
+    pub fn database_range_mut(&mut self, name: &str) -> Option<&mut DatabaseRange> {
+        self.database_ranges.get_mut(name)
+    }
+
+    /// All database ranges.
+    pub fn iter_database_ranges(&self) -> impl Iterator<Item = &DatabaseRange> {
+        self.database_ranges.values()
+    }
+
+    /// Adds a pivot table. The sheet is given by the target range.
+    /// Replaces any existing pivot table with the same name.
+    pub fn add_data_pilot_table(&mut self, table: DataPilotTable) {
+        self.data_pilot_tables.insert(table.name().clone(), table);
+    }
+
+    /// Removes a pivot table.
+    pub fn remove_data_pilot_table(&mut self, name: &str) -> Option<DataPilotTable> {
+        self.data_pilot_tables.remove(name)
+    }
+
+    /// Returns the pivot table.
+    pub fn data_pilot_table(&self, name: &str) -> Option<&DataPilotTable> {
+        self.data_pilot_tables.get(name)
+    }
+
+    /// Returns a mutable pivot table.
+    pub fn data_pilot_table_mut(&mut self, name: &str) -> Option<&mut DataPilotTable> {
+        self.data_pilot_tables.get_mut(name)
+    }
+
+    /// All pivot tables.
+    pub fn iter_data_pilot_tables(&self) -> impl Iterator<Item = &DataPilotTable> {
+        self.data_pilot_tables.values()
+    }
+
+    /// Tracked changes.
+    pub fn tracked_changes(&self) -> Option<&TrackedChanges> {
+        self.tracked_changes.as_ref()
+    }
+
+    /// Tracked changes.
+    pub fn tracked_changes_mut(&mut self) -> Option<&mut TrackedChanges> {
+        self.tracked_changes.as_mut()
+    }
+
+    /// Tracked changes. None removes all changes and switches off
+    /// change tracking.
+    pub fn set_tracked_changes(&mut self, tracked_changes: Option<TrackedChanges>) {
+        self.tracked_changes = tracked_changes;
+    }
+
+    /// Accepts the change and all pending changes it depends on.
+    /// The sheet data is not changed.
+    pub fn accept_change(&mut self, id: &str) -> Result<(), OdsError> {
+        trackedchanges::accept_change(self, id)
+    }
+
+    /// Rejects the change and all pending changes that depend on it.
+    /// The previous state is restored in the sheet data, as far as
+    /// the change recorded it. A deleted table can't be restored, nothing
+    /// is changed in that case.
+    pub fn reject_change(&mut self, id: &str) -> Result<(), OdsError> {
+        trackedchanges::reject_change(self, id)
+    }
+
+    /// Accepts all pending changes.
+    pub fn accept_all_changes(&mut self) -> Result<(), OdsError> {
+        for id in self.pending_change_ids() {
+            self.accept_change(&id)?;
+        }
+        Ok(())
+    }
+
+    /// Rejects all pending changes, the last change first.
+    pub fn reject_all_changes(&mut self) -> Result<(), OdsError> {
+        for id in self.pending_change_ids().into_iter().rev() {
+            self.reject_change(&id)?;
+        }
+        Ok(())
+    }
+
+    fn pending_change_ids(&self) -> Vec<String> {
+        self.tracked_changes
+            .iter()
+            .flat_map(|v| v.iter_pending())
+            .map(|v| v.id().clone())
+            .collect()
+    }
+
+    /// Settings for the formula calculation.
+    pub fn calculation_settings(&self) -> &CalculationSettings {
+        &self.calc_settings
+    }
+
+    /// Settings for the formula calculation.
+    pub fn calculation_settings_mut(&mut self) -> &mut CalculationSettings {
+        &mut self.calc_settings
+    }
+
+    /// Settings for the formula calculation.
+    pub fn set_calculation_settings(&mut self, settings: CalculationSettings) {
+        self.calc_settings = settings;
+    }
+
+    /// Document metadata like title, author and dates.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Document metadata like title, author and dates.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Document metadata like title, author and dates.
+    pub fn set_metadata(&mut self, metadata: Metadata) {
+        self.metadata = metadata;
+    }
 }
 
 /// Subset of the Workbook wide configurations.
@@ -1317,6 +1716,7 @@ pub struct Sheet {
     style: Option<String>,
 
     data: BTreeMap<(u32, u32), CellData>,
+    page_images: Vec<Image>,
 
     col_header: BTreeMap<u32, ColHeader>,
     row_header: BTreeMap<u32, RowHeader>,
@@ -1328,6 +1728,18 @@ pub struct Sheet {
     header_cols: Option<ColRange>,
     print_ranges: Option<Vec<CellRange>>,
 
+    group_rows: Vec<Grouped>,
+    group_cols: Vec<Grouped>,
+
+    named_ranges: HashMap<String, NamedRange>,
+    named_expressions: HashMap<String, NamedExpression>,
+
+    conditional_formats: Vec<ConditionalFormat>,
+
+    protection: Option<SheetProtection>,
+
+    scenario: Option<Scenario>,
+
     sheet_config: SheetConfig,
 
     extra: Vec<XmlTag>,
@@ -1437,6 +1849,9 @@ impl fmt::Debug for Sheet {
         for (k, v) in self.data.iter() {
             writeln!(f, "  data {:?} {:?}", k, v)?;
         }
+        for v in &self.page_images {
+            writeln!(f, "  page image {:?}", v)?;
+        }
         for (k, v) in &self.col_header {
             writeln!(f, "{:?} {:?}", k, v)?;
         }
@@ -1449,6 +1864,27 @@ impl fmt::Debug for Sheet {
         if let Some(header_cols) = &self.header_cols {
             writeln!(f, "header cols {:?}", header_cols)?;
         }
+        for v in &self.group_rows {
+            writeln!(f, "group rows {:?}", v)?;
+        }
+        for v in &self.group_cols {
+            writeln!(f, "group cols {:?}", v)?;
+        }
+        for v in self.named_ranges.values() {
+            writeln!(f, "named range {:?}", v)?;
+        }
+        for v in self.named_expressions.values() {
+            writeln!(f, "named expression {:?}", v)?;
+        }
+        for v in &self.conditional_formats {
+            writeln!(f, "conditional format {:?}", v)?;
+        }
+        if let Some(protection) = &self.protection {
+            writeln!(f, "protection {:?}", protection)?;
+        }
+        if let Some(scenario) = &self.scenario {
+            writeln!(f, "scenario {:?}", scenario)?;
+        }
         for xtr in &self.extra {
             writeln!(f, "extras {:?}", xtr)?;
         }
@@ -1476,11 +1912,19 @@ impl Sheet {
         Sheet {
             name: name.into(),
             data: BTreeMap::new(),
+            page_images: Default::default(),
             col_header: Default::default(),
             style: None,
             header_rows: None,
             header_cols: None,
             print_ranges: None,
+            group_rows: Default::default(),
+            group_cols: Default::default(),
+            named_ranges: Default::default(),
+            named_expressions: Default::default(),
+            conditional_formats: Default::default(),
+            protection: None,
+            scenario: None,
             sheet_config: Default::default(),
             extra: vec![],
             row_header: Default::default(),
@@ -1495,6 +1939,7 @@ impl Sheet {
             name: self.name.clone(),
             style: self.style.clone(),
             data: Default::default(),
+            page_images: self.page_images.clone(),
             col_header: self.col_header.clone(),
             row_header: self.row_header.clone(),
             display: self.display,
@@ -1502,6 +1947,13 @@ impl Sheet {
             header_rows: self.header_rows.clone(),
             header_cols: self.header_cols.clone(),
             print_ranges: self.print_ranges.clone(),
+            group_rows: self.group_rows.clone(),
+            group_cols: self.group_cols.clone(),
+            named_ranges: self.named_ranges.clone(),
+            named_expressions: self.named_expressions.clone(),
+            conditional_formats: self.conditional_formats.clone(),
+            protection: self.protection.clone(),
+            scenario: self.scenario.clone(),
             sheet_config: Default::default(),
             extra: self.extra.clone(),
         }
@@ -1807,6 +2259,9 @@ impl Sheet {
             style: value.style.clone(),
             formula: value.formula.clone(),
             validation_name: value.validation_name.clone(),
+            annotation: value.annotation().cloned(),
+            images: value.images().to_vec(),
+            charts: value.charts().to_vec(),
             span: value.span,
         })
     }
@@ -1821,6 +2276,7 @@ impl Sheet {
                 formula: cell.formula,
                 style: cell.style,
                 validation_name: cell.validation_name,
+                extra: CellDataExt::new_boxed(cell.annotation, cell.images, cell.charts),
                 span: cell.span,
             },
         );
@@ -1831,11 +2287,18 @@ impl Sheet {
         let value = self.data.remove(&(row, col));
 
         if let Some(value) = value {
+            let (annotation, images, charts) = match value.extra {
+                Some(extra) => (extra.annotation, extra.images, extra.charts),
+                None => (None, Vec::new(), Vec::new()),
+            };
             Some(CellContent {
                 value: value.value,
                 style: value.style,
                 formula: value.formula,
                 validation_name: value.validation_name,
+                annotation,
+                images,
+                charts,
                 span: value.span,
             })
         } else {
@@ -1843,6 +2306,54 @@ impl Sheet {
         }
     }
 
+    /// Inserts n empty rows before row. All following rows move down.
+    ///
+    /// Row headers, spans, print ranges, header rows, row groups,
+    /// conditional format ranges and scenario ranges move with the data.
+    /// Spans and ranges that contain the row are extended. The cursor and
+    /// a fixed split move too.
+    ///
+    /// References to this sheet in the formulas, charts, named ranges and
+    /// conditional formats of this sheet are changed too. Use
+    /// WorkBook::insert_rows() to change the references in other sheets
+    /// and the workbook.
+    pub fn insert_rows(&mut self, row: u32, n: u32) {
+        shift_sheet(self, Shift::new(true, true, row, n));
+    }
+
+    /// Deletes n rows starting with row. All following rows move up.
+    ///
+    /// Row headers, spans, print ranges, header rows, row groups,
+    /// conditional format ranges and scenario ranges move with the data.
+    /// Spans and ranges that overlap the deleted rows shrink, if they are
+    /// deleted completely they are removed. If the first cell of a span
+    /// is deleted, the rest of the span starts with the first row after
+    /// the deleted rows. The cursor and a fixed split move too.
+    ///
+    /// References to this sheet in the formulas, charts, named ranges and
+    /// conditional formats of this sheet are changed too. References to
+    /// deleted cells become #REF!. Use WorkBook::delete_rows() to change
+    /// the references in other sheets and the workbook.
+    pub fn delete_rows(&mut self, row: u32, n: u32) {
+        shift_sheet(self, Shift::new(true, false, row, n));
+    }
+
+    /// Inserts n empty columns before col. All following columns move
+    /// right.
+    ///
+    /// Works the same as insert_rows().
+    pub fn insert_cols(&mut self, col: u32, n: u32) {
+        shift_sheet(self, Shift::new(false, true, col, n));
+    }
+
+    /// Deletes n columns starting with col. All following columns move
+    /// left.
+    ///
+    /// Works the same as delete_rows().
+    pub fn delete_cols(&mut self, col: u32, n: u32) {
+        shift_sheet(self, Shift::new(false, false, col, n));
+    }
+
     /// Add a new cell. Main use is for reading the spreadsheet.
     pub(crate) fn add_cell_data(&mut self, row: u32, col: u32, cell: CellData) {
         self.data.insert((row, col), cell);
@@ -1942,6 +2453,174 @@ impl Sheet {
         }
     }
 
+    /// Sets an annotation for this cell. Creates a new cell if necessary.
+    pub fn set_annotation(&mut self, row: u32, col: u32, annotation: Annotation) {
+        let cell = self.data.entry((row, col)).or_default();
+        cell.extra_mut().annotation = Some(annotation);
+    }
+
+    /// Removes the annotation.
+    pub fn clear_annotation(&mut self, row: u32, col: u32) {
+        if let Some(cell) = self.data.get_mut(&(row, col)) {
+            if let Some(extra) = &mut cell.extra {
+                extra.annotation = None;
+            }
+        }
+    }
+
+    /// Returns the annotation for this cell.
+    pub fn annotation(&self, row: u32, col: u32) -> Option<&Annotation> {
+        if let Some(c) = self.data.get(&(row, col)) {
+            c.annotation()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the annotation for this cell.
+    pub fn annotation_mut(&mut self, row: u32, col: u32) -> Option<&mut Annotation> {
+        if let Some(c) = self.data.get_mut(&(row, col)) {
+            c.extra.as_mut().and_then(|v| v.annotation.as_mut())
+        } else {
+            None
+        }
+    }
+
+    /// Adds an image anchored to this cell. Creates a new cell if necessary.
+    ///
+    /// The data is stored as a file in the Pictures/ directory of the
+    /// package when the workbook is written. Returns the image for further
+    /// modifications, e.g. the graphic style.
+    pub fn add_image<S: Into<String>>(
+        &mut self,
+        row: u32,
+        col: u32,
+        width: Length,
+        height: Length,
+        data: Vec<u8>,
+        mime_type: S,
+    ) -> &mut Image {
+        let mut image = Image::new(mime_type, data);
+        image.set_size(width, height);
+        self.push_image(row, col, image)
+    }
+
+    /// Adds an image anchored to this cell. Creates a new cell if necessary.
+    pub fn push_image(&mut self, row: u32, col: u32, image: Image) -> &mut Image {
+        let cell = self.data.entry((row, col)).or_default();
+        let images = &mut cell.extra_mut().images;
+        images.push(image);
+        images.last_mut().expect("image")
+    }
+
+    /// Images anchored to this cell.
+    pub fn images(&self, row: u32, col: u32) -> &[Image] {
+        if let Some(c) = self.data.get(&(row, col)) {
+            c.images()
+        } else {
+            &[]
+        }
+    }
+
+    /// Images anchored to this cell.
+    pub fn images_mut(&mut self, row: u32, col: u32) -> Option<&mut Vec<Image>> {
+        if let Some(c) = self.data.get_mut(&(row, col)) {
+            c.extra.as_mut().map(|v| &mut v.images)
+        } else {
+            None
+        }
+    }
+
+    /// Removes all images anchored to this cell.
+    pub fn clear_images(&mut self, row: u32, col: u32) {
+        if let Some(cell) = self.data.get_mut(&(row, col)) {
+            if let Some(extra) = &mut cell.extra {
+                extra.images.clear();
+            }
+        }
+    }
+
+    /// All images of the sheet with their anchor cell.
+    pub fn iter_images(&self) -> Vec<((u32, u32), &Image)> {
+        self.data
+            .iter()
+            .flat_map(|(k, v)| v.images().iter().map(move |i| (*k, i)))
+            .collect()
+    }
+
+    /// Adds an image that is anchored to the page instead of a cell.
+    ///
+    /// The position of the image is relative to the top left corner of
+    /// the sheet.
+    pub fn push_page_image(&mut self, image: Image) -> &mut Image {
+        self.page_images.push(image);
+        self.page_images.last_mut().expect("image")
+    }
+
+    /// Images anchored to the page.
+    pub fn page_images(&self) -> &[Image] {
+        &self.page_images
+    }
+
+    /// Images anchored to the page.
+    pub fn page_images_mut(&mut self) -> &mut Vec<Image> {
+        &mut self.page_images
+    }
+
+    /// Adds a chart anchored to this cell. Creates a new cell if necessary.
+    ///
+    /// The chart is written as a separate chart document within the
+    /// package. Returns the chart for further modifications.
+    pub fn add_chart(
+        &mut self,
+        row: u32,
+        col: u32,
+        width: Length,
+        height: Length,
+        mut chart: Chart,
+    ) -> &mut Chart {
+        chart.set_size(width, height);
+        let cell = self.data.entry((row, col)).or_default();
+        let charts = &mut cell.extra_mut().charts;
+        charts.push(chart);
+        charts.last_mut().expect("chart")
+    }
+
+    /// Charts anchored to this cell.
+    pub fn charts(&self, row: u32, col: u32) -> &[Chart] {
+        if let Some(c) = self.data.get(&(row, col)) {
+            c.charts()
+        } else {
+            &[]
+        }
+    }
+
+    /// Charts anchored to this cell.
+    pub fn charts_mut(&mut self, row: u32, col: u32) -> Option<&mut Vec<Chart>> {
+        if let Some(c) = self.data.get_mut(&(row, col)) {
+            c.extra.as_mut().map(|v| &mut v.charts)
+        } else {
+            None
+        }
+    }
+
+    /// Removes all charts anchored to this cell.
+    pub fn clear_charts(&mut self, row: u32, col: u32) {
+        if let Some(cell) = self.data.get_mut(&(row, col)) {
+            if let Some(extra) = &mut cell.extra {
+                extra.charts.clear();
+            }
+        }
+    }
+
+    /// All charts of the sheet with their anchor cell.
+    pub fn iter_charts(&self) -> Vec<((u32, u32), &Chart)> {
+        self.data
+            .iter()
+            .flat_map(|(k, v)| v.charts().iter().map(move |i| (*k, i)))
+            .collect()
+    }
+
     /// Sets the rowspan of the cell. Must be greater than 0.
     pub fn set_row_span(&mut self, row: u32, col: u32, span: u32) {
         let mut cell = self.data.entry((row, col)).or_insert_with(CellData::new);
@@ -2018,6 +2697,183 @@ impl Sheet {
         self.print_ranges.as_ref()
     }
 
+    /// Adds a group of rows. Groups can be nested, but a group must not
+    /// partially overlap another group.
+    ///
+    /// With display set to false the group is collapsed. The rows
+    /// themselves should be hidden with set_row_visible() too.
+    ///
+    /// Fails if from > to or the group overlaps another group.
+    pub fn add_row_group(&mut self, from: u32, to: u32, display: bool) -> Result<(), OdsError> {
+        if from > to {
+            return Err(OdsError::Ods(format!("Invalid group {}..{}.", from, to)));
+        }
+        add_group(&mut self.group_rows, Grouped::new(from, to, display))
+    }
+
+    /// Removes the group with exactly this range.
+    pub fn remove_row_group(&mut self, from: u32, to: u32) {
+        self.group_rows.retain(|v| v.from != from || v.to != to);
+    }
+
+    /// Collapses or expands the group with exactly this range.
+    pub fn set_row_group_displayed(&mut self, from: u32, to: u32, display: bool) {
+        set_group_displayed(&mut self.group_rows, from, to, display);
+    }
+
+    /// Returns the row groups. Outer groups come before the groups
+    /// nested inside.
+    pub fn row_groups(&self) -> &[Grouped] {
+        &self.group_rows
+    }
+
+    /// Adds a group of columns. Groups can be nested, but a group must not
+    /// partially overlap another group.
+    ///
+    /// With display set to false the group is collapsed. The columns
+    /// themselves should be hidden with set_col_visible() too.
+    ///
+    /// Fails if from > to or the group overlaps another group.
+    pub fn add_col_group(&mut self, from: u32, to: u32, display: bool) -> Result<(), OdsError> {
+        if from > to {
+            return Err(OdsError::Ods(format!("Invalid group {}..{}.", from, to)));
+        }
+        add_group(&mut self.group_cols, Grouped::new(from, to, display))
+    }
+
+    /// Removes the group with exactly this range.
+    pub fn remove_col_group(&mut self, from: u32, to: u32) {
+        self.group_cols.retain(|v| v.from != from || v.to != to);
+    }
+
+    /// Collapses or expands the group with exactly this range.
+    pub fn set_col_group_displayed(&mut self, from: u32, to: u32, display: bool) {
+        set_group_displayed(&mut self.group_cols, from, to, display);
+    }
+
+    /// Returns the column groups. Outer groups come before the groups
+    /// nested inside.
+    pub fn col_groups(&self) -> &[Grouped] {
+        &self.group_cols
+    }
+
+    /// Adds a named range that is only valid within this sheet.
+    /// Replaces any existing range with the same name.
+    pub fn add_named_range(&mut self, range: NamedRange) {
+        self.named_ranges.insert(range.name().clone(), range);
+    }
+
+    /// Removes a named range.
+    pub fn remove_named_range(&mut self, name: &str) -> Option<NamedRange> {
+        self.named_ranges.remove(name)
+    }
+
+    /// Returns the named range.
+    pub fn named_range(&self, name: &str) -> Option<&NamedRange> {
+        self.named_ranges.get(name)
+    }
+
+    /// Returns a mutable named range.
+    pub fn named_range_mut(&mut self, name: &str) -> Option<&mut NamedRange> {
+        self.named_ranges.get_mut(name)
+    }
+
+    /// All named ranges of this sheet.
+    pub fn iter_named_ranges(&self) -> impl Iterator<Item = &NamedRange> {
+        self.named_ranges.values()
+    }
+
+    /// Adds a named expression that is only valid within this sheet.
+    /// Replaces any existing expression with the same name.
+    pub fn add_named_expression(&mut self, expr: NamedExpression) {
+        self.named_expressions.insert(expr.name().clone(), expr);
+    }
+
+    /// Removes a named expression.
+    pub fn remove_named_expression(&mut self, name: &str) -> Option<NamedExpression> {
+        self.named_expressions.remove(name)
+    }
+
+    /// Returns the named expression.
+    pub fn named_expression(&self, name: &str) -> Option<&NamedExpression> {
+        self.named_expressions.get(name)
+    }
+
+    /// Returns a mutable named expression.
+    pub fn named_expression_mut(&mut self, name: &str) -> Option<&mut NamedExpression> {
+        self.named_expressions.get_mut(name)
+    }
+
+    /// All named expressions of this sheet.
+    pub fn iter_named_expressions(&self) -> impl Iterator<Item = &NamedExpression> {
+        self.named_expressions.values()
+    }
+
+    /// Adds a conditional format.
+    pub fn add_conditional_format(&mut self, cf: ConditionalFormat) {
+        self.conditional_formats.push(cf);
+    }
+
+    /// Conditional formats of this sheet.
+    pub fn conditional_formats(&self) -> &Vec<ConditionalFormat> {
+        &self.conditional_formats
+    }
+
+    /// Conditional formats of this sheet.
+    pub fn conditional_formats_mut(&mut self) -> &mut Vec<ConditionalFormat> {
+        &mut self.conditional_formats
+    }
+
+    /// Protects the sheet. The password is stored as SHA-256 hash.
+    /// An empty password protects the sheet without a password.
+    ///
+    /// Which cells can still be edited is defined by the cell-style,
+    /// see CellStyle::set_protected().
+    pub fn protect(&mut self, password: &str, options: ProtectionOptions) {
+        self.protection = Some(SheetProtection::new(password, options));
+    }
+
+    /// Removes the sheet protection.
+    pub fn unprotect(&mut self) {
+        self.protection = None;
+    }
+
+    /// Sets the protection directly.
+    pub fn set_protection(&mut self, protection: Option<SheetProtection>) {
+        self.protection = protection;
+    }
+
+    /// Protection of the sheet.
+    pub fn protection(&self) -> Option<&SheetProtection> {
+        self.protection.as_ref()
+    }
+
+    /// The sheet is protected.
+    pub fn is_protected(&self) -> bool {
+        self.protection.is_some()
+    }
+
+    /// Makes this sheet a scenario sheet. It must follow its base sheet,
+    /// see WorkBook::add_scenario().
+    pub fn set_scenario(&mut self, scenario: Option<Scenario>) {
+        self.scenario = scenario;
+    }
+
+    /// Scenario data if this is a scenario sheet.
+    pub fn scenario(&self) -> Option<&Scenario> {
+        self.scenario.as_ref()
+    }
+
+    /// Scenario data if this is a scenario sheet.
+    pub fn scenario_mut(&mut self) -> Option<&mut Scenario> {
+        self.scenario.as_mut()
+    }
+
+    /// This is a scenario sheet.
+    pub fn is_scenario(&self) -> bool {
+        self.scenario.is_some()
+    }
+
     /// Split horizontally on a cell boundary. The splitting is fixed in
     /// position.
     pub fn split_col_header(&mut self, col: u32) {
@@ -2051,6 +2907,74 @@ impl Sheet {
     }
 }
 
+// Inserts the group, outer groups first.
+fn add_group(groups: &mut Vec<Grouped>, group: Grouped) -> Result<(), OdsError> {
+    for v in groups.iter() {
+        let disjoint = group.to < v.from || group.from > v.to;
+        let nested = group.contains(v) || v.contains(&group);
+        if !disjoint && !nested {
+            return Err(OdsError::Ods(format!(
+                "Group {}..{} overlaps group {}..{}.",
+                group.from, group.to, v.from, v.to
+            )));
+        }
+    }
+    let idx = groups
+        .iter()
+        .position(|v| v.from > group.from || v.from == group.from && v.to < group.to)
+        .unwrap_or(groups.len());
+    groups.insert(idx, group);
+    Ok(())
+}
+
+fn set_group_displayed(groups: &mut [Grouped], from: u32, to: u32, display: bool) {
+    for v in groups.iter_mut() {
+        if v.from == from && v.to == to {
+            v.display = display;
+        }
+    }
+}
+
+/// A group of rows or columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grouped {
+    from: u32,
+    to: u32,
+    display: bool,
+}
+
+impl Grouped {
+    /// New group. The range is inclusive.
+    ///
+    /// Panics
+    ///
+    /// If from > to.
+    pub fn new(from: u32, to: u32, display: bool) -> Self {
+        assert!(from <= to);
+        Self { from, to, display }
+    }
+
+    /// Start of the group.
+    pub fn from(&self) -> u32 {
+        self.from
+    }
+
+    /// End of the group, inclusive.
+    pub fn to(&self) -> u32 {
+        self.to
+    }
+
+    /// The group is expanded.
+    pub fn display(&self) -> bool {
+        self.display
+    }
+
+    /// The other group is within this one.
+    pub fn contains(&self, other: &Grouped) -> bool {
+        self.from <= other.from && other.to <= self.to
+    }
+}
+
 /// There are two ways a sheet can be split. There are fixed column/row header
 /// like splits, and there is a moveable split.
 ///
@@ -2219,6 +3143,8 @@ struct CellData {
     style: Option<String>,
     // Content validation name.
     validation_name: Option<String>,
+    // Rarely used data. Boxed to keep the cells small.
+    extra: Option<Box<CellDataExt>>,
     // Row/Column span.
     span: CellSpan,
 }
@@ -2231,9 +3157,66 @@ impl CellData {
             formula: None,
             style: None,
             validation_name: None,
+            extra: None,
             span: Default::default(),
         }
     }
+
+    /// Extra data, created if necessary.
+    pub(crate) fn extra_mut(&mut self) -> &mut CellDataExt {
+        self.extra.get_or_insert_with(Default::default)
+    }
+
+    /// Cell annotation.
+    pub(crate) fn annotation(&self) -> Option<&Annotation> {
+        self.extra.as_ref().and_then(|v| v.annotation.as_ref())
+    }
+
+    /// Images anchored to the cell.
+    pub(crate) fn images(&self) -> &[Image] {
+        match &self.extra {
+            Some(extra) => extra.images.as_slice(),
+            None => &[],
+        }
+    }
+
+    /// Charts anchored to the cell.
+    pub(crate) fn charts(&self) -> &[Chart] {
+        match &self.extra {
+            Some(extra) => extra.charts.as_slice(),
+            None => &[],
+        }
+    }
+}
+
+/// Rarely used parts of a cell.
+#[derive(Debug, Clone, Default)]
+struct CellDataExt {
+    // Cell annotation.
+    annotation: Option<Annotation>,
+    // Images anchored to the cell.
+    images: Vec<Image>,
+    // Charts anchored to the cell.
+    charts: Vec<Chart>,
+}
+
+impl CellDataExt {
+    /// Only allocates if there is any data.
+    fn new_boxed(
+        annotation: Option<Annotation>,
+        images: Vec<Image>,
+        charts: Vec<Chart>,
+    ) -> Option<Box<Self>> {
+        if annotation.is_none() && images.is_empty() && charts.is_empty() {
+            None
+        } else {
+            Some(Box::new(Self {
+                annotation,
+                images,
+                charts,
+            }))
+        }
+    }
 }
 
 /// Holds references to the combined content of a cell.
@@ -2248,6 +3231,12 @@ pub struct CellContentRef<'a> {
     pub formula: Option<&'a String>,
     /// Reference to a cell validation.
     pub validation_name: Option<&'a String>,
+    /// Reference to the annotation.
+    pub annotation: Option<&'a Annotation>,
+    /// Reference to the images.
+    pub images: Option<&'a Vec<Image>>,
+    /// Reference to the charts.
+    pub charts: Option<&'a Vec<Chart>>,
     /// Reference to the cellspan.
     pub span: Option<&'a CellSpan>,
 }
@@ -2259,11 +3248,29 @@ impl<'a> From<&'a CellData> for CellContentRef<'a> {
             style: cd.style.as_ref(),
             formula: cd.formula.as_ref(),
             validation_name: cd.validation_name.as_ref(),
+            annotation: cd.annotation(),
+            images: cd.extra.as_ref().map(|v| &v.images),
+            charts: cd.extra.as_ref().map(|v| &v.charts),
             span: Some(&cd.span),
         }
     }
 }
 
+impl<'a> From<&'a CellContent> for CellContentRef<'a> {
+    fn from(cc: &'a CellContent) -> Self {
+        CellContentRef {
+            value: Some(&cc.value),
+            style: cc.style.as_ref(),
+            formula: cc.formula.as_ref(),
+            validation_name: cc.validation_name.as_ref(),
+            annotation: cc.annotation.as_ref(),
+            images: Some(&cc.images),
+            charts: Some(&cc.charts),
+            span: Some(&cc.span),
+        }
+    }
+}
+
 impl<'a> CellContentRef<'a> {
     /// Returns the value.
     pub fn value(&self) -> &'a Value {
@@ -2289,6 +3296,29 @@ impl<'a> CellContentRef<'a> {
         self.validation_name
     }
 
+    /// Returns the annotation.
+    pub fn annotation(&self) -> Option<&'a Annotation> {
+        self.annotation
+    }
+
+    /// Returns the images.
+    pub fn images(&self) -> &'a [Image] {
+        if let Some(images) = self.images {
+            images.as_slice()
+        } else {
+            &[]
+        }
+    }
+
+    /// Returns the charts.
+    pub fn charts(&self) -> &'a [Chart] {
+        if let Some(charts) = self.charts {
+            charts.as_slice()
+        } else {
+            &[]
+        }
+    }
+
     /// Returns the row span.
     pub fn row_span(&self) -> u32 {
         if let Some(span) = self.span {
@@ -2319,6 +3349,12 @@ pub struct CellContent {
     pub formula: Option<String>,
     /// Reference to a validation rule.
     pub validation_name: Option<String>,
+    /// Annotation.
+    pub annotation: Option<Annotation>,
+    /// Images anchored to the cell.
+    pub images: Vec<Image>,
+    /// Charts anchored to the cell.
+    pub charts: Vec<Chart>,
     /// Cellspan.
     pub span: CellSpan,
 }
@@ -2331,6 +3367,9 @@ impl CellContent {
             style: None,
             formula: None,
             validation_name: None,
+            annotation: None,
+            images: Vec::new(),
+            charts: Vec::new(),
             span: Default::default(),
         }
     }
@@ -2390,6 +3429,21 @@ impl CellContent {
         self.validation_name = None;
     }
 
+    /// Returns the annotation.
+    pub fn annotation(&self) -> Option<&Annotation> {
+        self.annotation.as_ref()
+    }
+
+    /// Sets the annotation.
+    pub fn set_annotation(&mut self, annotation: Annotation) {
+        self.annotation = Some(annotation);
+    }
+
+    /// No annotation.
+    pub fn clear_annotation(&mut self) {
+        self.annotation = None;
+    }
+
     /// Sets the row span of this cell.
     /// Cells below with values will be lost when writing.
     pub fn set_row_span(&mut self, rows: u32) {
@@ -0,0 +1,314 @@
+//!
+//! Document metadata as stored in meta.xml.
+//!
+//! ```
+//! use chrono::NaiveDate;
+//! use spreadsheet_ods::metadata::MetaValue;
+//! use spreadsheet_ods::WorkBook;
+//!
+//! let mut book = WorkBook::new_empty();
+//! let meta = book.metadata_mut();
+//! meta.set_title("Sales 2023");
+//! meta.set_creator("Jane Doe");
+//! meta.push_keyword("sales");
+//! meta.set_creation_date(Some(
+//!     NaiveDate::from_ymd_opt(2023, 1, 1)
+//!         .unwrap()
+//!         .and_hms_opt(12, 0, 0)
+//!         .unwrap(),
+//! ));
+//! meta.set_user_defined("Reviewed", MetaValue::Boolean(true));
+//! ```
+//!
+
+use crate::xmltree::XmlTag;
+use chrono::{Duration, NaiveDateTime};
+
+/// Value of a user-defined property.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub enum MetaValue {
+    Boolean(bool),
+    DateTime(NaiveDateTime),
+    Float(f64),
+    TimeDuration(Duration),
+    String(String),
+}
+
+impl MetaValue {
+    /// The meta:value-type of this value.
+    pub fn value_type(&self) -> &'static str {
+        match self {
+            MetaValue::Boolean(_) => "boolean",
+            MetaValue::DateTime(_) => "date",
+            MetaValue::Float(_) => "float",
+            MetaValue::TimeDuration(_) => "time",
+            MetaValue::String(_) => "string",
+        }
+    }
+}
+
+/// A user-defined property.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetaUserDefined {
+    name: String,
+    value: MetaValue,
+}
+
+impl MetaUserDefined {
+    /// New property.
+    pub fn new<S: Into<String>>(name: S, value: MetaValue) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+
+    /// Name of the property.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Value of the property.
+    pub fn value(&self) -> &MetaValue {
+        &self.value
+    }
+
+    /// Value of the property.
+    pub fn set_value(&mut self, value: MetaValue) {
+        self.value = value;
+    }
+}
+
+/// Metadata of the document.
+///
+/// Empty strings and None values are not written. The generator is
+/// always replaced with this library when writing, and a missing
+/// creation date is set to the current time.
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    generator: String,
+    title: String,
+    subject: String,
+    description: String,
+    keywords: Vec<String>,
+    initial_creator: String,
+    creator: String,
+    printed_by: String,
+    creation_date: Option<NaiveDateTime>,
+    modification_date: Option<NaiveDateTime>,
+    print_date: Option<NaiveDateTime>,
+    language: String,
+    editing_cycles: u32,
+    editing_duration: Duration,
+    user_defined: Vec<MetaUserDefined>,
+
+    /// Template, auto-reload, hyperlink-behaviour, document-statistic
+    /// and everything else.
+    pub(crate) extra: Vec<XmlTag>,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Self {
+            generator: Default::default(),
+            title: Default::default(),
+            subject: Default::default(),
+            description: Default::default(),
+            keywords: Default::default(),
+            initial_creator: Default::default(),
+            creator: Default::default(),
+            printed_by: Default::default(),
+            creation_date: None,
+            modification_date: None,
+            print_date: None,
+            language: Default::default(),
+            editing_cycles: 1,
+            editing_duration: Duration::zero(),
+            user_defined: Default::default(),
+            extra: Default::default(),
+        }
+    }
+}
+
+impl Metadata {
+    /// Empty metadata.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Application that last saved the document, as read.
+    pub fn generator(&self) -> &String {
+        &self.generator
+    }
+
+    /// Application that last saved the document.
+    pub(crate) fn set_generator<S: Into<String>>(&mut self, generator: S) {
+        self.generator = generator.into();
+    }
+
+    /// Title of the document.
+    pub fn title(&self) -> &String {
+        &self.title
+    }
+
+    /// Title of the document.
+    pub fn set_title<S: Into<String>>(&mut self, title: S) {
+        self.title = title.into();
+    }
+
+    /// Subject of the document.
+    pub fn subject(&self) -> &String {
+        &self.subject
+    }
+
+    /// Subject of the document.
+    pub fn set_subject<S: Into<String>>(&mut self, subject: S) {
+        self.subject = subject.into();
+    }
+
+    /// Description of the document.
+    pub fn description(&self) -> &String {
+        &self.description
+    }
+
+    /// Description of the document.
+    pub fn set_description<S: Into<String>>(&mut self, description: S) {
+        self.description = description.into();
+    }
+
+    /// Keywords.
+    pub fn keywords(&self) -> &Vec<String> {
+        &self.keywords
+    }
+
+    /// Keywords.
+    pub fn keywords_mut(&mut self) -> &mut Vec<String> {
+        &mut self.keywords
+    }
+
+    /// Adds a keyword.
+    pub fn push_keyword<S: Into<String>>(&mut self, keyword: S) {
+        self.keywords.push(keyword.into());
+    }
+
+    /// Author who created the document.
+    pub fn initial_creator(&self) -> &String {
+        &self.initial_creator
+    }
+
+    /// Author who created the document.
+    pub fn set_initial_creator<S: Into<String>>(&mut self, creator: S) {
+        self.initial_creator = creator.into();
+    }
+
+    /// Author who last modified the document.
+    pub fn creator(&self) -> &String {
+        &self.creator
+    }
+
+    /// Author who last modified the document.
+    pub fn set_creator<S: Into<String>>(&mut self, creator: S) {
+        self.creator = creator.into();
+    }
+
+    /// Who last printed the document.
+    pub fn printed_by(&self) -> &String {
+        &self.printed_by
+    }
+
+    /// Who last printed the document.
+    pub fn set_printed_by<S: Into<String>>(&mut self, printed_by: S) {
+        self.printed_by = printed_by.into();
+    }
+
+    /// Creation date.
+    pub fn creation_date(&self) -> Option<NaiveDateTime> {
+        self.creation_date
+    }
+
+    /// Creation date. If this is None, the current time is written.
+    pub fn set_creation_date(&mut self, date: Option<NaiveDateTime>) {
+        self.creation_date = date;
+    }
+
+    /// Date of the last modification. This is dc:date.
+    pub fn modification_date(&self) -> Option<NaiveDateTime> {
+        self.modification_date
+    }
+
+    /// Date of the last modification. This is dc:date.
+    pub fn set_modification_date(&mut self, date: Option<NaiveDateTime>) {
+        self.modification_date = date;
+    }
+
+    /// Date of the last printout.
+    pub fn print_date(&self) -> Option<NaiveDateTime> {
+        self.print_date
+    }
+
+    /// Date of the last printout.
+    pub fn set_print_date(&mut self, date: Option<NaiveDateTime>) {
+        self.print_date = date;
+    }
+
+    /// Default language of the document, e.g. "de-AT".
+    pub fn language(&self) -> &String {
+        &self.language
+    }
+
+    /// Default language of the document, e.g. "de-AT".
+    pub fn set_language<S: Into<String>>(&mut self, language: S) {
+        self.language = language.into();
+    }
+
+    /// Number of times the document has been edited.
+    pub fn editing_cycles(&self) -> u32 {
+        self.editing_cycles
+    }
+
+    /// Number of times the document has been edited.
+    pub fn set_editing_cycles(&mut self, cycles: u32) {
+        self.editing_cycles = cycles;
+    }
+
+    /// Total time spent editing the document.
+    pub fn editing_duration(&self) -> Duration {
+        self.editing_duration
+    }
+
+    /// Total time spent editing the document.
+    pub fn set_editing_duration(&mut self, duration: Duration) {
+        self.editing_duration = duration;
+    }
+
+    /// Sets a user-defined property. Replaces a property with the same
+    /// name.
+    pub fn set_user_defined<S: Into<String>>(&mut self, name: S, value: MetaValue) {
+        let name = name.into();
+        if let Some(v) = self.user_defined.iter_mut().find(|v| v.name == name) {
+            v.value = value;
+        } else {
+            self.user_defined.push(MetaUserDefined::new(name, value));
+        }
+    }
+
+    /// Returns a user-defined property.
+    pub fn user_defined(&self, name: &str) -> Option<&MetaValue> {
+        self.user_defined
+            .iter()
+            .find(|v| v.name == name)
+            .map(|v| &v.value)
+    }
+
+    /// Removes a user-defined property.
+    pub fn remove_user_defined(&mut self, name: &str) -> Option<MetaValue> {
+        let idx = self.user_defined.iter().position(|v| v.name == name)?;
+        Some(self.user_defined.remove(idx).value)
+    }
+
+    /// Iterates all user-defined properties in document order.
+    pub fn iter_user_defined(&self) -> impl Iterator<Item = &MetaUserDefined> {
+        self.user_defined.iter()
+    }
+}
@@ -0,0 +1,140 @@
+//!
+//! Named ranges and named expressions.
+//!
+//! Both can be defined for the whole workbook or only for one sheet.
+//! The names can be used in formulas and validations.
+//!
+//! ```
+//! use spreadsheet_ods::named::{NamedExpression, NamedRange};
+//! use spreadsheet_ods::{CellRange, CellRef, Sheet, WorkBook};
+//!
+//! let mut book = WorkBook::new_empty();
+//! book.add_named_range(NamedRange::new(
+//!     "Prices",
+//!     CellRange::remote("Data", 1, 1, 20, 1).absolute(),
+//! ));
+//!
+//! let mut sheet = Sheet::new("Data");
+//! let mut expr = NamedExpression::new("Twice", "of:=2*[.A1]");
+//! expr.set_base_cell(Some(CellRef::remote("Data", 0, 0)));
+//! sheet.add_named_expression(expr);
+//! sheet.set_formula(0, 3, "of:=SUM([Prices])");
+//! book.push_sheet(sheet);
+//!
+//! assert!(book.named_range("Prices").is_some());
+//! ```
+//!
+
+use crate::{CellRange, CellRef};
+
+/// A named cell-range.
+#[derive(Clone, Debug, Default)]
+pub struct NamedRange {
+    name: String,
+    range: CellRange,
+    base_cell: Option<CellRef>,
+    usable_as: Option<String>,
+}
+
+impl NamedRange {
+    /// Named range. The range should contain the sheet name.
+    pub fn new<S: Into<String>>(name: S, range: CellRange) -> Self {
+        Self {
+            name: name.into(),
+            range,
+            base_cell: None,
+            usable_as: None,
+        }
+    }
+
+    /// Name.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Name.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// The named range.
+    pub fn range(&self) -> &CellRange {
+        &self.range
+    }
+
+    /// The named range.
+    pub fn set_range(&mut self, range: CellRange) {
+        self.range = range;
+    }
+
+    /// Relative references in the range are relative to this cell.
+    pub fn base_cell(&self) -> Option<&CellRef> {
+        self.base_cell.as_ref()
+    }
+
+    /// Relative references in the range are relative to this cell.
+    pub fn set_base_cell(&mut self, base_cell: Option<CellRef>) {
+        self.base_cell = base_cell;
+    }
+
+    /// Other uses of the range as a whitespace separated list of
+    /// "print-range", "filter", "repeat-row" and "repeat-column".
+    pub fn usable_as(&self) -> Option<&String> {
+        self.usable_as.as_ref()
+    }
+
+    /// Other uses of the range.
+    pub fn set_usable_as(&mut self, usable_as: Option<String>) {
+        self.usable_as = usable_as;
+    }
+}
+
+/// A named formula expression.
+#[derive(Clone, Debug, Default)]
+pub struct NamedExpression {
+    name: String,
+    expression: String,
+    base_cell: Option<CellRef>,
+}
+
+impl NamedExpression {
+    /// Named expression. The expression is a formula like for a cell,
+    /// with the namespace prefix "of:=".
+    pub fn new<S: Into<String>, T: Into<String>>(name: S, expression: T) -> Self {
+        Self {
+            name: name.into(),
+            expression: expression.into(),
+            base_cell: None,
+        }
+    }
+
+    /// Name.
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Name.
+    pub fn set_name<S: Into<String>>(&mut self, name: S) {
+        self.name = name.into();
+    }
+
+    /// The expression.
+    pub fn expression(&self) -> &String {
+        &self.expression
+    }
+
+    /// The expression.
+    pub fn set_expression<S: Into<String>>(&mut self, expression: S) {
+        self.expression = expression.into();
+    }
+
+    /// Relative references in the expression are relative to this cell.
+    pub fn base_cell(&self) -> Option<&CellRef> {
+        self.base_cell.as_ref()
+    }
+
+    /// Relative references in the expression are relative to this cell.
+    pub fn set_base_cell(&mut self, base_cell: Option<CellRef>) {
+        self.base_cell = base_cell;
+    }
+}
@@ -0,0 +1,200 @@
+//!
+//! Protection of sheets.
+//!
+//! A protected sheet only allows editing of cells whose cell-style is not
+//! protected. The password is stored as a SHA-256 hash, the same as
+//! LibreOffice does.
+//!
+//! ```
+//! use spreadsheet_ods::protection::ProtectionOptions;
+//! use spreadsheet_ods::defaultstyles::DefaultFormat;
+//! use spreadsheet_ods::{CellStyle, Sheet, WorkBook};
+//!
+//! let mut book = WorkBook::new_empty();
+//! let mut input = CellStyle::new("input", &DefaultFormat::default());
+//! input.set_protected(false);
+//! let input = book.add_cellstyle(input);
+//!
+//! let mut sheet = Sheet::new("Data");
+//! sheet.set_styled_value(0, 0, 42, &input);
+//! let mut options = ProtectionOptions::new();
+//! options.set_insert_rows(true);
+//! sheet.protect("secret", options);
+//!
+//! let protection = sheet.protection().expect("protected");
+//! assert!(protection.verify("secret"));
+//! assert!(!protection.verify("guess"));
+//! book.push_sheet(sheet);
+//! ```
+//!
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// Digest algorithm used by protect().
+pub const SHA256_URN: &str = "http://www.w3.org/2000/09/xmldsig#sha256";
+/// Digest algorithm of older documents.
+pub const SHA1_URN: &str = "http://www.w3.org/2000/09/xmldsig#sha1";
+
+/// What is still allowed on a protected sheet.
+///
+/// These are stored in the LibreOffice extension element
+/// loext:table-protection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtectionOptions {
+    select_protected_cells: bool,
+    select_unprotected_cells: bool,
+    insert_columns: bool,
+    insert_rows: bool,
+    delete_columns: bool,
+    delete_rows: bool,
+}
+
+impl Default for ProtectionOptions {
+    fn default() -> Self {
+        Self {
+            select_protected_cells: true,
+            select_unprotected_cells: true,
+            insert_columns: false,
+            insert_rows: false,
+            delete_columns: false,
+            delete_rows: false,
+        }
+    }
+}
+
+impl ProtectionOptions {
+    /// Selecting cells is allowed, everything else is forbidden.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Protected cells can be selected.
+    pub fn select_protected_cells(&self) -> bool {
+        self.select_protected_cells
+    }
+
+    /// Protected cells can be selected.
+    pub fn set_select_protected_cells(&mut self, select: bool) {
+        self.select_protected_cells = select;
+    }
+
+    /// Unprotected cells can be selected.
+    pub fn select_unprotected_cells(&self) -> bool {
+        self.select_unprotected_cells
+    }
+
+    /// Unprotected cells can be selected.
+    pub fn set_select_unprotected_cells(&mut self, select: bool) {
+        self.select_unprotected_cells = select;
+    }
+
+    /// Columns can be inserted.
+    pub fn insert_columns(&self) -> bool {
+        self.insert_columns
+    }
+
+    /// Columns can be inserted.
+    pub fn set_insert_columns(&mut self, insert: bool) {
+        self.insert_columns = insert;
+    }
+
+    /// Rows can be inserted.
+    pub fn insert_rows(&self) -> bool {
+        self.insert_rows
+    }
+
+    /// Rows can be inserted.
+    pub fn set_insert_rows(&mut self, insert: bool) {
+        self.insert_rows = insert;
+    }
+
+    /// Columns can be deleted.
+    pub fn delete_columns(&self) -> bool {
+        self.delete_columns
+    }
+
+    /// Columns can be deleted.
+    pub fn set_delete_columns(&mut self, delete: bool) {
+        self.delete_columns = delete;
+    }
+
+    /// Rows can be deleted.
+    pub fn delete_rows(&self) -> bool {
+        self.delete_rows
+    }
+
+    /// Rows can be deleted.
+    pub fn set_delete_rows(&mut self, delete: bool) {
+        self.delete_rows = delete;
+    }
+}
+
+/// Protection of a sheet.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SheetProtection {
+    pub(crate) key: String,
+    pub(crate) digest_algorithm: Option<String>,
+    pub(crate) options: ProtectionOptions,
+}
+
+impl SheetProtection {
+    /// Protection with the given password. An empty password protects
+    /// the sheet without a password.
+    pub fn new(password: &str, options: ProtectionOptions) -> Self {
+        if password.is_empty() {
+            Self {
+                key: Default::default(),
+                digest_algorithm: None,
+                options,
+            }
+        } else {
+            Self {
+                key: STANDARD.encode(Sha256::digest(password.as_bytes())),
+                digest_algorithm: Some(SHA256_URN.to_string()),
+                options,
+            }
+        }
+    }
+
+    /// The sheet has a password.
+    pub fn has_password(&self) -> bool {
+        !self.key.is_empty()
+    }
+
+    /// Checks the password. Only SHA-256 and SHA-1 digests can be
+    /// verified, for other algorithms this always returns false.
+    pub fn verify(&self, password: &str) -> bool {
+        if self.key.is_empty() {
+            return password.is_empty();
+        }
+        let digest = match self.digest_algorithm.as_deref() {
+            Some(SHA256_URN) => Sha256::digest(password.as_bytes()).to_vec(),
+            Some(SHA1_URN) => Sha1::digest(password.as_bytes()).to_vec(),
+            _ => return false,
+        };
+        STANDARD.encode(digest) == self.key
+    }
+
+    /// Base64 encoded hash of the password.
+    pub fn key(&self) -> &str {
+        self.key.as_str()
+    }
+
+    /// Digest algorithm of the key.
+    pub fn digest_algorithm(&self) -> Option<&String> {
+        self.digest_algorithm.as_ref()
+    }
+
+    /// What is still allowed on the protected sheet.
+    pub fn options(&self) -> &ProtectionOptions {
+        &self.options
+    }
+
+    /// What is still allowed on the protected sheet.
+    pub fn options_mut(&mut self) -> &mut ProtectionOptions {
+        &mut self.options
+    }
+}
@@ -6,11 +6,21 @@ use crate::refs::format_refs::{
     fmt_cell_range, fmt_cell_ref, fmt_col, fmt_col_range, fmt_row, fmt_row_range,
 };
 use crate::refs_impl::error::OFCode;
-use crate::refs_impl::{check_eof, parser, Span};
+use crate::refs_impl::{check_eof, parser, ParseResult, Span};
 use crate::OdsError;
 use std::fmt;
 use std::fmt::{Display, Formatter, Write};
 
+/// Rows or columns of a reference that were deleted. LibreOffice writes
+/// them as #REF! and keeps the rest of the reference.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RefError {
+    /// The rows were deleted.
+    pub(crate) rows: bool,
+    /// The columns were deleted.
+    pub(crate) cols: bool,
+}
+
 /// Basic cell reference.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct CRow {
@@ -201,11 +211,16 @@ impl CellRef {
 
     /// Returns a cell reference for a formula.
     pub fn to_formula(&self) -> String {
+        self.to_formula_err(RefError::default())
+    }
+
+    /// Returns a cell reference for a formula. The deleted rows or
+    /// columns are written as #REF!.
+    pub(crate) fn to_formula_err(&self, err: RefError) -> String {
         let mut buf = String::new();
         buf.push('[');
-        let _ = write!(buf, "{}", Fmt(|f| fmt_cell_ref(f, self)));
+        let _ = write!(buf, "{}", Fmt(|f| fmt_cell_ref(f, self, err)));
         buf.push(']');
-
         buf
     }
 
@@ -241,7 +256,7 @@ impl TryFrom<&str> for CellRef {
 
 impl Display for CellRef {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        fmt_cell_ref(f, self)
+        fmt_cell_ref(f, self, RefError::default())
     }
 }
 
@@ -519,9 +534,15 @@ impl CellRange {
 
     /// Returns a range reference for a formula.
     pub fn to_formula(&self) -> String {
+        self.to_formula_err(RefError::default())
+    }
+
+    /// Returns a range reference for a formula. The deleted rows or
+    /// columns are written as #REF!.
+    pub(crate) fn to_formula_err(&self, err: RefError) -> String {
         let mut buf = String::new();
         buf.push('[');
-        let _ = write!(buf, "{}", Fmt(|f| fmt_cell_range(f, self)));
+        let _ = write!(buf, "{}", Fmt(|f| fmt_cell_range(f, self, err)));
         buf.push(']');
         buf
     }
@@ -576,7 +597,7 @@ impl TryFrom<&str> for CellRange {
 
 impl Display for CellRange {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        fmt_cell_range(f, self)
+        fmt_cell_range(f, self, RefError::default())
     }
 }
 
@@ -715,9 +736,15 @@ impl ColRange {
 
     /// Returns a range reference for a formula.
     pub fn to_formula(&self) -> String {
+        self.to_formula_err(RefError::default())
+    }
+
+    /// Returns a range reference for a formula. The deleted rows or
+    /// columns are written as #REF!.
+    pub(crate) fn to_formula_err(&self, err: RefError) -> String {
         let mut buf = String::new();
         buf.push('[');
-        let _ = write!(buf, "{}", Fmt(|f| fmt_col_range(f, self)));
+        let _ = write!(buf, "{}", Fmt(|f| fmt_col_range(f, self, err)));
         buf.push(']');
         buf
     }
@@ -746,7 +773,7 @@ impl TryFrom<&str> for ColRange {
 
 impl Display for ColRange {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        fmt_col_range(f, self)
+        fmt_col_range(f, self, RefError::default())
     }
 }
 
@@ -882,9 +909,15 @@ impl RowRange {
 
     /// Returns a range reference for a formula.
     pub fn to_formula(&self) -> String {
+        self.to_formula_err(RefError::default())
+    }
+
+    /// Returns a range reference for a formula. The deleted rows or
+    /// columns are written as #REF!.
+    pub(crate) fn to_formula_err(&self, err: RefError) -> String {
         let mut buf = String::new();
         buf.push('[');
-        let _ = write!(buf, "{}", Fmt(|f| fmt_row_range(f, self)));
+        let _ = write!(buf, "{}", Fmt(|f| fmt_row_range(f, self, err)));
         buf.push(']');
         buf
     }
@@ -913,12 +946,12 @@ impl TryFrom<&str> for RowRange {
 
 impl Display for RowRange {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", Fmt(|f| fmt_row_range(f, self)))
+        fmt_row_range(f, self, RefError::default())
     }
 }
 
 mod format_refs {
-    use crate::refs::{CCol, CRow};
+    use crate::refs::{CCol, CRow, RefError};
     use crate::refs_impl::format::{fmt_abs, fmt_col_name, fmt_row_name};
     use crate::{CellRange, CellRef, ColRange, RowRange};
     use std::fmt;
@@ -938,8 +971,32 @@ mod format_refs {
         Ok(())
     }
 
+    // Appends the row or #REF! if it was deleted.
+    fn fmt_row_err(f: &mut Formatter<'_>, row: &CRow, deleted: bool) -> fmt::Result {
+        if deleted {
+            fmt_abs(f, row.row_abs())?;
+            write!(f, "#REF!")
+        } else {
+            fmt_row(f, row)
+        }
+    }
+
+    // Appends the column or #REF! if it was deleted.
+    fn fmt_col_err(f: &mut Formatter<'_>, col: &CCol, deleted: bool) -> fmt::Result {
+        if deleted {
+            fmt_abs(f, col.col_abs())?;
+            write!(f, "#REF!")
+        } else {
+            fmt_col(f, col)
+        }
+    }
+
     /// Appends the cell reference
-    pub(crate) fn fmt_cell_ref(f: &mut Formatter<'_>, cell_ref: &CellRef) -> fmt::Result {
+    pub(crate) fn fmt_cell_ref(
+        f: &mut Formatter<'_>,
+        cell_ref: &CellRef,
+        err: RefError,
+    ) -> fmt::Result {
         fmt_iri(f, cell_ref.iri())?;
         if let Some(sheet) = cell_ref.table().as_ref() {
             fmt_table_name(
@@ -949,13 +1006,17 @@ mod format_refs {
             )?;
         }
         write!(f, ".")?;
-        fmt_col(f, &cell_ref.col)?;
-        fmt_row(f, &cell_ref.row)?;
+        fmt_col_err(f, &cell_ref.col, err.cols)?;
+        fmt_row_err(f, &cell_ref.row, err.rows)?;
         Ok(())
     }
 
     /// Appends the range reference
-    pub(crate) fn fmt_cell_range(f: &mut Formatter<'_>, cell_range: &CellRange) -> fmt::Result {
+    pub(crate) fn fmt_cell_range(
+        f: &mut Formatter<'_>,
+        cell_range: &CellRange,
+        err: RefError,
+    ) -> fmt::Result {
         fmt_iri(f, cell_range.iri())?;
         if let Some(table) = cell_range.table().as_ref() {
             fmt_table_name(
@@ -969,8 +1030,8 @@ mod format_refs {
             )?;
         }
         write!(f, ".")?;
-        fmt_col(f, &cell_range.from_col)?;
-        fmt_row(f, &cell_range.from_row)?;
+        fmt_col_err(f, &cell_range.from_col, err.cols)?;
+        fmt_row_err(f, &cell_range.from_row, err.rows)?;
         write!(f, ":")?;
         if let Some(to_table) = cell_range.to_table().as_ref() {
             fmt_table_name(
@@ -984,13 +1045,17 @@ mod format_refs {
             )?;
         }
         write!(f, ".")?;
-        fmt_col(f, &cell_range.to_col)?;
-        fmt_row(f, &cell_range.to_row)?;
+        fmt_col_err(f, &cell_range.to_col, err.cols)?;
+        fmt_row_err(f, &cell_range.to_row, err.rows)?;
         Ok(())
     }
 
     /// Appends the cell reference
-    pub(crate) fn fmt_col_range(f: &mut Formatter<'_>, col_range: &ColRange) -> fmt::Result {
+    pub(crate) fn fmt_col_range(
+        f: &mut Formatter<'_>,
+        col_range: &ColRange,
+        err: RefError,
+    ) -> fmt::Result {
         fmt_iri(f, col_range.iri())?;
         if let Some(sheet) = col_range.table().as_ref() {
             fmt_table_name(
@@ -1000,7 +1065,7 @@ mod format_refs {
             )?;
         }
         write!(f, ".")?;
-        fmt_col(f, &col_range.from_col)?;
+        fmt_col_err(f, &col_range.from_col, err.cols)?;
         write!(f, ":")?;
         if let Some(to_sheet) = col_range.to_table().as_ref() {
             fmt_table_name(
@@ -1010,12 +1075,16 @@ mod format_refs {
             )?;
         }
         write!(f, ".")?;
-        fmt_col(f, &col_range.to_col)?;
+        fmt_col_err(f, &col_range.to_col, err.cols)?;
         Ok(())
     }
 
     /// Appends the cell reference
-    pub(crate) fn fmt_row_range(f: &mut Formatter<'_>, row_range: &RowRange) -> fmt::Result {
+    pub(crate) fn fmt_row_range(
+        f: &mut Formatter<'_>,
+        row_range: &RowRange,
+        err: RefError,
+    ) -> fmt::Result {
         fmt_iri(f, row_range.iri())?;
         if let Some(table) = row_range.table().as_ref() {
             fmt_table_name(
@@ -1025,7 +1094,7 @@ mod format_refs {
             )?;
         }
         write!(f, ".")?;
-        fmt_row(f, &row_range.from_row)?;
+        fmt_row_err(f, &row_range.from_row, err.rows)?;
         write!(f, ":")?;
         if let Some(to_table) = row_range.to_table().as_ref() {
             fmt_table_name(
@@ -1035,7 +1104,7 @@ mod format_refs {
             )?;
         }
         write!(f, ".")?;
-        fmt_row(f, &row_range.to_row)?;
+        fmt_row_err(f, &row_range.to_row, err.rows)?;
         Ok(())
     }
 
@@ -1058,7 +1127,8 @@ mod format_refs {
         abs: bool,
     ) -> fmt::Result {
         fmt_abs(f, abs)?;
-        if table_name.contains(|c| c == '\'' || c == ' ' || c == '.') {
+        // Characters that are not allowed in an unquoted sheet-name.
+        if table_name.contains(['\'', ' ', '.', ']', '#', '$']) {
             write!(f, "'")?;
             write!(f, "{}", &table_name.replace('\'', "''"))?;
             write!(f, "'")?;
@@ -1071,80 +1141,112 @@ mod format_refs {
 
 /// Parse a cell reference.
 pub fn parse_cellref(buf: &str, _pos: &mut usize) -> Result<CellRef, OdsError> {
-    let rest = Span::new(buf);
-
-    let (rest, tok) = crate::refs_impl::parser::parse_cell_ref(rest)?;
+    let (rest, v) = parse_cellref_span(Span::new(buf))?;
 
     check_eof(rest, OFCode::OFCCellRef)?;
 
-    Ok(CellRef::new_all(
-        tok.iri.map(|v| v.iri),
-        tok.table.map(|v| v.name),
-        tok.row.abs,
-        tok.row.row,
-        tok.col.abs,
-        tok.col.col,
+    Ok(v)
+}
+
+// Parses a cell reference at the start of the span.
+pub(crate) fn parse_cellref_span(rest: Span<'_>) -> ParseResult<'_, CellRef> {
+    let (rest, tok) = parser::parse_cell_ref(rest)?;
+
+    Ok((
+        rest,
+        CellRef::new_all(
+            tok.iri.map(|v| v.iri),
+            tok.table.map(|v| v.name),
+            tok.row.abs,
+            tok.row.row,
+            tok.col.abs,
+            tok.col.col,
+        ),
     ))
 }
 
 /// Parse a cell reference.
 pub fn parse_cellrange(buf: &str, _pos: &mut usize) -> Result<CellRange, OdsError> {
-    let rest = Span::new(buf);
-
-    let (rest, tok) = crate::refs_impl::parser::parse_cell_range(rest)?;
+    let (rest, v) = parse_cellrange_span(Span::new(buf))?;
 
     check_eof(rest, OFCode::OFCCellRef)?;
 
-    Ok(CellRange::new_all(
-        tok.iri.map(|v| v.iri),
-        tok.table.map(|v| v.name),
-        tok.row.abs,
-        tok.row.row,
-        tok.col.abs,
-        tok.col.col,
-        tok.to_table.map(|v| v.name),
-        tok.to_row.abs,
-        tok.to_row.row,
-        tok.to_col.abs,
-        tok.to_col.col,
+    Ok(v)
+}
+
+// Parses a cell range at the start of the span.
+pub(crate) fn parse_cellrange_span(rest: Span<'_>) -> ParseResult<'_, CellRange> {
+    let (rest, tok) = parser::parse_cell_range(rest)?;
+
+    Ok((
+        rest,
+        CellRange::new_all(
+            tok.iri.map(|v| v.iri),
+            tok.table.map(|v| v.name),
+            tok.row.abs,
+            tok.row.row,
+            tok.col.abs,
+            tok.col.col,
+            tok.to_table.map(|v| v.name),
+            tok.to_row.abs,
+            tok.to_row.row,
+            tok.to_col.abs,
+            tok.to_col.col,
+        ),
     ))
 }
 
 /// Parse a cell reference.
 pub fn parse_colrange(buf: &str, _pos: &mut usize) -> Result<ColRange, OdsError> {
-    let rest = Span::new(buf);
-
-    let (rest, tok) = crate::refs_impl::parser::parse_col_range(rest)?;
+    let (rest, v) = parse_colrange_span(Span::new(buf))?;
 
     check_eof(rest, OFCode::OFCColRange)?;
 
-    Ok(ColRange::new_all(
-        tok.iri.map(|v| v.iri),
-        tok.table.map(|v| v.name),
-        tok.col.abs,
-        tok.col.col,
-        tok.to_table.map(|v| v.name),
-        tok.to_col.abs,
-        tok.to_col.col,
+    Ok(v)
+}
+
+// Parses a column range at the start of the span.
+pub(crate) fn parse_colrange_span(rest: Span<'_>) -> ParseResult<'_, ColRange> {
+    let (rest, tok) = parser::parse_col_range(rest)?;
+
+    Ok((
+        rest,
+        ColRange::new_all(
+            tok.iri.map(|v| v.iri),
+            tok.table.map(|v| v.name),
+            tok.col.abs,
+            tok.col.col,
+            tok.to_table.map(|v| v.name),
+            tok.to_col.abs,
+            tok.to_col.col,
+        ),
     ))
 }
 
 /// Parse a cell reference.
 pub fn parse_rowrange(buf: &str, _pos: &mut usize) -> Result<RowRange, OdsError> {
-    let rest = Span::new(buf);
-
-    let (rest, tok) = crate::refs_impl::parser::parse_row_range(rest)?;
+    let (rest, v) = parse_rowrange_span(Span::new(buf))?;
 
     check_eof(rest, OFCode::OFCRowRange)?;
 
-    Ok(RowRange::new_all(
-        tok.iri.map(|v| v.iri),
-        tok.table.map(|v| v.name),
-        tok.row.abs,
-        tok.row.row,
-        tok.to_table.map(|v| v.name),
-        tok.to_row.abs,
-        tok.to_row.row,
+    Ok(v)
+}
+
+// Parses a row range at the start of the span.
+pub(crate) fn parse_rowrange_span(rest: Span<'_>) -> ParseResult<'_, RowRange> {
+    let (rest, tok) = parser::parse_row_range(rest)?;
+
+    Ok((
+        rest,
+        RowRange::new_all(
+            tok.iri.map(|v| v.iri),
+            tok.table.map(|v| v.name),
+            tok.row.abs,
+            tok.row.row,
+            tok.to_table.map(|v| v.name),
+            tok.to_row.abs,
+            tok.to_row.row,
+        ),
     ))
 }
 
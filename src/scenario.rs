@@ -0,0 +1,169 @@
+//!
+//! Scenarios for what-if calculations.
+//!
+//! A scenario is a hidden sheet that follows its base sheet. It holds an
+//! alternative set of values for some cell-ranges of the base sheet.
+//! The active scenario is the one whose values are currently shown in the
+//! base sheet. With copy-back set, changes in the base sheet are copied
+//! back to the active scenario.
+//!
+//! ```
+//! use color::Rgb;
+//! use spreadsheet_ods::scenario::Scenario;
+//! use spreadsheet_ods::{CellRange, Sheet, WorkBook};
+//!
+//! let mut book = WorkBook::new_empty();
+//! let mut sheet = Sheet::new("Budget");
+//! sheet.set_value(0, 0, "Growth");
+//! sheet.set_value(0, 1, 0.02);
+//! book.push_sheet(sheet);
+//!
+//! let mut scenario = Scenario::new(vec![CellRange::local(0, 1, 0, 1)]);
+//! scenario.set_comment("Optimistic growth");
+//! scenario.set_border_color(Some(Rgb::new(0, 128, 0)));
+//! let idx = book.add_scenario(0, "Budget_Optimistic", scenario);
+//! book.sheet_mut(idx).set_value(0, 1, 0.05);
+//!
+//! assert_eq!(book.scenarios(0), vec![idx]);
+//! ```
+//!
+
+use crate::CellRange;
+use color::Rgb;
+
+/// Scenario data of a sheet. A sheet with this data is a scenario sheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    ranges: Vec<CellRange>,
+    active: bool,
+    display_border: bool,
+    border_color: Option<Rgb<u8>>,
+    copy_back: bool,
+    copy_styles: bool,
+    copy_formulas: bool,
+    protected: bool,
+    comment: String,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            ranges: Default::default(),
+            active: false,
+            display_border: true,
+            border_color: None,
+            copy_back: true,
+            copy_styles: true,
+            copy_formulas: true,
+            protected: false,
+            comment: Default::default(),
+        }
+    }
+}
+
+impl Scenario {
+    /// Scenario for the given cell-ranges.
+    pub fn new(ranges: Vec<CellRange>) -> Self {
+        Self {
+            ranges,
+            ..Default::default()
+        }
+    }
+
+    /// Cell-ranges that are part of the scenario.
+    pub fn ranges(&self) -> &Vec<CellRange> {
+        &self.ranges
+    }
+
+    /// Cell-ranges that are part of the scenario.
+    pub fn ranges_mut(&mut self) -> &mut Vec<CellRange> {
+        &mut self.ranges
+    }
+
+    /// Cell-ranges that are part of the scenario.
+    pub fn set_ranges(&mut self, ranges: Vec<CellRange>) {
+        self.ranges = ranges;
+    }
+
+    /// This is the active scenario of the base sheet.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// This is the active scenario of the base sheet.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    /// Display a border around the scenario ranges in the base sheet.
+    pub fn display_border(&self) -> bool {
+        self.display_border
+    }
+
+    /// Display a border around the scenario ranges in the base sheet.
+    pub fn set_display_border(&mut self, display_border: bool) {
+        self.display_border = display_border;
+    }
+
+    /// Color of the border. None uses the application default.
+    pub fn border_color(&self) -> Option<Rgb<u8>> {
+        self.border_color
+    }
+
+    /// Color of the border. None uses the application default.
+    pub fn set_border_color(&mut self, border_color: Option<Rgb<u8>>) {
+        self.border_color = border_color;
+    }
+
+    /// Changes in the base sheet are copied back to the scenario.
+    pub fn copy_back(&self) -> bool {
+        self.copy_back
+    }
+
+    /// Changes in the base sheet are copied back to the scenario.
+    pub fn set_copy_back(&mut self, copy_back: bool) {
+        self.copy_back = copy_back;
+    }
+
+    /// The cell-styles are copied together with the values.
+    pub fn copy_styles(&self) -> bool {
+        self.copy_styles
+    }
+
+    /// The cell-styles are copied together with the values.
+    pub fn set_copy_styles(&mut self, copy_styles: bool) {
+        self.copy_styles = copy_styles;
+    }
+
+    /// Formulas are copied as formulas. Otherwise only the values
+    /// are copied.
+    pub fn copy_formulas(&self) -> bool {
+        self.copy_formulas
+    }
+
+    /// Formulas are copied as formulas. Otherwise only the values
+    /// are copied.
+    pub fn set_copy_formulas(&mut self, copy_formulas: bool) {
+        self.copy_formulas = copy_formulas;
+    }
+
+    /// The scenario ranges can't be edited in the base sheet.
+    pub fn protected(&self) -> bool {
+        self.protected
+    }
+
+    /// The scenario ranges can't be edited in the base sheet.
+    pub fn set_protected(&mut self, protected: bool) {
+        self.protected = protected;
+    }
+
+    /// Comment for the scenario.
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    /// Comment for the scenario.
+    pub fn set_comment<S: Into<String>>(&mut self, comment: S) {
+        self.comment = comment.into();
+    }
+}
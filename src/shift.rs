@@ -0,0 +1,918 @@
+//!
+//! Moves the sheet data when rows or columns are inserted or deleted,
+//! or when a range is moved. The references in formulas, validations,
+//! stylemaps, conditional formats, named ranges, database ranges, data
+//! pilot tables and charts are changed too.
+//!
+
+use crate::chart::Chart;
+use crate::condformat::{ConditionalEntry, FormatCondition};
+use crate::condition::{Condition, ValueCondition};
+use crate::database::{DatabaseRange, FilterItem};
+use crate::named::{NamedExpression, NamedRange};
+use crate::refs::{
+    parse_cellrange_span, parse_cellref_span, parse_colrange_span, parse_rowrange_span, CellRange,
+    CellRef, ColRange, RefError, RowRange,
+};
+use crate::refs_impl::{tokens, Span};
+use crate::{CellData, CellSpan, Grouped, Sheet, SheetConfig, SplitMode, WorkBook};
+use nom::InputTake;
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+
+/// Structural edit of a sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Shift {
+    /// Rows or columns.
+    pub(crate) rows: bool,
+    /// Insert or delete.
+    pub(crate) insert: bool,
+    /// First inserted/deleted row or column.
+    pub(crate) pos: u32,
+    /// Number of rows or columns.
+    pub(crate) n: u32,
+}
+
+impl Shift {
+    pub(crate) fn new(rows: bool, insert: bool, pos: u32, n: u32) -> Self {
+        Self {
+            rows,
+            insert,
+            pos,
+            n,
+        }
+    }
+
+    /// New position of a single row or column. None if it was deleted.
+    pub(crate) fn index(&self, idx: u32) -> Option<u32> {
+        self.interval(idx, idx).map(|(v, _)| v)
+    }
+
+    /// New position of the inclusive interval from..=to. An insert within
+    /// the interval extends it, a delete shrinks it. None if the interval
+    /// was deleted completely or was moved beyond the last row/column.
+    pub(crate) fn interval(&self, from: u32, to: u32) -> Option<(u32, u32)> {
+        // Calculated with u64, the result may be beyond u32::MAX.
+        let (from, to) = (from as u64, to as u64);
+        let (pos, n) = (self.pos as u64, self.n as u64);
+        let (from, to) = if self.insert {
+            if pos <= from {
+                (from + n, to + n)
+            } else if pos <= to {
+                (from, to + n)
+            } else {
+                (from, to)
+            }
+        } else {
+            let end = pos + n;
+            if to < pos {
+                (from, to)
+            } else if from >= end {
+                (from - n, to - n)
+            } else {
+                let overlap = to.min(end - 1) + 1 - from.max(pos);
+                let len = to - from + 1 - overlap;
+                if len == 0 {
+                    return None;
+                }
+                let from = from.min(pos);
+                (from, from + len - 1)
+            }
+        };
+        // An insert cuts off at the last row/column.
+        let max = u32::MAX as u64;
+        if from > max {
+            None
+        } else {
+            Some((from as u32, to.min(max) as u32))
+        }
+    }
+
+    /// New position of a single row or column. A deleted row or column
+    /// moves to the first one after the deleted ones.
+    pub(crate) fn position(&self, idx: u32) -> u32 {
+        self.index(idx).unwrap_or(self.pos)
+    }
+
+    /// Adjusts the range. Returns false if the range was deleted
+    /// completely.
+    pub(crate) fn range(&self, range: &mut CellRange) -> bool {
+        if self.rows {
+            match self.interval(range.row(), range.to_row()) {
+                Some((from, to)) => {
+                    range.set_row(from);
+                    range.set_to_row(to);
+                    true
+                }
+                None => false,
+            }
+        } else {
+            match self.interval(range.col(), range.to_col()) {
+                Some((from, to)) => {
+                    range.set_col(from);
+                    range.set_to_col(to);
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    /// Adjusts the cell reference. A deleted cell moves to the first
+    /// row/column after the deleted ones.
+    pub(crate) fn cell(&self, cell: &mut CellRef) {
+        if self.rows {
+            cell.set_row(self.position(cell.row()));
+        } else {
+            cell.set_col(self.position(cell.col()));
+        }
+    }
+}
+
+// Row or column span of the cell.
+fn span(span: &CellSpan, rows: bool) -> u32 {
+    if rows {
+        span.row_span.max(1)
+    } else {
+        span.col_span.max(1)
+    }
+}
+
+fn set_span(span: &mut CellSpan, rows: bool, count: u32) {
+    if rows {
+        span.row_span = count;
+    } else {
+        span.col_span = count;
+    }
+}
+
+// Moves the cells and adjusts the spans. If the first cell of a span is
+// deleted, the rest of the span starts with the next remaining cell.
+fn shift_data(data: &mut BTreeMap<(u32, u32), CellData>, shift: Shift) {
+    let mut moved_spans = Vec::new();
+    for ((row, col), mut cell) in mem::take(data) {
+        let idx = if shift.rows { row } else { col };
+        let count = span(&cell.span, shift.rows);
+        let (from, to) = match shift.interval(idx, idx.saturating_add(count - 1)) {
+            Some(v) => v,
+            None => continue,
+        };
+        let key = if shift.rows { (from, col) } else { (row, from) };
+        set_span(&mut cell.span, shift.rows, to - from + 1);
+        if shift.index(idx).is_some() {
+            data.insert(key, cell);
+        } else {
+            moved_spans.push((key, cell.span));
+        }
+    }
+    for (key, span) in moved_spans {
+        data.entry(key).or_default().span = span;
+    }
+}
+
+// Moves the keys of the row or column header. A header row with a repeat
+// count grows or shrinks like a span.
+fn shift_header<T, F>(header: &mut BTreeMap<u32, T>, shift: Shift, repeat: F)
+where
+    F: Fn(&mut T) -> Option<&mut u32>,
+{
+    for (idx, mut value) in mem::take(header) {
+        let count = repeat(&mut value).map(|v| *v).unwrap_or(1);
+        if let Some((from, to)) = shift.interval(idx, idx.saturating_add(count - 1)) {
+            if let Some(repeat) = repeat(&mut value) {
+                *repeat = to - from + 1;
+            }
+            header.insert(from, value);
+        }
+    }
+}
+
+// Moves the cursor, the first visible rows/columns and a split on a
+// cell boundary.
+fn shift_config(config: &mut SheetConfig, shift: Shift) {
+    if shift.rows {
+        config.cursor_y = shift.position(config.cursor_y);
+        config.position_top = shift.position(config.position_top);
+        config.position_bottom = shift.position(config.position_bottom);
+        shift_split(
+            &mut config.vert_split_mode,
+            &mut config.vert_split_pos,
+            shift,
+        );
+    } else {
+        config.cursor_x = shift.position(config.cursor_x);
+        config.position_left = shift.position(config.position_left);
+        config.position_right = shift.position(config.position_right);
+        shift_split(&mut config.hor_split_mode, &mut config.hor_split_pos, shift);
+    }
+}
+
+// With SplitMode::Heading the split position is the number of fixed
+// rows/columns. Otherwise it's a pixel value.
+fn shift_split(mode: &mut SplitMode, pos: &mut u32, shift: Shift) {
+    if matches!(mode, SplitMode::Heading) && *pos > 0 {
+        match shift.interval(0, *pos - 1) {
+            Some((_, to)) => *pos = to.saturating_add(1),
+            None => {
+                *mode = SplitMode::None;
+                *pos = 0;
+            }
+        }
+    }
+}
+
+fn shift_groups(groups: &mut Vec<Grouped>, shift: Shift) {
+    groups.retain_mut(|group| match shift.interval(group.from, group.to) {
+        Some((from, to)) => {
+            group.from = from;
+            group.to = to;
+            true
+        }
+        None => false,
+    });
+}
+
+/// Inserts or deletes rows or columns of the sheet. Changes the
+/// references to this sheet in the formulas of this sheet.
+pub(crate) fn shift_sheet(sheet: &mut Sheet, shift: Shift) {
+    shift_data(&mut sheet.data, shift);
+    if shift.rows {
+        shift_header(&mut sheet.row_header, shift, |v| Some(&mut v.repeat));
+    } else {
+        shift_header(&mut sheet.col_header, shift, |_| None);
+    }
+
+    if let Some(print_ranges) = &mut sheet.print_ranges {
+        print_ranges.retain_mut(|v| shift.range(v));
+        if print_ranges.is_empty() {
+            sheet.print_ranges = None;
+        }
+    }
+    if shift.rows {
+        if let Some(header_rows) = &mut sheet.header_rows {
+            match shift.interval(header_rows.row(), header_rows.to_row()) {
+                Some((from, to)) => {
+                    header_rows.set_row(from);
+                    header_rows.set_to_row(to);
+                }
+                None => sheet.header_rows = None,
+            }
+        }
+        shift_groups(&mut sheet.group_rows, shift);
+    } else {
+        if let Some(header_cols) = &mut sheet.header_cols {
+            match shift.interval(header_cols.col(), header_cols.to_col()) {
+                Some((from, to)) => {
+                    header_cols.set_col(from);
+                    header_cols.set_to_col(to);
+                }
+                None => sheet.header_cols = None,
+            }
+        }
+        shift_groups(&mut sheet.group_cols, shift);
+    }
+
+    for cf in &mut sheet.conditional_formats {
+        let mut ranges = cf.ranges().clone();
+        ranges.retain_mut(|v| shift.range(v));
+        cf.set_ranges(ranges);
+    }
+    sheet.conditional_formats.retain(|v| !v.ranges().is_empty());
+
+    // The ranges of a scenario are the same as in the base sheet.
+    if let Some(scenario) = &mut sheet.scenario {
+        scenario.ranges_mut().retain_mut(|v| shift.range(v));
+    }
+
+    shift_config(&mut sheet.sheet_config, shift);
+
+    let name = sheet.name.clone();
+    adjust_sheet_refs(
+        sheet,
+        &ShiftRefs {
+            sheet: &name,
+            shift,
+        },
+    );
+}
+
+/// Inserts or deletes rows or columns of the sheet at index idx. Changes
+/// the references to this sheet in all formulas of the workbook.
+pub(crate) fn shift_book(book: &mut WorkBook, idx: usize, shift: Shift) {
+    let sheet = book.sheets[idx].as_mut();
+    shift_sheet(sheet, shift);
+
+    let name = sheet.name.clone();
+    if !shift.rows {
+        for db in book.database_ranges.values_mut() {
+            shift_database_fields(db, &name, shift);
+        }
+    }
+    adjust_book_refs(
+        book,
+        Some(idx),
+        &ShiftRefs {
+            sheet: &name,
+            shift,
+        },
+    );
+
+    // The scenario sheets mirror the cells of the base sheet.
+    if !book.sheets[idx].is_scenario() {
+        for scenario_idx in book.scenarios(idx) {
+            shift_book(book, scenario_idx, shift);
+        }
+    }
+}
+
+// The fields of filters and sort keys are column indices relative to the
+// database range. Must be called before the range itself is changed.
+fn shift_database_fields(db: &mut DatabaseRange, sheet: &str, shift: Shift) {
+    let range = FormulaRef::Range(db.range().clone());
+    if !range.refers_to(sheet, "") {
+        return;
+    }
+    let col = db.range().col();
+    let new_col = match shift.interval(col, db.range().to_col()) {
+        Some((from, _)) => from,
+        None => return,
+    };
+    let field = |v: u32| {
+        shift
+            .index(col.saturating_add(v))
+            .map(|v| v.saturating_sub(new_col))
+    };
+
+    db.sort_keys_mut().retain_mut(|v| match field(v.field()) {
+        Some(f) => {
+            v.set_field(f);
+            true
+        }
+        None => false,
+    });
+    let filter = db
+        .filter_mut()
+        .map(|v| shift_filter_item(v.item_mut(), &field));
+    if filter == Some(false) {
+        db.set_filter(None);
+    }
+}
+
+// Changes the fields of the conditions. Returns false if no condition
+// is left.
+fn shift_filter_item<F>(item: &mut FilterItem, field: &F) -> bool
+where
+    F: Fn(u32) -> Option<u32>,
+{
+    match item {
+        FilterItem::And(items) | FilterItem::Or(items) => {
+            items.retain_mut(|v| shift_filter_item(v, field));
+            !items.is_empty()
+        }
+        FilterItem::Condition(c) => match field(c.field()) {
+            Some(f) => {
+                c.set_field(f);
+                true
+            }
+            None => false,
+        },
+    }
+}
+
+/// Moves the cells of the range in the sheet at index idx to the sheet at
+/// index to_idx. The references to the moved cells are changed in all
+/// formulas of the workbook.
+pub(crate) fn move_range(
+    book: &mut WorkBook,
+    idx: usize,
+    range: &CellRange,
+    to_idx: usize,
+    row: u32,
+    col: u32,
+) {
+    let src = book.sheets[idx].as_ref().name.clone();
+    let dst = book.sheets[to_idx].as_ref().name.clone();
+    let mv = MoveRefs {
+        src: &src,
+        dst: &dst,
+        row: range.row(),
+        col: range.col(),
+        to_row: range.to_row(),
+        to_col: range.to_col(),
+        dst_row: row,
+        dst_col: col,
+    };
+    adjust_book_refs(book, None, &mv);
+
+    let mut moved = Vec::new();
+    let data = &mut book.sheets[idx].as_mut().data;
+    let keys: Vec<(u32, u32)> = data
+        .range((mv.row, mv.col)..=(mv.to_row, mv.to_col))
+        .map(|(k, _)| *k)
+        .filter(|(row, col)| mv.contains(*row, *col))
+        .collect();
+    for (row, col) in keys {
+        if let Some(cell) = data.remove(&(row, col)) {
+            moved.push((mv.moved(row, col), cell));
+        }
+    }
+
+    let data = &mut book.sheets[to_idx].as_mut().data;
+    let to_row = row + (mv.to_row - mv.row);
+    let to_col = col + (mv.to_col - mv.col);
+    data.retain(|(r, c), _| *r < row || *r > to_row || *c < col || *c > to_col);
+    for (key, mut cell) in moved {
+        // The formula is now in another sheet.
+        if src != dst {
+            if let Some(formula) = &cell.formula {
+                cell.formula = Some(rewrite_refs(formula, |r| {
+                    if r.iri().is_none() && r.table().is_none() {
+                        r.set_table(&src);
+                    }
+                    Ok(())
+                }));
+            }
+        }
+        data.insert(key, cell);
+    }
+}
+
+/// A reference in a formula.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FormulaRef {
+    Cell(CellRef),
+    Range(CellRange),
+    Cols(ColRange),
+    Rows(RowRange),
+}
+
+impl FormulaRef {
+    // Parses a reference in brackets at the start of the span.
+    fn parse(rest: Span<'_>) -> Option<(Span<'_>, Self)> {
+        let (rest, _) = tokens::brackets_open(rest).ok()?;
+        let (rest, r) = if let Ok((rest, v)) = parse_cellrange_span(rest) {
+            (rest, FormulaRef::Range(v))
+        } else if let Ok((rest, v)) = parse_cellref_span(rest) {
+            (rest, FormulaRef::Cell(v))
+        } else if let Ok((rest, v)) = parse_colrange_span(rest) {
+            (rest, FormulaRef::Cols(v))
+        } else if let Ok((rest, v)) = parse_rowrange_span(rest) {
+            (rest, FormulaRef::Rows(v))
+        } else {
+            return None;
+        };
+        let (rest, _) = tokens::brackets_close(tokens::eat_space(rest)).ok()?;
+        Some((rest, r))
+    }
+
+    // The deleted rows/columns are written as #REF!.
+    fn to_formula(&self, err: RefError) -> String {
+        match self {
+            FormulaRef::Cell(v) => v.to_formula_err(err),
+            FormulaRef::Range(v) => v.to_formula_err(err),
+            FormulaRef::Cols(v) => v.to_formula_err(err),
+            FormulaRef::Rows(v) => v.to_formula_err(err),
+        }
+    }
+
+    fn iri(&self) -> Option<&String> {
+        match self {
+            FormulaRef::Cell(v) => v.iri(),
+            FormulaRef::Range(v) => v.iri(),
+            FormulaRef::Cols(v) => v.iri(),
+            FormulaRef::Rows(v) => v.iri(),
+        }
+    }
+
+    fn table(&self) -> Option<&String> {
+        match self {
+            FormulaRef::Cell(v) => v.table(),
+            FormulaRef::Range(v) => v.table(),
+            FormulaRef::Cols(v) => v.table(),
+            FormulaRef::Rows(v) => v.table(),
+        }
+    }
+
+    fn to_table(&self) -> Option<&String> {
+        match self {
+            FormulaRef::Cell(_) => None,
+            FormulaRef::Range(v) => v.to_table(),
+            FormulaRef::Cols(v) => v.to_table(),
+            FormulaRef::Rows(v) => v.to_table(),
+        }
+    }
+
+    // Sets the table. An existing to_table is changed too.
+    fn set_table(&mut self, table: &str) {
+        let to_table = self.to_table().is_some();
+        match self {
+            FormulaRef::Cell(v) => v.set_table(table),
+            FormulaRef::Range(v) => {
+                v.set_table(table);
+                if to_table {
+                    v.set_to_table(table);
+                }
+            }
+            FormulaRef::Cols(v) => {
+                v.set_table(table);
+                if to_table {
+                    v.set_to_table(table);
+                }
+            }
+            FormulaRef::Rows(v) => {
+                v.set_table(table);
+                if to_table {
+                    v.set_to_table(table);
+                }
+            }
+        }
+    }
+
+    // The reference points to the sheet. A reference without a table
+    // points to the sheet that contains the formula. References with an
+    // IRI point to another document.
+    fn refers_to(&self, sheet: &str, context: &str) -> bool {
+        if self.iri().is_some() {
+            return false;
+        }
+        let table = self.table().map(|v| v.as_str()).unwrap_or(context);
+        let to_table = self.to_table().map(|v| v.as_str()).unwrap_or(table);
+        table == sheet && to_table == sheet
+    }
+}
+
+/// Calls f for each reference in the formula and returns the changed
+/// formula. If f returns an error the deleted rows or columns of the
+/// reference are replaced with #REF!.
+pub(crate) fn rewrite_refs<F>(formula: &str, mut f: F) -> String
+where
+    F: FnMut(&mut FormulaRef) -> Result<(), RefError>,
+{
+    let mut buf = String::with_capacity(formula.len());
+    let mut rest = Span::new(formula);
+    while let Some(c) = rest.chars().next() {
+        // String literals can look like references.
+        if let Ok((rest1, tok)) = tokens::string(rest) {
+            buf.push_str(tok.fragment());
+            rest = rest1;
+        } else if let Some((rest1, r)) = FormulaRef::parse(rest) {
+            let mut v = r.clone();
+            match f(&mut v) {
+                Err(err) => buf.push_str(&r.to_formula(err)),
+                Ok(()) if v != r => buf.push_str(&v.to_formula(RefError::default())),
+                Ok(()) => buf.push_str(&formula[rest.location_offset()..rest1.location_offset()]),
+            }
+            rest = rest1;
+        } else {
+            buf.push(c);
+            rest = rest.take_split(c.len_utf8()).0;
+        }
+    }
+    buf
+}
+
+/// Changes the references in formulas.
+pub(crate) trait AdjustRefs {
+    /// Changes a reference in a formula of the sheet context. Fails if
+    /// the referenced cells were deleted.
+    fn adjust_ref(&self, r: &mut FormulaRef, context: &str) -> Result<(), RefError>;
+
+    /// Changes a base cell.
+    fn adjust_cell(&self, cell: &mut CellRef, context: &str);
+}
+
+// References for inserted or deleted rows or columns of one sheet.
+struct ShiftRefs<'a> {
+    sheet: &'a str,
+    shift: Shift,
+}
+
+impl AdjustRefs for ShiftRefs<'_> {
+    fn adjust_ref(&self, r: &mut FormulaRef, context: &str) -> Result<(), RefError> {
+        if !r.refers_to(self.sheet, context) {
+            return Ok(());
+        }
+        let shift = self.shift;
+        let err = RefError {
+            rows: shift.rows,
+            cols: !shift.rows,
+        };
+        match r {
+            FormulaRef::Cell(v) => {
+                let idx = if shift.rows { v.row() } else { v.col() };
+                match shift.index(idx) {
+                    Some(idx) if shift.rows => v.set_row(idx),
+                    Some(idx) => v.set_col(idx),
+                    None => return Err(err),
+                }
+            }
+            FormulaRef::Range(v) => {
+                if !shift.range(v) {
+                    return Err(err);
+                }
+            }
+            FormulaRef::Cols(v) if !shift.rows => match shift.interval(v.col(), v.to_col()) {
+                Some((from, to)) => {
+                    v.set_col(from);
+                    v.set_to_col(to);
+                }
+                None => return Err(err),
+            },
+            FormulaRef::Rows(v) if shift.rows => match shift.interval(v.row(), v.to_row()) {
+                Some((from, to)) => {
+                    v.set_row(from);
+                    v.set_to_row(to);
+                }
+                None => return Err(err),
+            },
+            FormulaRef::Cols(_) | FormulaRef::Rows(_) => {}
+        }
+        Ok(())
+    }
+
+    fn adjust_cell(&self, cell: &mut CellRef, context: &str) {
+        if cell.table().map(|v| v.as_str()).unwrap_or(context) == self.sheet {
+            self.shift.cell(cell);
+        }
+    }
+}
+
+// References for a moved range.
+struct MoveRefs<'a> {
+    src: &'a str,
+    dst: &'a str,
+    row: u32,
+    col: u32,
+    to_row: u32,
+    to_col: u32,
+    dst_row: u32,
+    dst_col: u32,
+}
+
+impl MoveRefs<'_> {
+    fn contains(&self, row: u32, col: u32) -> bool {
+        row >= self.row && row <= self.to_row && col >= self.col && col <= self.to_col
+    }
+
+    fn moved(&self, row: u32, col: u32) -> (u32, u32) {
+        (row - self.row + self.dst_row, col - self.col + self.dst_col)
+    }
+
+    // The cell is in the destination and is replaced by a moved cell.
+    fn contains_dst(&self, row: u32, col: u32) -> bool {
+        row >= self.dst_row
+            && row - self.dst_row <= self.to_row - self.row
+            && col >= self.dst_col
+            && col - self.dst_col <= self.to_col - self.col
+    }
+
+    // References to cells that are overwritten by the move are deleted.
+    // A range is deleted only if it is within the destination.
+    fn overwritten(&self, r: &FormulaRef, context: &str) -> Result<(), RefError> {
+        if !r.refers_to(self.dst, context) {
+            return Ok(());
+        }
+        let overwritten = match r {
+            FormulaRef::Cell(v) => self.contains_dst(v.row(), v.col()),
+            FormulaRef::Range(v) => {
+                self.contains_dst(v.row(), v.col()) && self.contains_dst(v.to_row(), v.to_col())
+            }
+            FormulaRef::Cols(_) | FormulaRef::Rows(_) => false,
+        };
+        if overwritten {
+            Err(RefError {
+                rows: true,
+                cols: true,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl AdjustRefs for MoveRefs<'_> {
+    fn adjust_ref(&self, r: &mut FormulaRef, context: &str) -> Result<(), RefError> {
+        if !r.refers_to(self.src, context) {
+            return self.overwritten(r, context);
+        }
+        match r {
+            FormulaRef::Cell(v) if self.contains(v.row(), v.col()) => {
+                let (row, col) = self.moved(v.row(), v.col());
+                v.set_row(row);
+                v.set_col(col);
+            }
+            FormulaRef::Range(v)
+                if self.contains(v.row(), v.col()) && self.contains(v.to_row(), v.to_col()) =>
+            {
+                let (row, col) = self.moved(v.row(), v.col());
+                let (to_row, to_col) = self.moved(v.to_row(), v.to_col());
+                v.set_row(row);
+                v.set_col(col);
+                v.set_to_row(to_row);
+                v.set_to_col(to_col);
+            }
+            _ => return self.overwritten(r, context),
+        }
+        if r.table().is_some() || self.dst != context {
+            r.set_table(self.dst);
+        }
+        Ok(())
+    }
+
+    fn adjust_cell(&self, cell: &mut CellRef, context: &str) {
+        if cell.table().map(|v| v.as_str()).unwrap_or(context) == self.src
+            && self.contains(cell.row(), cell.col())
+        {
+            let (row, col) = self.moved(cell.row(), cell.col());
+            cell.set_row(row);
+            cell.set_col(col);
+            if cell.table().is_some() || self.dst != context {
+                cell.set_table(self.dst);
+            }
+        }
+    }
+}
+
+// Changes a cell-range. Returns false if the range was deleted.
+fn adjust_range(adjust: &dyn AdjustRefs, range: &mut CellRange, context: &str) -> bool {
+    let mut r = FormulaRef::Range(range.clone());
+    let valid = adjust.adjust_ref(&mut r, context).is_ok();
+    if let FormulaRef::Range(v) = r {
+        *range = v;
+    }
+    valid
+}
+
+// Changes a cell reference. Returns false if the cell was deleted.
+fn adjust_cellref(adjust: &dyn AdjustRefs, cell: &mut CellRef, context: &str) -> bool {
+    let mut r = FormulaRef::Cell(cell.clone());
+    let valid = adjust.adjust_ref(&mut r, context).is_ok();
+    if let FormulaRef::Cell(v) = r {
+        *cell = v;
+    }
+    valid
+}
+
+fn adjust_named_ranges(
+    ranges: &mut HashMap<String, NamedRange>,
+    adjust: &dyn AdjustRefs,
+    context: Option<&str>,
+) {
+    ranges.retain(|_, v| {
+        let context = named_context(v.base_cell(), context);
+        let mut range = v.range().clone();
+        if !adjust_range(adjust, &mut range, &context) {
+            return false;
+        }
+        v.set_range(range);
+        if let Some(base_cell) = v.base_cell() {
+            let mut base_cell = base_cell.clone();
+            adjust.adjust_cell(&mut base_cell, &context);
+            v.set_base_cell(Some(base_cell));
+        }
+        true
+    });
+}
+
+fn adjust_named_expressions(
+    exprs: &mut HashMap<String, NamedExpression>,
+    adjust: &dyn AdjustRefs,
+    context: Option<&str>,
+) {
+    for v in exprs.values_mut() {
+        let context = named_context(v.base_cell(), context);
+        let expression = rewrite_refs(v.expression(), |r| adjust.adjust_ref(r, &context));
+        v.set_expression(expression);
+        if let Some(base_cell) = v.base_cell() {
+            let mut base_cell = base_cell.clone();
+            adjust.adjust_cell(&mut base_cell, &context);
+            v.set_base_cell(Some(base_cell));
+        }
+    }
+}
+
+// Names of a sheet are relative to the sheet, names of the workbook
+// to the table of their base cell.
+fn named_context(base_cell: Option<&CellRef>, context: Option<&str>) -> String {
+    match context {
+        Some(context) => context.to_string(),
+        None => base_cell
+            .and_then(|v| v.table())
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+// Changes the ranges of the chart. Series without values are removed.
+fn adjust_chart(chart: &mut Chart, adjust: &dyn AdjustRefs, context: &str) {
+    if let Some(categories) = chart.categories() {
+        let mut categories = categories.clone();
+        let valid = adjust_range(adjust, &mut categories, context);
+        chart.set_categories(valid.then_some(categories));
+    }
+    chart.series_mut().retain_mut(|series| {
+        let mut values = series.values().clone();
+        if !adjust_range(adjust, &mut values, context) {
+            return false;
+        }
+        series.set_values(values);
+        if let Some(label) = series.label() {
+            let mut label = label.clone();
+            let valid = adjust_cellref(adjust, &mut label, context);
+            series.set_label(valid.then_some(label));
+        }
+        if let Some(domain) = series.domain() {
+            let mut domain = domain.clone();
+            let valid = adjust_range(adjust, &mut domain, context);
+            series.set_domain(valid.then_some(domain));
+        }
+        true
+    });
+}
+
+// Changes the references in the formulas, charts, named ranges and
+// conditional formats of the sheet.
+fn adjust_sheet_refs(sheet: &mut Sheet, adjust: &dyn AdjustRefs) {
+    let context = sheet.name.as_str();
+    for cell in sheet.data.values_mut() {
+        if let Some(formula) = &cell.formula {
+            cell.formula = Some(rewrite_refs(formula, |r| adjust.adjust_ref(r, context)));
+        }
+        if let Some(extra) = &mut cell.extra {
+            for chart in &mut extra.charts {
+                adjust_chart(chart, adjust, context);
+            }
+        }
+    }
+    adjust_named_ranges(&mut sheet.named_ranges, adjust, Some(context));
+    adjust_named_expressions(&mut sheet.named_expressions, adjust, Some(context));
+    for cf in &mut sheet.conditional_formats {
+        for entry in cf.entries_mut() {
+            if let ConditionalEntry::Condition(rule) = entry {
+                let condition = rule.condition().to_string();
+                let condition = rewrite_refs(&condition, |r| adjust.adjust_ref(r, context));
+                rule.set_condition(FormatCondition::new(condition));
+                if let Some(base_cell) = rule.base_cell() {
+                    let mut base_cell = base_cell.clone();
+                    adjust.adjust_cell(&mut base_cell, context);
+                    rule.set_base_cell(Some(base_cell));
+                }
+            }
+        }
+    }
+}
+
+// Changes the references in all sheets except skip, the named ranges,
+// database ranges, data pilot tables, validations and stylemaps.
+// Ranges that were deleted are removed.
+fn adjust_book_refs(book: &mut WorkBook, skip: Option<usize>, adjust: &dyn AdjustRefs) {
+    for (idx, sheet) in book.sheets.iter_mut().enumerate() {
+        if Some(idx) != skip && !sheet.is_detached() {
+            adjust_sheet_refs(sheet.as_mut(), adjust);
+        }
+    }
+
+    adjust_named_ranges(&mut book.named_ranges, adjust, None);
+    adjust_named_expressions(&mut book.named_expressions, adjust, None);
+
+    book.database_ranges.retain(|_, v| {
+        let mut range = v.range().clone();
+        let valid = adjust_range(adjust, &mut range, "");
+        v.set_range(range);
+        valid
+    });
+
+    book.data_pilot_tables.retain(|_, v| {
+        let mut source = v.source().clone();
+        let mut target = v.target().clone();
+        let valid = adjust_range(adjust, &mut source, "") && adjust_range(adjust, &mut target, "");
+        v.set_source(source);
+        v.set_target(target);
+        valid
+    });
+
+    // Conditions without a table in the base cell can only be matched
+    // by explicit references.
+    for validation in book.validations.values_mut() {
+        let mut base_cell = validation.base_cell().clone();
+        let context = base_cell.table().cloned().unwrap_or_default();
+        let condition = rewrite_refs(validation.condition(), |r| adjust.adjust_ref(r, &context));
+        validation.set_condition(Condition::new(condition));
+        adjust.adjust_cell(&mut base_cell, &context);
+        validation.set_base_cell(base_cell);
+    }
+
+    for style in book.cellstyles.values_mut() {
+        if style.stylemaps().is_none() {
+            continue;
+        }
+        for stylemap in style.stylemaps_mut() {
+            let mut base_cell = stylemap.base_cell().clone();
+            let context = base_cell.table().cloned().unwrap_or_default();
+            let condition = rewrite_refs(stylemap.condition(), |r| adjust.adjust_ref(r, &context));
+            stylemap.set_condition(ValueCondition::new(condition));
+            adjust.adjust_cell(&mut base_cell, &context);
+            stylemap.set_base_cell(base_cell);
+        }
+    }
+}
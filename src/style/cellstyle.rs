@@ -12,11 +12,13 @@ use crate::style::units::{
 };
 use crate::style::{
     border_line_width_string, border_string, color_string, shadow_string, text_position,
-    StyleOrigin, StyleUse, TextStyleRef,
+    ParseStyleAttr, StyleOrigin, StyleUse, TextStyleRef,
 };
+use crate::OdsError;
 use color::Rgb;
 use icu_locid::Locale;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 style_ref!(CellStyleRef);
 
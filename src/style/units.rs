@@ -1041,6 +1041,60 @@ impl Display for CellProtect {
     }
 }
 
+impl CellProtect {
+    /// Combines the protected and formula-hidden flags.
+    pub fn from_flags(protected: bool, formula_hidden: bool) -> Self {
+        match (protected, formula_hidden) {
+            (true, true) => CellProtect::ProtectedFormulaHidden,
+            (true, false) => CellProtect::Protected,
+            (false, true) => CellProtect::FormulaHidden,
+            (false, false) => CellProtect::None,
+        }
+    }
+
+    /// Cell content cannot be edited.
+    pub fn is_protected(&self) -> bool {
+        matches!(
+            self,
+            CellProtect::Protected
+                | CellProtect::ProtectedFormulaHidden
+                | CellProtect::HiddenAndProtected
+        )
+    }
+
+    /// A formula is not displayed.
+    pub fn is_formula_hidden(&self) -> bool {
+        matches!(
+            self,
+            CellProtect::FormulaHidden | CellProtect::ProtectedFormulaHidden
+        )
+    }
+
+    /// Neither the content nor the formula result are displayed.
+    pub fn is_hidden(&self) -> bool {
+        matches!(self, CellProtect::HiddenAndProtected)
+    }
+}
+
+impl ParseStyleAttr<CellProtect> for CellProtect {
+    fn parse_attr(attr: Option<&String>) -> Result<Option<CellProtect>, OdsError> {
+        if let Some(attr) = attr {
+            match attr.as_str() {
+                "formula-hidden" => Ok(Some(CellProtect::FormulaHidden)),
+                "hidden-and-protected" => Ok(Some(CellProtect::HiddenAndProtected)),
+                "none" => Ok(Some(CellProtect::None)),
+                "protected" => Ok(Some(CellProtect::Protected)),
+                "protected formula-hidden" | "formula-hidden protected" => {
+                    Ok(Some(CellProtect::ProtectedFormulaHidden))
+                }
+                s => Err(OdsError::Parse(s.to_string())),
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
 /// 20.263 style:direction
 ///
 /// The style:direction attribute specifies the direction of characters.
@@ -0,0 +1,739 @@
+//!
+//! Tracked changes as recorded by Edit/Track Changes.
+//!
+//! Each change records what was done, by whom and when. The sheet data
+//! always shows the state after all changes. Accepting a change only
+//! marks it as accepted, rejecting a change restores the previous state
+//! in the sheet data.
+//!
+//! ```
+//! use spreadsheet_ods::{read_ods, OdsError};
+//!
+//! fn audit() -> Result<(), OdsError> {
+//!     let mut book = read_ods("tests/orders.ods")?;
+//!     if let Some(changes) = book.tracked_changes() {
+//!         for change in changes.iter() {
+//!             println!(
+//!                 "{} {} {:?}",
+//!                 change.info().creator(),
+//!                 change.id(),
+//!                 change.kind()
+//!             );
+//!         }
+//!     }
+//!     book.accept_all_changes()?;
+//!     Ok(())
+//! }
+//! ```
+//!
+
+use crate::attrmap2::AttrMap2;
+use crate::xmltree::XmlTag;
+use crate::{CellRange, OdsError, Sheet, Value, WorkBook};
+use chrono::NaiveDateTime;
+
+/// All tracked changes of the document.
+#[derive(Clone, Debug)]
+pub struct TrackedChanges {
+    track_changes: bool,
+    changes: Vec<TrackedChange>,
+}
+
+impl Default for TrackedChanges {
+    fn default() -> Self {
+        Self {
+            track_changes: true,
+            changes: Default::default(),
+        }
+    }
+}
+
+impl TrackedChanges {
+    /// Empty.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Changes are recorded when editing.
+    pub fn track_changes(&self) -> bool {
+        self.track_changes
+    }
+
+    /// Changes are recorded when editing.
+    pub fn set_track_changes(&mut self, track_changes: bool) {
+        self.track_changes = track_changes;
+    }
+
+    /// Adds a change.
+    pub fn push_change(&mut self, change: TrackedChange) {
+        self.changes.push(change);
+    }
+
+    /// All changes in document order.
+    pub fn changes(&self) -> &Vec<TrackedChange> {
+        &self.changes
+    }
+
+    /// All changes in document order.
+    pub fn changes_mut(&mut self) -> &mut Vec<TrackedChange> {
+        &mut self.changes
+    }
+
+    /// Iterates all changes in document order.
+    pub fn iter(&self) -> impl Iterator<Item = &TrackedChange> {
+        self.changes.iter()
+    }
+
+    /// Iterates the changes that are neither accepted nor rejected.
+    pub fn iter_pending(&self) -> impl Iterator<Item = &TrackedChange> {
+        self.changes
+            .iter()
+            .filter(|v| v.acceptance_state == AcceptanceState::Pending)
+    }
+
+    /// Finds a change by id.
+    pub fn change(&self, id: &str) -> Option<&TrackedChange> {
+        self.changes.iter().find(|v| v.id == id)
+    }
+
+    /// Finds a change by id.
+    pub fn change_mut(&mut self, id: &str) -> Option<&mut TrackedChange> {
+        self.changes.iter_mut().find(|v| v.id == id)
+    }
+}
+
+/// A single change.
+#[derive(Clone, Debug)]
+pub struct TrackedChange {
+    id: String,
+    acceptance_state: AcceptanceState,
+    rejecting_change_id: Option<String>,
+    info: ChangeInfo,
+    dependencies: Vec<String>,
+    deletions: Vec<ChangeDeletion>,
+    kind: ChangeKind,
+    /// Cut-offs and everything else.
+    pub(crate) extra: Vec<XmlTag>,
+}
+
+impl TrackedChange {
+    /// New pending change.
+    pub fn new<S: Into<String>>(id: S, kind: ChangeKind, info: ChangeInfo) -> Self {
+        Self {
+            id: id.into(),
+            acceptance_state: AcceptanceState::Pending,
+            rejecting_change_id: None,
+            info,
+            dependencies: Default::default(),
+            deletions: Default::default(),
+            kind,
+            extra: Default::default(),
+        }
+    }
+
+    /// Unique id of the change.
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+
+    /// Unique id of the change.
+    pub fn set_id<S: Into<String>>(&mut self, id: S) {
+        self.id = id.into();
+    }
+
+    /// Accepted, rejected or pending.
+    pub fn acceptance_state(&self) -> AcceptanceState {
+        self.acceptance_state
+    }
+
+    /// Accepted, rejected or pending. This doesn't change the sheet data,
+    /// use WorkBook::accept_change() or reject_change() for this.
+    pub fn set_acceptance_state(&mut self, state: AcceptanceState) {
+        self.acceptance_state = state;
+    }
+
+    /// Id of the change that rejected this one.
+    pub fn rejecting_change_id(&self) -> Option<&String> {
+        self.rejecting_change_id.as_ref()
+    }
+
+    /// Id of the change that rejected this one.
+    pub fn set_rejecting_change_id(&mut self, id: Option<String>) {
+        self.rejecting_change_id = id;
+    }
+
+    /// Author, date and comment.
+    pub fn info(&self) -> &ChangeInfo {
+        &self.info
+    }
+
+    /// Author, date and comment.
+    pub fn info_mut(&mut self) -> &mut ChangeInfo {
+        &mut self.info
+    }
+
+    /// Ids of the changes this change depends on. They must be accepted
+    /// before this one.
+    pub fn dependencies(&self) -> &Vec<String> {
+        &self.dependencies
+    }
+
+    /// Ids of the changes this change depends on. They must be accepted
+    /// before this one.
+    pub fn dependencies_mut(&mut self) -> &mut Vec<String> {
+        &mut self.dependencies
+    }
+
+    /// Changes and cell contents that were deleted by this change.
+    pub fn deletions(&self) -> &Vec<ChangeDeletion> {
+        &self.deletions
+    }
+
+    /// Changes and cell contents that were deleted by this change.
+    pub fn deletions_mut(&mut self) -> &mut Vec<ChangeDeletion> {
+        &mut self.deletions
+    }
+
+    /// What was changed.
+    pub fn kind(&self) -> &ChangeKind {
+        &self.kind
+    }
+
+    /// What was changed.
+    pub fn kind_mut(&mut self) -> &mut ChangeKind {
+        &mut self.kind
+    }
+}
+
+/// State of a change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum AcceptanceState {
+    Accepted,
+    Rejected,
+    Pending,
+}
+
+impl AcceptanceState {
+    /// Value for table:acceptance-state.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            AcceptanceState::Accepted => "accepted",
+            AcceptanceState::Rejected => "rejected",
+            AcceptanceState::Pending => "pending",
+        }
+    }
+
+    /// Parses table:acceptance-state.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "accepted" => Ok(AcceptanceState::Accepted),
+            "rejected" => Ok(AcceptanceState::Rejected),
+            "pending" => Ok(AcceptanceState::Pending),
+            _ => Err(OdsError::Parse(format!(
+                "unknown acceptance-state {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Author, date and comment of a change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChangeInfo {
+    creator: String,
+    date: Option<NaiveDateTime>,
+    comment: String,
+}
+
+impl ChangeInfo {
+    /// Change by the author at the given date.
+    pub fn new<S: Into<String>>(creator: S, date: Option<NaiveDateTime>) -> Self {
+        Self {
+            creator: creator.into(),
+            date,
+            comment: Default::default(),
+        }
+    }
+
+    /// Author.
+    pub fn creator(&self) -> &String {
+        &self.creator
+    }
+
+    /// Author.
+    pub fn set_creator<S: Into<String>>(&mut self, creator: S) {
+        self.creator = creator.into();
+    }
+
+    /// Date of the change.
+    pub fn date(&self) -> Option<NaiveDateTime> {
+        self.date
+    }
+
+    /// Date of the change.
+    pub fn set_date(&mut self, date: Option<NaiveDateTime>) {
+        self.date = date;
+    }
+
+    /// Comment. Multiple paragraphs are separated by newlines.
+    pub fn comment(&self) -> &String {
+        &self.comment
+    }
+
+    /// Comment. Multiple paragraphs are separated by newlines.
+    pub fn set_comment<S: Into<String>>(&mut self, comment: S) {
+        self.comment = comment.into();
+    }
+}
+
+/// What was changed.
+#[derive(Clone, Debug)]
+pub enum ChangeKind {
+    /// The content of a cell changed.
+    CellContent {
+        /// Changed cell.
+        address: ChangeAddress,
+        /// Id of the previous change of this cell.
+        previous_id: Option<String>,
+        /// Cell content before the change. None for a cell that was empty.
+        previous: Option<ChangeCell>,
+    },
+    /// Rows, columns or tables were inserted.
+    Insertion {
+        /// Rows, columns or tables.
+        change_type: ChangeType,
+        /// First inserted row, column or table.
+        position: u32,
+        /// Number of inserted rows, columns or tables.
+        count: u32,
+        /// Table index. Not used for inserted tables.
+        table: u32,
+    },
+    /// A row, column or table was deleted.
+    Deletion {
+        /// Row, column or table.
+        change_type: ChangeType,
+        /// Deleted row, column or table.
+        position: u32,
+        /// Table index. Not used for deleted tables.
+        table: u32,
+        /// Number of rows or columns deleted together with this one.
+        multi_deletion_spanned: Option<u32>,
+    },
+    /// A range of cells was moved.
+    Movement {
+        /// Original position.
+        source: ChangeRange,
+        /// New position.
+        target: ChangeRange,
+    },
+}
+
+/// Type of an insertion or deletion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum ChangeType {
+    Row,
+    Column,
+    Table,
+}
+
+impl ChangeType {
+    /// Value for table:type.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            ChangeType::Row => "row",
+            ChangeType::Column => "column",
+            ChangeType::Table => "table",
+        }
+    }
+
+    /// Parses table:type.
+    pub(crate) fn parse(value: &str) -> Result<Self, OdsError> {
+        match value {
+            "row" => Ok(ChangeType::Row),
+            "column" => Ok(ChangeType::Column),
+            "table" => Ok(ChangeType::Table),
+            _ => Err(OdsError::Parse(format!("unknown change type {}", value))),
+        }
+    }
+}
+
+/// Cell address of a change. The table is given by its index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChangeAddress {
+    table: u32,
+    row: u32,
+    col: u32,
+}
+
+impl ChangeAddress {
+    /// Cell in the table with the given index.
+    pub fn new(table: u32, row: u32, col: u32) -> Self {
+        Self { table, row, col }
+    }
+
+    /// Table index.
+    pub fn table(&self) -> u32 {
+        self.table
+    }
+
+    /// Row.
+    pub fn row(&self) -> u32 {
+        self.row
+    }
+
+    /// Column.
+    pub fn col(&self) -> u32 {
+        self.col
+    }
+}
+
+/// Cell range of a movement.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChangeRange {
+    from: ChangeAddress,
+    to: ChangeAddress,
+}
+
+impl ChangeRange {
+    /// Range from the first to the last cell, inclusive.
+    pub fn new(from: ChangeAddress, to: ChangeAddress) -> Self {
+        Self { from, to }
+    }
+
+    /// Range of a single cell.
+    pub fn cell(address: ChangeAddress) -> Self {
+        Self {
+            from: address,
+            to: address,
+        }
+    }
+
+    /// First cell.
+    pub fn from(&self) -> ChangeAddress {
+        self.from
+    }
+
+    /// Last cell.
+    pub fn to(&self) -> ChangeAddress {
+        self.to
+    }
+}
+
+/// Cell content as recorded in a change.
+#[derive(Clone, Debug, Default)]
+pub struct ChangeCell {
+    value: Value,
+    formula: Option<String>,
+    attr: AttrMap2,
+}
+
+impl ChangeCell {
+    /// Cell with a value.
+    pub fn new<V: Into<Value>>(value: V) -> Self {
+        Self {
+            value: value.into(),
+            formula: None,
+            attr: Default::default(),
+        }
+    }
+
+    /// Value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Value.
+    pub fn set_value<V: Into<Value>>(&mut self, value: V) {
+        self.value = value.into();
+    }
+
+    /// Formula.
+    pub fn formula(&self) -> Option<&String> {
+        self.formula.as_ref()
+    }
+
+    /// Formula.
+    pub fn set_formula(&mut self, formula: Option<String>) {
+        self.formula = formula;
+    }
+
+    /// Other attributes like matrix spans.
+    pub fn attrmap(&self) -> &AttrMap2 {
+        &self.attr
+    }
+
+    /// Other attributes like matrix spans.
+    pub fn attrmap_mut(&mut self) -> &mut AttrMap2 {
+        &mut self.attr
+    }
+}
+
+/// Something that was deleted by a change.
+#[derive(Clone, Debug)]
+pub enum ChangeDeletion {
+    /// Id of a change that was deleted.
+    Change(String),
+    /// Cell content that was deleted.
+    CellContent {
+        /// Id of the change that holds the content.
+        id: Option<String>,
+        /// Deleted cell.
+        address: Option<ChangeAddress>,
+        /// Deleted content.
+        cell: Option<ChangeCell>,
+    },
+}
+
+// Accepts the change and all pending changes it depends on.
+pub(crate) fn accept_change(book: &mut WorkBook, id: &str) -> Result<(), OdsError> {
+    let changes = book
+        .tracked_changes
+        .as_mut()
+        .ok_or_else(|| OdsError::Ods("no tracked changes".to_string()))?;
+    if changes.change(id).is_none() {
+        return Err(OdsError::Ods(format!("unknown change {}", id)));
+    }
+
+    let mut todo = vec![id.to_string()];
+    while let Some(id) = todo.pop() {
+        // Missing dependencies are ignored.
+        let change = match changes.change_mut(&id) {
+            Some(v) => v,
+            None => continue,
+        };
+        match change.acceptance_state {
+            AcceptanceState::Accepted => {}
+            AcceptanceState::Rejected => {
+                return Err(OdsError::Ods(format!("change {} is rejected", id)));
+            }
+            AcceptanceState::Pending => {
+                change.acceptance_state = AcceptanceState::Accepted;
+                todo.extend(change.dependencies.iter().cloned());
+            }
+        }
+    }
+    Ok(())
+}
+
+// Rejects the change and all pending changes that depend on it. The
+// sheet data is restored in reverse order. Nothing is changed if one of
+// the changes can't be undone.
+pub(crate) fn reject_change(book: &mut WorkBook, id: &str) -> Result<(), OdsError> {
+    let changes = book
+        .tracked_changes
+        .as_mut()
+        .ok_or_else(|| OdsError::Ods("no tracked changes".to_string()))?;
+    let change = changes
+        .change(id)
+        .ok_or_else(|| OdsError::Ods(format!("unknown change {}", id)))?;
+    match change.acceptance_state {
+        AcceptanceState::Rejected => return Ok(()),
+        AcceptanceState::Accepted => {
+            return Err(OdsError::Ods(format!("change {} is accepted", id)));
+        }
+        AcceptanceState::Pending => {}
+    }
+
+    let mut seen = Vec::new();
+    let mut rejected = Vec::new();
+    collect_rejected(changes, id, &mut seen, &mut rejected);
+
+    let mut undo = Vec::new();
+    for id in &rejected {
+        if let Some(change) = changes.change(id) {
+            check_undo(&change.kind, book.sheets.len())?;
+            undo.push((change.kind.clone(), change.deletions.clone()));
+        }
+    }
+    for id in &rejected {
+        if let Some(change) = changes.change_mut(id) {
+            change.acceptance_state = AcceptanceState::Rejected;
+        }
+    }
+
+    for (kind, deletions) in undo {
+        undo_change(book, &kind, &deletions)?;
+    }
+    Ok(())
+}
+
+// Collects the change and the pending changes that depend on it.
+// The dependent changes come first, the last change first.
+fn collect_rejected(
+    changes: &TrackedChanges,
+    id: &str,
+    seen: &mut Vec<String>,
+    rejected: &mut Vec<String>,
+) {
+    // A dependency cycle ends here.
+    if seen.iter().any(|v| v == id) {
+        return;
+    }
+    seen.push(id.to_string());
+
+    let dependent: Vec<&String> = changes
+        .changes
+        .iter()
+        .rev()
+        .filter(|v| v.acceptance_state == AcceptanceState::Pending)
+        .filter(|v| v.dependencies.iter().any(|d| d == id))
+        .map(|v| &v.id)
+        .collect();
+    for dep in dependent {
+        collect_rejected(changes, dep, seen, rejected);
+    }
+    rejected.push(id.to_string());
+}
+
+// Checks if the change can be undone.
+fn check_undo(kind: &ChangeKind, num_sheets: usize) -> Result<(), OdsError> {
+    let check_table = |table: u32| {
+        if (table as usize) < num_sheets {
+            Ok(())
+        } else {
+            Err(OdsError::Ods(format!("no table with index {}", table)))
+        }
+    };
+    match kind {
+        ChangeKind::CellContent { address, .. } => check_table(address.table),
+        ChangeKind::Insertion {
+            change_type: ChangeType::Table,
+            ..
+        } => Ok(()),
+        ChangeKind::Insertion { table, .. } => check_table(*table),
+        ChangeKind::Deletion {
+            change_type: ChangeType::Table,
+            ..
+        } => Err(OdsError::Ods(
+            "a deleted table can't be restored".to_string(),
+        )),
+        ChangeKind::Deletion { table, .. } => check_table(*table),
+        ChangeKind::Movement { source, target } => {
+            check_table(source.from.table)?;
+            check_table(target.from.table)
+        }
+    }
+}
+
+// Restores the state before the change.
+fn undo_change(
+    book: &mut WorkBook,
+    kind: &ChangeKind,
+    deletions: &[ChangeDeletion],
+) -> Result<(), OdsError> {
+    match kind {
+        ChangeKind::CellContent {
+            address, previous, ..
+        } => {
+            let sheet = change_sheet(book, address.table)?;
+            restore_cell(sheet, address, previous.as_ref());
+        }
+        ChangeKind::Insertion {
+            change_type: ChangeType::Table,
+            position,
+            count,
+            ..
+        } => {
+            for _ in 0..*count {
+                if (*position as usize) < book.sheets.len() {
+                    book.sheets.remove(*position as usize);
+                }
+            }
+        }
+        ChangeKind::Insertion {
+            change_type,
+            position,
+            count,
+            table,
+        } => {
+            change_sheet(book, *table)?;
+            if *change_type == ChangeType::Row {
+                book.delete_rows(*table as usize, *position, *count);
+            } else {
+                book.delete_cols(*table as usize, *position, *count);
+            }
+        }
+        ChangeKind::Deletion {
+            change_type: ChangeType::Table,
+            ..
+        } => {
+            return Err(OdsError::Ods(
+                "a deleted table can't be restored".to_string(),
+            ));
+        }
+        ChangeKind::Deletion {
+            change_type,
+            position,
+            table,
+            ..
+        } => {
+            change_sheet(book, *table)?;
+            if *change_type == ChangeType::Row {
+                book.insert_rows(*table as usize, *position, 1);
+            } else {
+                book.insert_cols(*table as usize, *position, 1);
+            }
+            restore_deletions(book, deletions)?;
+        }
+        ChangeKind::Movement { source, target } => {
+            change_sheet(book, source.from.table)?;
+            change_sheet(book, target.from.table)?;
+            let range = CellRange::local(
+                target.from.row,
+                target.from.col,
+                target.to.row,
+                target.to.col,
+            );
+            book.move_range(
+                target.from.table as usize,
+                &range,
+                source.from.table as usize,
+                source.from.row,
+                source.from.col,
+            );
+            // the content that was replaced by the movement.
+            restore_deletions(book, deletions)?;
+        }
+    }
+    Ok(())
+}
+
+// Sheet for a table index.
+fn change_sheet(book: &mut WorkBook, table: u32) -> Result<&mut Sheet, OdsError> {
+    match book.sheets.get_mut(table as usize) {
+        Some(sheet) => Ok(sheet.as_mut()),
+        None => Err(OdsError::Ods(format!("no table with index {}", table))),
+    }
+}
+
+// Restores the deleted cell contents.
+fn restore_deletions(book: &mut WorkBook, deletions: &[ChangeDeletion]) -> Result<(), OdsError> {
+    for deletion in deletions {
+        if let ChangeDeletion::CellContent {
+            address: Some(address),
+            cell: Some(cell),
+            ..
+        } = deletion
+        {
+            let sheet = change_sheet(book, address.table)?;
+            restore_cell(sheet, address, Some(cell));
+        }
+    }
+    Ok(())
+}
+
+// Sets value and formula of the cell. Style and the rest are kept.
+fn restore_cell(sheet: &mut Sheet, address: &ChangeAddress, cell: Option<&ChangeCell>) {
+    let pos = (address.row, address.col);
+    if let Some(cell) = cell {
+        let data = sheet.data.entry(pos).or_default();
+        data.value = cell.value.clone();
+        data.formula = cell.formula.clone();
+    } else if let Some(data) = sheet.data.get_mut(&pos) {
+        data.value = Value::Empty;
+        data.formula = None;
+    }
+}
@@ -204,13 +204,14 @@ impl XmlTag {
         }
     }
 
-    /// Converts the content into a `Vec<XmlTag>`. Any occurring text content
-    /// is an error.
+    /// Converts the content into a `Vec<XmlTag>`. Whitespace between the
+    /// tags is dropped, any other text content is an error.
     pub fn into_vec(self) -> Result<Vec<XmlTag>, OdsError> {
         let mut content = Vec::new();
 
         for c in self.content {
             match c {
+                XmlContent::Text(v) if v.trim().is_empty() => {}
                 XmlContent::Text(v) => {
                     return Err(OdsError::Parse(format!("Unexpected literal text '{}'", v)))
                 }
@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+use spreadsheet_ods::annotation::Annotation;
+use spreadsheet_ods::text::TextP;
+use spreadsheet_ods::{read_ods, write_ods, Length, OdsError, Sheet, WorkBook};
+
+#[test]
+fn test_annotation() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+
+    sh.set_value(0, 0, "A");
+    let mut note = Annotation::new("first line");
+    note.push_text(TextP::new().text("second line").into_xmltag());
+    note.set_creator(Some("Reviewer".to_string()));
+    note.set_date(Some(
+        NaiveDate::from_ymd_opt(2023, 5, 17)
+            .unwrap()
+            .and_hms_opt(10, 30, 0)
+            .unwrap(),
+    ));
+    note.set_display(true);
+    note.set_display_rect(
+        Length::Cm(2.0),
+        Length::Cm(0.5),
+        Length::Cm(4.0),
+        Length::Cm(1.5),
+    );
+    sh.set_annotation(0, 0, note);
+
+    // annotation without value
+    sh.set_annotation(3, 1, Annotation::new("empty cell"));
+
+    wb.push_sheet(sh);
+    write_ods(&mut wb, "test_out/annotation.ods")?;
+
+    let wi = read_ods("test_out/annotation.ods")?;
+    let si = wi.sheet(0);
+
+    assert_eq!(si.value(0, 0).as_str_or(""), "A");
+    let note = si.annotation(0, 0).expect("annotation");
+    assert_eq!(note.creator().map(|v| v.as_str()), Some("Reviewer"));
+    assert_eq!(
+        note.date().map(|v| v.to_string()),
+        Some("2023-05-17 10:30:00".to_string())
+    );
+    assert!(note.display());
+    assert_eq!(note.text().len(), 2);
+    let mut buf = String::new();
+    note.text()[1].extract_text(&mut buf);
+    assert_eq!(buf, "second line");
+    assert_eq!(
+        note.display_rect()?,
+        Some((
+            Length::Cm(2.0),
+            Length::Cm(0.5),
+            Length::Cm(4.0),
+            Length::Cm(1.5)
+        ))
+    );
+
+    let note = si.annotation(3, 1).expect("annotation");
+    assert!(!note.display());
+    assert!(si.value(3, 1).as_str_opt().is_none());
+    assert!(si.annotation(0, 1).is_none());
+
+    Ok(())
+}
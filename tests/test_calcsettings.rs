@@ -0,0 +1,63 @@
+use chrono::NaiveDate;
+use spreadsheet_ods::calcsettings::CalculationSettings;
+use spreadsheet_ods::{read_ods, write_ods, OdsError, Sheet, WorkBook};
+
+#[test]
+fn test_calcsettings() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(Sheet::new("1"));
+
+    let calc = wb.calculation_settings_mut();
+    calc.set_case_sensitive(false);
+    calc.set_precision_as_shown(true);
+    calc.set_null_date(NaiveDate::from_ymd_opt(1904, 1, 1).unwrap());
+    calc.set_null_year(1950);
+    calc.set_iteration(true);
+    calc.set_iteration_steps(50);
+    calc.set_iteration_minimum_difference(0.01);
+    calc.set_use_regular_expressions(false);
+    calc.set_use_wildcards(true);
+
+    write_ods(&mut wb, "test_out/calcsettings.ods")?;
+
+    let wi = read_ods("test_out/calcsettings.ods")?;
+    let calc = wi.calculation_settings();
+    assert!(!calc.case_sensitive());
+    assert!(calc.precision_as_shown());
+    assert!(calc.search_criteria_must_apply_to_whole_cell());
+    assert_eq!(
+        calc.null_date(),
+        NaiveDate::from_ymd_opt(1904, 1, 1).unwrap()
+    );
+    assert_eq!(calc.null_year(), 1950);
+    assert!(calc.iteration());
+    assert_eq!(calc.iteration_steps(), 50);
+    assert_eq!(calc.iteration_minimum_difference(), 0.01);
+    assert!(!calc.use_regular_expressions());
+    assert!(calc.use_wildcards());
+    assert_eq!(calc, wb.calculation_settings());
+
+    Ok(())
+}
+
+#[test]
+fn test_calcsettings_default() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(Sheet::new("1"));
+    write_ods(&mut wb, "test_out/calcsettings_default.ods")?;
+    let wi = read_ods("test_out/calcsettings_default.ods")?;
+    assert_eq!(wi.calculation_settings(), &CalculationSettings::default());
+
+    // LibreOffice defaults
+    let wi = read_ods("tests/orders.ods")?;
+    let calc = wi.calculation_settings();
+    assert!(!calc.automatic_find_labels());
+    assert!(!calc.use_regular_expressions());
+    assert!(calc.use_wildcards());
+    assert_eq!(
+        calc.null_date(),
+        NaiveDate::from_ymd_opt(1899, 12, 30).unwrap()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use std::fs::File;
+use std::io::Read;
+
+use spreadsheet_ods::chart::{Chart, ChartType, LegendPosition, Series};
+use spreadsheet_ods::{
+    read_fods_buf, read_ods, write_fods_buf, write_ods, CellRange, CellRef, Length, OdsError,
+    Sheet, WorkBook,
+};
+
+fn kpi_book() -> WorkBook {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("KPI 2023");
+    sh.set_value(0, 1, "Sales");
+    sh.set_value(0, 2, "Cost");
+    for i in 0..12 {
+        sh.set_value(i + 1, 0, format!("M{}", i + 1));
+        sh.set_value(i + 1, 1, 100 + i);
+        sh.set_value(i + 1, 2, 80 + 2 * i);
+    }
+
+    let mut chart = Chart::new(ChartType::Bar);
+    chart.set_name("Sales");
+    chart.set_title(Some("Sales per month".to_string()));
+    chart.set_legend(Some(LegendPosition::Bottom));
+    chart.set_x_axis_title(Some("Month".to_string()));
+    chart.set_y_axis_title(Some("EUR".to_string()));
+    chart.set_categories(Some(CellRange::remote("KPI 2023", 1, 0, 12, 0)));
+    for col in 1..=2 {
+        let mut series = Series::new(CellRange::remote("KPI 2023", 1, col, 12, col));
+        series.set_label(Some(CellRef::remote("KPI 2023", 0, col)));
+        chart.push_series(series);
+    }
+    sh.add_chart(0, 4, Length::Cm(12.0), Length::Cm(8.0), chart);
+
+    let mut chart = Chart::new(ChartType::Scatter);
+    let mut series = Series::new(CellRange::remote("KPI 2023", 1, 2, 12, 2));
+    series.set_domain(Some(CellRange::remote("KPI 2023", 1, 1, 12, 1)));
+    chart.push_series(series);
+    sh.add_chart(20, 4, Length::Cm(8.0), Length::Cm(8.0), chart);
+
+    wb.push_sheet(sh);
+    wb
+}
+
+fn check_book(wb: &WorkBook) {
+    let sh = wb.sheet(0);
+    assert_eq!(sh.charts(0, 4).len(), 1);
+    assert_eq!(sh.charts(20, 4).len(), 1);
+
+    let chart = &sh.charts(0, 4)[0];
+    assert_eq!(chart.chart_type(), ChartType::Bar);
+    assert_eq!(chart.name().map(|v| v.as_str()), Some("Sales"));
+    assert_eq!(chart.title().map(|v| v.as_str()), Some("Sales per month"));
+    assert_eq!(chart.legend(), Some(LegendPosition::Bottom));
+    assert_eq!(chart.x_axis_title().map(|v| v.as_str()), Some("Month"));
+    assert_eq!(chart.y_axis_title().map(|v| v.as_str()), Some("EUR"));
+    assert_eq!(chart.size(), (Length::Cm(12.0), Length::Cm(8.0)));
+    let categories = chart.categories().expect("categories");
+    assert_eq!(categories.table().map(|v| v.as_str()), Some("KPI 2023"));
+    assert_eq!((categories.row(), categories.col()), (1, 0));
+    assert_eq!((categories.to_row(), categories.to_col()), (12, 0));
+    assert_eq!(chart.series().len(), 2);
+    let series = &chart.series()[1];
+    assert_eq!(series.values().col(), 2);
+    assert_eq!(series.values().to_row(), 12);
+    let label = series.label().expect("label");
+    assert_eq!((label.row(), label.col()), (0, 2));
+
+    let chart = &sh.charts(20, 4)[0];
+    assert_eq!(chart.chart_type(), ChartType::Scatter);
+    assert_eq!(chart.title(), None);
+    assert_eq!(chart.legend(), None);
+    let domain = chart.series()[0].domain().expect("domain");
+    assert_eq!(domain.col(), 1);
+}
+
+#[test]
+fn test_chart() -> Result<(), OdsError> {
+    let mut wb = kpi_book();
+    write_ods(&mut wb, "test_out/chart.ods")?;
+
+    // the chart documents are part of the package.
+    let mut zip = zip::ZipArchive::new(File::open("test_out/chart.ods")?)?;
+    let mut content = String::new();
+    zip.by_name("Object 1/content.xml")?
+        .read_to_string(&mut content)?;
+    assert!(content.contains("chart:class=\"chart:bar\""));
+    assert!(zip.by_name("Object 2/styles.xml").is_ok());
+    let mut manifest = String::new();
+    zip.by_name("META-INF/manifest.xml")?
+        .read_to_string(&mut manifest)?;
+    assert!(manifest.contains("manifest:full-path=\"Object 1/\""));
+    assert!(manifest.contains("manifest:full-path=\"Object 2/content.xml\""));
+
+    let mut wi = read_ods("test_out/chart.ods")?;
+    check_book(&wi);
+    assert_eq!(wi.sheet(0).iter_charts().len(), 2);
+    assert_eq!(
+        wi.sheet(0).charts(0, 4)[0].href().map(|v| v.as_str()),
+        Some("Object 1")
+    );
+
+    // new charts don't collide with existing ones.
+    let mut chart = Chart::new(ChartType::Pie);
+    chart.push_series(Series::new(CellRange::remote("KPI 2023", 1, 1, 12, 1)));
+    wi.sheet_mut(0)
+        .add_chart(40, 4, Length::Cm(8.0), Length::Cm(8.0), chart);
+    write_ods(&mut wi, "test_out/chart2.ods")?;
+    let wi = read_ods("test_out/chart2.ods")?;
+    check_book(&wi);
+    let chart = &wi.sheet(0).charts(40, 4)[0];
+    assert_eq!(chart.chart_type(), ChartType::Pie);
+    assert_eq!(chart.href().map(|v| v.as_str()), Some("Object 3"));
+
+    Ok(())
+}
+
+#[test]
+fn test_chart_fods() -> Result<(), OdsError> {
+    let mut wb = kpi_book();
+    let buf = write_fods_buf(&mut wb, Vec::new())?;
+    let xml = String::from_utf8(buf).expect("utf8");
+    assert!(xml.contains("<office:chart>"));
+
+    let wi = read_fods_buf(xml.as_bytes())?;
+    check_book(&wi);
+
+    Ok(())
+}
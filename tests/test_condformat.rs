@@ -0,0 +1,148 @@
+use color::Rgb;
+use spreadsheet_ods::condformat::{
+    ColorScale, ConditionRule, ConditionalEntry, ConditionalFormat, DataBar, DateIs, DateType,
+    FormatCondition, FormatEntry, FormatEntryType, IconSet,
+};
+use spreadsheet_ods::style::{StyleOrigin, StyleUse};
+use spreadsheet_ods::{
+    read_ods, write_ods, CellRange, CellRef, CellStyle, OdsError, Sheet, WorkBook,
+};
+
+#[test]
+fn test_condformat() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut red = CellStyle::new_empty();
+    red.set_name("red");
+    red.set_origin(StyleOrigin::Styles);
+    red.set_styleuse(StyleUse::Named);
+    red.set_color(Rgb::new(255, 0, 0));
+    let red = wb.add_cellstyle(red);
+
+    let mut sh = Sheet::new("one");
+    for i in 0..10 {
+        sh.set_value(i, 0, i as i32 - 5);
+        sh.set_value(i, 1, i);
+    }
+
+    let mut cf = ConditionalFormat::new(vec![
+        CellRange::remote("one", 0, 0, 9, 0),
+        CellRange::remote("one", 0, 3, 9, 3),
+    ]);
+    cf.push_condition(ConditionRule::new(
+        FormatCondition::lt(0),
+        &red,
+        Some(CellRef::remote("one", 0, 0)),
+    ));
+    cf.push_condition(ConditionRule::new(
+        FormatCondition::begins_with("x\"y"),
+        &red,
+        None,
+    ));
+    cf.push_date_is(DateIs::new(DateType::Last7Days, &red));
+    sh.add_conditional_format(cf);
+
+    let mut cf = ConditionalFormat::new(vec![CellRange::remote("one", 0, 1, 9, 1)]);
+    let mut scale = ColorScale::new();
+    scale.push_entry(
+        FormatEntry::new(FormatEntryType::Minimum, "0"),
+        Rgb::new(255, 0, 0),
+    );
+    scale.push_entry(
+        FormatEntry::new(FormatEntryType::Percentile, "50"),
+        Rgb::new(255, 255, 0),
+    );
+    scale.push_entry(
+        FormatEntry::new(FormatEntryType::Maximum, "0"),
+        Rgb::new(0, 255, 0),
+    );
+    cf.push_color_scale(scale);
+    let mut bar = DataBar::new(Rgb::new(0x2a, 0x60, 0x99));
+    bar.set_negative_color(Some(Rgb::new(255, 0, 0)));
+    bar.set_max(FormatEntry::new(FormatEntryType::Number, "20"));
+    bar.set_gradient(false);
+    cf.push_data_bar(bar);
+    let mut icons = IconSet::new("3Arrows");
+    icons.push_entry(FormatEntry::new(FormatEntryType::Percent, "0"));
+    icons.push_entry(FormatEntry::new(FormatEntryType::Percent, "33"));
+    icons.push_entry(FormatEntry::new(FormatEntryType::Percent, "67"));
+    icons.set_show_value(false);
+    cf.push_icon_set(icons);
+    sh.add_conditional_format(cf);
+
+    wb.push_sheet(sh);
+    write_ods(&mut wb, "test_out/condformat.ods")?;
+
+    let wi = read_ods("test_out/condformat.ods")?;
+    let si = wi.sheet(0);
+    assert_eq!(si.conditional_formats().len(), 2);
+
+    let cf = &si.conditional_formats()[0];
+    assert_eq!(cf.ranges().len(), 2);
+    assert_eq!(cf.ranges()[1], CellRange::remote("one", 0, 3, 9, 3));
+    assert_eq!(cf.entries().len(), 3);
+    let ConditionalEntry::Condition(rule) = &cf.entries()[0] else {
+        panic!("condition expected");
+    };
+    assert_eq!(rule.condition().to_string(), "<0");
+    assert_eq!(rule.style(), "red");
+    assert_eq!(rule.base_cell(), Some(&CellRef::remote("one", 0, 0)));
+    let ConditionalEntry::Condition(rule) = &cf.entries()[1] else {
+        panic!("condition expected");
+    };
+    assert_eq!(rule.condition().to_string(), "begins-with(\"x\"\"y\")");
+    assert!(rule.base_cell().is_none());
+    let ConditionalEntry::DateIs(date_is) = &cf.entries()[2] else {
+        panic!("date-is expected");
+    };
+    assert_eq!(date_is.date(), DateType::Last7Days);
+
+    let cf = &si.conditional_formats()[1];
+    let ConditionalEntry::ColorScale(scale) = &cf.entries()[0] else {
+        panic!("color-scale expected");
+    };
+    assert_eq!(scale.entries().len(), 3);
+    assert_eq!(
+        scale.entries()[1].0.entry_type(),
+        FormatEntryType::Percentile
+    );
+    assert_eq!(scale.entries()[1].0.value(), "50");
+    assert_eq!(scale.entries()[1].1, Rgb::new(255, 255, 0));
+    let ConditionalEntry::DataBar(bar) = &cf.entries()[1] else {
+        panic!("data-bar expected");
+    };
+    assert_eq!(bar.positive_color(), Rgb::new(0x2a, 0x60, 0x99));
+    assert_eq!(bar.negative_color(), Some(Rgb::new(255, 0, 0)));
+    assert!(bar.axis_color().is_none());
+    assert!(!bar.gradient());
+    assert_eq!(bar.min().entry_type(), FormatEntryType::AutoMinimum);
+    assert_eq!(bar.max().entry_type(), FormatEntryType::Number);
+    assert_eq!(bar.max().value(), "20");
+    let ConditionalEntry::IconSet(icons) = &cf.entries()[2] else {
+        panic!("icon-set expected");
+    };
+    assert_eq!(icons.icon_set_type(), "3Arrows");
+    assert!(!icons.show_value());
+    assert_eq!(icons.entries().len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_condformat_libreoffice() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/orders.ods")?;
+    let n = wb.sheet(0).conditional_formats().len();
+    assert!(n > 0);
+
+    let cf = &wb.sheet(0).conditional_formats()[0];
+    let ConditionalEntry::Condition(rule) = &cf.entries()[0] else {
+        panic!("condition expected");
+    };
+    assert_eq!(rule.condition().to_string(), "=\"BS\"");
+    assert_eq!(rule.style(), "Neutral");
+
+    write_ods(&mut wb, "test_out/condformat_orders.ods")?;
+    let wi = read_ods("test_out/condformat_orders.ods")?;
+    assert_eq!(wi.sheet(0).conditional_formats().len(), n);
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use std::fs::File;
+use std::io::Read;
+
+use spreadsheet_ods::chart::{Chart, ChartType, Series};
+use spreadsheet_ods::{
+    read_ods, read_ods_buf_with_password, read_ods_with_password, write_ods,
+    write_ods_buf_encrypted, write_ods_encrypted, CellRange, Length, OdsError, Sheet, WorkBook,
+};
+
+fn assert_same(wb: &WorkBook, we: &WorkBook) {
+    assert_eq!(wb.num_sheets(), we.num_sheets());
+    for i in 0..wb.num_sheets() {
+        let sb = wb.sheet(i);
+        let se = we.sheet(i);
+        assert_eq!(sb.name(), se.name());
+        assert_eq!(sb.iter().count(), se.iter().count());
+        for ((row, col), cell) in sb.iter() {
+            assert_eq!(
+                format!("{:?}", cell.value()),
+                format!("{:?}", se.value(row, col))
+            );
+        }
+    }
+}
+
+#[test]
+fn read_aes() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+    let mut we = read_ods_with_password("tests/encrypted.ods", "secret")?;
+    assert_same(&wb, &we);
+
+    // written without encryption.
+    write_ods(&mut we, "test_out/decrypted.ods")?;
+    let wd = read_ods("test_out/decrypted.ods")?;
+    assert_same(&wb, &wd);
+
+    Ok(())
+}
+
+#[test]
+fn read_blowfish() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+    let buf = std::fs::read("tests/encrypted_blowfish.ods")?;
+    let we = read_ods_buf_with_password(&buf, "secret")?;
+    assert_same(&wb, &we);
+
+    Ok(())
+}
+
+#[test]
+fn read_wrong_password() {
+    match read_ods_with_password("tests/encrypted.ods", "wrong") {
+        Err(OdsError::Password(_)) => {}
+        v => panic!("{:?}", v.map(|_| ())),
+    }
+    match read_ods("tests/encrypted.ods") {
+        Err(OdsError::Password(_)) => {}
+        v => panic!("{:?}", v.map(|_| ())),
+    }
+    match read_ods_with_password("tests/encrypted_blowfish.ods", "wrong") {
+        Err(OdsError::Password(_)) => {}
+        v => panic!("{:?}", v.map(|_| ())),
+    }
+}
+
+#[test]
+fn write_encrypted() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/orders.ods")?;
+
+    write_ods_encrypted(&mut wb, "test_out/encrypted.ods", "payroll")?;
+    let we = read_ods_with_password("test_out/encrypted.ods", "payroll")?;
+    assert_same(&wb, &we);
+
+    let buf = write_ods_buf_encrypted(&mut wb, Vec::new(), "payroll")?;
+    match read_ods_buf_with_password(&buf, "wrong") {
+        Err(OdsError::Password(_)) => {}
+        v => panic!("{:?}", v.map(|_| ())),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn write_encrypted_chart() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("Data");
+    for i in 0..4 {
+        sh.set_value(i, 0, i);
+    }
+    let mut chart = Chart::new(ChartType::Line);
+    chart.push_series(Series::new(CellRange::remote("Data", 0, 0, 3, 0)));
+    sh.add_chart(0, 2, Length::Cm(8.0), Length::Cm(6.0), chart);
+    wb.push_sheet(sh);
+
+    write_ods_encrypted(&mut wb, "test_out/encrypted_chart.ods", "payroll")?;
+
+    let mut zip = zip::ZipArchive::new(File::open("test_out/encrypted_chart.ods")?)?;
+    let mut manifest = String::new();
+    zip.by_name("META-INF/manifest.xml")?
+        .read_to_string(&mut manifest)?;
+    // directory entry of the chart without encryption.
+    assert!(manifest.contains(
+        r#"<manifest:file-entry manifest:full-path="Object 1/" manifest:version="1.3" manifest:media-type="application/vnd.oasis.opendocument.chart"/>"#
+    ));
+    assert!(manifest.contains(
+        r#"manifest:full-path="Object 1/content.xml" manifest:media-type="text/xml" manifest:size="#
+    ));
+    assert!(manifest.contains(
+        r#"manifest:start-key-generation-name="http://www.w3.org/2000/09/xmldsig#sha256" manifest:key-size="32""#
+    ));
+
+    let we = read_ods_with_password("test_out/encrypted_chart.ods", "payroll")?;
+    assert_eq!(we.sheet(0).charts(0, 2).len(), 1);
+
+    Ok(())
+}
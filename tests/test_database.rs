@@ -0,0 +1,98 @@
+use spreadsheet_ods::database::{
+    DatabaseRange, Filter, FilterCondition, FilterDataType, FilterItem, FilterOperator, SortKey,
+    SortOrder,
+};
+use spreadsheet_ods::{read_ods, write_ods, CellRange, OdsError, Sheet, WorkBook};
+
+#[test]
+fn test_database() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("Orders");
+    sh.set_value(0, 0, "Customer");
+    sh.set_value(0, 1, "Amount");
+    sh.set_value(1, 0, "Alpha");
+    sh.set_value(1, 1, 120);
+    wb.push_sheet(sh);
+
+    let mut db = DatabaseRange::new("Orders", CellRange::remote("Orders", 0, 0, 10, 1));
+    db.set_display_filter_buttons(true);
+    let mut filter = Filter::new(FilterItem::Or(vec![
+        FilterItem::And(vec![
+            FilterItem::Condition(FilterCondition::new(0, FilterOperator::BeginsWith, "A")),
+            FilterItem::Condition(FilterCondition::number(1, FilterOperator::Greater, 100.0)),
+        ]),
+        FilterItem::Condition(FilterCondition::one_of(0, vec!["Beta", "Gamma"])),
+    ]));
+    filter.set_display_duplicates(false);
+    db.set_filter(Some(filter));
+    db.push_sort_key(SortKey::new(1, SortOrder::Descending));
+    wb.add_database_range(db);
+
+    let mut db = DatabaseRange::new("Plain", CellRange::remote("Orders", 0, 3, 5, 4));
+    db.set_contains_header(false);
+    wb.add_database_range(db);
+
+    write_ods(&mut wb, "test_out/database.ods")?;
+
+    let wi = read_ods("test_out/database.ods")?;
+    assert_eq!(wi.iter_database_ranges().count(), 2);
+
+    let db = wi.database_range("Orders").expect("database range");
+    assert_eq!(db.range(), &CellRange::remote("Orders", 0, 0, 10, 1));
+    assert!(db.contains_header());
+    assert!(db.display_filter_buttons());
+    assert_eq!(db.sort_keys().len(), 1);
+    assert_eq!(db.sort_keys()[0].field(), 1);
+    assert_eq!(db.sort_keys()[0].order(), SortOrder::Descending);
+
+    let filter = db.filter().expect("filter");
+    assert!(!filter.display_duplicates());
+    let FilterItem::Or(items) = filter.item() else {
+        panic!("filter-or expected");
+    };
+    assert_eq!(items.len(), 2);
+    let FilterItem::And(conds) = &items[0] else {
+        panic!("filter-and expected");
+    };
+    let FilterItem::Condition(cond) = &conds[1] else {
+        panic!("condition expected");
+    };
+    assert_eq!(cond.field(), 1);
+    assert_eq!(cond.operator(), FilterOperator::Greater);
+    assert_eq!(cond.data_type(), FilterDataType::Number);
+    assert_eq!(cond.value(), "100");
+    let FilterItem::Condition(cond) = &items[1] else {
+        panic!("condition expected");
+    };
+    assert_eq!(
+        cond.set_items(),
+        &vec!["Beta".to_string(), "Gamma".to_string()]
+    );
+
+    let db = wi.database_range("Plain").expect("database range");
+    assert!(!db.contains_header());
+    assert!(db.filter().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_database_libreoffice() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/orders.ods")?;
+
+    let db = wb
+        .database_range("__Anonymous_Sheet_DB__0")
+        .expect("database range");
+    assert_eq!(db.range().table(), Some(&"Saatgut Bestellung".to_string()));
+    let fields: Vec<_> = db.sort_keys().iter().map(|v| v.field()).collect();
+    assert_eq!(fields, vec![2, 3, 1]);
+
+    write_ods(&mut wb, "test_out/database_orders.ods")?;
+    let wi = read_ods("test_out/database_orders.ods")?;
+    let db = wi
+        .database_range("__Anonymous_Sheet_DB__0")
+        .expect("database range");
+    assert_eq!(db.sort_keys().len(), 3);
+
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use spreadsheet_ods::database::{Filter, FilterCondition, FilterItem, FilterOperator, SortOrder};
+use spreadsheet_ods::datapilot::{
+    DataPilotField, DataPilotFunction, DataPilotGroup, DataPilotGroupedBy, DataPilotGroups,
+    DataPilotMember, DataPilotOrientation, DataPilotSort, DataPilotSortMode, DataPilotTable,
+    GrandTotal,
+};
+use spreadsheet_ods::{read_fods_buf, read_ods, write_ods, CellRange, OdsError, Sheet, WorkBook};
+
+#[test]
+fn test_datapilot() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sheet = Sheet::new("Sales");
+    sheet.set_value(0, 0, "Region");
+    sheet.set_value(0, 1, "Product");
+    sheet.set_value(0, 2, "Date");
+    sheet.set_value(0, 3, "Amount");
+    for r in 1..20 {
+        sheet.set_value(r, 0, ["North", "South", "East"][r as usize % 3]);
+        sheet.set_value(r, 1, ["Apples", "Pears"][r as usize % 2]);
+        sheet.set_value(r, 3, r as f64 * 10.0);
+    }
+    wb.push_sheet(sheet);
+    wb.push_sheet(Sheet::new("Pivot"));
+
+    let mut dp = DataPilotTable::new(
+        "Pivot1",
+        CellRange::remote("Sales", 0, 0, 19, 3),
+        CellRange::remote("Pivot", 2, 0, 8, 3),
+    );
+    dp.set_grand_total(GrandTotal::Column);
+    dp.set_ignore_empty_rows(true);
+    dp.set_show_filter_button(false);
+    dp.set_source_filter(Some(Filter::new(FilterItem::Condition(
+        FilterCondition::number(3, FilterOperator::Greater, 0.0),
+    ))));
+
+    let mut region = DataPilotField::new("Region", DataPilotOrientation::Row);
+    region.subtotals_mut().push(DataPilotFunction::Sum);
+    region.push_member(DataPilotMember::new("North"));
+    let mut east = DataPilotMember::new("East");
+    east.set_display(false);
+    region.push_member(east);
+    region.set_sort(Some(DataPilotSort::new(
+        DataPilotSortMode::Data("Amount".to_string()),
+        SortOrder::Descending,
+    )));
+    dp.push_field(region);
+
+    let mut product = DataPilotField::new("Product", DataPilotOrientation::Column);
+    let mut groups = DataPilotGroups::new();
+    groups.push_group(DataPilotGroup::new(
+        "Fruit",
+        vec!["Apples".to_string(), "Pears".to_string()],
+    ));
+    product.set_groups(Some(groups));
+    dp.push_field(product);
+
+    let mut date = DataPilotField::page("Date", Some("2023".to_string()));
+    date.set_groups(Some(DataPilotGroups::date(DataPilotGroupedBy::Years)));
+    dp.push_field(date);
+
+    let mut amount = DataPilotField::new("Amount", DataPilotOrientation::Hidden);
+    amount.set_groups(Some(DataPilotGroups::numeric(Some(0.0), None, 50.0)));
+    dp.push_field(amount);
+
+    dp.push_field(DataPilotField::data("Amount", DataPilotFunction::Average));
+    dp.push_field(DataPilotField::data_layout(DataPilotOrientation::Column));
+    wb.add_data_pilot_table(dp);
+
+    write_ods(&mut wb, "test_out/datapilot.ods")?;
+
+    let wi = read_ods("test_out/datapilot.ods")?;
+    let dp = wi.data_pilot_table("Pivot1").expect("pivot");
+    assert_eq!(dp.source(), &CellRange::remote("Sales", 0, 0, 19, 3));
+    assert_eq!(dp.target(), &CellRange::remote("Pivot", 2, 0, 8, 3));
+    assert_eq!(dp.grand_total(), GrandTotal::Column);
+    assert!(dp.ignore_empty_rows());
+    assert!(!dp.identify_categories());
+    assert!(!dp.show_filter_button());
+    assert!(dp.drill_down_on_double_click());
+    assert!(dp.source_filter().is_some());
+    assert_eq!(dp.fields().len(), 6);
+
+    let region = dp.field("Region").expect("region");
+    assert_eq!(region.orientation(), DataPilotOrientation::Row);
+    assert_eq!(region.subtotals(), &vec![DataPilotFunction::Sum]);
+    assert_eq!(region.members().len(), 2);
+    assert!(!region.members()[1].display());
+    assert_eq!(
+        region.sort(),
+        Some(&DataPilotSort::new(
+            DataPilotSortMode::Data("Amount".to_string()),
+            SortOrder::Descending
+        ))
+    );
+
+    let product = dp.field("Product").expect("product");
+    let groups = product.groups().expect("groups");
+    assert_eq!(groups.source_field_name(), "Product");
+    assert_eq!(groups.groups()[0].name(), "Fruit");
+    assert_eq!(groups.groups()[0].members().len(), 2);
+
+    let date = dp.field("Date").expect("date");
+    assert_eq!(date.orientation(), DataPilotOrientation::Page);
+    assert_eq!(date.selected_page().map(|v| v.as_str()), Some("2023"));
+    assert_eq!(
+        date.groups().and_then(|v| v.grouped_by()),
+        Some(DataPilotGroupedBy::Years)
+    );
+
+    let amount = dp.field("Amount").expect("amount");
+    let groups = amount.groups().expect("groups");
+    assert_eq!(groups.start(), Some(0.0));
+    assert_eq!(groups.end(), None);
+    assert_eq!(groups.step(), Some(50.0));
+
+    let data = &dp.fields()[4];
+    assert_eq!(data.orientation(), DataPilotOrientation::Data);
+    assert_eq!(data.function(), Some(DataPilotFunction::Average));
+    assert!(dp.fields()[5].is_data_layout_field());
+
+    Ok(())
+}
+
+// Shortened content as written by LibreOffice.
+const LIBREOFFICE_PIVOT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:calcext="urn:org:documentfoundation:names:experimental:calc:xmlns:calcext:1.0" xmlns:loext="urn:org:documentfoundation:names:experimental:office:xmlns:loext:1.0" office:version="1.3" office:mimetype="application/vnd.oasis.opendocument.spreadsheet">
+<office:body>
+<office:spreadsheet>
+<table:table table:name="Sheet1"><table:table-column/><table:table-row><table:table-cell/></table:table-row></table:table>
+<table:data-pilot-tables>
+<table:data-pilot-table table:name="DataPilot1" table:application-data="" table:target-range-address="Sheet1.F1:Sheet1.H6" table:buttons="Sheet1.F1 Sheet1.F2">
+<table:source-cell-range table:cell-range-address="Sheet1.A1:Sheet1.C10"/>
+<table:data-pilot-field table:source-field-name="Region" table:orientation="row" table:used-hierarchy="-1">
+<table:data-pilot-level table:show-empty="false" calcext:repeat-item-labels="false">
+<table:data-pilot-subtotals><table:data-pilot-subtotal table:function="auto"/></table:data-pilot-subtotals>
+<table:data-pilot-members>
+<table:data-pilot-member table:name="North" table:display="true" table:show-details="true"/>
+<table:data-pilot-member table:name="South" table:display="false" table:show-details="true"/>
+</table:data-pilot-members>
+<table:data-pilot-display-info table:enabled="false" table:display-member-mode="from-top" table:member-count="0" table:data-field=""/>
+<table:data-pilot-sort-info table:sort-mode="name" table:order="ascending"/>
+<table:data-pilot-layout-info table:layout-mode="tabular-layout" table:add-empty-lines="false"/>
+</table:data-pilot-level>
+</table:data-pilot-field>
+<table:data-pilot-field table:source-field-name="" table:is-data-layout-field="true" table:orientation="column" table:used-hierarchy="-1">
+<table:data-pilot-level table:show-empty="false" calcext:repeat-item-labels="false"/>
+</table:data-pilot-field>
+<table:data-pilot-field table:source-field-name="Amount" table:orientation="data" table:used-hierarchy="-1" table:function="sum">
+<table:data-pilot-level table:show-empty="false" calcext:repeat-item-labels="false"/>
+</table:data-pilot-field>
+<table:data-pilot-grand-total table:display="true" table:orientation="both"/>
+</table:data-pilot-table>
+</table:data-pilot-tables>
+</office:spreadsheet>
+</office:body>
+</office:document>
+"#;
+
+#[test]
+fn test_datapilot_libreoffice() -> Result<(), OdsError> {
+    let mut wb = read_fods_buf(LIBREOFFICE_PIVOT.as_bytes())?;
+
+    let dp = wb.data_pilot_table("DataPilot1").expect("pivot");
+    let mut source = CellRange::remote("Sheet1", 0, 0, 9, 2);
+    source.set_to_table("Sheet1");
+    assert_eq!(dp.source(), &source);
+    let mut target = CellRange::remote("Sheet1", 0, 5, 5, 7);
+    target.set_to_table("Sheet1");
+    assert_eq!(dp.target(), &target);
+    assert_eq!(dp.grand_total(), GrandTotal::Both);
+    assert_eq!(dp.fields().len(), 3);
+    assert_eq!(dp.extra().len(), 1);
+    assert!(dp.attrmap().attr("table:buttons").is_some());
+
+    let region = dp.field("Region").expect("region");
+    assert_eq!(region.subtotals(), &vec![DataPilotFunction::Auto]);
+    assert_eq!(region.members().len(), 2);
+    assert!(!region.members()[1].display());
+    assert_eq!(region.level_extra().len(), 2);
+    assert_eq!(
+        region
+            .attrmap()
+            .attr("table:used-hierarchy")
+            .map(|v| v.as_str()),
+        Some("-1")
+    );
+    assert!(region
+        .level_attrmap()
+        .attr("calcext:repeat-item-labels")
+        .is_some());
+    assert_eq!(
+        region.sort().map(|v| v.mode()),
+        Some(&DataPilotSortMode::Name)
+    );
+
+    assert!(dp.fields()[1].is_data_layout_field());
+    assert_eq!(dp.fields()[2].function(), Some(DataPilotFunction::Sum));
+
+    // modify and write.
+    let dp = wb.data_pilot_table_mut("DataPilot1").expect("pivot");
+    dp.field_mut("Amount")
+        .expect("amount")
+        .set_function(Some(DataPilotFunction::Max));
+    dp.set_grand_total(GrandTotal::None);
+
+    write_ods(&mut wb, "test_out/datapilot_lo.ods")?;
+    let wi = read_ods("test_out/datapilot_lo.ods")?;
+    let dp = wi.data_pilot_table("DataPilot1").expect("pivot");
+    assert_eq!(dp.grand_total(), GrandTotal::None);
+    assert_eq!(dp.fields()[2].function(), Some(DataPilotFunction::Max));
+    assert_eq!(dp.extra().len(), 1);
+    assert_eq!(dp.fields()[0].level_extra().len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use spreadsheet_ods::condformat::{
+    ConditionRule, ConditionalEntry, ConditionalFormat, FormatCondition,
+};
+use spreadsheet_ods::condition::{Condition, ValueCondition};
+use spreadsheet_ods::defaultstyles::DefaultFormat;
+use spreadsheet_ods::style::stylemap::StyleMap;
+use spreadsheet_ods::validation::Validation;
+use spreadsheet_ods::{
+    read_ods, write_ods, CellRange, CellRef, CellStyle, OdsError, Sheet, WorkBook,
+};
+
+#[test]
+fn test_sheet_insert_rows() {
+    let mut sh = Sheet::new("Data");
+    sh.set_formula(0, 0, "of:=SUM([.A2:.A4])+[.$B$5]+[Other.B5]");
+    sh.set_formula(1, 1, "of:=\"[.A4]\"&[Data.A4]&[.A1]");
+    sh.set_formula(2, 1, "of:=SUM([.3:.5])+SUM([.A:.B])");
+
+    sh.insert_rows(2, 2);
+
+    assert_eq!(
+        sh.formula(0, 0).map(|v| v.as_str()),
+        Some("of:=SUM([.A2:.A6])+[.$B$7]+[Other.B5]")
+    );
+    assert_eq!(
+        sh.formula(1, 1).map(|v| v.as_str()),
+        Some("of:=\"[.A4]\"&[Data.A6]&[.A1]")
+    );
+    assert_eq!(
+        sh.formula(4, 1).map(|v| v.as_str()),
+        Some("of:=SUM([.5:.7])+SUM([.A:.B])")
+    );
+}
+
+#[test]
+fn test_sheet_delete() {
+    let mut sh = Sheet::new("Data");
+    sh.set_formula(0, 0, "of:=SUM([.A2:.A4])+[.A3]+[.B5]");
+    sh.set_formula(0, 1, "of:=SUM([.B:.D])+SUM([.C:.C])+[.D1]");
+
+    sh.delete_rows(2, 1);
+    assert_eq!(
+        sh.formula(0, 0).map(|v| v.as_str()),
+        Some("of:=SUM([.A2:.A3])+[.A#REF!]+[.B4]")
+    );
+
+    sh.delete_cols(2, 1);
+    assert_eq!(
+        sh.formula(0, 1).map(|v| v.as_str()),
+        Some("of:=SUM([.B:.C])+SUM([.#REF!:.#REF!])+[.C1]")
+    );
+}
+
+#[test]
+fn test_workbook_refs() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+
+    let mut valid = Validation::new();
+    valid.set_name("v1");
+    valid.set_condition(Condition::content_is_in_cellrange(CellRange::remote(
+        "My Data", 3, 0, 5, 0,
+    )));
+    valid.set_base_cell(CellRef::remote("My Data", 0, 1));
+    wb.add_validation(valid);
+
+    let mut cs = CellStyle::new("highlight", &DefaultFormat::default());
+    cs.push_stylemap(StyleMap::new(
+        ValueCondition::is_true_formula("[.A4]>0"),
+        "default",
+        CellRef::remote("My Data", 0, 0),
+    ));
+    let cs = wb.add_cellstyle(cs);
+
+    let mut sh = Sheet::new("My Data");
+    sh.set_value(3, 0, 1);
+    sh.set_formula(6, 0, "of:=SUM([.A4:.A6])");
+    sh.set_cellstyle(0, 0, &cs);
+    let mut cf = ConditionalFormat::new(vec![CellRange::remote("My Data", 0, 0, 0, 0)]);
+    cf.push_condition(ConditionRule::new(
+        FormatCondition::formula_is("[.A4]>1"),
+        &cs,
+        Some(CellRef::remote("My Data", 0, 0)),
+    ));
+    sh.add_conditional_format(cf);
+    wb.push_sheet(sh);
+
+    let mut sh = Sheet::new("Other");
+    sh.set_formula(0, 0, "of:=['My Data'.A4]+[.A4]+['My Data'.A1]");
+    wb.push_sheet(sh);
+
+    wb.insert_rows(0, 1, 2);
+
+    assert_eq!(
+        wb.sheet(0).formula(8, 0).map(|v| v.as_str()),
+        Some("of:=SUM([.A6:.A8])")
+    );
+    assert_eq!(
+        wb.sheet(1).formula(0, 0).map(|v| v.as_str()),
+        Some("of:=['My Data'.A6]+[.A4]+['My Data'.A1]")
+    );
+    let valid = wb.validation("v1").expect("validation");
+    assert_eq!(
+        valid.condition(),
+        "cell-content-is-in-list(['My Data'.A6:.A8])"
+    );
+    assert_eq!(valid.base_cell(), &CellRef::remote("My Data", 0, 1));
+    let stylemap = &wb
+        .cellstyle("highlight")
+        .expect("style")
+        .stylemaps()
+        .expect("map")[0];
+    assert_eq!(stylemap.condition(), "is-true-formula([.A6]>0)");
+    match &wb.sheet(0).conditional_formats()[0].entries()[0] {
+        ConditionalEntry::Condition(rule) => {
+            assert_eq!(rule.condition().to_string(), "formula-is([.A6]>1)");
+        }
+        _ => panic!("condition expected"),
+    }
+
+    write_ods(&mut wb, "test_out/formula_refs.ods")?;
+    let mut wi = read_ods("test_out/formula_refs.ods")?;
+
+    wi.delete_rows(0, 4, 3);
+    assert_eq!(
+        wi.sheet(0).formula(5, 0).map(|v| v.as_str()),
+        Some("of:=SUM([.A5:.A5])")
+    );
+    assert_eq!(
+        wi.sheet(1).formula(0, 0).map(|v| v.as_str()),
+        Some("of:=['My Data'.A#REF!]+[.A4]+['My Data'.A1]")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_move_range() {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("Data");
+    sh.set_value(0, 0, 1);
+    sh.set_value(1, 0, 2);
+    sh.set_formula(2, 0, "of:=SUM([.A1:.A2])+[.A1]+[.A3]");
+    sh.set_formula(0, 1, "of:=[.A1]*2");
+    wb.push_sheet(sh);
+    let mut sh = Sheet::new("Other");
+    sh.set_formula(0, 0, "of:=[Data.A2]");
+    sh.set_value(5, 5, "replaced");
+    wb.push_sheet(sh);
+
+    // move within the sheet.
+    wb.move_range(0, &CellRange::local(0, 0, 1, 0), 0, 0, 3);
+    let sh = wb.sheet(0);
+    assert!(sh.cell(0, 0).is_none());
+    assert_eq!(sh.value(0, 3).as_i32_or(0), 1);
+    assert_eq!(
+        sh.formula(2, 0).map(|v| v.as_str()),
+        Some("of:=SUM([.D1:.D2])+[.D1]+[.A3]")
+    );
+    assert_eq!(
+        wb.sheet(1).formula(0, 0).map(|v| v.as_str()),
+        Some("of:=[Data.D2]")
+    );
+
+    // move to another sheet.
+    wb.move_range(0, &CellRange::local(0, 1, 2, 3), 1, 4, 4);
+    let sh = wb.sheet(1);
+    assert!(sh.cell(5, 5).is_none());
+    assert_eq!(sh.value(4, 6).as_i32_or(0), 1);
+    assert_eq!(
+        sh.formula(4, 4).map(|v| v.as_str()),
+        Some("of:=[Other.G5]*2")
+    );
+    assert_eq!(sh.formula(0, 0).map(|v| v.as_str()), Some("of:=[Other.G6]"));
+    assert_eq!(
+        wb.sheet(0).formula(2, 0).map(|v| v.as_str()),
+        Some("of:=SUM([Other.G5:.G6])+[Other.G5]+[.A3]")
+    );
+}
+
+#[test]
+fn test_move_range_overwrite() {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("Data");
+    sh.set_value(0, 0, 1);
+    sh.set_value(0, 3, 2);
+    sh.set_formula(5, 0, "of:=[.D1]+SUM([.D1:.D1])+SUM([.D1:.D2])+[.$A$1]");
+    wb.push_sheet(sh);
+    let mut sh = Sheet::new("Other");
+    sh.set_formula(0, 0, "of:=[Data.D1]+[$Data.$D$1]");
+    wb.push_sheet(sh);
+
+    wb.move_range(0, &CellRange::local(0, 0, 0, 0), 0, 0, 3);
+    assert_eq!(wb.sheet(0).value(0, 3).as_i32_or(0), 1);
+    assert_eq!(
+        wb.sheet(0).formula(5, 0).map(|v| v.as_str()),
+        Some("of:=[.#REF!#REF!]+SUM([.#REF!#REF!:.#REF!#REF!])+SUM([.D1:.D2])+[.$D$1]")
+    );
+    assert_eq!(
+        wb.sheet(1).formula(0, 0).map(|v| v.as_str()),
+        Some("of:=[Data.#REF!#REF!]+[$Data.$#REF!$#REF!]")
+    );
+}
+
+#[test]
+fn test_refs_quoted() {
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(Sheet::new("a]b"));
+    wb.push_sheet(Sheet::new("It's"));
+    let mut sh = Sheet::new("Other");
+    sh.set_formula(
+        0,
+        0,
+        "of:=['a]b'.A3]&\"['a]b'.A3]\"&['It''s'.A3:.B4]&\"\"\"[.A3]\"&[.A3]",
+    );
+    wb.push_sheet(sh);
+
+    wb.insert_rows(0, 1, 1);
+    wb.insert_rows(1, 0, 2);
+    assert_eq!(
+        wb.sheet(2).formula(0, 0).map(|v| v.as_str()),
+        Some("of:=['a]b'.A4]&\"['a]b'.A3]\"&['It''s'.A5:.B6]&\"\"\"[.A3]\"&[.A3]")
+    );
+
+    wb.delete_cols(1, 0, 2);
+    assert_eq!(
+        wb.sheet(2).formula(0, 0).map(|v| v.as_str()),
+        Some("of:=['a]b'.A4]&\"['a]b'.A3]\"&['It''s'.#REF!5:.#REF!6]&\"\"\"[.A3]\"&[.A3]")
+    );
+}
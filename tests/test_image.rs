@@ -0,0 +1,139 @@
+use std::fs::File;
+use std::io::Read;
+
+use spreadsheet_ods::draw::Image;
+use spreadsheet_ods::style::GraphicStyle;
+use spreadsheet_ods::{
+    read_fods_buf, read_ods, write_fods_buf, write_ods, Length, OdsError, Sheet, WorkBook,
+};
+
+#[test]
+fn test_image() -> Result<(), OdsError> {
+    let png = std::fs::read("tests/pixel.png")?;
+
+    let mut wb = WorkBook::new_empty();
+    let gr = wb.add_graphicstyle(GraphicStyle::new_empty());
+
+    let mut sh = Sheet::new("1");
+    sh.set_value(0, 0, "picture");
+    let image = sh.add_image(
+        1,
+        1,
+        Length::Cm(3.0),
+        Length::Cm(2.0),
+        png.clone(),
+        "image/png",
+    );
+    image.set_style(&gr);
+    image.set_name("Pixel");
+    image.set_title(Some("One pixel".to_string()));
+    let href = image.href().to_string();
+    wb.push_sheet(sh);
+
+    write_ods(&mut wb, "test_out/image.ods")?;
+
+    // the picture is part of the package.
+    let mut zip = zip::ZipArchive::new(File::open("test_out/image.ods")?)?;
+    assert_eq!(zip.by_name(href.as_str())?.size(), png.len() as u64);
+    let mut manifest = String::new();
+    zip.by_name("META-INF/manifest.xml")?
+        .read_to_string(&mut manifest)?;
+    assert!(manifest.contains(href.as_str()));
+
+    let mut wi = read_ods("test_out/image.ods")?;
+    let si = wi.sheet(0);
+    assert_eq!(si.images(0, 0).len(), 0);
+    let images = si.images(1, 1);
+    assert_eq!(images.len(), 1);
+    let image = &images[0];
+    assert_eq!(image.href(), href);
+    assert_eq!(image.mime_type(), "image/png");
+    assert_eq!(image.data(), png.as_slice());
+    assert_eq!(image.size(), (Length::Cm(3.0), Length::Cm(2.0)));
+    assert_eq!(image.style(), Some(&gr.to_string()));
+    assert_eq!(image.name().map(|v| v.as_str()), Some("Pixel"));
+    assert_eq!(image.title().map(|v| v.as_str()), Some("One pixel"));
+    assert_eq!(si.iter_images().len(), 1);
+
+    // round trip keeps the picture once.
+    write_ods(&mut wi, "test_out/image2.ods")?;
+    let wi = read_ods("test_out/image2.ods")?;
+    assert_eq!(wi.sheet(0).images(1, 1)[0].data(), png.as_slice());
+
+    Ok(())
+}
+
+// Shortened content as written by LibreOffice, with the mime-type removed.
+const PAGE_IMAGE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:draw="urn:oasis:names:tc:opendocument:xmlns:drawing:1.0" xmlns:svg="urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0" xmlns:xlink="http://www.w3.org/1999/xlink" office:version="1.3" office:mimetype="application/vnd.oasis.opendocument.spreadsheet">
+<office:body>
+<office:spreadsheet>
+<table:table table:name="Sheet1">
+<table:shapes>
+<draw:frame draw:z-index="0" draw:name="Logo" svg:width="2cm" svg:height="1cm" svg:x="3cm" svg:y="1.5cm"><draw:image><office:binary-data>iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==</office:binary-data></draw:image><svg:title>Company logo</svg:title></draw:frame>
+<draw:custom-shape draw:z-index="1" draw:name="Arrow" svg:width="2cm" svg:height="1cm" svg:x="6cm" svg:y="1cm"><draw:enhanced-geometry draw:type="right-arrow"/></draw:custom-shape>
+</table:shapes>
+<table:table-column/>
+<table:table-row><table:table-cell office:value-type="float" office:value="1"><text:p>1</text:p></table:table-cell></table:table-row>
+</table:table>
+</office:spreadsheet>
+</office:body>
+</office:document>
+"#;
+
+#[test]
+fn test_page_image() -> Result<(), OdsError> {
+    let png = std::fs::read("tests/pixel.png")?;
+
+    let mut wb = read_fods_buf(PAGE_IMAGE.as_bytes())?;
+    let images = wb.sheet(0).page_images();
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].mime_type(), "image/png");
+    assert_eq!(images[0].data(), png.as_slice());
+    assert_eq!(images[0].name().map(|v| v.as_str()), Some("Logo"));
+    assert_eq!(images[0].position(), (Length::Cm(3.0), Length::Cm(1.5)));
+    assert_eq!(images[0].title().map(|v| v.as_str()), Some("Company logo"));
+    let href = images[0].href().to_string();
+
+    // no mime-type at all.
+    let mut image = Image::new("", png.clone());
+    image.set_position(Length::Cm(1.0), Length::Cm(1.0));
+    image.set_size(Length::Cm(1.0), Length::Cm(1.0));
+    let href2 = wb.sheet_mut(0).push_page_image(image).href().to_string();
+
+    write_ods(&mut wb, "test_out/page_image.ods")?;
+
+    let mut zip = zip::ZipArchive::new(File::open("test_out/page_image.ods")?)?;
+    let mut manifest = String::new();
+    zip.by_name("META-INF/manifest.xml")?
+        .read_to_string(&mut manifest)?;
+    assert!(manifest.contains(&format!(
+        r#"manifest:full-path="{}" manifest:media-type="image/png""#,
+        href
+    )));
+    assert!(manifest.contains(&format!(
+        r#"manifest:full-path="{}" manifest:media-type="image/png""#,
+        href2
+    )));
+    assert!(!manifest.contains(r#"manifest:media-type="""#));
+
+    let wi = read_ods("test_out/page_image.ods")?;
+    let images = wi.sheet(0).page_images();
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].href(), href);
+    assert_eq!(images[0].data(), png.as_slice());
+    assert_eq!(images[1].href(), href2);
+    assert_eq!(images[1].mime_type(), "image/png");
+    assert_eq!(images[1].data(), png.as_slice());
+    assert_eq!(wi.sheet(0).value(0, 0).as_i32_or(0), 1);
+
+    // the other shapes are kept.
+    let mut wi = wi;
+    let buf = write_fods_buf(&mut wi, Vec::new())?;
+    let fods = String::from_utf8_lossy(&buf);
+    assert!(fods.contains("<draw:custom-shape"));
+    assert!(fods.contains(r#"draw:name="Arrow""#));
+    assert_eq!(fods.matches("<table:shapes>").count(), 1);
+
+    Ok(())
+}
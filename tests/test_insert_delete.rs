@@ -0,0 +1,349 @@
+use spreadsheet_ods::chart::{Chart, ChartType, Series};
+use spreadsheet_ods::condformat::ConditionalFormat;
+use spreadsheet_ods::database::{
+    DatabaseRange, Filter, FilterCondition, FilterItem, FilterOperator, SortKey, SortOrder,
+};
+use spreadsheet_ods::datapilot::DataPilotTable;
+use spreadsheet_ods::named::{NamedExpression, NamedRange};
+use spreadsheet_ods::scenario::Scenario;
+use spreadsheet_ods::validation::Validation;
+use spreadsheet_ods::{
+    read_ods, write_ods, CellRange, CellRef, ColRange, Length, OdsError, RowRange, Sheet,
+    SplitMode, Value, WorkBook,
+};
+
+#[test]
+fn test_insert_rows() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("1");
+    sh.set_value(0, 0, "a");
+    sh.set_value(2, 0, "b");
+    sh.set_value(3, 1, "span");
+    sh.set_row_span(1, 1, 3);
+    sh.set_row_height(2, Length::Cm(2.0));
+    sh.set_header_rows(0, 1);
+    sh.add_print_range(CellRange::local(1, 0, 4, 1));
+    sh.add_row_group(2, 3, true)?;
+
+    sh.insert_rows(2, 2);
+
+    assert_eq!(sh.value(0, 0).as_str_or(""), "a");
+    assert!(matches!(sh.value(2, 0), Value::Empty));
+    assert_eq!(sh.value(4, 0).as_str_or(""), "b");
+    assert_eq!(sh.value(5, 1).as_str_or(""), "span");
+    assert_eq!(sh.row_span(1, 1), 5);
+    assert_eq!(sh.row_height(2), Length::Default);
+    assert_eq!(sh.row_height(4), Length::Cm(2.0));
+    assert_eq!(sh.header_rows(), &Some(RowRange::new(0, 1)));
+    assert_eq!(sh.print_ranges(), Some(&vec![CellRange::local(1, 0, 6, 1)]));
+    assert_eq!(sh.row_groups()[0].from(), 4);
+    assert_eq!(sh.row_groups()[0].to(), 5);
+
+    // insert before everything.
+    sh.insert_rows(0, 1);
+    assert_eq!(sh.value(1, 0).as_str_or(""), "a");
+    assert_eq!(sh.row_span(2, 1), 5);
+    assert_eq!(sh.header_rows(), &Some(RowRange::new(1, 2)));
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_rows() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("1");
+    for r in 0..10 {
+        sh.set_value(r, 0, r);
+    }
+    sh.set_row_span(1, 1, 4);
+    sh.set_value(1, 1, "origin");
+    sh.set_row_span(6, 1, 2);
+    sh.set_row_span(8, 2, 3);
+    sh.set_header_rows(2, 3);
+    sh.add_print_range(CellRange::local(2, 0, 3, 1));
+    sh.add_print_range(CellRange::local(0, 0, 9, 1));
+    sh.add_row_group(5, 6, false)?;
+    sh.add_conditional_format(ConditionalFormat::new(vec![CellRange::local(2, 0, 3, 0)]));
+    sh.set_row_repeat(8, 2);
+
+    sh.delete_rows(2, 2);
+
+    assert_eq!(sh.value(1, 0).as_i32_or(-1), 1);
+    assert_eq!(sh.value(2, 0).as_i32_or(-1), 4);
+    assert_eq!(sh.value(7, 0).as_i32_or(-1), 9);
+    assert!(matches!(sh.value(8, 0), Value::Empty));
+    // span shrinks.
+    assert_eq!(sh.row_span(1, 1), 2);
+    assert_eq!(sh.row_span(4, 1), 2);
+    assert_eq!(sh.header_rows(), &None);
+    assert_eq!(sh.print_ranges(), Some(&vec![CellRange::local(0, 0, 7, 1)]));
+    assert_eq!(sh.row_groups()[0].from(), 3);
+    assert_eq!(sh.row_groups()[0].to(), 4);
+    assert!(!sh.row_groups()[0].display());
+    assert!(sh.conditional_formats().is_empty());
+    assert_eq!(sh.row_repeat(6), 2);
+
+    // delete the origin of a span. the rest starts at the first row after.
+    sh.delete_rows(6, 1);
+    assert_eq!(sh.row_span(6, 2), 2);
+    assert!(matches!(sh.value(6, 2), Value::Empty));
+    assert_eq!(sh.row_repeat(6), 1);
+
+    // delete a span completely.
+    sh.delete_rows(4, 2);
+    assert_eq!(sh.row_span(4, 1), 1);
+    assert_eq!(sh.row_span(4, 2), 2);
+    assert_eq!(sh.row_groups()[0].from(), 3);
+    assert_eq!(sh.row_groups()[0].to(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_insert_delete_cols() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("1");
+    for c in 0..5 {
+        sh.set_value(0, c, c);
+    }
+    sh.set_col_span(1, 0, 3);
+    sh.set_col_width(3, Length::Cm(3.0));
+    sh.set_header_cols(0, 0);
+    sh.add_col_group(1, 2, true)?;
+
+    sh.insert_cols(1, 1);
+    assert_eq!(sh.value(0, 0).as_i32_or(-1), 0);
+    assert!(matches!(sh.value(0, 1), Value::Empty));
+    assert_eq!(sh.value(0, 5).as_i32_or(-1), 4);
+    assert_eq!(sh.col_span(1, 0), 4);
+    assert_eq!(sh.col_width(4), Length::Cm(3.0));
+    assert_eq!(sh.header_cols(), &Some(ColRange::new(0, 0)));
+    assert_eq!(sh.col_groups()[0].from(), 2);
+
+    sh.delete_cols(0, 2);
+    assert_eq!(sh.value(0, 0).as_i32_or(-1), 1);
+    assert_eq!(sh.value(0, 3).as_i32_or(-1), 4);
+    assert_eq!(sh.col_span(1, 0), 2);
+    assert_eq!(sh.col_width(2), Length::Cm(3.0));
+    assert_eq!(sh.header_cols(), &None);
+    assert_eq!(sh.col_groups()[0].from(), 0);
+    assert_eq!(sh.col_groups()[0].to(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_insert_delete_workbook() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut valid = Validation::new();
+    valid.set_name("v1");
+    valid.set_base_cell(CellRef::remote("Data", 4, 1));
+    let valid = wb.add_validation(valid);
+    let mut valid2 = Validation::new();
+    valid2.set_name("v2");
+    valid2.set_base_cell(CellRef::remote("Other", 4, 1));
+    wb.add_validation(valid2);
+
+    let mut sh = Sheet::new("Data");
+    sh.set_value(4, 1, 10);
+    sh.set_validation(4, 1, &valid);
+    sh.set_row_span(3, 0, 3);
+    wb.push_sheet(sh);
+    wb.push_sheet(Sheet::new("Other"));
+
+    wb.insert_rows(0, 0, 2);
+    wb.insert_cols(0, 0, 1);
+    assert_eq!(wb.sheet(0).value(6, 2).as_i32_or(0), 10);
+    assert_eq!(wb.validation("v1").map(|v| v.base_cell().row()), Some(6));
+    assert_eq!(wb.validation("v1").map(|v| v.base_cell().col()), Some(2));
+    assert_eq!(wb.validation("v2").map(|v| v.base_cell().row()), Some(4));
+
+    write_ods(&mut wb, "test_out/insert_delete.ods")?;
+    let mut wi = read_ods("test_out/insert_delete.ods")?;
+    assert_eq!(wi.sheet(0).value(6, 2).as_i32_or(0), 10);
+    assert_eq!(wi.sheet(0).row_span(5, 1), 3);
+    assert_eq!(wi.sheet(0).validation(6, 2).map(|v| v.as_str()), Some("v1"));
+
+    wi.delete_rows(0, 5, 2);
+    wi.delete_cols(0, 0, 1);
+    assert!(matches!(wi.sheet(0).value(6, 2), Value::Empty));
+    assert_eq!(wi.sheet(0).row_span(5, 0), 1);
+    assert_eq!(wi.validation("v1").map(|v| v.base_cell().row()), Some(5));
+    assert_eq!(wi.validation("v1").map(|v| v.base_cell().col()), Some(1));
+
+    Ok(())
+}
+
+#[test]
+fn test_insert_delete_bounds() {
+    let mut sh = Sheet::new("1");
+    for r in 0..10 {
+        sh.set_value(r, 0, r);
+    }
+    sh.set_value(u32::MAX - 1, 0, "last");
+    sh.add_print_range(CellRange::local(0, 0, u32::MAX, 0));
+
+    // cells beyond the last row are lost.
+    sh.insert_rows(3, 5);
+    assert_eq!(sh.value(8, 0).as_i32_or(-1), 3);
+    assert!(matches!(sh.value(u32::MAX - 1, 0), Value::Empty));
+    assert_eq!(
+        sh.print_ranges(),
+        Some(&vec![CellRange::local(0, 0, u32::MAX, 0)])
+    );
+
+    sh.insert_rows(u32::MAX, u32::MAX);
+    assert_eq!(sh.value(14, 0).as_i32_or(-1), 9);
+
+    sh.delete_rows(5, u32::MAX);
+    assert_eq!(sh.value(2, 0).as_i32_or(-1), 2);
+    assert!(matches!(sh.value(8, 0), Value::Empty));
+    assert_eq!(sh.print_ranges(), Some(&vec![CellRange::local(0, 0, 4, 0)]));
+
+    sh.delete_cols(0, u32::MAX);
+    assert!(matches!(sh.value(0, 0), Value::Empty));
+    assert_eq!(sh.print_ranges(), None);
+}
+
+#[test]
+fn test_insert_delete_ranges() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+
+    let mut sh = Sheet::new("Data");
+    for r in 0..10 {
+        sh.set_value(r, 0, r);
+        sh.set_value(r, 1, r * 2);
+        sh.set_value(r, 2, r * 3);
+    }
+    sh.add_named_range(NamedRange::new(
+        "local",
+        CellRange::remote("Data", 2, 0, 4, 0),
+    ));
+    sh.split_row_header(2);
+    sh.split_col_header(1);
+    sh.config_mut().cursor_y = 6;
+    let mut chart = Chart::new(ChartType::Line);
+    chart.set_categories(Some(CellRange::remote("Data", 0, 0, 9, 0)));
+    let mut series = Series::new(CellRange::remote("Data", 0, 1, 9, 1));
+    series.set_label(Some(CellRef::remote("Data", 0, 2)));
+    chart.push_series(series);
+    chart.push_series(Series::new(CellRange::remote("Data", 0, 2, 9, 2)));
+    sh.add_chart(0, 4, Length::Cm(8.0), Length::Cm(6.0), chart);
+    wb.push_sheet(sh);
+    wb.add_scenario(
+        0,
+        "Data_1",
+        Scenario::new(vec![CellRange::local(5, 0, 5, 0)]),
+    );
+    wb.push_sheet(Sheet::new("Pivot"));
+
+    wb.add_named_range(NamedRange::new(
+        "all",
+        CellRange::remote("Data", 0, 0, 9, 1),
+    ));
+    wb.add_named_range(NamedRange::new(
+        "gone",
+        CellRange::remote("Data", 3, 0, 3, 1),
+    ));
+    wb.add_named_expression(NamedExpression::new("expr", "of:=[Data.A9]*2"));
+    let mut db = DatabaseRange::new("db", CellRange::remote("Data", 0, 0, 9, 2));
+    db.push_sort_key(SortKey::new(1, SortOrder::Descending));
+    db.push_sort_key(SortKey::new(2, SortOrder::Ascending));
+    db.set_filter(Some(Filter::new(FilterItem::Condition(
+        FilterCondition::number(2, FilterOperator::Greater, 3.0),
+    ))));
+    wb.add_database_range(db);
+    wb.add_data_pilot_table(DataPilotTable::new(
+        "dp",
+        CellRange::remote("Data", 0, 0, 9, 2),
+        CellRange::remote("Pivot", 0, 0, 5, 2),
+    ));
+
+    wb.insert_rows(0, 1, 2);
+    assert_eq!(
+        wb.named_range("all").map(|v| v.range()),
+        Some(&CellRange::remote("Data", 0, 0, 11, 1))
+    );
+    assert_eq!(
+        wb.sheet(0).named_range("local").map(|v| v.range()),
+        Some(&CellRange::remote("Data", 4, 0, 6, 0))
+    );
+    assert_eq!(
+        wb.named_expression("expr").map(|v| v.expression().as_str()),
+        Some("of:=[Data.A11]*2")
+    );
+    assert_eq!(
+        wb.database_range("db").map(|v| v.range()),
+        Some(&CellRange::remote("Data", 0, 0, 11, 2))
+    );
+    assert_eq!(
+        wb.data_pilot_table("dp").map(|v| v.source()),
+        Some(&CellRange::remote("Data", 0, 0, 11, 2))
+    );
+    assert_eq!(
+        wb.data_pilot_table("dp").map(|v| v.target()),
+        Some(&CellRange::remote("Pivot", 0, 0, 5, 2))
+    );
+    let scenario = wb.sheet(1).scenario().expect("scenario");
+    assert_eq!(scenario.ranges(), &vec![CellRange::local(7, 0, 7, 0)]);
+    assert_eq!(wb.sheet(1).value(7, 0).as_i32_or(-1), 5);
+    let config = wb.sheet(0).config();
+    assert_eq!(config.cursor_y, 8);
+    assert_eq!(config.vert_split_pos, 5);
+    assert_eq!(config.position_bottom, 5);
+    let charts = wb.sheet(0).charts(0, 4);
+    assert_eq!(
+        charts[0].categories(),
+        Some(&CellRange::remote("Data", 0, 0, 11, 0))
+    );
+    assert_eq!(
+        charts[0].series()[0].values(),
+        &CellRange::remote("Data", 0, 1, 11, 1)
+    );
+
+    wb.delete_rows(0, 5, 1);
+    assert!(wb.named_range("gone").is_none());
+
+    wb.delete_rows(0, 6, 1);
+    assert!(wb
+        .sheet(1)
+        .scenario()
+        .expect("scenario")
+        .ranges()
+        .is_empty());
+
+    wb.delete_cols(0, 0, 1);
+    assert_eq!(
+        wb.named_range("all").map(|v| v.range()),
+        Some(&CellRange::remote("Data", 0, 0, 9, 0))
+    );
+    assert!(wb.sheet(0).named_range("local").is_none());
+    let db = wb.database_range("db").expect("db");
+    assert_eq!(db.range(), &CellRange::remote("Data", 0, 0, 9, 1));
+    let fields: Vec<_> = db.sort_keys().iter().map(|v| v.field()).collect();
+    assert_eq!(fields, vec![0, 1]);
+    match db.filter().map(|v| v.item()) {
+        Some(FilterItem::Condition(c)) => assert_eq!(c.field(), 1),
+        v => panic!("{:?}", v),
+    }
+    let charts = wb.sheet(0).charts(0, 3);
+    assert_eq!(charts[0].categories(), None);
+    assert_eq!(
+        charts[0].series()[0].label(),
+        Some(&CellRef::remote("Data", 0, 1))
+    );
+    let config = wb.sheet(0).config();
+    assert_eq!(config.hor_split_pos, 1);
+
+    wb.delete_cols(0, 0, 2);
+    assert!(wb.named_range("all").is_none());
+    assert!(wb.database_range("db").is_none());
+    assert!(wb.data_pilot_table("dp").is_none());
+    assert!(wb.sheet(0).charts(0, 1)[0].series().is_empty());
+    assert!(matches!(
+        wb.sheet(0).config().hor_split_mode,
+        SplitMode::None
+    ));
+
+    write_ods(&mut wb, "test_out/insert_delete_ranges.ods")?;
+    read_ods("test_out/insert_delete_ranges.ods")?;
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use spreadsheet_ods::chart::{Chart, ChartType, Series};
+use spreadsheet_ods::{
+    read_ods, write_ods, CellRange, LazyWorkBook, Length, OdsError, Sheet, WorkBook,
+};
+
+#[test]
+fn test_lazy() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+    let mut lazy = LazyWorkBook::open("tests/orders.ods")?;
+
+    assert_eq!(wb.num_sheets(), lazy.num_sheets());
+    for i in 0..wb.num_sheets() {
+        assert_eq!(wb.sheet(i).name(), lazy.sheet_name(i));
+        assert!(!lazy.is_loaded(i));
+    }
+    assert!(lazy.book().cellstyle("Default").is_some());
+
+    let idx = lazy.sheet_idx("Lieferanten").expect("sheet");
+    let sl = lazy.sheet(idx)?;
+    let sb = wb.sheet(idx);
+    assert_eq!(sb.iter().count(), sl.iter().count());
+    for ((row, col), cell) in sb.iter() {
+        assert_eq!(
+            format!("{:?}", cell.value()),
+            format!("{:?}", sl.value(row, col))
+        );
+        assert_eq!(cell.style(), sl.cellstyle(row, col));
+    }
+    assert_eq!(sb.col_width(0), sl.col_width(0));
+    assert_eq!(sb.config().show_grid, sl.config().show_grid);
+
+    assert!(lazy.is_loaded(idx));
+    assert!(!lazy.is_loaded(0));
+
+    let mut wl = lazy.into_workbook()?;
+    for i in 0..wb.num_sheets() {
+        assert_eq!(wb.sheet(i).iter().count(), wl.sheet(i).iter().count());
+    }
+    write_ods(&mut wl, "test_out/lazy.ods")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_lazy_media() -> Result<(), OdsError> {
+    let png = std::fs::read("tests/pixel.png")?;
+
+    let mut wb = WorkBook::new_empty();
+    for name in ["1", "2"] {
+        let mut sh = Sheet::new(name);
+        sh.set_value(0, 0, 1);
+        sh.add_image(
+            1,
+            1,
+            Length::Cm(1.0),
+            Length::Cm(1.0),
+            png.clone(),
+            "image/png",
+        );
+        let mut chart = Chart::new(ChartType::Bar);
+        chart.push_series(Series::new(CellRange::remote(name, 0, 0, 0, 0)));
+        sh.add_chart(2, 2, Length::Cm(5.0), Length::Cm(5.0), chart);
+        wb.push_sheet(sh);
+    }
+    write_ods(&mut wb, "test_out/lazy_media.ods")?;
+
+    // both sheets use the same picture.
+    let mut lazy = LazyWorkBook::open("test_out/lazy_media.ods")?;
+    assert_eq!(lazy.sheet(1)?.images(1, 1)[0].data(), png.as_slice());
+    assert_eq!(lazy.sheet(1)?.charts(2, 2).len(), 1);
+    assert_eq!(lazy.sheet(0)?.images(1, 1)[0].data(), png.as_slice());
+    assert_eq!(lazy.sheet(0)?.charts(2, 2).len(), 1);
+
+    let mut wl = lazy.into_workbook()?;
+    write_ods(&mut wl, "test_out/lazy_media2.ods")?;
+    let wi = read_ods("test_out/lazy_media2.ods")?;
+    for i in 0..2 {
+        assert_eq!(wi.sheet(i).images(1, 1)[0].data(), png.as_slice());
+        assert_eq!(wi.sheet(i).charts(2, 2).len(), 1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_lazy_encrypted() {
+    match LazyWorkBook::open("tests/encrypted.ods") {
+        Err(OdsError::Ods(_)) => {}
+        v => panic!("{:?}", v.map(|_| ())),
+    }
+}
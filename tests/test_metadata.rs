@@ -0,0 +1,128 @@
+use chrono::{Duration, NaiveDate};
+use spreadsheet_ods::metadata::MetaValue;
+use spreadsheet_ods::{read_fods, read_ods, write_fods, write_ods, OdsError, Sheet, WorkBook};
+
+fn metadata_book() -> WorkBook {
+    let mut wb = WorkBook::new_empty();
+    wb.push_sheet(Sheet::new("1"));
+
+    let date = NaiveDate::from_ymd_opt(2023, 4, 1)
+        .unwrap()
+        .and_hms_opt(10, 30, 0)
+        .unwrap();
+
+    let meta = wb.metadata_mut();
+    meta.set_title("Sales & Orders");
+    meta.set_subject("Sales");
+    meta.set_description("All orders of 2023.");
+    meta.push_keyword("sales");
+    meta.push_keyword("2023");
+    meta.set_initial_creator("Jane Doe");
+    meta.set_creator("John Doe");
+    meta.set_creation_date(Some(date));
+    meta.set_modification_date(Some(date + Duration::days(1)));
+    meta.set_language("de-AT");
+    meta.set_editing_cycles(7);
+    meta.set_editing_duration(Duration::minutes(90));
+    meta.set_user_defined("Reviewed", MetaValue::Boolean(true));
+    meta.set_user_defined("Version", MetaValue::Float(2.5));
+    meta.set_user_defined("Department", MetaValue::String("<Sales>".to_string()));
+    meta.set_user_defined("Due", MetaValue::DateTime(date));
+    meta.set_user_defined("Effort", MetaValue::TimeDuration(Duration::hours(3)));
+    wb
+}
+
+fn check_metadata(wi: &WorkBook) {
+    let date = NaiveDate::from_ymd_opt(2023, 4, 1)
+        .unwrap()
+        .and_hms_opt(10, 30, 0)
+        .unwrap();
+
+    let meta = wi.metadata();
+    assert!(meta.generator().starts_with("spreadsheet-ods"));
+    assert_eq!(meta.title(), "Sales & Orders");
+    assert_eq!(meta.subject(), "Sales");
+    assert_eq!(meta.description(), "All orders of 2023.");
+    assert_eq!(
+        meta.keywords(),
+        &vec!["sales".to_string(), "2023".to_string()]
+    );
+    assert_eq!(meta.initial_creator(), "Jane Doe");
+    assert_eq!(meta.creator(), "John Doe");
+    assert_eq!(meta.creation_date(), Some(date));
+    assert_eq!(meta.modification_date(), Some(date + Duration::days(1)));
+    assert_eq!(meta.print_date(), None);
+    assert_eq!(meta.language(), "de-AT");
+    assert_eq!(meta.editing_cycles(), 7);
+    assert_eq!(meta.editing_duration(), Duration::minutes(90));
+    assert_eq!(
+        meta.user_defined("Reviewed"),
+        Some(&MetaValue::Boolean(true))
+    );
+    assert_eq!(meta.user_defined("Version"), Some(&MetaValue::Float(2.5)));
+    assert_eq!(
+        meta.user_defined("Department"),
+        Some(&MetaValue::String("<Sales>".to_string()))
+    );
+    assert_eq!(meta.user_defined("Due"), Some(&MetaValue::DateTime(date)));
+    assert_eq!(
+        meta.user_defined("Effort"),
+        Some(&MetaValue::TimeDuration(Duration::hours(3)))
+    );
+    assert_eq!(meta.iter_user_defined().count(), 5);
+}
+
+#[test]
+fn test_metadata() -> Result<(), OdsError> {
+    let mut wb = metadata_book();
+    write_ods(&mut wb, "test_out/metadata.ods")?;
+    let mut wi = read_ods("test_out/metadata.ods")?;
+    check_metadata(&wi);
+
+    // survives a second round trip.
+    write_ods(&mut wi, "test_out/metadata_2.ods")?;
+    let wi = read_ods("test_out/metadata_2.ods")?;
+    check_metadata(&wi);
+
+    Ok(())
+}
+
+#[test]
+fn test_metadata_fods() -> Result<(), OdsError> {
+    let mut wb = metadata_book();
+    write_fods(&mut wb, "test_out/metadata.fods")?;
+    let wi = read_fods("test_out/metadata.fods")?;
+    check_metadata(&wi);
+    Ok(())
+}
+
+#[test]
+fn test_metadata_user_defined() {
+    let mut wb = WorkBook::new_empty();
+    let meta = wb.metadata_mut();
+    meta.set_user_defined("A", MetaValue::Float(1.0));
+    meta.set_user_defined("A", MetaValue::Float(2.0));
+    assert_eq!(meta.iter_user_defined().count(), 1);
+    assert_eq!(meta.user_defined("A"), Some(&MetaValue::Float(2.0)));
+    assert_eq!(meta.remove_user_defined("A"), Some(MetaValue::Float(2.0)));
+    assert_eq!(meta.user_defined("A"), None);
+}
+
+#[test]
+fn test_metadata_libreoffice() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/orders.ods")?;
+    assert!(wb.metadata().generator().contains("LibreOffice"));
+    assert!(wb.metadata().creation_date().is_some());
+    assert_eq!(wb.metadata().printed_by(), "Thomas Scharler");
+    assert_eq!(wb.metadata().editing_cycles(), 162);
+    assert_eq!(
+        wb.metadata().editing_duration(),
+        Duration::days(3) + Duration::hours(2) + Duration::minutes(10) + Duration::seconds(34)
+    );
+    let created = wb.metadata().creation_date();
+
+    write_ods(&mut wb, "test_out/metadata_orders.ods")?;
+    let wi = read_ods("test_out/metadata_orders.ods")?;
+    assert_eq!(wi.metadata().creation_date(), created);
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use spreadsheet_ods::named::{NamedExpression, NamedRange};
+use spreadsheet_ods::{read_ods, write_ods, CellRange, CellRef, OdsError, Sheet, WorkBook};
+
+#[test]
+fn test_named() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+
+    let mut range = NamedRange::new("Prices", CellRange::remote("Data", 1, 1, 20, 1).absolute());
+    range.set_base_cell(Some(CellRef::remote("Data", 0, 0).absolute()));
+    wb.add_named_range(range);
+    let mut range = NamedRange::new("Header", CellRange::remote("Data", 0, 0, 0, 3).absolute());
+    range.set_usable_as(Some("print-range repeat-row".to_string()));
+    wb.add_named_range(range);
+    wb.add_named_expression(NamedExpression::new("Tax", "of:=0.2"));
+
+    let mut sh = Sheet::new("Data");
+    sh.set_value(0, 0, "A");
+    let mut expr = NamedExpression::new("Twice", "of:=2*[.A1]");
+    expr.set_base_cell(Some(CellRef::remote("Data", 0, 0).absolute()));
+    sh.add_named_expression(expr);
+    sh.add_named_range(NamedRange::new(
+        "Local",
+        CellRange::remote("Data", 2, 2, 4, 4).absolute(),
+    ));
+    wb.push_sheet(sh);
+
+    write_ods(&mut wb, "test_out/named.ods")?;
+
+    let mut wi = read_ods("test_out/named.ods")?;
+
+    assert_eq!(wi.iter_named_ranges().count(), 2);
+    let range = wi.named_range("Prices").expect("range");
+    assert_eq!(
+        range.range(),
+        &CellRange::remote("Data", 1, 1, 20, 1).absolute()
+    );
+    assert_eq!(
+        range.base_cell(),
+        Some(&CellRef::remote("Data", 0, 0).absolute())
+    );
+    let range = wi.named_range("Header").expect("range");
+    assert_eq!(
+        range.usable_as().map(|v| v.as_str()),
+        Some("print-range repeat-row")
+    );
+    let expr = wi.named_expression("Tax").expect("expr");
+    assert_eq!(expr.expression(), "of:=0.2");
+
+    let si = wi.sheet(0);
+    assert_eq!(si.value(0, 0).as_str_or(""), "A");
+    let expr = si.named_expression("Twice").expect("expr");
+    assert_eq!(expr.expression(), "of:=2*[.A1]");
+    assert!(si.named_range("Local").is_some());
+    assert!(si.named_range("Prices").is_none());
+
+    assert!(wi.remove_named_range("Prices").is_some());
+    assert!(wi.named_range("Prices").is_none());
+
+    Ok(())
+}
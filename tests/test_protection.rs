@@ -0,0 +1,96 @@
+use spreadsheet_ods::defaultstyles::DefaultFormat;
+use spreadsheet_ods::protection::{ProtectionOptions, SheetProtection, SHA256_URN};
+use spreadsheet_ods::style::units::CellProtect;
+use spreadsheet_ods::{read_ods, write_ods, CellStyle, OdsError, Sheet, WorkBook};
+
+#[test]
+fn test_protection() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+
+    let mut st = CellStyle::new("input", &DefaultFormat::default());
+    st.set_protected(false);
+    st.set_formula_hidden(true);
+    st.set_print_content(false);
+    let input = wb.add_cellstyle(st);
+
+    let mut st = CellStyle::new("secret", &DefaultFormat::default());
+    st.set_hidden_and_protected(true);
+    let secret = wb.add_cellstyle(st);
+
+    let mut sheet = Sheet::new("1");
+    sheet.set_styled_value(0, 0, 1, &input);
+    sheet.set_styled_value(1, 0, 2, &secret);
+    let mut options = ProtectionOptions::new();
+    options.set_select_protected_cells(false);
+    options.set_insert_rows(true);
+    options.set_delete_columns(true);
+    sheet.protect("geheim", options);
+    wb.push_sheet(sheet);
+
+    let mut sheet = Sheet::new("2");
+    sheet.protect("", ProtectionOptions::new());
+    wb.push_sheet(sheet);
+
+    wb.push_sheet(Sheet::new("3"));
+
+    write_ods(&mut wb, "test_out/protection.ods")?;
+
+    let wi = read_ods("test_out/protection.ods")?;
+
+    let p = wi.sheet(0).protection().expect("protection");
+    assert!(p.has_password());
+    assert!(p.verify("geheim"));
+    assert!(!p.verify("Geheim"));
+    assert_eq!(p.digest_algorithm().map(|v| v.as_str()), Some(SHA256_URN));
+    assert!(!p.options().select_protected_cells());
+    assert!(p.options().select_unprotected_cells());
+    assert!(!p.options().insert_columns());
+    assert!(p.options().insert_rows());
+    assert!(p.options().delete_columns());
+    assert!(!p.options().delete_rows());
+    assert_eq!(Some(p), wb.sheet(0).protection());
+
+    let p = wi.sheet(1).protection().expect("protection");
+    assert!(!p.has_password());
+    assert!(p.verify(""));
+    assert_eq!(p.options(), &ProtectionOptions::new());
+
+    assert!(!wi.sheet(2).is_protected());
+
+    let st = wi.cellstyle("input").expect("style");
+    assert_eq!(st.cell_protect()?, Some(CellProtect::FormulaHidden));
+    assert_eq!(st.print_content(), Some(false));
+    let st = wi.cellstyle("secret").expect("style");
+    assert_eq!(st.cell_protect()?, Some(CellProtect::HiddenAndProtected));
+
+    Ok(())
+}
+
+#[test]
+fn test_cell_protect() -> Result<(), OdsError> {
+    let mut st = CellStyle::new_empty();
+    assert_eq!(st.cell_protect()?, None);
+
+    st.set_formula_hidden(true);
+    assert_eq!(
+        st.cell_protect()?,
+        Some(CellProtect::ProtectedFormulaHidden)
+    );
+    st.set_protected(false);
+    assert_eq!(st.cell_protect()?, Some(CellProtect::FormulaHidden));
+    st.set_formula_hidden(false);
+    assert_eq!(st.cell_protect()?, Some(CellProtect::None));
+    st.set_hidden_and_protected(true);
+    assert!(st.cell_protect()?.expect("protect").is_hidden());
+    st.set_hidden_and_protected(false);
+    assert_eq!(st.cell_protect()?, Some(CellProtect::Protected));
+
+    Ok(())
+}
+
+#[test]
+fn test_protection_key() {
+    // Base64 of the SHA-256 digest of "test".
+    let p = SheetProtection::new("test", ProtectionOptions::new());
+    assert_eq!(p.key(), "n4bQgYhMfWWaL+qgxVrQFaO/TxsrC4Is0V1sFbDwCgg=");
+}
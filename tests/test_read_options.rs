@@ -0,0 +1,53 @@
+use spreadsheet_ods::{read_ods, write_ods, OdsError, ReadOptions};
+
+#[test]
+fn read_values_only() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+    let mut wv = ReadOptions::new().values_only().read_ods("tests/orders.ods")?;
+
+    assert_eq!(wb.num_sheets(), wv.num_sheets());
+    for i in 0..wb.num_sheets() {
+        let sb = wb.sheet(i);
+        let sv = wv.sheet(i);
+        assert_eq!(sb.name(), sv.name());
+        for ((row, col), cell) in sb.iter() {
+            assert_eq!(
+                format!("{:?}", cell.value()),
+                format!("{:?}", sv.value(row, col))
+            );
+            assert_eq!(sv.cellstyle(row, col), None);
+        }
+    }
+    assert!(wv.cellstyle("Default").is_none());
+
+    // still writable
+    write_ods(&mut wv, "test_out/orders_values.ods")?;
+    let wr = read_ods("test_out/orders_values.ods")?;
+    assert_eq!(wb.num_sheets(), wr.num_sheets());
+
+    Ok(())
+}
+
+#[test]
+fn read_selected_sheets() -> Result<(), OdsError> {
+    let wb = ReadOptions::new()
+        .sheets(["Lieferanten"])
+        .read_ods("tests/orders.ods")?;
+    assert_eq!(wb.num_sheets(), 1);
+    assert_eq!(wb.sheet(0).name(), "Lieferanten");
+    assert!(wb.cellstyle("Default").is_some());
+
+    let wb = ReadOptions::new()
+        .sheets(["Lieferanten"])
+        .skip_formats(true)
+        .read_fods("tests/orders.fods")?;
+    assert_eq!(wb.num_sheets(), 1);
+    assert_eq!(wb.sheet(0).name(), "Lieferanten");
+
+    let wb = ReadOptions::new()
+        .sheets(Vec::<String>::new())
+        .read_ods("tests/orders.ods")?;
+    assert_eq!(wb.num_sheets(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use color::Rgb;
+use spreadsheet_ods::scenario::Scenario;
+use spreadsheet_ods::{read_fods_buf, read_ods, write_ods, CellRange, OdsError, Sheet, WorkBook};
+
+#[test]
+fn test_scenario() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sheet = Sheet::new("Budget");
+    sheet.set_value(0, 0, "Growth");
+    sheet.set_value(0, 1, 0.02);
+    sheet.set_value(1, 0, "Costs");
+    sheet.set_value(1, 1, 1000);
+    sheet.set_value(1, 2, "outside");
+    wb.push_sheet(sheet);
+    wb.push_sheet(Sheet::new("Other"));
+
+    let mut scenario = Scenario::new(vec![CellRange::local(0, 1, 1, 1)]);
+    scenario.set_active(true);
+    scenario.set_comment("Pessimistic");
+    scenario.set_border_color(Some(Rgb::new(255, 0, 0)));
+    scenario.set_copy_back(false);
+    let pessimistic = wb.add_scenario(0, "Budget_Pessimistic", scenario);
+    assert_eq!(pessimistic, 1);
+    wb.sheet_mut(pessimistic).set_value(0, 1, -0.01);
+
+    let mut scenario = Scenario::new(vec![CellRange::local(0, 1, 1, 1)]);
+    scenario.set_display_border(false);
+    scenario.set_protected(true);
+    let optimistic = wb.add_scenario(0, "Budget_Optimistic", scenario);
+    assert_eq!(optimistic, 2);
+    wb.sheet_mut(optimistic).set_value(0, 1, 0.05);
+
+    assert_eq!(wb.scenarios(0), vec![1, 2]);
+    assert_eq!(wb.sheet(3).name(), "Other");
+
+    let sheet = wb.sheet(optimistic);
+    assert!(!sheet.display());
+    assert_eq!(sheet.value(1, 1).as_f64_or(0.0), 1000.0);
+    assert!(sheet.cell(1, 2).is_none());
+    assert!(sheet.cell(0, 0).is_none());
+
+    write_ods(&mut wb, "test_out/scenario.ods")?;
+    let wi = read_ods("test_out/scenario.ods")?;
+
+    assert_eq!(wi.scenarios(0), vec![1, 2]);
+    assert!(!wi.sheet(0).is_scenario());
+
+    let sheet = wi.sheet(1);
+    let scenario = sheet.scenario().expect("scenario");
+    assert!(scenario.active());
+    assert_eq!(scenario.comment(), "Pessimistic");
+    assert_eq!(scenario.border_color(), Some(Rgb::new(255, 0, 0)));
+    assert!(!scenario.copy_back());
+    assert!(scenario.copy_styles());
+    assert!(scenario.display_border());
+    assert_eq!(scenario.ranges(), &vec![CellRange::local(0, 1, 1, 1)]);
+    assert_eq!(sheet.value(0, 1).as_f64_or(0.0), -0.01);
+
+    let scenario = wi.sheet(2).scenario().expect("scenario");
+    assert!(!scenario.active());
+    assert!(!scenario.display_border());
+    assert!(scenario.protected());
+    assert_eq!(scenario.border_color(), None);
+
+    Ok(())
+}
+
+// Shortened content as written by LibreOffice.
+const LIBREOFFICE_SCENARIO: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<office:document xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" office:version="1.3" office:mimetype="application/vnd.oasis.opendocument.spreadsheet">
+<office:body>
+<office:spreadsheet>
+<table:table table:name="Sheet1">
+<table:table-column table:number-columns-repeated="2"/>
+<table:table-row><table:table-cell/><table:table-cell office:value-type="float" office:value="10"><text:p>10</text:p></table:table-cell></table:table-row>
+</table:table>
+<table:table table:name="Sheet1_Scenario_1" table:display="false">
+<table:scenario table:border-color="#c0c0c0" table:copy-back="true" table:copy-styles="true" table:copy-formulas="true" table:is-active="true" table:scenario-ranges="Sheet1_Scenario_1.B1:Sheet1_Scenario_1.B1" table:comment="created by Jane"/>
+<table:table-column table:number-columns-repeated="2"/>
+<table:table-row><table:table-cell/><table:table-cell office:value-type="float" office:value="10"><text:p>10</text:p></table:table-cell></table:table-row>
+</table:table>
+</office:spreadsheet>
+</office:body>
+</office:document>
+"##;
+
+#[test]
+fn test_scenario_libreoffice() -> Result<(), OdsError> {
+    let mut wb = read_fods_buf(LIBREOFFICE_SCENARIO.as_bytes())?;
+
+    assert_eq!(wb.scenarios(0), vec![1]);
+    let scenario = wb.sheet(1).scenario().expect("scenario");
+    assert!(scenario.active());
+    assert_eq!(scenario.comment(), "created by Jane");
+    assert_eq!(scenario.border_color(), Some(Rgb::new(0xc0, 0xc0, 0xc0)));
+    assert!(scenario.copy_formulas());
+    let range = &scenario.ranges()[0];
+    assert_eq!((range.row(), range.col()), (0, 1));
+    assert_eq!((range.to_row(), range.to_col()), (0, 1));
+
+    wb.sheet_mut(1)
+        .scenario_mut()
+        .expect("scenario")
+        .set_comment("changed");
+    write_ods(&mut wb, "test_out/scenario_lo.ods")?;
+    let wi = read_ods("test_out/scenario_lo.ods")?;
+    let scenario = wi.sheet(1).scenario().expect("scenario");
+    assert_eq!(scenario.comment(), "changed");
+
+    Ok(())
+}
@@ -178,6 +178,144 @@ fn test_header() -> Result<(), OdsError> {
     Ok(())
 }
 
+#[test]
+fn test_group() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+
+    let mut sh = Sheet::new("1");
+    for i in 0..10 {
+        for j in 0..5 {
+            sh.set_value(i, j, i + j);
+        }
+    }
+    sh.add_row_group(1, 8, true)?;
+    sh.add_row_group(2, 4, false)?;
+    sh.add_row_group(6, 7, true)?;
+    sh.add_col_group(1, 3, false)?;
+    sh.add_col_group(6, 9, true)?;
+    sh.set_header_rows(0, 0);
+    wb.push_sheet(sh);
+
+    // groups in the gaps between the rows and beyond the last row.
+    let mut sh = Sheet::new("2");
+    sh.set_value(0, 0, 0);
+    sh.set_value(9, 0, 0);
+    sh.add_row_group(2, 12, true)?;
+    sh.add_row_group(3, 5, false)?;
+    sh.set_header_rows(3, 4);
+    wb.push_sheet(sh);
+
+    // groups that start after the last row.
+    let mut sh = Sheet::new("3");
+    sh.set_value(0, 0, 0);
+    sh.add_row_group(5, 8, false)?;
+    sh.add_row_group(10, 11, true)?;
+    sh.add_col_group(5, 8, false)?;
+    wb.push_sheet(sh);
+
+    write_ods(&mut wb, "test_out/test_group.ods")?;
+
+    let wb = read_ods("test_out/test_group.ods")?;
+
+    let sh = wb.sheet(0);
+    let rows: Vec<_> = sh
+        .row_groups()
+        .iter()
+        .map(|v| (v.from(), v.to(), v.display()))
+        .collect();
+    assert_eq!(rows, vec![(1, 8, true), (2, 4, false), (6, 7, true)]);
+    let cols: Vec<_> = sh
+        .col_groups()
+        .iter()
+        .map(|v| (v.from(), v.to(), v.display()))
+        .collect();
+    assert_eq!(cols, vec![(1, 3, false), (6, 9, true)]);
+    assert_eq!(sh.header_rows().clone(), Some(RowRange::new(0, 0)));
+
+    let sh = wb.sheet(1);
+    let rows: Vec<_> = sh
+        .row_groups()
+        .iter()
+        .map(|v| (v.from(), v.to(), v.display()))
+        .collect();
+    assert_eq!(rows, vec![(2, 12, true), (3, 5, false)]);
+    assert_eq!(sh.header_rows().clone(), Some(RowRange::new(3, 4)));
+
+    let sh = wb.sheet(2);
+    let rows: Vec<_> = sh
+        .row_groups()
+        .iter()
+        .map(|v| (v.from(), v.to(), v.display()))
+        .collect();
+    assert_eq!(rows, vec![(5, 8, false), (10, 11, true)]);
+    let cols: Vec<_> = sh
+        .col_groups()
+        .iter()
+        .map(|v| (v.from(), v.to(), v.display()))
+        .collect();
+    assert_eq!(cols, vec![(5, 8, false)]);
+
+    Ok(())
+}
+
+#[test]
+fn test_group_overlap() -> Result<(), OdsError> {
+    let mut sh = Sheet::new("1");
+    sh.add_row_group(1, 5, true)?;
+    assert!(sh.add_row_group(3, 7, true).is_err());
+    assert!(sh.add_row_group(7, 6, true).is_err());
+    sh.add_row_group(1, 2, true)?;
+    assert!(sh.add_col_group(3, 2, true).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_group_header() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    for i in 0..10 {
+        for j in 0..10 {
+            sh.set_value(i, j, i * j);
+        }
+    }
+    sh.set_header_rows(0, 1);
+    sh.set_header_cols(0, 1);
+    sh.add_row_group(0, 6, true)?;
+    sh.add_row_group(2, 3, false)?;
+    sh.add_col_group(0, 1, true)?;
+    sh.add_col_group(4, 5, true)?;
+    wb.push_sheet(sh);
+
+    write_ods(&mut wb, "test_out/test_group_header.ods")?;
+
+    let wb = read_ods("test_out/test_group_header.ods")?;
+    let sh = wb.sheet(0);
+    let rows: Vec<_> = sh.row_groups().iter().map(|v| (v.from(), v.to())).collect();
+    assert_eq!(rows, vec![(0, 6), (2, 3)]);
+    let cols: Vec<_> = sh.col_groups().iter().map(|v| (v.from(), v.to())).collect();
+    assert_eq!(cols, vec![(0, 1), (4, 5)]);
+    assert_eq!(sh.header_rows().clone(), Some(RowRange::new(0, 1)));
+    assert_eq!(sh.header_cols().clone(), Some(ColRange::new(0, 1)));
+
+    // groups can't start or end within the header.
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("1");
+    sh.set_value(9, 9, 1);
+    sh.set_header_rows(0, 1);
+    sh.add_row_group(1, 6, true)?;
+    sh.add_row_group(2, 3, true)?;
+    wb.push_sheet(sh);
+    assert!(write_ods(&mut wb, "test_out/test_group_header_err.ods").is_err());
+
+    let sh = wb.sheet_mut(0);
+    sh.remove_row_group(1, 6);
+    sh.set_header_cols(2, 4);
+    sh.add_col_group(3, 3, true)?;
+    assert!(write_ods(&mut wb, "test_out/test_group_header_err.ods").is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_print_range() -> Result<(), OdsError> {
     let mut wb = WorkBook::new_empty();
@@ -0,0 +1,167 @@
+use spreadsheet_ods::draw::Image;
+use spreadsheet_ods::{
+    read_ods, write_ods, CellContent, Length, OdsError, OdsReader, OdsStreamWriter, Sheet,
+    Visibility, WorkBook,
+};
+
+#[test]
+fn test_stream_read() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+
+    let mut ods = OdsReader::open("tests/orders.ods")?;
+    let mut content = ods.content()?;
+
+    let mut idx = 0;
+    while let Some(sheet) = content.next_sheet()? {
+        let sh = wb.sheet(idx);
+        assert_eq!(sheet.name(), sh.name());
+
+        let mut count = 0;
+        while let Some((row, cells)) = content.next_row()? {
+            for (col, cell) in cells {
+                assert_eq!(
+                    format!("{:?}", cell.value()),
+                    format!("{:?}", sh.value(row, col))
+                );
+                assert_eq!(cell.formula(), sh.formula(row, col));
+                count += 1;
+            }
+        }
+        assert_eq!(count, sh.iter().count());
+
+        idx += 1;
+    }
+    assert_eq!(idx, wb.num_sheets());
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_skip() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("one");
+    for r in 0..100 {
+        sh.set_value(r, 0, r);
+    }
+    sh.set_col_width(0, Length::Cm(3.0));
+    wb.push_sheet(sh);
+    let mut sh = Sheet::new("two");
+    sh.set_value(5, 3, "two");
+    wb.push_sheet(sh);
+    write_ods(&mut wb, "test_out/test_stream_skip.ods")?;
+
+    let mut ods = OdsReader::open("test_out/test_stream_skip.ods")?;
+    let mut content = ods.content()?;
+
+    let sheet = content.next_sheet()?.expect("sheet one");
+    assert_eq!(sheet.name(), "one");
+    let (row, cells) = content.next_row()?.expect("row 0");
+    assert_eq!(row, 0);
+    assert_eq!(cells.len(), 1);
+    assert_eq!(content.sheet().col_width(0), Length::Cm(3.0));
+
+    // skips the rest of sheet one.
+    let sheet = content.next_sheet()?.expect("sheet two");
+    assert_eq!(sheet.name(), "two");
+    let (row, cells) = content.next_row()?.expect("row 5");
+    assert_eq!(row, 5);
+    assert_eq!(cells[0].0, 3);
+    assert_eq!(cells[0].1.value().as_str_or(""), "two");
+    assert!(content.next_row()?.is_none());
+    assert!(content.next_sheet()?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_write() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("one");
+    sh.set_col_width(0, Length::Cm(4.0));
+    sh.set_col_width(3, Length::Cm(2.0));
+    sh.set_header_rows(0, 0);
+    wb.push_sheet(sh);
+    let mut sh = Sheet::new("two");
+    sh.set_value(1, 1, "not streamed");
+    wb.push_sheet(sh);
+
+    let mut ods = OdsStreamWriter::create(wb, "test_out/test_stream_write.ods")?;
+    ods.start_sheet()?;
+    let mut header = CellContent::new();
+    header.set_value("header");
+    header.set_col_span(2);
+    ods.write_row(0, &[(0, header)])?;
+    for row in (2..1000).step_by(3) {
+        let mut c0 = CellContent::new();
+        c0.set_value(row);
+        let mut c3 = CellContent::new();
+        c3.set_value(format!("row {}", row));
+        ods.write_row(row, &[(0, c0), (3, c3)])?;
+    }
+    assert!(ods.write_row(5, &[(0, CellContent::new())]).is_err());
+    ods.finish()?;
+
+    let wb = read_ods("test_out/test_stream_write.ods")?;
+    assert_eq!(wb.num_sheets(), 2);
+    let sh = wb.sheet(0);
+    assert_eq!(sh.name(), "one");
+    assert_eq!(sh.value(0, 0).as_str_or(""), "header");
+    assert_eq!(sh.col_span(0, 0), 2);
+    assert_eq!(sh.value(2, 0).as_i32_or(0), 2);
+    assert_eq!(sh.value(998, 3).as_str_or(""), "row 998");
+    assert!(sh.value(3, 0).as_str_opt().is_none());
+    assert_eq!(sh.col_width(0), Length::Cm(4.0));
+    assert_eq!(sh.header_rows().as_ref().map(|v| v.row()), Some(0));
+    assert_eq!(sh.iter().count(), 1 + 2 * 333);
+
+    let sh = wb.sheet(1);
+    assert_eq!(sh.name(), "two");
+    assert_eq!(sh.value(1, 1).as_str_or(""), "not streamed");
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_write_empty() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sh = Sheet::new("one");
+    sh.set_row_height(2, Length::Cm(2.0));
+    sh.set_row_visible(3, Visibility::Collapsed);
+    wb.push_sheet(sh);
+
+    let mut ods = OdsStreamWriter::create(wb, "test_out/test_stream_empty.ods")?;
+    ods.start_sheet()?;
+    let mut c0 = CellContent::new();
+    c0.set_value("first");
+    ods.write_row(0, &[(0, c0)])?;
+    ods.write_row(2, &[])?;
+    ods.write_row(3, &[])?;
+    assert!(ods.write_row(3, &[]).is_err());
+
+    let mut image = CellContent::new();
+    image.images.push(Image::new("image/png", Vec::new()));
+    assert!(ods.write_row(4, &[(0, image)]).is_err());
+
+    let mut c0 = CellContent::new();
+    c0.set_value("last");
+    ods.write_row(5, &[(0, c0)])?;
+    ods.finish()?;
+
+    let wb = read_ods("test_out/test_stream_empty.ods")?;
+    let sh = wb.sheet(0);
+    assert_eq!(sh.value(0, 0).as_str_or(""), "first");
+    assert_eq!(sh.value(5, 0).as_str_or(""), "last");
+    assert_eq!(sh.row_height(2), Length::Cm(2.0));
+    assert_eq!(sh.row_visible(3), Visibility::Collapsed);
+    assert_eq!(sh.iter().count(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_read_encrypted() {
+    match OdsReader::open("tests/encrypted.ods") {
+        Err(OdsError::Password(_)) => {}
+        v => panic!("{:?}", v.map(|_| ())),
+    }
+}
@@ -0,0 +1,326 @@
+use chrono::NaiveDate;
+use spreadsheet_ods::trackedchanges::{
+    AcceptanceState, ChangeAddress, ChangeCell, ChangeDeletion, ChangeInfo, ChangeKind,
+    ChangeRange, ChangeType, TrackedChange, TrackedChanges,
+};
+use spreadsheet_ods::{read_fods_buf, read_ods, write_ods, OdsError, Sheet, Value, WorkBook};
+
+// Shortened content as written by LibreOffice. The sheet shows the state
+// after all changes.
+const LIBREOFFICE_CHANGES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:calcext="urn:org:documentfoundation:names:experimental:calc:xmlns:calcext:1.0" office:version="1.3" office:mimetype="application/vnd.oasis.opendocument.spreadsheet">
+<office:body>
+<office:spreadsheet>
+<table:tracked-changes>
+<table:cell-content-change table:id="ct1">
+<table:cell-address table:column="0" table:row="0" table:table="0"/>
+<office:change-info><dc:creator>Jane Auditor</dc:creator><dc:date>2023-05-02T09:15:00</dc:date></office:change-info>
+<table:previous><table:change-track-table-cell office:value-type="float" office:value="10" calcext:value-type="float"><text:p>10</text:p></table:change-track-table-cell></table:previous>
+</table:cell-content-change>
+<table:insertion table:id="ct2" table:type="row" table:position="2" table:count="1" table:table="0">
+<office:change-info><dc:creator>John Doe</dc:creator><dc:date>2023-05-02T09:16:00</dc:date></office:change-info>
+</table:insertion>
+<table:cell-content-change table:id="ct3">
+<table:cell-address table:column="0" table:row="2" table:table="0"/>
+<office:change-info><dc:creator>John Doe</dc:creator><dc:date>2023-05-02T09:16:30</dc:date></office:change-info>
+<table:dependencies><table:dependency table:id="ct2"/></table:dependencies>
+<table:previous><table:change-track-table-cell/></table:previous>
+</table:cell-content-change>
+<table:deletion table:id="ct4" table:type="column" table:position="3" table:table="0">
+<office:change-info><dc:creator>John Doe</dc:creator><dc:date>2023-05-02T09:17:00</dc:date><text:p>Column not needed.</text:p></office:change-info>
+<table:deletions>
+<table:cell-content-deletion table:id="ct5"><table:cell-address table:column="3" table:row="0" table:table="0"/><table:change-track-table-cell office:value-type="string" calcext:value-type="string"><text:p>old</text:p></table:change-track-table-cell></table:cell-content-deletion>
+</table:deletions>
+</table:deletion>
+<table:movement table:id="ct6">
+<table:source-range-address table:start-column="5" table:start-row="0" table:start-table="0" table:end-column="5" table:end-row="1" table:end-table="0"/>
+<table:target-range-address table:start-column="6" table:start-row="0" table:start-table="0" table:end-column="6" table:end-row="1" table:end-table="0"/>
+<office:change-info><dc:creator>Jane Auditor</dc:creator><dc:date>2023-05-02T09:18:00</dc:date></office:change-info>
+</table:movement>
+</table:tracked-changes>
+<table:table table:name="Sheet1">
+<table:table-column table:number-columns-repeated="7"/>
+<table:table-row>
+<table:table-cell office:value-type="float" office:value="20"><text:p>20</text:p></table:table-cell>
+<table:table-cell table:number-columns-repeated="2"/>
+<table:table-cell office:value-type="string"><text:p>keep</text:p></table:table-cell>
+<table:table-cell table:number-columns-repeated="2"/>
+<table:table-cell office:value-type="float" office:value="1"><text:p>1</text:p></table:table-cell>
+</table:table-row>
+<table:table-row>
+<table:table-cell office:value-type="float" office:value="5"><text:p>5</text:p></table:table-cell>
+<table:table-cell table:number-columns-repeated="5"/>
+<table:table-cell office:value-type="float" office:value="2"><text:p>2</text:p></table:table-cell>
+</table:table-row>
+<table:table-row>
+<table:table-cell office:value-type="string"><text:p>new</text:p></table:table-cell>
+</table:table-row>
+<table:table-row>
+<table:table-cell office:value-type="float" office:value="7"><text:p>7</text:p></table:table-cell>
+</table:table-row>
+</table:table>
+</office:spreadsheet>
+</office:body>
+</office:document>
+"#;
+
+fn check_changes(wb: &WorkBook) {
+    let changes = wb.tracked_changes().expect("changes");
+    assert!(changes.track_changes());
+    assert_eq!(changes.changes().len(), 5);
+    assert_eq!(changes.iter_pending().count(), 5);
+
+    let ct1 = changes.change("ct1").expect("ct1");
+    assert_eq!(ct1.info().creator(), "Jane Auditor");
+    assert_eq!(
+        ct1.info().date(),
+        NaiveDate::from_ymd_opt(2023, 5, 2)
+            .unwrap()
+            .and_hms_opt(9, 15, 0)
+    );
+    match ct1.kind() {
+        ChangeKind::CellContent {
+            address, previous, ..
+        } => {
+            assert_eq!(*address, ChangeAddress::new(0, 0, 0));
+            let previous = previous.as_ref().expect("previous");
+            assert_eq!(previous.value().as_f64_or(0.0), 10.0);
+        }
+        _ => panic!("cell content expected"),
+    }
+
+    let ct2 = changes.change("ct2").expect("ct2");
+    assert!(matches!(
+        ct2.kind(),
+        ChangeKind::Insertion {
+            change_type: ChangeType::Row,
+            position: 2,
+            count: 1,
+            table: 0
+        }
+    ));
+
+    let ct3 = changes.change("ct3").expect("ct3");
+    assert_eq!(ct3.dependencies(), &vec!["ct2".to_string()]);
+    assert!(matches!(
+        ct3.kind(),
+        ChangeKind::CellContent { previous: None, .. }
+    ));
+
+    let ct4 = changes.change("ct4").expect("ct4");
+    assert_eq!(ct4.info().comment(), "Column not needed.");
+    assert!(matches!(
+        ct4.kind(),
+        ChangeKind::Deletion {
+            change_type: ChangeType::Column,
+            position: 3,
+            ..
+        }
+    ));
+    match &ct4.deletions()[0] {
+        ChangeDeletion::CellContent { id, address, cell } => {
+            assert_eq!(id.as_deref(), Some("ct5"));
+            assert_eq!(*address, Some(ChangeAddress::new(0, 0, 3)));
+            assert_eq!(cell.as_ref().expect("cell").value().as_str_or(""), "old");
+        }
+        _ => panic!("cell content expected"),
+    }
+
+    let ct6 = changes.change("ct6").expect("ct6");
+    match ct6.kind() {
+        ChangeKind::Movement { source, target } => {
+            assert_eq!(source.from(), ChangeAddress::new(0, 0, 5));
+            assert_eq!(source.to(), ChangeAddress::new(0, 1, 5));
+            assert_eq!(target.from(), ChangeAddress::new(0, 0, 6));
+        }
+        _ => panic!("movement expected"),
+    }
+}
+
+#[test]
+fn test_trackedchanges_read() -> Result<(), OdsError> {
+    let mut wb = read_fods_buf(LIBREOFFICE_CHANGES.as_bytes())?;
+    check_changes(&wb);
+
+    write_ods(&mut wb, "test_out/trackedchanges.ods")?;
+    let wi = read_ods("test_out/trackedchanges.ods")?;
+    check_changes(&wi);
+
+    Ok(())
+}
+
+#[test]
+fn test_trackedchanges_reject_all() -> Result<(), OdsError> {
+    let mut wb = read_fods_buf(LIBREOFFICE_CHANGES.as_bytes())?;
+    wb.reject_all_changes()?;
+
+    let changes = wb.tracked_changes().expect("changes");
+    assert_eq!(changes.iter_pending().count(), 0);
+    assert!(changes
+        .iter()
+        .all(|v| v.acceptance_state() == AcceptanceState::Rejected));
+
+    let sheet = wb.sheet(0);
+    assert_eq!(sheet.value(0, 0).as_f64_or(0.0), 10.0);
+    assert_eq!(sheet.value(1, 0).as_f64_or(0.0), 5.0);
+    assert_eq!(sheet.value(2, 0).as_f64_or(0.0), 7.0);
+    assert!(matches!(sheet.value(3, 0), Value::Empty));
+    assert_eq!(sheet.value(0, 3).as_str_or(""), "old");
+    assert_eq!(sheet.value(0, 4).as_str_or(""), "keep");
+    assert_eq!(sheet.value(0, 6).as_f64_or(0.0), 1.0);
+    assert_eq!(sheet.value(1, 6).as_f64_or(0.0), 2.0);
+    assert!(matches!(sheet.value(0, 7), Value::Empty));
+
+    write_ods(&mut wb, "test_out/trackedchanges_rejected.ods")?;
+    let wi = read_ods("test_out/trackedchanges_rejected.ods")?;
+    let changes = wi.tracked_changes().expect("changes");
+    assert_eq!(
+        changes.change("ct1").map(|v| v.acceptance_state()),
+        Some(AcceptanceState::Rejected)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_trackedchanges_dependencies() -> Result<(), OdsError> {
+    let mut wb = read_fods_buf(LIBREOFFICE_CHANGES.as_bytes())?;
+
+    // ct3 depends on ct2 and is rejected first.
+    wb.reject_change("ct2")?;
+    let changes = wb.tracked_changes().expect("changes");
+    assert_eq!(
+        changes.change("ct3").map(|v| v.acceptance_state()),
+        Some(AcceptanceState::Rejected)
+    );
+    assert_eq!(wb.sheet(0).value(2, 0).as_f64_or(0.0), 7.0);
+
+    // accepting ct3 accepts ct2 too.
+    let mut wb = read_fods_buf(LIBREOFFICE_CHANGES.as_bytes())?;
+    wb.accept_change("ct3")?;
+    let changes = wb.tracked_changes().expect("changes");
+    assert_eq!(
+        changes.change("ct2").map(|v| v.acceptance_state()),
+        Some(AcceptanceState::Accepted)
+    );
+    assert!(wb.reject_change("ct2").is_err());
+    assert!(wb.accept_change("ct99").is_err());
+    assert_eq!(wb.sheet(0).value(2, 0).as_str_or(""), "new");
+
+    Ok(())
+}
+
+#[test]
+fn test_trackedchanges_new() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sheet = Sheet::new("1");
+    sheet.set_value(0, 0, "after");
+    wb.push_sheet(sheet);
+
+    let date = NaiveDate::from_ymd_opt(2023, 6, 1)
+        .unwrap()
+        .and_hms_opt(8, 0, 0);
+    let mut info = ChangeInfo::new("Jane Auditor", date);
+    info.set_comment("first line\nsecond line");
+    let change = TrackedChange::new(
+        "ct1",
+        ChangeKind::CellContent {
+            address: ChangeAddress::new(0, 0, 0),
+            previous_id: None,
+            previous: Some(ChangeCell::new("before")),
+        },
+        info,
+    );
+    let mut changes = TrackedChanges::new();
+    changes.push_change(change);
+    wb.set_tracked_changes(Some(changes));
+
+    write_ods(&mut wb, "test_out/trackedchanges_new.ods")?;
+    let mut wi = read_ods("test_out/trackedchanges_new.ods")?;
+    let change = wi
+        .tracked_changes()
+        .and_then(|v| v.change("ct1"))
+        .expect("ct1");
+    assert_eq!(change.info().comment(), "first line\nsecond line");
+    assert_eq!(change.info().date(), date);
+
+    wi.reject_change("ct1")?;
+    assert_eq!(wi.sheet(0).value(0, 0).as_str_or(""), "before");
+
+    Ok(())
+}
+
+#[test]
+fn test_trackedchanges_movement() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sheet = Sheet::new("1");
+    sheet.set_value(5, 0, "moved");
+    sheet.set_formula(0, 1, "of:=[.A6]");
+    wb.push_sheet(sheet);
+
+    let mut change = TrackedChange::new(
+        "ct1",
+        ChangeKind::Movement {
+            source: ChangeRange::cell(ChangeAddress::new(0, 0, 0)),
+            target: ChangeRange::cell(ChangeAddress::new(0, 5, 0)),
+        },
+        ChangeInfo::new("Jane Auditor", None),
+    );
+    change.deletions_mut().push(ChangeDeletion::CellContent {
+        id: None,
+        address: Some(ChangeAddress::new(0, 5, 0)),
+        cell: Some(ChangeCell::new("overwritten")),
+    });
+    let mut changes = TrackedChanges::new();
+    changes.push_change(change);
+    wb.set_tracked_changes(Some(changes));
+
+    wb.reject_change("ct1")?;
+    let sheet = wb.sheet(0);
+    assert_eq!(sheet.value(0, 0).as_str_or(""), "moved");
+    assert_eq!(sheet.value(5, 0).as_str_or(""), "overwritten");
+    assert_eq!(sheet.formula(0, 1).map(|v| v.as_str()), Some("of:=[.A1]"));
+
+    Ok(())
+}
+
+#[test]
+fn test_trackedchanges_reject_error() -> Result<(), OdsError> {
+    let mut wb = WorkBook::new_empty();
+    let mut sheet = Sheet::new("1");
+    sheet.set_value(0, 0, "after");
+    wb.push_sheet(sheet);
+
+    let info = ChangeInfo::new("Jane Auditor", None);
+    let mut changes = TrackedChanges::new();
+    changes.push_change(TrackedChange::new(
+        "ct1",
+        ChangeKind::CellContent {
+            address: ChangeAddress::new(0, 0, 0),
+            previous_id: None,
+            previous: Some(ChangeCell::new("before")),
+        },
+        info.clone(),
+    ));
+    let mut change = TrackedChange::new(
+        "ct2",
+        ChangeKind::Deletion {
+            change_type: ChangeType::Table,
+            position: 1,
+            table: 0,
+            multi_deletion_spanned: None,
+        },
+        info,
+    );
+    change.dependencies_mut().push("ct1".to_string());
+    changes.push_change(change);
+    wb.set_tracked_changes(Some(changes));
+
+    // the deleted table can't be restored, nothing is rejected.
+    assert!(wb.reject_change("ct1").is_err());
+    let changes = wb.tracked_changes().expect("changes");
+    assert_eq!(changes.iter_pending().count(), 2);
+    assert_eq!(wb.sheet(0).value(0, 0).as_str_or(""), "after");
+
+    Ok(())
+}
@@ -1,10 +1,10 @@
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{Cursor, Read, Write};
 use std::path::Path;
 
 use spreadsheet_ods::{
-    read_ods, read_ods_buf, write_ods, write_ods_buf, OdsError, Sheet, SplitMode, ValueType,
-    WorkBook,
+    read_ods, read_ods_buf, read_ods_from, write_ods, write_ods_atomic, write_ods_buf,
+    write_ods_to, OdsError, Sheet, SplitMode, ValueType, WorkBook,
 };
 use std::time::Instant;
 
@@ -150,3 +150,156 @@ fn test_read_buf() -> Result<(), OdsError> {
     write_ods(&mut wb, "test_out/orders.ods")?;
     Ok(())
 }
+
+#[test]
+fn test_write_atomic() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/orders.ods")?;
+
+    write_ods_atomic(&mut wb, "test_out/orders_atomic.ods")?;
+    let wb2 = read_ods("test_out/orders_atomic.ods")?;
+    assert_eq!(wb.num_sheets(), wb2.num_sheets());
+
+    let tmp = std::fs::read_dir("test_out")?
+        .filter_map(|v| v.ok())
+        .any(|v| {
+            v.file_name()
+                .to_string_lossy()
+                .starts_with(".orders_atomic.ods")
+        });
+    assert!(!tmp);
+
+    assert!(write_ods_atomic(&mut wb, "test_out/missing/orders_atomic.ods").is_err());
+    assert!(!Path::new("test_out/missing").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_write_atomic_threads() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+
+    let handles = (0..4)
+        .map(|_| {
+            let mut wb = wb.clone();
+            std::thread::spawn(move || write_ods_atomic(&mut wb, "test_out/orders_threads.ods"))
+        })
+        .collect::<Vec<_>>();
+    for h in handles {
+        h.join().expect("thread")?;
+    }
+
+    let wb2 = read_ods("test_out/orders_threads.ods")?;
+    assert_eq!(wb.num_sheets(), wb2.num_sheets());
+
+    let tmp = std::fs::read_dir("test_out")?
+        .filter_map(|v| v.ok())
+        .any(|v| {
+            v.file_name()
+                .to_string_lossy()
+                .starts_with(".orders_threads.ods")
+        });
+    assert!(!tmp);
+
+    Ok(())
+}
+
+#[test]
+fn test_read_write_stream() -> Result<(), OdsError> {
+    let f = File::open("tests/orders.ods")?;
+    let mut wb = read_ods_from(f)?;
+
+    let f = File::create("test_out/orders_to.ods")?;
+    write_ods_to(&mut wb, f)?;
+
+    let v = write_ods_to(&mut wb, Cursor::new(Vec::new()))?;
+    let wb2 = read_ods_from(Cursor::new(v.into_inner()))?;
+    assert_eq!(wb.num_sheets(), wb2.num_sheets());
+    assert_eq!(wb.sheet(0).used_grid_size(), wb2.sheet(0).used_grid_size());
+
+    Ok(())
+}
+
+#[test]
+fn read_fods() -> Result<(), OdsError> {
+    let wb = read_ods("tests/orders.ods")?;
+    let wf = spreadsheet_ods::read_fods("tests/orders.fods")?;
+
+    assert_eq!(wb.num_sheets(), wf.num_sheets());
+    for i in 0..wb.num_sheets() {
+        let sb = wb.sheet(i);
+        let sf = wf.sheet(i);
+        assert_eq!(sb.name(), sf.name());
+        assert_eq!(sb.iter().count(), sf.iter().count());
+        for ((row, col), cell) in sb.iter() {
+            assert_eq!(
+                format!("{:?}", cell.value()),
+                format!("{:?}", sf.value(row, col))
+            );
+            assert_eq!(cell.style(), sf.cellstyle(row, col));
+        }
+    }
+    assert_eq!(wb.config().active_table, wf.config().active_table);
+    assert!(wf.cellstyle("Default").is_some());
+    for name in ["Default", "Report"] {
+        let mb = wb.masterpage(name).expect("masterpage");
+        let mf = wf.masterpage(name).expect("masterpage");
+        assert_eq!(
+            format!("{:?}", mb.header().left()),
+            format!("{:?}", mf.header().left())
+        );
+        assert_eq!(
+            format!("{:?}", mb.footer().center()),
+            format!("{:?}", mf.footer().center())
+        );
+    }
+
+    let buf = std::fs::read("tests/orders.fods")?;
+    let wf = spreadsheet_ods::read_fods_buf(&buf)?;
+    assert_eq!(wb.num_sheets(), wf.num_sheets());
+
+    Ok(())
+}
+
+#[test]
+fn write_fods() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/orders.ods")?;
+    spreadsheet_ods::write_fods(&mut wb, "test_out/orders.fods")?;
+    let wf = spreadsheet_ods::read_fods("test_out/orders.fods")?;
+
+    assert_eq!(wb.num_sheets(), wf.num_sheets());
+    for i in 0..wb.num_sheets() {
+        let sb = wb.sheet(i);
+        let sf = wf.sheet(i);
+        assert_eq!(sb.name(), sf.name());
+        assert_eq!(sb.iter().count(), sf.iter().count());
+        for ((row, col), cell) in sb.iter() {
+            assert_eq!(
+                format!("{:?}", cell.value()),
+                format!("{:?}", sf.value(row, col))
+            );
+            assert_eq!(cell.style(), sf.cellstyle(row, col));
+        }
+    }
+    assert_eq!(wb.config().active_table, wf.config().active_table);
+
+    let buf = spreadsheet_ods::write_fods_buf(&mut wb, Vec::new())?;
+    let wf = spreadsheet_ods::read_fods_buf(&buf)?;
+    assert_eq!(wb.num_sheets(), wf.num_sheets());
+
+    Ok(())
+}
+
+#[test]
+fn write_fods_picture() -> Result<(), OdsError> {
+    let mut wb = read_ods("tests/picture.ods")?;
+    let buf = spreadsheet_ods::write_fods_buf(&mut wb, Vec::new())?;
+    let xml = String::from_utf8(buf).expect("utf8");
+
+    assert!(xml.contains("<office:binary-data>iVBORw0KGgo"));
+    assert!(!xml.contains("xlink:href=\"Pictures/pixel.png\""));
+
+    let wf = spreadsheet_ods::read_fods_buf(xml.as_bytes())?;
+    assert_eq!(wb.num_sheets(), wf.num_sheets());
+
+    Ok(())
+}